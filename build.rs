@@ -4,8 +4,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Check if proto files exist before trying to compile them
     if std::path::Path::new("proto/geyser.proto").exists() {
+        // Server stubs only back the in-process mock Geyser used by tests
         tonic_build::configure()
-            .build_server(false)
+            .build_server(std::env::var_os("CARGO_FEATURE_MOCK_GEYSER").is_some())
             .compile(&["proto/geyser.proto"], &["proto"])?;
         println!("cargo:rerun-if-changed=proto/geyser.proto");
     } else {
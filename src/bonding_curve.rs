@@ -1,8 +1,58 @@
+use crate::bounded_map::{BoundedMap, DEFAULT_MAX_TRACKED_TOKENS};
 use crate::constants::*;
 use crate::error::{Result, SniperError};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+// PumpFun Global account offsets of the reserves every new curve starts with:
+// discriminator, initialized: bool, authority and fee_recipient Pubkeys, then
+// initial_virtual_token_reserves: u64 and initial_virtual_sol_reserves: u64
+const GLOBAL_INITIAL_VIRTUAL_TOKENS_OFFSET: usize = 73;
+const GLOBAL_INITIAL_VIRTUAL_SOL_OFFSET: usize = 81;
+
+/// Market cap in SOL of a brand-new curve starting from these virtual reserves.
+pub fn initial_market_cap_sol(virtual_sol: f64, virtual_tokens: f64) -> f64 {
+    if virtual_tokens <= 0.0 {
+        return f64::INFINITY;
+    }
+    virtual_sol / virtual_tokens * TOTAL_SUPPLY as f64
+}
+
+/// Checks that fresh curves built from these reserves start at a market cap
+/// within `min_sol..=max_sol`. Every market cap the bot computes derives from
+/// the initial reserves, so a stale pair silently skews all of them. Returns
+/// the initial market cap in SOL, or a description of what is off.
+pub fn check_initial_reserves(
+    virtual_sol: f64,
+    virtual_tokens: f64,
+    min_sol: f64,
+    max_sol: f64,
+) -> std::result::Result<f64, String> {
+    let market_cap_sol = initial_market_cap_sol(virtual_sol, virtual_tokens);
+    if market_cap_sol.is_finite() && (min_sol..=max_sol).contains(&market_cap_sol) {
+        return Ok(market_cap_sol);
+    }
+    Err(format!(
+        "initial virtual reserves of {} SOL / {} tokens give a fresh-curve market cap of {:.2} SOL, outside the expected {:.2}..{:.2} SOL",
+        virtual_sol, virtual_tokens, market_cap_sol, min_sol, max_sol
+    ))
+}
+
+/// Reads `(initial_virtual_sol, initial_virtual_tokens)` in whole units from
+/// PumpFun Global account data.
+pub fn parse_global_initial_reserves(data: &[u8]) -> Result<(f64, f64)> {
+    let read_u64 = |offset: usize| -> Option<u64> {
+        Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+    };
+    let (tokens, lamports) = read_u64(GLOBAL_INITIAL_VIRTUAL_TOKENS_OFFSET)
+        .zip(read_u64(GLOBAL_INITIAL_VIRTUAL_SOL_OFFSET))
+        .ok_or_else(|| SniperError::Transaction(format!("Global account too short: {} bytes", data.len())))?;
+    Ok((
+        lamports as f64 / LAMPORTS_PER_SOL as f64,
+        tokens as f64 / 10f64.powi(TOKEN_DECIMALS as i32),
+    ))
+}
 
 #[derive(Debug, Clone)]
 pub struct BondingCurveState {
@@ -11,6 +61,8 @@ pub struct BondingCurveState {
     pub real_sol: f64,
     pub real_tokens: f64,
     pub k: f64, // Constant product
+    /// Set once the curve has graduated; PumpFun then refuses buys and sells.
+    pub complete: bool,
 }
 
 impl BondingCurveState {
@@ -21,6 +73,7 @@ impl BondingCurveState {
             real_sol: 0.0,
             real_tokens: 0.0,
             k: INITIAL_VIRTUAL_SOL * INITIAL_VIRTUAL_TOKENS,
+            complete: false,
         }
     }
 
@@ -35,7 +88,38 @@ impl BondingCurveState {
             real_sol: initial_sol,
             real_tokens: 0.0,
             k,
+            complete: false,
+        }
+    }
+
+    /// Decodes an on-chain PumpFun bonding curve account. Reserves are converted to
+    /// SOL and whole tokens so the result matches the rest of the curve math.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        // 8-byte discriminator followed by virtual token, virtual SOL, real token
+        // and real SOL reserves as little-endian u64s, then the token supply and
+        // the `complete` flag
+        if data.len() < 40 {
+            return Err(SniperError::Transaction(format!(
+                "Bonding curve account too short: {} bytes", data.len()
+            )));
         }
+
+        let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let token_scale = 10f64.powi(TOKEN_DECIMALS as i32);
+
+        let virtual_tokens = read_u64(8) as f64 / token_scale;
+        let virtual_sol = read_u64(16) as f64 / LAMPORTS_PER_SOL as f64;
+        let real_tokens = read_u64(24) as f64 / token_scale;
+        let real_sol = read_u64(32) as f64 / LAMPORTS_PER_SOL as f64;
+
+        Ok(Self {
+            virtual_sol,
+            virtual_tokens,
+            real_sol,
+            real_tokens,
+            k: virtual_sol * virtual_tokens,
+            complete: data.get(48).is_some_and(|&flag| flag != 0),
+        })
     }
 
     pub fn get_current_price(&self) -> f64 {
@@ -45,27 +129,75 @@ impl BondingCurveState {
         self.virtual_sol / self.virtual_tokens
     }
 
+    /// Spot price (SOL per token) when the curve holds `virtual_sol`.
+    /// With `tokens = k / sol`, the price `sol / tokens` is `sol² / k`.
+    pub fn price_at_sol_reserve(&self, virtual_sol: f64) -> f64 {
+        if self.k == 0.0 {
+            return 0.0;
+        }
+        virtual_sol * virtual_sol / self.k
+    }
+
+    /// SOL that must be bought into the curve to move the spot price to
+    /// `target_price`. Inverts `price_at_sol_reserve`: the required reserve is
+    /// `sqrt(target_price * k)`. Returns 0.0 if the price is already at or above target.
+    pub fn sol_needed_for_price(&self, target_price: f64) -> f64 {
+        if target_price <= 0.0 {
+            return 0.0;
+        }
+        let target_virtual_sol = (target_price * self.k).sqrt();
+        (target_virtual_sol - self.virtual_sol).max(0.0)
+    }
+
     pub fn get_market_cap(&self, sol_price_usd: f64) -> f64 {
         let price_per_token = self.get_current_price();
         price_per_token * sol_price_usd * TOTAL_SUPPLY as f64
     }
 
+    /// Returns `(tokens_out, effective_price_per_token)` for buying with `sol_input` SOL.
     pub fn calculate_buy_output(&self, sol_input: f64) -> (f64, f64) {
         let new_virtual_sol = self.virtual_sol + sol_input;
         let new_virtual_tokens = self.k / new_virtual_sol;
         let tokens_output = self.virtual_tokens - new_virtual_tokens;
-        let sol_output = sol_input; // For simplicity, assuming 1:1 for now
+        let effective_price = if tokens_output > 0.0 { sol_input / tokens_output } else { 0.0 };
 
-        (tokens_output, sol_output)
+        (tokens_output, effective_price)
     }
 
+    pub fn simulate_buy(&self, sol_amount: f64, sol_price_usd: f64) -> BuySimulation {
+        let (tokens_output, effective_price) = self.calculate_buy_output(sol_amount);
+        let new_virtual_sol = self.virtual_sol + sol_amount;
+        let new_virtual_tokens = self.virtual_tokens - tokens_output;
+        let new_price_per_token = new_virtual_sol / new_virtual_tokens;
+        let new_market_cap = new_price_per_token * sol_price_usd * TOTAL_SUPPLY as f64;
+        let spot_price = self.get_current_price();
+
+        BuySimulation {
+            tokens_received: tokens_output,
+            new_price_per_token,
+            new_market_cap,
+            price_impact: (new_price_per_token - spot_price) / spot_price,
+            slippage: (effective_price - spot_price) / spot_price,
+        }
+    }
+
+    /// Returns `(sol_out, effective_price_per_token)` for selling `tokens_input` tokens.
     pub fn calculate_sell_output(&self, tokens_input: f64) -> (f64, f64) {
         let new_virtual_tokens = self.virtual_tokens + tokens_input;
         let new_virtual_sol = self.k / new_virtual_tokens;
         let sol_output = self.virtual_sol - new_virtual_sol;
-        let tokens_output = tokens_input; // For simplicity
+        let effective_price = if tokens_input > 0.0 { sol_output / tokens_input } else { 0.0 };
+
+        (sol_output, effective_price)
+    }
 
-        (sol_output, tokens_output)
+    /// SOL received from buying with `sol_input` and immediately selling every
+    /// token back, with `fee_rate` taken off both legs. The constant product
+    /// makes the two legs cancel, so what is lost is the fees.
+    pub fn simulate_round_trip(&self, sol_input: f64, fee_rate: f64) -> f64 {
+        let mut curve = self.clone();
+        let tokens = curve.apply_buy(sol_input);
+        curve.calculate_sell_output(tokens).0 * (1.0 - fee_rate)
     }
 
     pub fn apply_buy(&mut self, sol_input: f64) -> f64 {
@@ -84,21 +216,31 @@ impl BondingCurveState {
 }
 
 pub struct BondingCurveCalculator {
-    curves: HashMap<Pubkey, BondingCurveState>,
+    curves: BoundedMap<Pubkey, BondingCurveState>,
     sol_price_usd: f64,
 }
 
 impl BondingCurveCalculator {
     pub fn new(sol_price_usd: f64) -> Self {
         Self {
-            curves: HashMap::new(),
+            curves: BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS),
             sol_price_usd,
         }
     }
 
+    /// Caps how many curves are kept; the oldest are dropped first.
+    pub fn with_max_tracked_tokens(mut self, max_tokens: usize) -> Self {
+        self.curves.set_capacity(max_tokens);
+        self
+    }
+
+    pub fn tracked_tokens(&self) -> usize {
+        self.curves.len()
+    }
+
     pub fn update_sol_price(&mut self, sol_price_usd: f64) {
         self.sol_price_usd = sol_price_usd;
-        info!("Updated SOL price: ${:.2}", sol_price_usd);
+        debug!("Updated SOL price: ${:.2}", sol_price_usd);
     }
 
     pub fn initialize_token(&mut self, mint: &Pubkey, initial_sol_deposit: f64) -> Result<BondingCurveState> {
@@ -115,6 +257,11 @@ impl BondingCurveCalculator {
         self.curves.get(mint)
     }
 
+    /// Tracks a curve read from chain, replacing any earlier state for `mint`.
+    pub fn track_curve(&mut self, mint: &Pubkey, curve: BondingCurveState) {
+        self.curves.insert(*mint, curve);
+    }
+
     pub fn calculate_optimal_buy_amount(&self, mint: &Pubkey, target_market_cap: f64) -> Result<f64> {
         let curve = self.curves.get(mint)
             .ok_or_else(|| SniperError::Generic(anyhow::anyhow!("Token not found: {}", mint)))?;
@@ -127,75 +274,43 @@ impl BondingCurveCalculator {
 
         // Calculate how much SOL is needed to reach target market cap
         let target_price_per_token = target_market_cap / (TOTAL_SUPPLY as f64 * self.sol_price_usd);
-        let current_price_per_token = curve.get_current_price();
-        
-        if target_price_per_token <= current_price_per_token {
-            return Ok(0.0);
-        }
-
-        // Use binary search to find optimal buy amount
-        let mut low = 0.0;
-        let mut high = 1.0; // Start with 1 SOL max
-        let mut best_amount = 0.0;
-
-        for _ in 0..100 { // Max 100 iterations
-            let mid = (low + high) / 2.0;
-            let (tokens_output, _) = curve.calculate_buy_output(mid);
-            let new_virtual_sol = curve.virtual_sol + mid;
-            let new_virtual_tokens = curve.virtual_tokens - tokens_output;
-            let new_price_per_token = new_virtual_sol / new_virtual_tokens;
-            let new_market_cap = new_price_per_token * self.sol_price_usd * TOTAL_SUPPLY as f64;
-
-            if new_market_cap >= target_market_cap {
-                best_amount = mid;
-                high = mid;
-            } else {
-                low = mid;
-            }
-
-            if (high - low) < 0.0001 {
-                break;
-            }
-        }
 
-        Ok(best_amount)
+        Ok(curve.sol_needed_for_price(target_price_per_token))
     }
 
     pub fn simulate_buy(&self, mint: &Pubkey, sol_amount: f64) -> Result<BuySimulation> {
         let curve = self.curves.get(mint)
             .ok_or_else(|| SniperError::Generic(anyhow::anyhow!("Token not found: {}", mint)))?;
 
-        let (tokens_output, _) = curve.calculate_buy_output(sol_amount);
-        let new_virtual_sol = curve.virtual_sol + sol_amount;
-        let new_virtual_tokens = curve.virtual_tokens - tokens_output;
-        let new_price_per_token = new_virtual_sol / new_virtual_tokens;
-        let new_market_cap = new_price_per_token * self.sol_price_usd * TOTAL_SUPPLY as f64;
+        Ok(curve.simulate_buy(sol_amount, self.sol_price_usd))
+    }
 
-        Ok(BuySimulation {
-            tokens_received: tokens_output,
-            new_price_per_token,
-            new_market_cap,
-            price_impact: (new_price_per_token - curve.get_current_price()) / curve.get_current_price(),
-            slippage: 0.0, // Would need to calculate based on order book
-        })
+    /// Simulates each candidate buy size against the same curve state, in the
+    /// order given, so entry sizes can be compared side by side.
+    pub fn compare_buys(&self, mint: &Pubkey, amounts: &[f64]) -> Result<Vec<BuySimulation>> {
+        let curve = self.curves.get(mint)
+            .ok_or_else(|| SniperError::Generic(anyhow::anyhow!("Token not found: {}", mint)))?;
+
+        Ok(amounts.iter().map(|&sol_amount| curve.simulate_buy(sol_amount, self.sol_price_usd)).collect())
     }
 
     pub fn simulate_sell(&self, mint: &Pubkey, tokens_amount: f64) -> Result<SellSimulation> {
         let curve = self.curves.get(mint)
             .ok_or_else(|| SniperError::Generic(anyhow::anyhow!("Token not found: {}", mint)))?;
 
-        let (sol_output, _) = curve.calculate_sell_output(tokens_amount);
+        let (sol_output, effective_price) = curve.calculate_sell_output(tokens_amount);
         let new_virtual_tokens = curve.virtual_tokens + tokens_amount;
         let new_virtual_sol = curve.virtual_sol - sol_output;
         let new_price_per_token = new_virtual_sol / new_virtual_tokens;
         let new_market_cap = new_price_per_token * self.sol_price_usd * TOTAL_SUPPLY as f64;
+        let spot_price = curve.get_current_price();
 
         Ok(SellSimulation {
             sol_received: sol_output,
             new_price_per_token,
             new_market_cap,
-            price_impact: (curve.get_current_price() - new_price_per_token) / curve.get_current_price(),
-            slippage: 0.0, // Would need to calculate based on order book
+            price_impact: (spot_price - new_price_per_token) / spot_price,
+            slippage: (spot_price - effective_price) / spot_price,
         })
     }
 
@@ -312,6 +427,23 @@ pub struct SellSimulationWithFees {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_initial_reserve_sanity_check() {
+        let market_cap_sol = check_initial_reserves(INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS, 20.0, 40.0).unwrap();
+        assert!((market_cap_sol - 27.958).abs() < 1e-3);
+
+        // Ported constants with the token reserves in raw units
+        let error = check_initial_reserves(INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS * 1e6, 20.0, 40.0).unwrap_err();
+        assert!(error.contains("outside the expected"));
+        assert!(check_initial_reserves(INITIAL_VIRTUAL_SOL, 0.0, 20.0, 40.0).is_err());
+
+        let mut global = vec![0u8; 200];
+        global[73..81].copy_from_slice(&1_073_000_000_000_000u64.to_le_bytes());
+        global[81..89].copy_from_slice(&30_000_000_000u64.to_le_bytes());
+        assert_eq!(parse_global_initial_reserves(&global).unwrap(), (INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS));
+        assert!(parse_global_initial_reserves(&global[..80]).is_err());
+    }
+
     #[test]
     fn test_bonding_curve_basic() {
         let curve = BondingCurveState::new();
@@ -326,6 +458,149 @@ mod tests {
         assert!(tokens > 0.0);
     }
 
+    #[test]
+    fn test_from_account_data() {
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(&1_073_000_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&30_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&793_100_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+
+        let curve = BondingCurveState::from_account_data(&data).unwrap();
+        assert_eq!(curve.virtual_sol, INITIAL_VIRTUAL_SOL);
+        assert_eq!(curve.virtual_tokens, INITIAL_VIRTUAL_TOKENS);
+        assert_eq!(curve.real_sol, 0.0);
+        assert!(!curve.complete);
+
+        assert!(BondingCurveState::from_account_data(&data[..20]).is_err());
+
+        // Token supply, then the flag PumpFun sets when the curve graduates
+        data.extend_from_slice(&1_000_000_000_000_000u64.to_le_bytes());
+        data.push(0);
+        assert!(!BondingCurveState::from_account_data(&data).unwrap().complete);
+        data[48] = 1;
+        assert!(BondingCurveState::from_account_data(&data).unwrap().complete);
+    }
+
+    #[test]
+    fn test_buy_output_effective_price() {
+        let curve = BondingCurveState::new();
+        let (tokens, effective_price) = curve.calculate_buy_output(1.0);
+
+        // tokens_out = T·Δ/(s+Δ) and effective price = (s+Δ)/T for s = 30, Δ = 1
+        assert!((tokens - INITIAL_VIRTUAL_TOKENS / 31.0).abs() < 1e-3);
+        assert!((effective_price - 31.0 / INITIAL_VIRTUAL_TOKENS).abs() < 1e-18);
+    }
+
+    #[test]
+    fn test_sell_output_effective_price() {
+        let curve = BondingCurveState::new();
+        let tokens_in = INITIAL_VIRTUAL_TOKENS / 30.0;
+        let (sol_out, effective_price) = curve.calculate_sell_output(tokens_in);
+
+        // sol_out = s·x/(T+x) and effective price = s/(T+x)
+        let expected_sol = 30.0 * tokens_in / (INITIAL_VIRTUAL_TOKENS + tokens_in);
+        assert!((sol_out - expected_sol).abs() < 1e-9);
+        assert!((effective_price - 30.0 / (INITIAL_VIRTUAL_TOKENS + tokens_in)).abs() < 1e-18);
+    }
+
+    #[test]
+    fn test_simulation_slippage() {
+        let mut calculator = BondingCurveCalculator::new(100.0);
+        let mint = Pubkey::new_unique();
+        calculator.initialize_token(&mint, 0.0).unwrap();
+
+        // Buying Δ SOL into a reserve of s SOL pays Δ/s over spot
+        let buy = calculator.simulate_buy(&mint, 1.0).unwrap();
+        assert!((buy.slippage - 1.0 / 30.0).abs() < 1e-9);
+
+        // Selling x tokens into T tokens receives x/(T+x) under spot
+        let tokens_in = INITIAL_VIRTUAL_TOKENS / 30.0;
+        let sell = calculator.simulate_sell(&mint, tokens_in).unwrap();
+        assert!((sell.slippage - tokens_in / (INITIAL_VIRTUAL_TOKENS + tokens_in)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_buy_price_impact() {
+        // Δ SOL into s SOL of reserves moves the price by ((s+Δ)/s)² - 1
+        let curve = BondingCurveState::from_initial_deposit(0.0);
+        let simulation = curve.simulate_buy(3.0, 100.0);
+        assert!((simulation.price_impact - 0.21).abs() < 1e-9);
+        assert!(curve.simulate_buy(0.001, 100.0).price_impact < 0.001);
+    }
+
+    #[test]
+    fn test_price_sol_round_trip() {
+        let curve = BondingCurveState::new();
+        assert!((curve.price_at_sol_reserve(curve.virtual_sol) - curve.get_current_price()).abs() < 1e-18);
+
+        for target_price in [5e-8, 1e-7, 1e-6] {
+            let sol_needed = curve.sol_needed_for_price(target_price);
+            let price = curve.price_at_sol_reserve(curve.virtual_sol + sol_needed);
+            assert!((price - target_price).abs() / target_price < 1e-9);
+        }
+
+        // Below the current price nothing needs to be bought
+        assert_eq!(curve.sol_needed_for_price(curve.get_current_price() / 2.0), 0.0);
+    }
+
+    #[test]
+    fn test_round_trip_loses_only_fees() {
+        let curve = BondingCurveState::from_initial_deposit(2.0);
+        assert!((curve.simulate_round_trip(1.0, 0.0) - 1.0).abs() < 1e-9);
+        assert!((curve.simulate_round_trip(1.0, 0.01) - 0.99).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_optimal_buy_amount_reaches_target() {
+        let mut calculator = BondingCurveCalculator::new(100.0);
+        let mint = Pubkey::new_unique();
+        calculator.initialize_token(&mint, 0.0).unwrap();
+
+        let target_market_cap = 10_000.0;
+        let amount = calculator.calculate_optimal_buy_amount(&mint, target_market_cap).unwrap();
+        let simulation = calculator.simulate_buy(&mint, amount).unwrap();
+        assert!((simulation.new_market_cap - target_market_cap).abs() / target_market_cap < 1e-9);
+    }
+
+    #[test]
+    fn test_compare_buys_price_impact_grows_with_size() {
+        let mut calculator = BondingCurveCalculator::new(100.0);
+        let mint = Pubkey::new_unique();
+        calculator.initialize_token(&mint, 1.0).unwrap();
+
+        let amounts = [0.1, 0.5, 1.0, 5.0];
+        let simulations = calculator.compare_buys(&mint, &amounts).unwrap();
+        assert_eq!(simulations.len(), amounts.len());
+        for pair in simulations.windows(2) {
+            assert!(pair[1].price_impact > pair[0].price_impact);
+            assert!(pair[1].tokens_received > pair[0].tokens_received);
+            assert!(pair[1].new_market_cap > pair[0].new_market_cap);
+        }
+
+        // Each size is simulated against the same starting curve
+        let single = calculator.simulate_buy(&mint, 1.0).unwrap();
+        assert_eq!(simulations[2].tokens_received, single.tokens_received);
+
+        assert!(calculator.compare_buys(&Pubkey::new_unique(), &amounts).is_err());
+    }
+
+    #[test]
+    fn test_portfolio_value_marks_tracked_curves() {
+        let mut calculator = BondingCurveCalculator::new(100.0);
+        let (held, migrated) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let curve = BondingCurveState::from_initial_deposit(5.0);
+        let price = curve.get_current_price();
+        calculator.track_curve(&held, curve);
+
+        // Only holdings with a tracked curve are valued
+        let holdings = HashMap::from([(held, 1_000_000.0), (migrated, 5_000_000.0)]);
+        assert!((calculator.calculate_portfolio_value(&holdings) - price * 100.0 * 1_000_000.0).abs() < 1e-9);
+
+        calculator.update_sol_price(200.0);
+        assert!((calculator.calculate_portfolio_value(&holdings) - price * 200.0 * 1_000_000.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_market_cap_calculation() {
         let curve = BondingCurveState::new();
@@ -333,6 +608,19 @@ mod tests {
         assert!(market_cap > 0.0);
     }
 
+    #[test]
+    fn test_calculator_evicts_oldest_curve() {
+        let mut calculator = BondingCurveCalculator::new(100.0).with_max_tracked_tokens(2);
+        let mints: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for mint in &mints {
+            calculator.initialize_token(mint, 1.0).unwrap();
+        }
+
+        assert_eq!(calculator.tracked_tokens(), 2);
+        assert!(calculator.get_token_state(&mints[0]).is_none());
+        assert!(calculator.get_token_state(&mints[2]).is_some());
+    }
+
     #[test]
     fn test_calculator() {
         let mut calculator = BondingCurveCalculator::new(100.0);
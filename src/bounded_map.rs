@@ -0,0 +1,135 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Default number of tokens each per-token cache keeps before evicting.
+pub const DEFAULT_MAX_TRACKED_TOKENS: usize = 10_000;
+
+/// A `HashMap` that holds at most `capacity` entries, evicting the least
+/// recently inserted one when full. Re-inserting a key counts as fresh.
+#[derive(Debug, Clone)]
+pub struct BoundedMap<K, V> {
+    entries: HashMap<K, V>,
+    order: VecDeque<K>,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Copy, V> BoundedMap<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Shrinks or grows the cap, evicting the oldest entries if it now overflows.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.entries.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    /// Inserts `value`, returning the entry evicted to make room, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if self.entries.insert(key, value).is_some() {
+            self.order.retain(|k| *k != key);
+            self.order.push_back(key);
+            return None;
+        }
+
+        self.order.push_back(key);
+        if self.entries.len() > self.capacity {
+            return self.evict_oldest();
+        }
+        None
+    }
+
+    fn evict_oldest(&mut self) -> Option<(K, V)> {
+        let key = self.order.pop_front()?;
+        self.entries.remove(&key).map(|value| (key, value))
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.entries.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.entries.remove(key)?;
+        self.order.retain(|k| k != key);
+        Some(value)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &V) -> bool) {
+        self.entries.retain(|k, v| keep(k, v));
+        let entries = &self.entries;
+        self.order.retain(|k| entries.contains_key(k));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_oldest_beyond_capacity() {
+        let mut map = BoundedMap::new(2);
+        assert!(map.insert(1, "a").is_none());
+        assert!(map.insert(2, "b").is_none());
+
+        // Refreshing 1 makes 2 the oldest
+        assert!(map.insert(1, "a2").is_none());
+        assert_eq!(map.insert(3, "c"), Some((2, "b")));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"a2"));
+        assert!(!map.contains_key(&2));
+
+        map.set_capacity(1);
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key(&3));
+    }
+
+    #[test]
+    fn test_retain_and_remove_keep_order_in_sync() {
+        let mut map = BoundedMap::new(3);
+        map.insert(1, 10);
+        map.insert(2, 20);
+        map.insert(3, 30);
+
+        map.retain(|_, v| *v != 10);
+        assert_eq!(map.remove(&2), Some(20));
+        map.insert(4, 40);
+        map.insert(5, 50);
+
+        // Only 3 was left from before, so it is the one evicted
+        assert_eq!(map.insert(6, 60), Some((3, 30)));
+    }
+}
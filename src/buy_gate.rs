@@ -0,0 +1,1129 @@
+use crate::bonding_curve::BondingCurveState;
+use crate::config::Config;
+use crate::constants::*;
+use crate::error::{Result, SniperError};
+use crate::notifier::{Notifications, TradeEvent};
+use crate::risk_management::{count_holders, holder_velocity};
+use crate::scam_detection::{ScamDetector, ScamRecommendation, TokenMetadata, TradingData};
+use crate::priority_fee::{priority_fee_lamports, COMPUTE_UNIT_LIMIT, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS, LAMPORTS_PER_SIGNATURE};
+use crate::social_links::SocialLinkKind;
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// SOL kept back on top of the buy itself for the token account rent and fees.
+const BUY_FEE_RESERVE_SOL: f64 = 0.01;
+
+/// Longest `HOLDER_VELOCITY_DELAY_MS` accepted. The risk gate waits this long
+/// between holder samples, so the whole delay is added to every buy it passes.
+pub const MAX_HOLDER_VELOCITY_DELAY: Duration = Duration::from_secs(10);
+
+pub const DEFAULT_GATE_ORDER: &str = "naming,lists,structure,authorities,socials,liquidity,market_cap,economics,scam,risk,balance";
+
+/// Everything known about a freshly created token when deciding whether to buy it.
+#[derive(Debug, Clone)]
+pub struct BuyCandidate {
+    pub metadata: TokenMetadata,
+    pub associated_bonding_curve: Pubkey,
+    pub dev_buy_sol: f64,
+    pub market_cap_sol: f64,
+    /// `market_cap_sol` at `sol_price_usd`; 0 while no SOL price is known
+    pub market_cap_usd: f64,
+    pub sol_price_usd: f64,
+    pub create_structure: CreateStructure,
+}
+
+/// The shape of the transaction a token was created in, read straight from
+/// the streamed message. Coordinated launches tend to pack many wallets and
+/// buys into the create.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreateStructure {
+    pub signers: usize,
+    pub writable_accounts: usize,
+    /// PumpFun buys in the transaction, including ones made through another program
+    pub buys: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GateDecision {
+    Allow,
+    Deny(String),
+}
+
+/// The buy gates that can be placed in `BUY_GATE_ORDER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuyGateKind {
+    Naming,
+    Lists,
+    Structure,
+    Authorities,
+    Socials,
+    Liquidity,
+    MarketCap,
+    Economics,
+    Scam,
+    Risk,
+    Balance,
+}
+
+impl FromStr for BuyGateKind {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "naming" => Ok(BuyGateKind::Naming),
+            "lists" => Ok(BuyGateKind::Lists),
+            "structure" => Ok(BuyGateKind::Structure),
+            "authorities" => Ok(BuyGateKind::Authorities),
+            "socials" => Ok(BuyGateKind::Socials),
+            "liquidity" => Ok(BuyGateKind::Liquidity),
+            "market_cap" => Ok(BuyGateKind::MarketCap),
+            "economics" => Ok(BuyGateKind::Economics),
+            "scam" => Ok(BuyGateKind::Scam),
+            "risk" => Ok(BuyGateKind::Risk),
+            "balance" => Ok(BuyGateKind::Balance),
+            other => Err(SniperError::Config(format!(
+                "Unknown buy gate '{}', expected naming, lists, structure, authorities, socials, liquidity, market_cap, economics, scam, risk or balance", other
+            ))),
+        }
+    }
+}
+
+/// Characters a token's name or symbol can be refused for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameCharacterClass {
+    /// Control characters anywhere, e.g. NULs or ANSI escapes.
+    Control,
+    /// Nothing but emoji, ignoring whitespace and emoji joiners.
+    EmojiOnly,
+    /// Anything outside printable ASCII.
+    NonAscii,
+}
+
+impl NameCharacterClass {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            NameCharacterClass::Control => text.chars().any(char::is_control),
+            NameCharacterClass::EmojiOnly => {
+                let mut visible = text.chars().filter(|c| !c.is_whitespace() && !is_emoji_modifier(*c)).peekable();
+                visible.peek().is_some() && visible.all(is_emoji)
+            }
+            NameCharacterClass::NonAscii => !text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()),
+        }
+    }
+}
+
+impl fmt::Display for NameCharacterClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameCharacterClass::Control => write!(f, "control characters"),
+            NameCharacterClass::EmojiOnly => write!(f, "only emoji"),
+            NameCharacterClass::NonAscii => write!(f, "non-ASCII characters"),
+        }
+    }
+}
+
+impl FromStr for NameCharacterClass {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "control" => Ok(NameCharacterClass::Control),
+            "emoji_only" => Ok(NameCharacterClass::EmojiOnly),
+            "non_ascii" => Ok(NameCharacterClass::NonAscii),
+            other => Err(SniperError::Config(format!(
+                "Unknown character class '{}', expected control, emoji_only or non_ascii", other
+            ))),
+        }
+    }
+}
+
+/// Parses a comma-separated class list such as `control,emoji_only`.
+pub fn parse_character_classes(text: &str) -> std::result::Result<Vec<NameCharacterClass>, SniperError> {
+    let mut classes = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let class = entry.parse()?;
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    Ok(classes)
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, transport, flags and supplements
+        | 0x2600..=0x27BF // miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF // arrows, stars and shapes
+        | 0x2190..=0x21FF // arrows
+        | 0x2300..=0x23FF) // technical symbols such as the hourglass
+}
+
+/// Joiners, variation selectors and keycap marks that only modify emoji.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c as u32, 0x200D | 0xFE0E | 0xFE0F | 0x20E3 | 0xE0020..=0xE007F)
+}
+
+/// Parses a comma-separated gate order such as `lists,market_cap,balance`.
+pub fn parse_gate_order(text: &str) -> std::result::Result<Vec<BuyGateKind>, SniperError> {
+    let mut order = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let kind = entry.parse()?;
+        if order.contains(&kind) {
+            return Err(SniperError::Config(format!("Buy gate '{}' is listed twice", entry)));
+        }
+        order.push(kind);
+    }
+    Ok(order)
+}
+
+/// One step of the buy decision. Gates run in `BUY_GATE_ORDER` and the first
+/// deny stops the pipeline.
+#[async_trait]
+pub trait BuyGate: Send + Sync {
+    fn kind(&self) -> BuyGateKind;
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision>;
+}
+
+/// Runs `gates` in order, returning the first deny.
+pub async fn evaluate_gates(gates: &[Box<dyn BuyGate>], candidate: &BuyCandidate) -> Result<GateDecision> {
+    for gate in gates {
+        if let GateDecision::Deny(reason) = gate.check(candidate).await? {
+            return Ok(GateDecision::Deny(format!("{:?} gate: {}", gate.kind(), reason)));
+        }
+    }
+    Ok(GateDecision::Allow)
+}
+
+/// Builds the gates named in `config.buy_gate_order`. The naming, structure, socials and
+/// economics gates are only built when one of their limits is set, authority, scam and risk gates only when
+/// their features are enabled, and the balance gate is left out of dry runs
+/// since nothing is spent.
+pub fn build_gates(config: &Config, buyer: Pubkey, notifications: &Notifications) -> Vec<Box<dyn BuyGate>> {
+    let mut gates: Vec<Box<dyn BuyGate>> = Vec::new();
+    for kind in &config.buy_gate_order {
+        match kind {
+            BuyGateKind::Naming
+                if config.max_token_name_length.is_some()
+                    || config.max_token_symbol_length.is_some()
+                    || !config.rejected_name_characters.is_empty() =>
+            {
+                gates.push(Box::new(NamingGate {
+                    max_name_length: config.max_token_name_length,
+                    max_symbol_length: config.max_token_symbol_length,
+                    rejected_characters: config.rejected_name_characters.clone(),
+                }));
+            }
+            BuyGateKind::Lists => gates.push(Box::new(CreatorListGate::new(
+                config.creator_allowlist.iter().copied().collect(),
+                config.creator_blacklist.iter().copied().collect(),
+            ))),
+            BuyGateKind::Structure
+                if config.max_create_signers.is_some()
+                    || config.max_create_writable_accounts.is_some()
+                    || config.max_create_buys.is_some() =>
+            {
+                gates.push(Box::new(StructureGate {
+                    max_signers: config.max_create_signers,
+                    max_writable_accounts: config.max_create_writable_accounts,
+                    max_buys: config.max_create_buys,
+                }));
+            }
+            BuyGateKind::Authorities
+                if config.check_mint_authorities && (config.skip_mint_authority || config.skip_freeze_authority) =>
+            {
+                gates.push(Box::new(AuthorityGate {
+                    skip_mint_authority: config.skip_mint_authority,
+                    skip_freeze_authority: config.skip_freeze_authority,
+                }));
+            }
+            BuyGateKind::Socials if !config.required_social_links.is_empty() => {
+                gates.push(Box::new(SocialsGate { required: config.required_social_links.clone() }));
+            }
+            BuyGateKind::Liquidity => gates.push(Box::new(LiquidityGate { min_curve_sol: config.min_curve_sol })),
+            BuyGateKind::MarketCap => gates.push(Box::new(MarketCapGate {
+                min: config.min_market_cap(),
+                max: config.max_market_cap(),
+            })),
+            BuyGateKind::Economics
+                if config.max_trade_cost_pct_of_buy.is_some() || config.max_trade_cost_pct_of_profit.is_some() =>
+            {
+                let tip_lamports = if config.enable_jito { config.jito_tip_lamports } else { 0 };
+                let fixed_cost_lamports = tip_lamports
+                    + priority_fee_lamports(COMPUTE_UNIT_LIMIT, config.priority_fee_floor_micro_lamports)
+                    + priority_fee_lamports(COMPUTE_UNIT_LIMIT, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS)
+                    + 2 * LAMPORTS_PER_SIGNATURE;
+                gates.push(Box::new(EconomicsGate {
+                    buy_amount_sol: config.buy_amount_sol,
+                    take_profit_pct: config.take_profit_percentage,
+                    fixed_costs_sol: fixed_cost_lamports as f64 / LAMPORTS_PER_SOL as f64,
+                    max_cost_pct_of_buy: config.max_trade_cost_pct_of_buy,
+                    max_cost_pct_of_profit: config.max_trade_cost_pct_of_profit,
+                }));
+            }
+            BuyGateKind::Scam if config.enable_scam_detection => {
+                let mut detector = ScamDetector::new().with_deny_patterns(config.scam_deny_patterns.clone());
+                if let Some(path) = &config.scam_audit_log_path {
+                    detector = detector.with_audit_log(path);
+                }
+                gates.push(Box::new(ScamGate::new(detector, config.scam_exit_recommendation, notifications.clone())));
+            }
+            BuyGateKind::Risk if config.enable_risk_management => {
+                // Same-block snipes have no time to wait for a holder count
+                let holder_velocity = config.holder_velocity_delay
+                    .filter(|_| !config.same_block_snipe)
+                    .map(|delay| HolderVelocityCheck {
+                        rpc_client: config.nonblocking_rpc_client(&config.solana_rpc_endpoint),
+                        delay,
+                        min_velocity: config.min_holder_velocity,
+                    });
+                gates.push(Box::new(RiskGate {
+                    min_dev_buy_sol: config.min_dev_buy_sol,
+                    max_dev_buy_sol: config.max_dev_buy_sol,
+                    buy_amount_sol: config.buy_amount_sol,
+                    max_price_impact_pct: config.max_price_impact_pct,
+                    holder_velocity,
+                }));
+            }
+            BuyGateKind::Balance if !config.dry_run => {
+                gates.push(Box::new(BalanceGate {
+                    rpc_client: config.nonblocking_rpc_client(&config.solana_rpc_endpoint),
+                    wallet: buyer,
+                    required_sol: required_buy_sol(config, config.buy_amount_sol),
+                    reserve_sol: config.reserve_sol,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    let kinds: Vec<BuyGateKind> = gates.iter().map(|gate| gate.kind()).collect();
+    info!("🚧 Buy gates: {:?}", kinds);
+    gates
+}
+
+/// Refuses tokens whose name or symbol breaks the configured naming rules.
+/// Lengths are counted in characters. Runs on the parsed create instruction
+/// alone, so it is far cheaper than scam scoring.
+pub struct NamingGate {
+    pub max_name_length: Option<usize>,
+    pub max_symbol_length: Option<usize>,
+    pub rejected_characters: Vec<NameCharacterClass>,
+}
+
+impl NamingGate {
+    fn violation(&self, field: &str, text: &str, max_length: Option<usize>) -> Option<String> {
+        let length = text.chars().count();
+        if let Some(max_length) = max_length.filter(|max_length| length > *max_length) {
+            return Some(format!("{} {:?} is {} characters, over the {} allowed", field, text, length, max_length));
+        }
+        self.rejected_characters
+            .iter()
+            .find(|class| class.matches(text))
+            .map(|class| format!("{} {:?} contains {}", field, text, class))
+    }
+}
+
+#[async_trait]
+impl BuyGate for NamingGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Naming
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        let metadata = &candidate.metadata;
+        let violation = self
+            .violation("name", &metadata.name, self.max_name_length)
+            .or_else(|| self.violation("symbol", &metadata.symbol, self.max_symbol_length));
+        Ok(violation.map_or(GateDecision::Allow, GateDecision::Deny))
+    }
+}
+
+/// Only buys from allowlisted creators when an allowlist is set, and never
+/// from blacklisted ones.
+pub struct CreatorListGate {
+    allowlist: HashSet<Pubkey>,
+    blacklist: HashSet<Pubkey>,
+}
+
+impl CreatorListGate {
+    pub fn new(allowlist: HashSet<Pubkey>, blacklist: HashSet<Pubkey>) -> Self {
+        Self { allowlist, blacklist }
+    }
+}
+
+#[async_trait]
+impl BuyGate for CreatorListGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Lists
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        let creator = &candidate.metadata.creator;
+        if self.blacklist.contains(creator) {
+            return Ok(GateDecision::Deny(format!("creator {} is blacklisted", creator)));
+        }
+        if !self.allowlist.is_empty() && !self.allowlist.contains(creator) {
+            return Ok(GateDecision::Deny(format!("creator {} is not allowlisted", creator)));
+        }
+        Ok(GateDecision::Allow)
+    }
+}
+
+/// Refuses tokens created in a transaction with more signers, writable
+/// accounts or buys than allowed, the footprint of a sybil-bundled launch.
+pub struct StructureGate {
+    pub max_signers: Option<usize>,
+    pub max_writable_accounts: Option<usize>,
+    pub max_buys: Option<usize>,
+}
+
+#[async_trait]
+impl BuyGate for StructureGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Structure
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        let structure = candidate.create_structure;
+        for (what, count, max) in [
+            ("signers", structure.signers, self.max_signers),
+            ("writable accounts", structure.writable_accounts, self.max_writable_accounts),
+            ("buys", structure.buys, self.max_buys),
+        ] {
+            if let Some(max) = max.filter(|max| count > *max) {
+                return Ok(GateDecision::Deny(format!("create transaction has {} {}, above {}", count, what, max)));
+            }
+        }
+        Ok(GateDecision::Allow)
+    }
+}
+
+/// Refuses tokens whose creator can still mint more supply or freeze holders'
+/// accounts. It fails open: a mint that could not be read, often one too new
+/// for the RPC to see yet, is let through. PumpFun's own creates renounce both
+/// authorities, so this only catches mints set up some other way.
+pub struct AuthorityGate {
+    pub skip_mint_authority: bool,
+    pub skip_freeze_authority: bool,
+}
+
+#[async_trait]
+impl BuyGate for AuthorityGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Authorities
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        let Some(authorities) = candidate.metadata.authorities else {
+            return Ok(GateDecision::Allow);
+        };
+        if let Some(authority) = authorities.mint_authority.filter(|_| self.skip_mint_authority) {
+            return Ok(GateDecision::Deny(format!("mint authority {} not renounced", authority)));
+        }
+        if let Some(authority) = authorities.freeze_authority.filter(|_| self.skip_freeze_authority) {
+            return Ok(GateDecision::Deny(format!("freeze authority {} not renounced", authority)));
+        }
+        Ok(GateDecision::Allow)
+    }
+}
+
+/// Refuses tokens whose off-chain metadata carries none of the `required`
+/// social links. A token whose metadata could not be fetched in time is
+/// refused as well, since its links cannot be confirmed.
+pub struct SocialsGate {
+    pub required: Vec<SocialLinkKind>,
+}
+
+#[async_trait]
+impl BuyGate for SocialsGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Socials
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        let Some(socials) = &candidate.metadata.socials else {
+            return Ok(GateDecision::Deny("metadata could not be fetched to check social links".to_string()));
+        };
+        if socials.has_any(&self.required) {
+            return Ok(GateDecision::Allow);
+        }
+        let required: Vec<String> = self.required.iter().map(ToString::to_string).collect();
+        Ok(GateDecision::Deny(format!("no {} link in metadata", required.join(" or "))))
+    }
+}
+
+/// A market cap bound as configured: in SOL, which needs no price feed, or in
+/// USD, converted at the SOL price when a token is checked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketCapLimit {
+    Sol(f64),
+    Usd(f64),
+}
+
+impl MarketCapLimit {
+    /// The bound in SOL, or `None` for a USD bound while no SOL price is known.
+    pub fn in_sol(&self, sol_price_usd: f64) -> Option<f64> {
+        match *self {
+            MarketCapLimit::Sol(sol) => Some(sol),
+            MarketCapLimit::Usd(usd) => (sol_price_usd > 0.0).then(|| usd / sol_price_usd),
+        }
+    }
+
+    pub fn needs_sol_price(&self) -> bool {
+        matches!(self, MarketCapLimit::Usd(_))
+    }
+}
+
+impl fmt::Display for MarketCapLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketCapLimit::Sol(sol) => write!(f, "{:.2} SOL", sol),
+            MarketCapLimit::Usd(usd) => write!(f, "${:.2}", usd),
+        }
+    }
+}
+
+/// A market cap in SOL and, when the SOL price is known, in USD.
+pub fn format_market_cap(market_cap_sol: f64, sol_price_usd: f64) -> String {
+    if sol_price_usd > 0.0 {
+        format!("{:.2} SOL (${:.2})", market_cap_sol, market_cap_sol * sol_price_usd)
+    } else {
+        format!("{:.2} SOL (USD unavailable)", market_cap_sol)
+    }
+}
+
+/// Checks `market_cap_sol` against the configured band, or says why it is out
+/// of it. A USD bound cannot be checked without a SOL price and fails closed.
+pub fn check_market_cap_band(
+    market_cap_sol: f64,
+    sol_price_usd: f64,
+    min: MarketCapLimit,
+    max: Option<MarketCapLimit>,
+) -> std::result::Result<(), String> {
+    let market_cap = format_market_cap(market_cap_sol, sol_price_usd);
+    let in_sol = |limit: MarketCapLimit| {
+        limit.in_sol(sol_price_usd).ok_or_else(|| format!("no SOL price to check the {} market cap bound", limit))
+    };
+    if market_cap_sol < in_sol(min)? {
+        return Err(format!("market cap {} below minimum {}", market_cap, min));
+    }
+    if let Some(max) = max {
+        if market_cap_sol > in_sol(max)? {
+            return Err(format!("market cap {} above maximum {}", market_cap, max));
+        }
+    }
+    Ok(())
+}
+
+pub struct MarketCapGate {
+    pub min: MarketCapLimit,
+    pub max: Option<MarketCapLimit>,
+}
+
+#[async_trait]
+impl BuyGate for MarketCapGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::MarketCap
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        Ok(match check_market_cap_band(candidate.market_cap_sol, candidate.sol_price_usd, self.min, self.max) {
+            Ok(()) => GateDecision::Allow,
+            Err(reason) => GateDecision::Deny(reason),
+        })
+    }
+}
+
+/// Skips tokens whose bonding curve holds too little real SOL. The curve starts
+/// out with just the creator's deposit, so this needs no RPC and always runs,
+/// logging every candidate's curve SOL even when no minimum is set.
+pub struct LiquidityGate {
+    pub min_curve_sol: f64,
+}
+
+#[async_trait]
+impl BuyGate for LiquidityGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Liquidity
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        info!("💧 Curve SOL for {}: {:.4} SOL (minimum {:.4})",
+              candidate.metadata.mint, candidate.dev_buy_sol, self.min_curve_sol);
+        if candidate.dev_buy_sol < self.min_curve_sol {
+            return Ok(GateDecision::Deny(format!(
+                "curve holds {:.4} SOL, below minimum {:.4} SOL", candidate.dev_buy_sol, self.min_curve_sol
+            )));
+        }
+        Ok(GateDecision::Allow)
+    }
+}
+
+/// What a snipe is expected to cost and earn, in SOL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeEconomics {
+    /// Jito tip, priority fees and signature fees for the buy and the sell.
+    pub fixed_costs_sol: f64,
+    /// Lost buying and immediately selling back on the curve, PumpFun fees included.
+    pub round_trip_loss_sol: f64,
+    /// Gross gain from selling at the take-profit price, before costs.
+    pub expected_profit_sol: f64,
+}
+
+impl TradeEconomics {
+    pub fn total_cost_sol(&self) -> f64 {
+        self.fixed_costs_sol + self.round_trip_loss_sol
+    }
+}
+
+/// Refuses snipes whose costs outweigh what they can plausibly earn. Costs
+/// are the tip and fees of a buy and a sell plus the round-trip loss on the
+/// curve; the upside is what the position would sell for once the curve has
+/// been bought up to the take-profit price.
+pub struct EconomicsGate {
+    pub buy_amount_sol: f64,
+    pub take_profit_pct: f64,
+    pub fixed_costs_sol: f64,
+    pub max_cost_pct_of_buy: Option<f64>,
+    pub max_cost_pct_of_profit: Option<f64>,
+}
+
+impl EconomicsGate {
+    fn economics(&self, curve: &BondingCurveState) -> TradeEconomics {
+        let fee_rate = PUMPFUN_FEE_BASIS_POINTS as f64 / 10_000.0;
+        let round_trip_loss_sol = self.buy_amount_sol * (1.0 + fee_rate)
+            - curve.simulate_round_trip(self.buy_amount_sol, fee_rate);
+
+        // Selling into the curve realizes less than the take-profit spot price
+        let mut after = curve.clone();
+        let tokens = after.apply_buy(self.buy_amount_sol);
+        let target_price = self.buy_amount_sol / tokens * (1.0 + self.take_profit_pct / 100.0);
+        after.apply_buy(after.sol_needed_for_price(target_price));
+        let expected_profit_sol = after.calculate_sell_output(tokens).0 - self.buy_amount_sol;
+
+        TradeEconomics { fixed_costs_sol: self.fixed_costs_sol, round_trip_loss_sol, expected_profit_sol }
+    }
+
+    fn decide(&self, mint: &Pubkey, economics: &TradeEconomics) -> GateDecision {
+        let total_cost_sol = economics.total_cost_sol();
+        info!("💸 Economics for {}: cost {:.6} SOL (fixed {:.6} + round trip {:.6}) vs buy {:.4} SOL, expected profit {:.6} SOL",
+              mint, total_cost_sol, economics.fixed_costs_sol, economics.round_trip_loss_sol,
+              self.buy_amount_sol, economics.expected_profit_sol);
+
+        if let Some(max_pct) = self.max_cost_pct_of_buy {
+            let limit_sol = self.buy_amount_sol * max_pct / 100.0;
+            if total_cost_sol > limit_sol {
+                return GateDecision::Deny(format!(
+                    "trade costs {:.6} SOL exceed {:.1}% of the {:.4} SOL buy", total_cost_sol, max_pct, self.buy_amount_sol
+                ));
+            }
+        }
+        if let Some(max_pct) = self.max_cost_pct_of_profit {
+            let limit_sol = economics.expected_profit_sol.max(0.0) * max_pct / 100.0;
+            if total_cost_sol > limit_sol {
+                return GateDecision::Deny(format!(
+                    "trade costs {:.6} SOL exceed {:.1}% of the {:.6} SOL expected profit",
+                    total_cost_sol, max_pct, economics.expected_profit_sol
+                ));
+            }
+        }
+        GateDecision::Allow
+    }
+}
+
+#[async_trait]
+impl BuyGate for EconomicsGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Economics
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        let curve = BondingCurveState::from_initial_deposit(candidate.dev_buy_sol);
+        Ok(self.decide(&candidate.metadata.mint, &self.economics(&curve)))
+    }
+}
+
+/// Scores the new token with the `ScamDetector` and refuses anything at or
+/// above `deny_at`.
+pub struct ScamGate {
+    detector: ScamDetector,
+    deny_at: ScamRecommendation,
+    notifications: Notifications,
+}
+
+impl ScamGate {
+    pub fn new(detector: ScamDetector, deny_at: ScamRecommendation, notifications: Notifications) -> Self {
+        Self {
+            detector,
+            deny_at,
+            notifications,
+        }
+    }
+}
+
+#[async_trait]
+impl BuyGate for ScamGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Scam
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        // Nothing has traded yet: the dev is the only holder of the new curve
+        let trading_data = TradingData {
+            mint: candidate.metadata.mint,
+            liquidity: candidate.dev_buy_sol,
+            volume_24h: 0.0,
+            price_change_24h: 0.0,
+            holder_count: 1,
+            top_holder_percentage: None,
+            transaction_count: 0,
+            market_cap: candidate.market_cap_usd,
+            last_update: Instant::now(),
+        };
+        let analysis = self.detector.analyze_token(&candidate.metadata, &trading_data).await;
+        if analysis.recommendation < self.deny_at {
+            return Ok(GateDecision::Allow);
+        }
+
+        self.notifications.send(TradeEvent::ScamFlagged {
+            mint: analysis.mint,
+            scam_score: analysis.scam_score,
+            recommendation: analysis.recommendation,
+        });
+        Ok(GateDecision::Deny(format!(
+            "scam score {:.2} ({:?})", analysis.scam_score, analysis.recommendation
+        )))
+    }
+}
+
+struct HolderVelocityCheck {
+    rpc_client: NonblockingRpcClient,
+    delay: Duration,
+    min_velocity: f64,
+}
+
+/// Dev buy size, our own price impact, and optionally how fast the launch is
+/// gaining holders.
+pub struct RiskGate {
+    min_dev_buy_sol: f64,
+    max_dev_buy_sol: Option<f64>,
+    buy_amount_sol: f64,
+    max_price_impact_pct: f64,
+    holder_velocity: Option<HolderVelocityCheck>,
+}
+
+#[async_trait]
+impl BuyGate for RiskGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Risk
+    }
+
+    async fn check(&self, candidate: &BuyCandidate) -> Result<GateDecision> {
+        if candidate.dev_buy_sol < self.min_dev_buy_sol {
+            return Ok(GateDecision::Deny(format!(
+                "dev buy {:.4} SOL below minimum {:.4} SOL", candidate.dev_buy_sol, self.min_dev_buy_sol
+            )));
+        }
+        if let Some(max_dev_buy_sol) = self.max_dev_buy_sol.filter(|max| candidate.dev_buy_sol > *max) {
+            return Ok(GateDecision::Deny(format!(
+                "dev buy {:.4} SOL above maximum {:.4} SOL", candidate.dev_buy_sol, max_dev_buy_sol
+            )));
+        }
+
+        // Skip curves so thin that our own buy would move the price too far
+        let curve = BondingCurveState::from_initial_deposit(candidate.dev_buy_sol);
+        let price_impact_pct = curve.simulate_buy(self.buy_amount_sol, candidate.sol_price_usd).price_impact * 100.0;
+        info!("📊 Buy price impact for {}: {:.2}%", candidate.metadata.mint, price_impact_pct);
+        if price_impact_pct > self.max_price_impact_pct {
+            return Ok(GateDecision::Deny(format!(
+                "price impact {:.2}% above maximum {:.2}%", price_impact_pct, self.max_price_impact_pct
+            )));
+        }
+
+        // Sample the holder count twice, `delay` apart; the buy waits out the delay
+        if let Some(check) = &self.holder_velocity {
+            let mint = &candidate.metadata.mint;
+            let excluded = [candidate.associated_bonding_curve];
+            let first = count_holders(&check.rpc_client, mint, &excluded).await?;
+            let first_at = Instant::now();
+            tokio::time::sleep(check.delay).await;
+            let second = count_holders(&check.rpc_client, mint, &excluded).await?;
+
+            let velocity = holder_velocity(first, second, first_at.elapsed());
+            info!("👥 Holder velocity for {}: {:.2}/s ({} -> {})", mint, velocity, first, second);
+            if velocity < check.min_velocity {
+                return Ok(GateDecision::Deny(format!(
+                    "holder velocity {:.2}/s below minimum {:.2}/s", velocity, check.min_velocity
+                )));
+            }
+        }
+
+        Ok(GateDecision::Allow)
+    }
+}
+
+/// Refuses buys the wallet cannot pay for, including slippage, tip and fees,
+/// without touching `RESERVE_SOL`. Only buys are gated: sells may still spend
+/// the reserve, which is what it is kept for.
+pub struct BalanceGate {
+    rpc_client: NonblockingRpcClient,
+    wallet: Pubkey,
+    required_sol: f64,
+    /// Kept in the wallet for fees on later exits; buys never spend it.
+    reserve_sol: f64,
+}
+
+impl BalanceGate {
+    fn decide(&self, balance_sol: f64) -> GateDecision {
+        balance_decision(balance_sol, self.required_sol, self.reserve_sol)
+    }
+}
+
+/// The SOL a buy of `buy_amount_sol` needs in the wallet: the buy at full
+/// slippage, plus the Jito tip when tipping and the account rent and fees.
+pub fn required_buy_sol(config: &Config, buy_amount_sol: f64) -> f64 {
+    let jito_tip_sol = if config.enable_jito {
+        config.jito_tip_lamports as f64 / LAMPORTS_PER_SOL as f64
+    } else {
+        0.0
+    };
+    buy_amount_sol * (1.0 + config.buy_slippage_percentage / 100.0) + jito_tip_sol + BUY_FEE_RESERVE_SOL
+}
+
+/// Whether a wallet holding `balance_sol` can pay `required_sol` for a buy
+/// and still keep `reserve_sol` back.
+pub fn balance_decision(balance_sol: f64, required_sol: f64, reserve_sol: f64) -> GateDecision {
+    if balance_sol < required_sol {
+        return GateDecision::Deny(format!(
+            "wallet balance {:.4} SOL below the {:.4} SOL a buy needs", balance_sol, required_sol
+        ));
+    }
+    let spendable_sol = balance_sol - reserve_sol;
+    if spendable_sol < required_sol {
+        warn!("🛡️ Buy blocked by the {:.4} SOL reserve: {:.4} SOL balance leaves {:.4} SOL spendable, a buy needs {:.4}",
+              reserve_sol, balance_sol, spendable_sol.max(0.0), required_sol);
+        return GateDecision::Deny(format!(
+            "buy would dip into the {:.4} SOL reserve ({:.4} SOL spendable of {:.4} SOL, needs {:.4} SOL)",
+            reserve_sol, spendable_sol.max(0.0), balance_sol, required_sol
+        ));
+    }
+    GateDecision::Allow
+}
+
+#[async_trait]
+impl BuyGate for BalanceGate {
+    fn kind(&self) -> BuyGateKind {
+        BuyGateKind::Balance
+    }
+
+    async fn check(&self, _candidate: &BuyCandidate) -> Result<GateDecision> {
+        let balance_lamports = self.rpc_client
+            .get_balance(&self.wallet)
+            .await
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch wallet balance: {}", e)))?;
+        Ok(self.decide(balance_lamports as f64 / LAMPORTS_PER_SOL as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scam_detection::MintAuthorities;
+    use crate::social_links::SocialLinks;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn candidate(market_cap_usd: f64) -> BuyCandidate {
+        let sol_price_usd = 150.0;
+        BuyCandidate {
+            metadata: TokenMetadata {
+                mint: Pubkey::new_unique(),
+                name: "Token".to_string(),
+                symbol: "TKN".to_string(),
+                description: String::new(),
+                image_uri: String::new(),
+                creator: Pubkey::new_unique(),
+                creation_time: Instant::now(),
+                initial_supply: TOTAL_SUPPLY,
+                decimals: TOKEN_DECIMALS,
+                authorities: None,
+                socials: None,
+            },
+            associated_bonding_curve: Pubkey::new_unique(),
+            dev_buy_sol: 1.0,
+            market_cap_sol: market_cap_usd / sol_price_usd,
+            market_cap_usd,
+            sol_price_usd,
+            create_structure: CreateStructure { signers: 2, writable_accounts: 9, buys: 1 },
+        }
+    }
+
+    struct CountingGate {
+        kind: BuyGateKind,
+        decision: GateDecision,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl BuyGate for CountingGate {
+        fn kind(&self) -> BuyGateKind {
+            self.kind
+        }
+
+        async fn check(&self, _candidate: &BuyCandidate) -> Result<GateDecision> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.decision.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_deny_short_circuits() {
+        let calls: Vec<Arc<AtomicUsize>> = (0..3).map(|_| Arc::new(AtomicUsize::new(0))).collect();
+        let decisions = [
+            GateDecision::Allow,
+            GateDecision::Deny("too risky".to_string()),
+            GateDecision::Allow,
+        ];
+        let kinds = [BuyGateKind::Lists, BuyGateKind::Risk, BuyGateKind::Balance];
+        let gates: Vec<Box<dyn BuyGate>> = (0..3)
+            .map(|i| Box::new(CountingGate {
+                kind: kinds[i],
+                decision: decisions[i].clone(),
+                calls: Arc::clone(&calls[i]),
+            }) as Box<dyn BuyGate>)
+            .collect();
+
+        let decision = evaluate_gates(&gates, &candidate(10_000.0)).await.unwrap();
+        assert_eq!(decision, GateDecision::Deny("Risk gate: too risky".to_string()));
+        let counts: Vec<usize> = calls.iter().map(|calls| calls.load(Ordering::SeqCst)).collect();
+        assert_eq!(counts, [1, 1, 0]);
+
+        assert_eq!(evaluate_gates(&gates[..1], &candidate(10_000.0)).await.unwrap(), GateDecision::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_market_cap_band() {
+        let gate = MarketCapGate { min: MarketCapLimit::Usd(5_000.0), max: Some(MarketCapLimit::Usd(50_000.0)) };
+        assert!(matches!(gate.check(&candidate(1_000.0)).await.unwrap(), GateDecision::Deny(_)));
+        assert_eq!(gate.check(&candidate(10_000.0)).await.unwrap(), GateDecision::Allow);
+        assert!(matches!(gate.check(&candidate(60_000.0)).await.unwrap(), GateDecision::Deny(_)));
+    }
+
+    #[tokio::test]
+    async fn test_market_cap_band_in_sol_needs_no_price() {
+        let without_price = |market_cap_sol: f64| BuyCandidate {
+            market_cap_sol,
+            market_cap_usd: 0.0,
+            sol_price_usd: 0.0,
+            ..candidate(0.0)
+        };
+        let gate = MarketCapGate { min: MarketCapLimit::Sol(30.0), max: Some(MarketCapLimit::Sol(300.0)) };
+        assert_eq!(gate.check(&without_price(40.0)).await.unwrap(), GateDecision::Allow);
+        let GateDecision::Deny(reason) = gate.check(&without_price(20.0)).await.unwrap() else { panic!() };
+        assert_eq!(reason, "market cap 20.00 SOL (USD unavailable) below minimum 30.00 SOL");
+        assert!(matches!(gate.check(&without_price(400.0)).await.unwrap(), GateDecision::Deny(_)));
+
+        // A USD bound converts at the SOL price, and fails closed without one
+        let usd_gate = MarketCapGate { min: MarketCapLimit::Usd(4_500.0), max: None };
+        assert_eq!(usd_gate.check(&candidate(6_000.0)).await.unwrap(), GateDecision::Allow);
+        let GateDecision::Deny(reason) = usd_gate.check(&without_price(40.0)).await.unwrap() else { panic!() };
+        assert!(reason.contains("no SOL price"), "{}", reason);
+        assert_eq!(MarketCapLimit::Usd(4_500.0).in_sol(150.0), Some(30.0));
+        assert_eq!(format_market_cap(30.0, 150.0), "30.00 SOL ($4500.00)");
+    }
+
+    #[tokio::test]
+    async fn test_liquidity_gate_boundary() {
+        let gate = LiquidityGate { min_curve_sol: 1.0 };
+        let with_curve_sol = |sol: f64| BuyCandidate { dev_buy_sol: sol, ..candidate(10_000.0) };
+        assert!(matches!(gate.check(&with_curve_sol(0.999_999)).await.unwrap(), GateDecision::Deny(_)));
+        assert_eq!(gate.check(&with_curve_sol(1.0)).await.unwrap(), GateDecision::Allow);
+        assert_eq!(gate.check(&with_curve_sol(1.000_001)).await.unwrap(), GateDecision::Allow);
+
+        // Without a minimum even an empty curve passes
+        let off = LiquidityGate { min_curve_sol: 0.0 };
+        assert_eq!(off.check(&with_curve_sol(0.0)).await.unwrap(), GateDecision::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_creator_lists() {
+        let token = candidate(10_000.0);
+        let creator = token.metadata.creator;
+
+        let open = CreatorListGate::new(HashSet::new(), HashSet::new());
+        assert_eq!(open.check(&token).await.unwrap(), GateDecision::Allow);
+
+        let blacklisted = CreatorListGate::new(HashSet::new(), HashSet::from([creator]));
+        assert!(matches!(blacklisted.check(&token).await.unwrap(), GateDecision::Deny(_)));
+
+        let allowlisted = CreatorListGate::new(HashSet::from([creator]), HashSet::new());
+        assert_eq!(allowlisted.check(&token).await.unwrap(), GateDecision::Allow);
+        assert!(matches!(allowlisted.check(&candidate(10_000.0)).await.unwrap(), GateDecision::Deny(_)));
+    }
+
+    #[tokio::test]
+    async fn test_authority_gate() {
+        let authority = Pubkey::new_unique();
+        let gate = AuthorityGate { skip_mint_authority: true, skip_freeze_authority: true };
+        let mut token = candidate(10_000.0);
+
+        // Mint accounts the RPC could not return yet are let through
+        assert_eq!(gate.check(&token).await.unwrap(), GateDecision::Allow);
+
+        token.metadata.authorities = Some(MintAuthorities::default());
+        assert_eq!(gate.check(&token).await.unwrap(), GateDecision::Allow);
+
+        token.metadata.authorities = Some(MintAuthorities { mint_authority: None, freeze_authority: Some(authority) });
+        assert!(matches!(gate.check(&token).await.unwrap(), GateDecision::Deny(_)));
+        let freeze_allowed = AuthorityGate { skip_mint_authority: true, skip_freeze_authority: false };
+        assert_eq!(freeze_allowed.check(&token).await.unwrap(), GateDecision::Allow);
+
+        token.metadata.authorities = Some(MintAuthorities { mint_authority: Some(authority), freeze_authority: None });
+        assert!(matches!(gate.check(&token).await.unwrap(), GateDecision::Deny(_)));
+    }
+
+    #[tokio::test]
+    async fn test_structure_gate_thresholds() {
+        let gate = StructureGate { max_signers: Some(3), max_writable_accounts: Some(12), max_buys: None };
+        let mut token = candidate(10_000.0);
+        token.create_structure = CreateStructure { signers: 3, writable_accounts: 12, buys: 6 };
+        assert_eq!(gate.check(&token).await.unwrap(), GateDecision::Allow);
+
+        token.create_structure.signers = 4;
+        assert_eq!(
+            gate.check(&token).await.unwrap(),
+            GateDecision::Deny("create transaction has 4 signers, above 3".to_string())
+        );
+
+        token.create_structure = CreateStructure { signers: 1, writable_accounts: 13, buys: 6 };
+        assert!(matches!(gate.check(&token).await.unwrap(), GateDecision::Deny(_)));
+
+        let bundled = StructureGate { max_signers: None, max_writable_accounts: None, max_buys: Some(1) };
+        token.create_structure.buys = 1;
+        assert_eq!(bundled.check(&token).await.unwrap(), GateDecision::Allow);
+        token.create_structure.buys = 2;
+        assert!(matches!(bundled.check(&token).await.unwrap(), GateDecision::Deny(_)));
+    }
+
+    #[tokio::test]
+    async fn test_socials_gate() {
+        let gate = SocialsGate { required: vec![SocialLinkKind::Twitter, SocialLinkKind::Telegram] };
+        let mut token = candidate(10_000.0);
+        assert!(matches!(gate.check(&token).await.unwrap(), GateDecision::Deny(_)));
+
+        token.metadata.socials = Some(SocialLinks { website: Some("https://cat.example".to_string()), ..SocialLinks::default() });
+        assert_eq!(
+            gate.check(&token).await.unwrap(),
+            GateDecision::Deny("no twitter or telegram link in metadata".to_string())
+        );
+
+        token.metadata.socials = Some(SocialLinks { telegram: Some("https://t.me/cat".to_string()), ..SocialLinks::default() });
+        assert_eq!(gate.check(&token).await.unwrap(), GateDecision::Allow);
+    }
+
+    #[test]
+    fn test_balance_gate_keeps_reserve() {
+        let gate = BalanceGate {
+            rpc_client: NonblockingRpcClient::new("http://127.0.0.1:1".to_string()),
+            wallet: Pubkey::new_unique(),
+            required_sol: 0.12,
+            reserve_sol: 0.05,
+        };
+        assert!(matches!(gate.decide(0.1), GateDecision::Deny(reason) if reason.contains("below")));
+        // Enough for the buy, but only by spending the reserve
+        assert!(matches!(gate.decide(0.15), GateDecision::Deny(reason) if reason.contains("reserve")));
+        assert_eq!(gate.decide(0.18), GateDecision::Allow);
+
+        let no_reserve = BalanceGate { reserve_sol: 0.0, ..gate };
+        assert_eq!(no_reserve.decide(0.12), GateDecision::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_economics_gate() {
+        let gate = EconomicsGate {
+            buy_amount_sol: 0.1,
+            take_profit_pct: 50.0,
+            fixed_costs_sol: 0.002,
+            max_cost_pct_of_buy: Some(5.0),
+            max_cost_pct_of_profit: Some(20.0),
+        };
+        let economics = gate.economics(&BondingCurveState::from_initial_deposit(1.0));
+        // Only the 1% fee on each leg is lost on the round trip
+        assert!((economics.round_trip_loss_sol - 0.002).abs() < 1e-6);
+        // Selling into the curve realizes less than the full 50%
+        assert!(economics.expected_profit_sol > 0.03 && economics.expected_profit_sol < 0.05);
+        assert_eq!(gate.check(&candidate(10_000.0)).await.unwrap(), GateDecision::Allow);
+
+        let expensive_tip = EconomicsGate { fixed_costs_sol: 0.01, ..gate };
+        assert!(matches!(expensive_tip.decide(&Pubkey::new_unique(), &expensive_tip.economics(&BondingCurveState::new())),
+                         GateDecision::Deny(reason) if reason.contains("of the 0.1000 SOL buy")));
+
+        let small_upside = EconomicsGate { take_profit_pct: 5.0, max_cost_pct_of_buy: None, ..gate };
+        assert!(matches!(small_upside.check(&candidate(10_000.0)).await.unwrap(),
+                         GateDecision::Deny(reason) if reason.contains("expected profit")));
+    }
+
+    #[tokio::test]
+    async fn test_naming_gate() {
+        let gate = NamingGate {
+            max_name_length: Some(32),
+            max_symbol_length: Some(10),
+            rejected_characters: vec![NameCharacterClass::Control, NameCharacterClass::EmojiOnly],
+        };
+        let token = |name: &str, symbol: &str| {
+            let mut token = candidate(10_000.0);
+            token.metadata.name = name.to_string();
+            token.metadata.symbol = symbol.to_string();
+            token
+        };
+
+        // Lengths at the limit pass, one over is refused; characters, not bytes, count
+        assert_eq!(gate.check(&token(&"a".repeat(32), &"S".repeat(10))).await.unwrap(), GateDecision::Allow);
+        assert!(matches!(gate.check(&token(&"a".repeat(33), "SYM")).await.unwrap(), GateDecision::Deny(reason) if reason.contains("33 characters")));
+        assert!(matches!(gate.check(&token("Token", &"S".repeat(11))).await.unwrap(), GateDecision::Deny(reason) if reason.starts_with("symbol")));
+        assert_eq!(gate.check(&token(&"é".repeat(32), "SYM")).await.unwrap(), GateDecision::Allow);
+
+        assert!(matches!(gate.check(&token("Rug\u{0}\u{0}", "RUG")).await.unwrap(), GateDecision::Deny(reason) if reason.contains("control")));
+        assert!(matches!(gate.check(&token("🚀🌕 🚀", "MOON")).await.unwrap(), GateDecision::Deny(reason) if reason.contains("only emoji")));
+        assert!(matches!(gate.check(&token("Dog", "❤️")).await.unwrap(), GateDecision::Deny(_)));
+        assert_eq!(gate.check(&token("Moon 🚀", "MOON")).await.unwrap(), GateDecision::Allow);
+
+        assert!(NameCharacterClass::NonAscii.matches("Café"));
+        assert!(!NameCharacterClass::NonAscii.matches("Cafe Coin"));
+        assert_eq!(
+            parse_character_classes("control, Emoji-Only,control").unwrap(),
+            [NameCharacterClass::Control, NameCharacterClass::EmojiOnly]
+        );
+        assert!(parse_character_classes("vowels").is_err());
+    }
+
+    #[test]
+    fn test_parse_gate_order() {
+        assert_eq!(
+            parse_gate_order(DEFAULT_GATE_ORDER).unwrap(),
+            [
+                BuyGateKind::Naming,
+                BuyGateKind::Lists,
+                BuyGateKind::Structure,
+                BuyGateKind::Authorities,
+                BuyGateKind::Socials,
+                BuyGateKind::Liquidity,
+                BuyGateKind::MarketCap,
+                BuyGateKind::Economics,
+                BuyGateKind::Scam,
+                BuyGateKind::Risk,
+                BuyGateKind::Balance,
+            ]
+        );
+        assert_eq!(parse_gate_order(" balance , Market-Cap ").unwrap(), [BuyGateKind::Balance, BuyGateKind::MarketCap]);
+        assert!(parse_gate_order("scam,scam").is_err());
+        assert!(parse_gate_order("vibes").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::bounded_map::BoundedMap;
+use parking_lot::Mutex;
+use solana_sdk::pubkey::Pubkey;
+use std::time::{Duration, Instant};
+
+/// How long a mint's buyers are counted after its create. A buy decided later
+/// than this is no longer "early" under any sensible limit.
+pub const BUYER_WINDOW_TTL: Duration = Duration::from_secs(60);
+
+/// Counts the buys other wallets make into freshly created mints, for
+/// `MAX_BUYERS_AHEAD`: the bot only buys while it would still be among the
+/// first few buyers. Counting starts at the create; buys in the create's own
+/// transaction, i.e. the dev buy, are not counted.
+pub struct BuyerWindow {
+    /// When each mint's create was seen and how many buys followed it
+    counts: Mutex<BoundedMap<Pubkey, (Instant, usize)>>,
+    max_buyers_ahead: usize,
+    ttl: Duration,
+}
+
+impl BuyerWindow {
+    pub fn new(max_buyers_ahead: usize, max_tracked_tokens: usize) -> Self {
+        Self {
+            counts: Mutex::new(BoundedMap::new(max_tracked_tokens)),
+            max_buyers_ahead,
+            ttl: BUYER_WINDOW_TTL,
+        }
+    }
+
+    pub fn max_buyers_ahead(&self) -> usize {
+        self.max_buyers_ahead
+    }
+
+    /// Starts counting buys into `mint`, whose create was just seen. Mints
+    /// whose window has closed are dropped along the way.
+    pub fn track(&self, mint: Pubkey, now: Instant) {
+        let mut counts = self.counts.lock();
+        counts.retain(|_, (created_at, _)| now.duration_since(*created_at) < self.ttl);
+        counts.insert(mint, (now, 0));
+    }
+
+    /// Counts one buy into `mint`, returning the new count, or `None` when the
+    /// mint is not tracked or its window has closed.
+    pub fn record_buy(&self, mint: &Pubkey, now: Instant) -> Option<usize> {
+        let mut counts = self.counts.lock();
+        let (created_at, buyers) = counts.get_mut(mint)?;
+        if now.duration_since(*created_at) >= self.ttl {
+            return None;
+        }
+        *buyers += 1;
+        Some(*buyers)
+    }
+
+    /// Buys seen into `mint` since its create.
+    pub fn buyers_ahead(&self, mint: &Pubkey) -> usize {
+        self.counts.lock().get(mint).map_or(0, |(_, buyers)| *buyers)
+    }
+
+    /// Whether a buy into `mint` would still be early enough. Returns the
+    /// number of buyers ahead of us either way, for logging.
+    pub fn admits(&self, mint: &Pubkey) -> Result<usize, usize> {
+        let ahead = self.buyers_ahead(mint);
+        if ahead > self.max_buyers_ahead {
+            Err(ahead)
+        } else {
+            Ok(ahead)
+        }
+    }
+
+    /// Stops counting buys into `mint`.
+    pub fn forget(&self, mint: &Pubkey) {
+        self.counts.lock().remove(mint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buys_are_counted_from_the_create() {
+        let window = BuyerWindow::new(2, 100);
+        let (mint, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let created_at = Instant::now();
+
+        // Buys into mints whose create was never seen are ignored
+        assert_eq!(window.record_buy(&mint, created_at), None);
+
+        window.track(mint, created_at);
+        assert_eq!(window.admits(&mint), Ok(0));
+        assert_eq!(window.record_buy(&mint, created_at), Some(1));
+        assert_eq!(window.record_buy(&mint, created_at), Some(2));
+        assert_eq!(window.record_buy(&other, created_at), None);
+        assert_eq!(window.admits(&mint), Ok(2));
+
+        // One more buyer than allowed means we were too slow
+        window.record_buy(&mint, created_at);
+        assert_eq!(window.admits(&mint), Err(3));
+
+        // Counting stops once the window closes
+        assert_eq!(window.record_buy(&mint, created_at + BUYER_WINDOW_TTL), None);
+        window.track(other, created_at + BUYER_WINDOW_TTL);
+        assert_eq!(window.buyers_ahead(&mint), 0);
+
+        window.forget(&other);
+        assert_eq!(window.record_buy(&other, created_at + BUYER_WINDOW_TTL), None);
+    }
+}
@@ -1,31 +1,245 @@
-use anyhow::{anyhow, Result};
-use solana_sdk::pubkey::Pubkey;
+use crate::buy_gate::{
+    parse_character_classes, parse_gate_order, BuyGateKind, MarketCapLimit, NameCharacterClass, DEFAULT_GATE_ORDER,
+    MAX_HOLDER_VELOCITY_DELAY,
+};
+use crate::constants::{JITO_BLOCK_ENGINE_URL, SLOT_MILLIS};
+use crate::copy_trading::{AutoFollowConfig, CopyAmountSource};
+use crate::dead_man_switch::DeadManAction;
+use crate::funding::TopUpPolicy;
+use crate::grpc_manager::GeyserFilterMode;
+use crate::position_manager::{AverageDownPolicy, MigrationPolicy};
+use crate::priority_fee::{PriorityFeeLevel, PriorityFeeSourceKind};
+use crate::price_cache::{parse_price_sources, PriceHttpConfig, PriceSourceKind, PriceSourceStrategy, DEFAULT_USER_AGENT};
+use crate::pumpfun::{parse_discriminator, parse_events, Discriminators, PumpFunEvent};
+use crate::rug_monitor::RugReaction;
+use crate::same_block_execution::GasCeilingAction;
+use crate::scam_detection::{parse_deny_patterns, DenyPattern, ScamRecommendation};
+use crate::social_links::{parse_social_link_kinds, SocialLinkKind};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use std::collections::HashMap;
 use std::env;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub buyer_private_key: String,
+    /// Only needed to sign locally; a bot given its signer runs without it
+    pub buyer_private_key: Option<String>,
+    pub keypair_password: Option<String>,
     pub grpc_endpoint: String,
     pub grpc_auth_token: String,
+    pub stream_events: Vec<PumpFunEvent>,
+    /// Whether Geyser filters name their account in `account_include` or `account_required`
+    pub geyser_filter_mode: GeyserFilterMode,
+    pub max_transaction_accounts: usize,
     pub solana_rpc_endpoint: String,
+    pub send_rpc_endpoint: String,
+    /// Extra endpoints every buy is also submitted to when `fanout_send` is on
+    pub send_endpoints: Vec<String>,
+    pub fanout_send: bool,
+    pub rpc_timeout: Duration,
+    pub rpc_max_retries: u32,
+    /// Attempts after the first at reaching the RPC and gRPC endpoints on
+    /// startup, while they fail in a way that may clear up
+    pub startup_retries: u32,
+    /// Wait before the first startup retry, doubling for each one after
+    pub startup_backoff: Duration,
+    pub coingecko_api_key: Option<String>,
+    pub price_refresh_interval: Duration,
+    /// Longest wait between price refreshes while every source keeps failing
+    pub price_backoff_max: Duration,
+    /// Random spread applied to each backoff delay, as a fraction of it
+    pub price_backoff_jitter: f64,
+    /// Proxy for the SOL price requests; unset goes direct
+    pub price_proxy: Option<String>,
+    pub price_user_agent: String,
+    pub price_connect_timeout: Duration,
+    pub price_read_timeout: Duration,
+    /// Longest a whole price request may take
+    pub price_timeout: Duration,
+    /// Fixed SOL price for the migration and creator-revenue USD figures,
+    /// instead of the live price
+    pub migration_sol_price_usd: Option<f64>,
+    pub price_sources: Vec<PriceSourceKind>,
+    pub price_source_strategy: PriceSourceStrategy,
     pub market_cap_threshold_usd: f64,
+    pub max_market_cap_usd: Option<f64>,
+    /// Market cap band in SOL; each bound set here replaces its USD one, so
+    /// entry works without a SOL price
+    pub market_cap_threshold_sol: Option<f64>,
+    pub max_market_cap_sol: Option<f64>,
+    /// Minimum real SOL in a new token's bonding curve; 0 lets any curve through
+    pub min_curve_sol: f64,
+    /// Expected range of a brand-new curve's market cap in SOL, checked at startup
+    pub initial_market_cap_min_sol: f64,
+    pub initial_market_cap_max_sol: f64,
+    pub buy_gate_order: Vec<BuyGateKind>,
+    /// SOL the balance gate always leaves in the wallet for exit fees
+    pub reserve_sol: f64,
+    /// Naming gate limits, in characters; `None` leaves that length unchecked
+    pub max_token_name_length: Option<usize>,
+    pub max_token_symbol_length: Option<usize>,
+    pub rejected_name_characters: Vec<NameCharacterClass>,
+    /// Structure gate limits on the create transaction; `None` leaves that count unchecked
+    pub max_create_signers: Option<usize>,
+    pub max_create_writable_accounts: Option<usize>,
+    pub max_create_buys: Option<usize>,
+    pub creator_allowlist: Vec<Pubkey>,
+    pub creator_blacklist: Vec<Pubkey>,
     pub buy_amount_sol: f64,
+    pub min_dev_buy_sol: f64,
+    pub max_dev_buy_sol: Option<f64>,
+    pub max_concurrent_buys: usize,
+    /// Most positions held at once in tokens from any one creator; `None` is unlimited
+    pub max_positions_per_creator: Option<usize>,
+    pub global_buy_cooldown: Option<Duration>,
+    /// Most rival buys into a new mint, counted from its create, that a buy
+    /// may still follow; `None` buys regardless of how many got in first
+    pub max_buyers_ahead: Option<usize>,
+    pub max_tracked_tokens: usize,
+    pub tracked_token_max_age: Duration,
+    /// `USE_WSOL`, accepted for compatibility and ignored: PumpFun buys pay
+    /// the bonding curve in native SOL, and PumpSwap sells always unwrap their
+    /// WSOL proceeds
+    pub use_wsol: bool,
+    pub dry_run: bool,
+    /// In dry run, simulate each built buy against the RPC instead of only logging it
+    pub dry_run_simulate: bool,
+    pub warmup: bool,
+    pub fee_recipient_override: Option<Pubkey>,
     
     // New features configuration
     pub enable_jito: bool,
     pub enable_copy_trading: bool,
+    /// Wallets streamed alongside the sniper's filters when copy trading is enabled
+    pub copy_traders: Vec<Pubkey>,
+    /// Follows and drops traders by their PumpFun record on the stream;
+    /// `None` unless `ENABLE_AUTO_FOLLOW` is set
+    pub auto_follow: Option<AutoFollowConfig>,
+    /// PumpFun instruction discriminators, the built-in ones unless overridden
+    pub discriminators: Discriminators,
+    /// Known on-chain PumpFun trades the buy and sell discriminators are
+    /// checked against at startup
+    pub discriminator_reference_signatures: Vec<Signature>,
     pub enable_scam_detection: bool,
+    pub check_mint_authorities: bool,
+    pub skip_mint_authority: bool,
+    pub skip_freeze_authority: bool,
+    /// Fetch each new token's off-chain metadata for its social links, which
+    /// feed scam scoring
+    pub check_social_links: bool,
+    /// Social links a token needs at least one of to be bought; empty accepts any
+    pub required_social_links: Vec<SocialLinkKind>,
+    pub metadata_fetch_timeout: Duration,
+    pub scam_audit_log_path: Option<String>,
+    pub trade_journal_path: Option<String>,
+    /// JSON file the open positions are saved to on every change and
+    /// restored from on startup
+    pub positions_path: Option<String>,
+    /// Where the Prometheus metrics endpoint listens; off when unset
+    pub metrics_addr: Option<SocketAddr>,
+    pub scam_deny_patterns: Vec<DenyPattern>,
+    pub scam_recheck_interval: Option<Duration>,
+    pub scam_exit_recommendation: ScamRecommendation,
     pub enable_same_block_execution: bool,
+    pub same_block_snipe: bool,
+    /// Re-read the slot, from the Geyser slot feed when it is live, right
+    /// before a same-block snipe is sent, and aim from that.
+    pub same_block_verify_slot: bool,
+    /// Most any buy may pay in priority fees, in lamports; same-block snipes included
+    pub max_gas_price_lamports: u64,
+    pub gas_ceiling_action: GasCeilingAction,
+    pub ata_race_retries: u32,
+    /// Read the token account after a buy confirms and hold what it received
+    /// rather than the curve estimate
+    pub verify_token_balance: bool,
+    pub priority_fee_floor_micro_lamports: u64,
+    pub priority_fee_step_micro_lamports: u64,
+    pub priority_fee_ceiling_micro_lamports: u64,
+    /// Start buys at a sampled fee estimate, kept between the floor and ceiling
+    pub dynamic_priority_fee: bool,
+    pub priority_fee_source: PriorityFeeSourceKind,
+    pub priority_fee_level: PriorityFeeLevel,
+    /// Endpoint the fee source queries; defaults to the read RPC
+    pub priority_fee_estimate_url: String,
+    pub buy_land_retries: u32,
+    /// Commitment a buy must reach before its position counts as open
+    pub buy_confirmation_commitment: CommitmentConfig,
+    pub buy_min_confirmations: usize,
+    /// Buys still unconfirmed after this long are treated as dropped
+    pub pending_buy_timeout: Duration,
+    /// Tighter budget for an unconfirmed buy, in slots
+    pub pending_buy_max_slots: Option<u64>,
+    /// Confirm the bot's own buys and sells from the Geyser stream's
+    /// transaction statuses, with RPC only as a fallback
+    pub stream_transaction_statuses: bool,
     pub enable_risk_management: bool,
+    pub buy_slippage_percentage: f64,
+    pub sell_slippage_percentage: f64,
+    /// `MAX_SLIPPAGE_PERCENTAGE`, the old tolerance for both sides. Nothing
+    /// reads it; it only seeds whichever of the two above is not set.
+    #[deprecated(note = "use buy_slippage_percentage and sell_slippage_percentage")]
     pub max_slippage_percentage: f64,
+    /// Most a buy's cost may rise between detection and send; when set, the
+    /// curve is re-read right before every buy is signed
+    pub max_send_slippage_percentage: Option<f64>,
+    pub max_price_impact_pct: f64,
+    /// Skip snipes whose tip, fees and round-trip loss exceed this percent of the buy
+    pub max_trade_cost_pct_of_buy: Option<f64>,
+    /// Skip snipes whose costs exceed this percent of the profit expected at take-profit
+    pub max_trade_cost_pct_of_profit: Option<f64>,
+    /// Wait between the risk gate's two holder samples, added in full to the
+    /// latency of every buy the gate checks
+    pub holder_velocity_delay: Option<Duration>,
+    pub entry_delay: Option<Duration>,
+    pub min_holder_velocity: f64,
     pub stop_loss_percentage: f64,
     pub take_profit_percentage: f64,
+    pub max_hold_duration: Option<Duration>,
+    /// Extra buy into positions that dip without reaching their stop loss
+    pub average_down: Option<AverageDownPolicy>,
+    pub max_session_drawdown_sol: Option<f64>,
+    pub max_session_loss_usd: Option<f64>,
+    pub close_positions_on_halt: bool,
+    pub close_ata_after_sell: bool,
+    pub max_session_fees_sol: Option<f64>,
+    pub rug_drop_percentage: f64,
+    pub rug_window: Duration,
+    pub rug_reaction: RugReaction,
+    pub dead_man_timeout: Option<Duration>,
+    pub dead_man_action: DeadManAction,
+    /// Key of the wallet that tops up the buyer; only read when top-ups are on
+    pub funding_private_key: Option<String>,
+    /// Automatic top-ups of the buyer wallet; `None` unless `ENABLE_TOP_UP` is set
+    pub top_up: Option<TopUpPolicy>,
+    pub enable_notifications: bool,
+    pub notify_webhook_url: Option<String>,
+    pub discord_webhook_url: Option<String>,
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
     pub copy_trading_percentage: f64,
+    /// Where a followed trader's trade size is read from before
+    /// `COPY_TRADING_PERCENTAGE` is applied.
+    pub copy_amount_source: CopyAmountSource,
     pub jito_tip_lamports: u64,
+    pub jito_max_tip_fraction_of_buy: f64,
+    pub jito_min_tip_lamports: u64,
+    pub jito_strict: bool,
+    /// Block engine endpoint same-block snipes send their bundles to.
+    pub jito_block_engine_url: String,
     
     // Season 2 Features
     pub enable_migration_detection: bool,
+    /// What happens to a held position when an on-chain migration of its token is seen
+    pub on_migration: MigrationPolicy,
     pub enable_pump_swap_monitoring: bool,
+    pub pump_swap_poll_interval: Duration,
     pub enable_creator_revenue_tracking: bool,
     pub migration_threshold: f64,
 }
@@ -33,133 +247,959 @@ pub struct Config {
 impl Config {
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok(); // Load .env file if it exists
+        Self::from_source(|key| env::var(key).ok())
+    }
+
+    /// Loads settings from a TOML or YAML file, picked by its extension. Keys
+    /// are the environment variable names, matched case-insensitively, and any
+    /// variable that is also set in the environment overrides the file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        dotenv::dotenv().ok();
+        let values = parse_config_file(path.as_ref())?;
+        Self::from_source(|key| env::var(key).ok().or_else(|| values.get(key).cloned()))
+    }
 
-        let buyer_private_key = env::var("BUYER_PRIVATE_KEY_PATH")
-            .map_err(|_| anyhow!("BUYER_PRIVATE_KEY_PATH environment variable not set"))?;
+    fn from_source(source: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| source(key).ok_or(env::VarError::NotPresent);
 
-        let grpc_endpoint = env::var("GRPC_ENDPOINT")
+        let buyer_private_key = var("BUYER_PRIVATE_KEY_PATH").ok().filter(|key| !key.is_empty());
+        let keypair_password = var("KEYPAIR_PASSWORD").ok().filter(|password| !password.is_empty());
+
+        let grpc_endpoint = var("GRPC_ENDPOINT")
             .map_err(|_| anyhow!("GRPC_ENDPOINT environment variable not set"))?;
 
-        let grpc_auth_token = env::var("GRPC_AUTH_TOKEN")
+        let grpc_auth_token = var("GRPC_AUTH_TOKEN")
             .map_err(|_| anyhow!("GRPC_AUTH_TOKEN environment variable not set"))?;
 
-        let solana_rpc_endpoint = if let Ok(endpoint) = env::var("SOLANA_RPC_ENDPOINT") {
+        let stream_events = parse_events(
+            &var("STREAM_EVENTS").unwrap_or_else(|_| "create,migrate".to_string()),
+        )
+        .map_err(|e| anyhow!("Invalid STREAM_EVENTS value: {}", e))?;
+
+        let geyser_filter_mode = var("GEYSER_FILTER_MODE")
+            .unwrap_or_else(|_| "include".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid GEYSER_FILTER_MODE value: {}", e))?;
+
+        let max_transaction_accounts = var("MAX_TRANSACTION_ACCOUNTS")
+            .unwrap_or_else(|_| "256".to_string())
+            .parse()
+            .map_err(|_| anyhow!("Invalid MAX_TRANSACTION_ACCOUNTS value"))?;
+
+        let solana_rpc_endpoint = if let Ok(endpoint) = var("SOLANA_RPC_ENDPOINT") {
             endpoint
-        } else if let Ok(api_key) = env::var("HELIUS_API_KEY") {
+        } else if let Ok(api_key) = var("HELIUS_API_KEY") {
             format!("https://pomaded-lithotomies-xfbhnqagbt-dedicated.helius-rpc.com/?api-key={}", api_key)
         } else {
             return Err(anyhow!("Missing HELIUS_API_KEY or SOLANA_RPC_ENDPOINT"));
         };
 
-        let market_cap_threshold_usd = env::var("MARKET_CAP_THRESHOLD_USD")
+        // Transactions go to SEND_RPC_ENDPOINT when set; reads always use the primary endpoint
+        let send_rpc_endpoint = var("SEND_RPC_ENDPOINT")
+            .ok()
+            .filter(|endpoint| !endpoint.is_empty())
+            .unwrap_or_else(|| solana_rpc_endpoint.clone());
+
+        let send_endpoints: Vec<String> = var("SEND_ENDPOINTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|endpoint| !endpoint.is_empty() && *endpoint != send_rpc_endpoint)
+            .map(str::to_string)
+            .collect();
+
+        let fanout_send = var("FANOUT_SEND")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let rpc_timeout_ms: u64 = var("RPC_TIMEOUT_MS")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse()
+            .unwrap_or(5000);
+        let rpc_timeout = Duration::from_millis(rpc_timeout_ms);
+
+        let rpc_max_retries = var("RPC_MAX_RETRIES")
+            .unwrap_or_else(|_| "2".to_string())
+            .parse()
+            .unwrap_or(2);
+
+        let startup_retries = var("STARTUP_RETRIES")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5);
+        let startup_backoff = Duration::from_millis(
+            var("STARTUP_BACKOFF_MS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+        );
+
+        let coingecko_api_key = var("COINGECKO_API_KEY").ok().filter(|key| !key.is_empty());
+
+        let price_refresh_seconds: u64 = var("PRICE_REFRESH_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .map_err(|_| anyhow!("Invalid PRICE_REFRESH_SECONDS value"))?;
+        let price_refresh_interval = Duration::from_secs(price_refresh_seconds);
+
+        let price_backoff_max = Duration::from_secs(
+            var("PRICE_BACKOFF_MAX_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+        );
+        let price_backoff_jitter: f64 = var("PRICE_BACKOFF_JITTER")
+            .unwrap_or_else(|_| "0.2".to_string())
+            .parse()
+            .unwrap_or(0.2);
+
+        let price_proxy = var("PRICE_PROXY_URL").ok().filter(|url| !url.trim().is_empty());
+        let price_user_agent = var("PRICE_USER_AGENT").unwrap_or_else(|_| DEFAULT_USER_AGENT.to_string());
+        let price_connect_timeout = Duration::from_millis(
+            var("PRICE_CONNECT_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+        );
+        let price_read_timeout = Duration::from_millis(
+            var("PRICE_READ_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+        );
+        let price_timeout = Duration::from_millis(
+            var("PRICE_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+        );
+        let migration_sol_price_usd = match var("MIGRATION_SOL_PRICE_USD") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MIGRATION_SOL_PRICE_USD value"))?),
+            Err(_) => None,
+        };
+
+        let price_sources = parse_price_sources(&var("PRICE_SOURCES").unwrap_or_else(|_| "coingecko".to_string()))
+            .map_err(|e| anyhow!("Invalid PRICE_SOURCES value: {}", e))?;
+        let price_source_strategy = var("PRICE_SOURCE_STRATEGY")
+            .unwrap_or_else(|_| "sequential".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid PRICE_SOURCE_STRATEGY value: {}", e))?;
+
+        let market_cap_threshold_usd = var("MARKET_CAP_THRESHOLD_USD")
             .unwrap_or_else(|_| "8000.0".to_string())
             .parse()
             .map_err(|_| anyhow!("Invalid MARKET_CAP_THRESHOLD_USD value"))?;
 
-        let buy_amount_sol = env::var("BUY_AMOUNT_SOL")
+        let max_market_cap_usd = match var("MAX_MARKET_CAP_USD") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_MARKET_CAP_USD value"))?),
+            Err(_) => None,
+        };
+
+        let market_cap_threshold_sol = match var("MARKET_CAP_THRESHOLD_SOL") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MARKET_CAP_THRESHOLD_SOL value"))?),
+            Err(_) => None,
+        };
+        let max_market_cap_sol = match var("MAX_MARKET_CAP_SOL") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_MARKET_CAP_SOL value"))?),
+            Err(_) => None,
+        };
+
+        let min_curve_sol: f64 = var("MIN_CURVE_SOL")
+            .unwrap_or_else(|_| "0.0".to_string())
+            .parse()
+            .unwrap_or(0.0);
+
+        let initial_market_cap_min_sol: f64 = var("INITIAL_MARKET_CAP_MIN_SOL")
+            .unwrap_or_else(|_| "20.0".to_string())
+            .parse()
+            .unwrap_or(20.0);
+        let initial_market_cap_max_sol: f64 = var("INITIAL_MARKET_CAP_MAX_SOL")
+            .unwrap_or_else(|_| "40.0".to_string())
+            .parse()
+            .unwrap_or(40.0);
+
+        let buy_gate_order = parse_gate_order(
+            &var("BUY_GATE_ORDER").unwrap_or_else(|_| DEFAULT_GATE_ORDER.to_string()),
+        )
+        .map_err(|e| anyhow!("Invalid BUY_GATE_ORDER value: {}", e))?;
+
+        let reserve_sol: f64 = var("RESERVE_SOL")
+            .unwrap_or_else(|_| "0.0".to_string())
+            .parse()
+            .unwrap_or(0.0);
+
+        let max_token_name_length: usize = var("MAX_TOKEN_NAME_LENGTH")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let max_token_name_length = (max_token_name_length > 0).then_some(max_token_name_length);
+
+        let max_token_symbol_length: usize = var("MAX_TOKEN_SYMBOL_LENGTH")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let max_token_symbol_length = (max_token_symbol_length > 0).then_some(max_token_symbol_length);
+
+        let [max_create_signers, max_create_writable_accounts, max_create_buys] =
+            ["MAX_CREATE_SIGNERS", "MAX_CREATE_WRITABLE_ACCOUNTS", "MAX_CREATE_BUYS"].map(|name| {
+                let limit: usize = var(name).unwrap_or_else(|_| "0".to_string()).parse().unwrap_or(0);
+                (limit > 0).then_some(limit)
+            });
+
+        let rejected_name_characters = parse_character_classes(&var("REJECT_NAME_CHARACTERS").unwrap_or_default())
+            .map_err(|e| anyhow!("Invalid REJECT_NAME_CHARACTERS value: {}", e))?;
+
+        let creator_allowlist = parse_pubkey_list("CREATOR_ALLOWLIST", &var("CREATOR_ALLOWLIST").unwrap_or_default())?;
+        let creator_blacklist = parse_pubkey_list("CREATOR_BLACKLIST", &var("CREATOR_BLACKLIST").unwrap_or_default())?;
+        let copy_traders = parse_pubkey_list("COPY_TRADERS", &var("COPY_TRADERS").unwrap_or_default())?;
+
+        let mut discriminators = Discriminators::default();
+        for (name, slot) in [
+            ("CREATE_DISCRIMINATOR", &mut discriminators.create),
+            ("BUY_DISCRIMINATOR", &mut discriminators.buy),
+            ("SELL_DISCRIMINATOR", &mut discriminators.sell),
+            ("MIGRATE_DISCRIMINATOR", &mut discriminators.migrate),
+            ("INSTANT_MIGRATION_DISCRIMINATOR", &mut discriminators.instant_migration),
+        ] {
+            if let Ok(value) = var(name) {
+                *slot = parse_discriminator(&value).map_err(|e| anyhow!("Invalid {} value: {}", name, e))?;
+            }
+        }
+        discriminators.alternate_creates = var("ALTERNATE_CREATE_DISCRIMINATORS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                parse_discriminator(entry).map_err(|e| anyhow!("Invalid ALTERNATE_CREATE_DISCRIMINATORS entry: {}", e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let discriminator_reference_signatures = var("DISCRIMINATOR_REFERENCE_SIGNATURES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry.parse::<Signature>()
+                    .map_err(|_| anyhow!("Invalid DISCRIMINATOR_REFERENCE_SIGNATURES entry '{}'", entry))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let buy_amount_sol = var("BUY_AMOUNT_SOL")
             .unwrap_or_else(|_| "0.001".to_string())
             .parse()
             .map_err(|_| anyhow!("Invalid BUY_AMOUNT_SOL value"))?;
 
+        let min_dev_buy_sol = var("MIN_DEV_BUY_SOL")
+            .unwrap_or_else(|_| "0.0".to_string())
+            .parse()
+            .map_err(|_| anyhow!("Invalid MIN_DEV_BUY_SOL value"))?;
+
+        let max_dev_buy_sol = match var("MAX_DEV_BUY_SOL") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_DEV_BUY_SOL value"))?),
+            Err(_) => None,
+        };
+
+        let max_session_drawdown_sol = match var("MAX_SESSION_DRAWDOWN_SOL") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_SESSION_DRAWDOWN_SOL value"))?),
+            Err(_) => None,
+        };
+
+        let max_session_loss_usd = match var("MAX_SESSION_LOSS_USD") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_SESSION_LOSS_USD value"))?),
+            Err(_) => None,
+        };
+
+        let max_session_fees_sol = match var("MAX_SESSION_FEES_SOL") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_SESSION_FEES_SOL value"))?),
+            Err(_) => None,
+        };
+
+        let close_ata_after_sell = var("CLOSE_ATA_AFTER_SELL")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let close_positions_on_halt = var("CLOSE_POSITIONS_ON_HALT")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let max_concurrent_buys = var("MAX_CONCURRENT_BUYS")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse()
+            .map_err(|_| anyhow!("Invalid MAX_CONCURRENT_BUYS value"))?;
+
+        let max_positions_per_creator: usize = var("MAX_POSITIONS_PER_CREATOR")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let max_positions_per_creator = (max_positions_per_creator > 0).then_some(max_positions_per_creator);
+
+        let max_buyers_ahead = match var("MAX_BUYERS_AHEAD") {
+            Ok(value) => Some(value.parse().map_err(|_| anyhow!("Invalid MAX_BUYERS_AHEAD value"))?),
+            Err(_) => None,
+        };
+
+        let global_buy_cooldown_ms: u64 = var("GLOBAL_BUY_COOLDOWN_MS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let global_buy_cooldown = (global_buy_cooldown_ms > 0).then(|| Duration::from_millis(global_buy_cooldown_ms));
+
+        let max_tracked_tokens = var("MAX_TRACKED_TOKENS")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .map_err(|_| anyhow!("Invalid MAX_TRACKED_TOKENS value"))?;
+
+        let tracked_token_max_age_seconds: u64 = var("TRACKED_TOKEN_MAX_AGE_SECONDS")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .unwrap_or(86400);
+        let tracked_token_max_age = Duration::from_secs(tracked_token_max_age_seconds);
+
+        let use_wsol = var("USE_WSOL")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        if use_wsol {
+            warn!("USE_WSOL is ignored, buys pay the bonding curve in native SOL");
+        }
+
+        let dry_run = var("DRY_RUN")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let dry_run_simulate = var("DRY_RUN_SIMULATE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let warmup = var("WARMUP")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true);
+
+        let fee_recipient_override = match var("FEE_RECIPIENT_OVERRIDE") {
+            Ok(value) => Some(value.parse::<Pubkey>().map_err(|_| anyhow!("Invalid FEE_RECIPIENT_OVERRIDE value"))?),
+            Err(_) => None,
+        };
+
         // New features configuration
-        let enable_jito = env::var("ENABLE_JITO")
+        let enable_jito = var("ENABLE_JITO")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let enable_copy_trading = env::var("ENABLE_COPY_TRADING")
+        let enable_copy_trading = var("ENABLE_COPY_TRADING")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let enable_auto_follow: bool = var("ENABLE_AUTO_FOLLOW")
             .unwrap_or_else(|_| "false".to_string())
             .parse()
             .unwrap_or(false);
+        let auto_follow = enable_auto_follow.then(|| AutoFollowConfig {
+            min_success_rate: var("AUTO_FOLLOW_MIN_SUCCESS_RATE").unwrap_or_else(|_| "0.7".to_string()).parse().unwrap_or(0.7),
+            min_trades: var("AUTO_FOLLOW_MIN_TRADES").unwrap_or_else(|_| "10".to_string()).parse().unwrap_or(10),
+            unfollow_success_rate: var("AUTO_UNFOLLOW_SUCCESS_RATE").unwrap_or_else(|_| "0.5".to_string()).parse().unwrap_or(0.5),
+            recent_window: var("AUTO_FOLLOW_RECENT_WINDOW").unwrap_or_else(|_| "20".to_string()).parse().unwrap_or(20),
+            min_recent_trades: var("AUTO_UNFOLLOW_MIN_RECENT_TRADES").unwrap_or_else(|_| "5".to_string()).parse().unwrap_or(5),
+            max_traders: var("AUTO_FOLLOW_MAX_TRADERS").unwrap_or_else(|_| "5".to_string()).parse().unwrap_or(5),
+        });
 
-        let enable_scam_detection = env::var("ENABLE_SCAM_DETECTION")
+        let enable_scam_detection = var("ENABLE_SCAM_DETECTION")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let enable_same_block_execution = env::var("ENABLE_SAME_BLOCK_EXECUTION")
+        let check_mint_authorities = var("CHECK_MINT_AUTHORITIES")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let skip_mint_authority = var("SKIP_MINT_AUTHORITY")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let enable_risk_management = env::var("ENABLE_RISK_MANAGEMENT")
+        let skip_freeze_authority = var("SKIP_FREEZE_AUTHORITY")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let max_slippage_percentage = env::var("MAX_SLIPPAGE_PERCENTAGE")
-            .unwrap_or_else(|_| "20.0".to_string())
+        let check_social_links = var("CHECK_SOCIAL_LINKS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let required_social_links = parse_social_link_kinds(&var("REQUIRED_SOCIAL_LINKS").unwrap_or_default())
+            .map_err(|e| anyhow!("Invalid REQUIRED_SOCIAL_LINKS value: {}", e))?;
+        let metadata_fetch_timeout = Duration::from_millis(
+            var("METADATA_FETCH_TIMEOUT_MS")
+                .unwrap_or_else(|_| "1500".to_string())
+                .parse()
+                .unwrap_or(1500),
+        );
+
+        let scam_audit_log_path = var("SCAM_AUDIT_LOG_PATH").ok();
+
+        let trade_journal_path = var("TRADE_JOURNAL_PATH").ok().filter(|path| !path.is_empty());
+        let positions_path = var("POSITIONS_PATH").ok().filter(|path| !path.is_empty());
+        let metrics_addr = match var("METRICS_ADDR") {
+            Ok(value) if !value.is_empty() => Some(value.parse().map_err(|_| anyhow!("Invalid METRICS_ADDR value"))?),
+            _ => None,
+        };
+
+        // Extra scam name patterns, inline and/or from a file, merged with the built-ins
+        let mut scam_deny_patterns = match var("SCAM_DENY_PATTERNS") {
+            Ok(patterns) => parse_deny_patterns(&patterns).map_err(|e| anyhow!("{}", e))?,
+            Err(_) => Vec::new(),
+        };
+        if let Ok(path) = var("SCAM_DENY_PATTERNS_FILE") {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read SCAM_DENY_PATTERNS_FILE {}: {}", path, e))?;
+            scam_deny_patterns.extend(parse_deny_patterns(&contents).map_err(|e| anyhow!("{}", e))?);
+        }
+
+        // Re-scoring held tokens; 0 turns it off
+        let scam_recheck_seconds: u64 = var("SCAM_RECHECK_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30);
+        let scam_recheck_interval = (scam_recheck_seconds > 0).then(|| Duration::from_secs(scam_recheck_seconds));
+
+        let scam_exit_recommendation = var("SCAM_EXIT_RECOMMENDATION")
+            .unwrap_or_else(|_| "high_risk".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid SCAM_EXIT_RECOMMENDATION value: {}", e))?;
+
+        let enable_same_block_execution = var("ENABLE_SAME_BLOCK_EXECUTION")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true);
+
+        let same_block_snipe = var("SAME_BLOCK_SNIPE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let same_block_verify_slot = var("SAME_BLOCK_VERIFY_SLOT")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true);
+
+        let max_gas_price_lamports = var("MAX_GAS_PRICE_LAMPORTS")
+            .unwrap_or_else(|_| "1000000".to_string())
+            .parse()
+            .map_err(|_| anyhow!("Invalid MAX_GAS_PRICE_LAMPORTS value"))?;
+
+        let gas_ceiling_action = var("GAS_CEILING_ACTION")
+            .unwrap_or_else(|_| "skip".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid GAS_CEILING_ACTION value: {}", e))?;
+
+        let ata_race_retries = var("ATA_RACE_RETRIES")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .unwrap_or(1);
+
+        let verify_token_balance = var("VERIFY_TOKEN_BALANCE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let priority_fee_floor_micro_lamports = var("PRIORITY_FEE_FLOOR")
+            .unwrap_or_else(|_| "500000".to_string())
+            .parse()
+            .unwrap_or(500_000);
+
+        let priority_fee_step_micro_lamports = var("PRIORITY_FEE_STEP")
+            .unwrap_or_else(|_| "250000".to_string())
+            .parse()
+            .unwrap_or(250_000);
+
+        let priority_fee_ceiling_micro_lamports = var("PRIORITY_FEE_CEILING")
+            .unwrap_or_else(|_| "2000000".to_string())
+            .parse()
+            .unwrap_or(2_000_000);
+
+        let dynamic_priority_fee = var("DYNAMIC_PRIORITY_FEE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let priority_fee_source = var("PRIORITY_FEE_SOURCE")
+            .unwrap_or_else(|_| "rpc".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid PRIORITY_FEE_SOURCE value: {}", e))?;
+
+        let priority_fee_level = var("PRIORITY_FEE_LEVEL")
+            .unwrap_or_else(|_| "medium".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid PRIORITY_FEE_LEVEL value: {}", e))?;
+
+        let priority_fee_estimate_url = var("PRIORITY_FEE_ESTIMATE_URL")
+            .unwrap_or_else(|_| solana_rpc_endpoint.clone());
+
+        let buy_land_retries = var("BUY_LAND_RETRIES")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+
+        let buy_confirmation_commitment: CommitmentConfig = var("BUY_CONFIRMATION_COMMITMENT")
+            .unwrap_or_else(|_| "confirmed".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid BUY_CONFIRMATION_COMMITMENT value: {}", e))?;
+        let buy_min_confirmations = var("BUY_MIN_CONFIRMATIONS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let pending_buy_timeout_seconds: u64 = var("PENDING_BUY_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60);
+        let pending_buy_timeout = Duration::from_secs(pending_buy_timeout_seconds);
+        let pending_buy_max_slots: u64 = var("PENDING_BUY_MAX_SLOTS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let pending_buy_max_slots = (pending_buy_max_slots > 0).then_some(pending_buy_max_slots);
+        let stream_transaction_statuses = var("STREAM_TRANSACTION_STATUSES")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true);
+
+        let enable_risk_management = var("ENABLE_RISK_MANAGEMENT")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true);
+
+        // MAX_SLIPPAGE_PERCENTAGE is the old single setting; it still applies to
+        // whichever side has no setting of its own
+        let legacy_slippage = var("MAX_SLIPPAGE_PERCENTAGE").ok();
+        let max_slippage_percentage = legacy_slippage
+            .as_deref()
+            .unwrap_or("20.0")
             .parse()
             .unwrap_or(20.0);
+        if legacy_slippage.is_some() {
+            let unset: Vec<&str> = ["BUY_SLIPPAGE_PERCENTAGE", "SELL_SLIPPAGE_PERCENTAGE"]
+                .into_iter()
+                .filter(|key| var(key).is_err())
+                .collect();
+            if unset.is_empty() {
+                warn!("MAX_SLIPPAGE_PERCENTAGE is deprecated and ignored, BUY_SLIPPAGE_PERCENTAGE and SELL_SLIPPAGE_PERCENTAGE are both set");
+            } else {
+                warn!("MAX_SLIPPAGE_PERCENTAGE is deprecated, using its {}% as {}; set those instead",
+                      max_slippage_percentage, unset.join(" and "));
+            }
+        }
+
+        let buy_slippage_percentage = var("BUY_SLIPPAGE_PERCENTAGE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(max_slippage_percentage);
+
+        let sell_slippage_percentage = var("SELL_SLIPPAGE_PERCENTAGE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(max_slippage_percentage);
+
+        let max_send_slippage_percentage = var("MAX_SEND_SLIPPAGE_PERCENTAGE").ok().and_then(|value| value.parse().ok());
 
-        let stop_loss_percentage = env::var("STOP_LOSS_PERCENTAGE")
+        let max_price_impact_pct = var("MAX_PRICE_IMPACT_PCT")
             .unwrap_or_else(|_| "10.0".to_string())
             .parse()
             .unwrap_or(10.0);
 
-        let take_profit_percentage = env::var("TAKE_PROFIT_PERCENTAGE")
+        // Zero disables either limit of the economics gate
+        let max_trade_cost_pct_of_buy: f64 = var("MAX_TRADE_COST_PCT_OF_BUY")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0.0);
+        let max_trade_cost_pct_of_buy = (max_trade_cost_pct_of_buy > 0.0).then_some(max_trade_cost_pct_of_buy);
+        let max_trade_cost_pct_of_profit: f64 = var("MAX_TRADE_COST_PCT_OF_PROFIT")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0.0);
+        let max_trade_cost_pct_of_profit = (max_trade_cost_pct_of_profit > 0.0).then_some(max_trade_cost_pct_of_profit);
+
+        let holder_velocity_delay_ms: u64 = var("HOLDER_VELOCITY_DELAY_MS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let holder_velocity_delay = (holder_velocity_delay_ms > 0).then(|| Duration::from_millis(holder_velocity_delay_ms));
+
+        let entry_delay_ms: u64 = var("ENTRY_DELAY_MS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let entry_delay = (entry_delay_ms > 0).then(|| Duration::from_millis(entry_delay_ms));
+
+        let min_holder_velocity = var("MIN_HOLDER_VELOCITY")
+            .unwrap_or_else(|_| "1.0".to_string())
+            .parse()
+            .unwrap_or(1.0);
+
+        let stop_loss_percentage = var("STOP_LOSS_PERCENTAGE")
+            .unwrap_or_else(|_| "10.0".to_string())
+            .parse()
+            .unwrap_or(10.0);
+
+        let take_profit_percentage = var("TAKE_PROFIT_PERCENTAGE")
             .unwrap_or_else(|_| "50.0".to_string())
             .parse()
             .unwrap_or(50.0);
 
-        let copy_trading_percentage = env::var("COPY_TRADING_PERCENTAGE")
+        // Zero disables the hold timeout
+        let max_hold_seconds: u64 = var("MAX_HOLD_SECONDS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let max_hold_duration = (max_hold_seconds > 0).then(|| Duration::from_secs(max_hold_seconds));
+
+        // Averaging down is off unless a dip is set
+        let average_down = match var("AVERAGE_DOWN_DIP_PERCENTAGE") {
+            Ok(value) => Some(AverageDownPolicy {
+                dip_percentage: value.parse().map_err(|_| anyhow!("Invalid AVERAGE_DOWN_DIP_PERCENTAGE value"))?,
+                buy_amount_sol: var("AVERAGE_DOWN_BUY_SOL")
+                    .unwrap_or_else(|_| buy_amount_sol.to_string())
+                    .parse()
+                    .unwrap_or(buy_amount_sol),
+                max_buys: var("AVERAGE_DOWN_MAX_BUYS")
+                    .unwrap_or_else(|_| "1".to_string())
+                    .parse()
+                    .unwrap_or(1),
+            }),
+            Err(_) => None,
+        };
+
+        let rug_drop_percentage = var("RUG_DROP_PERCENTAGE")
+            .unwrap_or_else(|_| "30.0".to_string())
+            .parse()
+            .unwrap_or(30.0);
+
+        let rug_window_seconds: u64 = var("RUG_WINDOW_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10);
+        let rug_window = Duration::from_secs(rug_window_seconds);
+
+        let rug_reaction = var("RUG_REACTION")
+            .unwrap_or_else(|_| "exit".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid RUG_REACTION value: {}", e))?;
+
+        let dead_man_timeout_seconds: u64 = var("DEAD_MAN_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let dead_man_timeout = (dead_man_timeout_seconds > 0).then(|| Duration::from_secs(dead_man_timeout_seconds));
+
+        let dead_man_action = var("DEAD_MAN_ACTION")
+            .unwrap_or_else(|_| "close_all".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid DEAD_MAN_ACTION value: {}", e))?;
+
+        let funding_private_key = var("FUNDING_PRIVATE_KEY_PATH").ok().filter(|key| !key.is_empty());
+        let enable_top_up: bool = var("ENABLE_TOP_UP")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let sol_to_lamports = |sol: f64| (sol * 1_000_000_000.0) as u64;
+        let top_up = enable_top_up.then(|| TopUpPolicy {
+            threshold_lamports: sol_to_lamports(
+                var("TOP_UP_THRESHOLD_SOL").unwrap_or_else(|_| "0.1".to_string()).parse().unwrap_or(0.1),
+            ),
+            amount_lamports: sol_to_lamports(
+                var("TOP_UP_AMOUNT_SOL").unwrap_or_else(|_| "0.5".to_string()).parse().unwrap_or(0.5),
+            ),
+            max_per_window_lamports: sol_to_lamports(
+                var("TOP_UP_MAX_SOL_PER_WINDOW").unwrap_or_else(|_| "1.0".to_string()).parse().unwrap_or(1.0),
+            ),
+            window: Duration::from_secs(
+                var("TOP_UP_WINDOW_SECONDS").unwrap_or_else(|_| "3600".to_string()).parse().unwrap_or(3600),
+            ),
+        });
+
+        let enable_notifications = var("ENABLE_NOTIFICATIONS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let notify_webhook_url = var("NOTIFY_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+        let discord_webhook_url = var("DISCORD_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+        let telegram_bot_token = var("TELEGRAM_BOT_TOKEN").ok().filter(|token| !token.is_empty());
+        let telegram_chat_id = var("TELEGRAM_CHAT_ID").ok().filter(|chat_id| !chat_id.is_empty());
+
+        let copy_trading_percentage = var("COPY_TRADING_PERCENTAGE")
             .unwrap_or_else(|_| "10.0".to_string())
             .parse()
             .unwrap_or(10.0);
 
-        let jito_tip_lamports = env::var("JITO_TIP_LAMPORTS")
+        let copy_amount_source = var("COPY_AMOUNT_SOURCE")
+            .unwrap_or_else(|_| "transfer".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid COPY_AMOUNT_SOURCE value: {}", e))?;
+
+        let jito_tip_lamports = var("JITO_TIP_LAMPORTS")
             .unwrap_or_else(|_| "10000".to_string())
             .parse()
             .unwrap_or(10000);
 
+        let jito_max_tip_fraction_of_buy = var("JITO_MAX_TIP_FRACTION_OF_BUY")
+            .unwrap_or_else(|_| "0.05".to_string())
+            .parse()
+            .unwrap_or(0.05);
+
+        let jito_min_tip_lamports = var("JITO_MIN_TIP_LAMPORTS")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse()
+            .unwrap_or(1000);
+
+        let jito_strict = var("JITO_STRICT")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+
+        let jito_block_engine_url = var("JITO_BLOCK_ENGINE_URL")
+            .unwrap_or_else(|_| JITO_BLOCK_ENGINE_URL.to_string());
+
         // Season 2 Features
-        let enable_migration_detection = env::var("ENABLE_MIGRATION_DETECTION")
+        let enable_migration_detection = var("ENABLE_MIGRATION_DETECTION")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let enable_pump_swap_monitoring = env::var("ENABLE_PUMP_SWAP_MONITORING")
+        let on_migration = var("ON_MIGRATION")
+            .unwrap_or_else(|_| "hold".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid ON_MIGRATION value: {}", e))?;
+
+        let enable_pump_swap_monitoring = var("ENABLE_PUMP_SWAP_MONITORING")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let enable_creator_revenue_tracking = env::var("ENABLE_CREATOR_REVENUE_TRACKING")
+        let pump_swap_poll_seconds: u64 = var("PUMP_SWAP_POLL_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10);
+        let pump_swap_poll_interval = Duration::from_secs(pump_swap_poll_seconds);
+
+        let enable_creator_revenue_tracking = var("ENABLE_CREATOR_REVENUE_TRACKING")
             .unwrap_or_else(|_| "true".to_string())
             .parse()
             .unwrap_or(true);
 
-        let migration_threshold = env::var("MIGRATION_THRESHOLD")
+        let migration_threshold = var("MIGRATION_THRESHOLD")
             .unwrap_or_else(|_| "0.95".to_string())
             .parse()
             .unwrap_or(0.95);
 
-        Ok(Config {
+        #[allow(deprecated)]
+        let config = Config {
             buyer_private_key,
+            keypair_password,
             grpc_endpoint,
             grpc_auth_token,
+            stream_events,
+            geyser_filter_mode,
+            max_transaction_accounts,
             solana_rpc_endpoint,
+            send_rpc_endpoint,
+            send_endpoints,
+            fanout_send,
+            rpc_timeout,
+            rpc_max_retries,
+            startup_retries,
+            startup_backoff,
+            coingecko_api_key,
+            price_refresh_interval,
+            price_backoff_max,
+            price_backoff_jitter,
+            price_proxy,
+            price_user_agent,
+            price_connect_timeout,
+            price_read_timeout,
+            price_timeout,
+            migration_sol_price_usd,
+            price_sources,
+            price_source_strategy,
             market_cap_threshold_usd,
+            max_market_cap_usd,
+            market_cap_threshold_sol,
+            max_market_cap_sol,
+            min_curve_sol,
+            initial_market_cap_min_sol,
+            initial_market_cap_max_sol,
+            buy_gate_order,
+            reserve_sol,
+            max_token_name_length,
+            max_token_symbol_length,
+            rejected_name_characters,
+            max_create_signers,
+            max_create_writable_accounts,
+            max_create_buys,
+            creator_allowlist,
+            creator_blacklist,
             buy_amount_sol,
+            min_dev_buy_sol,
+            max_dev_buy_sol,
+            max_concurrent_buys,
+            max_positions_per_creator,
+            max_buyers_ahead,
+            global_buy_cooldown,
+            max_tracked_tokens,
+            tracked_token_max_age,
+            use_wsol,
+            dry_run,
+            dry_run_simulate,
+            warmup,
+            fee_recipient_override,
             enable_jito,
             enable_copy_trading,
+            copy_traders,
+            auto_follow,
+            discriminators,
+            discriminator_reference_signatures,
             enable_scam_detection,
+            check_mint_authorities,
+            skip_mint_authority,
+            skip_freeze_authority,
+            check_social_links,
+            required_social_links,
+            metadata_fetch_timeout,
+            scam_audit_log_path,
+            trade_journal_path,
+            positions_path,
+            metrics_addr,
+            scam_deny_patterns,
+            scam_recheck_interval,
+            scam_exit_recommendation,
             enable_same_block_execution,
+            same_block_snipe,
+            same_block_verify_slot,
+            max_gas_price_lamports,
+            gas_ceiling_action,
+            ata_race_retries,
+            verify_token_balance,
+            priority_fee_floor_micro_lamports,
+            priority_fee_step_micro_lamports,
+            priority_fee_ceiling_micro_lamports,
+            dynamic_priority_fee,
+            priority_fee_source,
+            priority_fee_level,
+            priority_fee_estimate_url,
+            buy_land_retries,
+            buy_confirmation_commitment,
+            buy_min_confirmations,
+            pending_buy_timeout,
+            pending_buy_max_slots,
+            stream_transaction_statuses,
             enable_risk_management,
+            buy_slippage_percentage,
+            max_send_slippage_percentage,
+            sell_slippage_percentage,
             max_slippage_percentage,
+            max_price_impact_pct,
+            max_trade_cost_pct_of_buy,
+            max_trade_cost_pct_of_profit,
+            holder_velocity_delay,
+            entry_delay,
+            min_holder_velocity,
             stop_loss_percentage,
             take_profit_percentage,
+            max_hold_duration,
+            average_down,
+            max_session_drawdown_sol,
+            max_session_loss_usd,
+            close_positions_on_halt,
+            close_ata_after_sell,
+            max_session_fees_sol,
+            rug_drop_percentage,
+            rug_window,
+            rug_reaction,
+            dead_man_timeout,
+            dead_man_action,
+            funding_private_key,
+            top_up,
+            enable_notifications,
+            notify_webhook_url,
+            discord_webhook_url,
+            telegram_bot_token,
+            telegram_chat_id,
             copy_trading_percentage,
+            copy_amount_source,
             jito_tip_lamports,
+            jito_max_tip_fraction_of_buy,
+            jito_min_tip_lamports,
+            jito_strict,
+            jito_block_engine_url,
             enable_migration_detection,
+            on_migration,
             enable_pump_swap_monitoring,
+            pump_swap_poll_interval,
             enable_creator_revenue_tracking,
             migration_threshold,
-        })
+        };
+        Ok(config)
+    }
+
+    /// How long a sent buy may stay unconfirmed: `pending_buy_timeout`, or
+    /// `pending_buy_max_slots` at the target slot time when that is sooner.
+    pub fn pending_buy_budget(&self) -> Duration {
+        let slots = self.pending_buy_max_slots.map(|slots| Duration::from_millis(slots.saturating_mul(SLOT_MILLIS)));
+        slots.map_or(self.pending_buy_timeout, |slots| slots.min(self.pending_buy_timeout))
+    }
+
+    /// The lower market cap bound: in SOL when `MARKET_CAP_THRESHOLD_SOL` is
+    /// set, in USD otherwise.
+    pub fn min_market_cap(&self) -> MarketCapLimit {
+        match self.market_cap_threshold_sol {
+            Some(sol) => MarketCapLimit::Sol(sol),
+            None => MarketCapLimit::Usd(self.market_cap_threshold_usd),
+        }
+    }
+
+    /// The upper market cap bound, if any, preferring `MAX_MARKET_CAP_SOL`.
+    pub fn max_market_cap(&self) -> Option<MarketCapLimit> {
+        self.max_market_cap_sol
+            .map(MarketCapLimit::Sol)
+            .or(self.max_market_cap_usd.map(MarketCapLimit::Usd))
+    }
+
+    /// Whether new tokens' metadata is fetched for their social links.
+    pub fn fetches_social_links(&self) -> bool {
+        self.check_social_links || !self.required_social_links.is_empty()
+    }
+
+    /// Proxy, user agent and timeouts for the SOL price requests.
+    pub fn price_http(&self) -> PriceHttpConfig {
+        PriceHttpConfig {
+            proxy: self.price_proxy.clone(),
+            user_agent: self.price_user_agent.clone(),
+            connect_timeout: self.price_connect_timeout,
+            read_timeout: self.price_read_timeout,
+            timeout: self.price_timeout,
+        }
+    }
+
+    /// An RPC client for `endpoint` that gives up after `rpc_timeout` rather
+    /// than the library's much longer default.
+    pub fn rpc_client(&self, endpoint: &str) -> RpcClient {
+        RpcClient::new_with_timeout_and_commitment(endpoint.to_string(), self.rpc_timeout, CommitmentConfig::default())
+    }
+
+    pub fn nonblocking_rpc_client(&self, endpoint: &str) -> NonblockingRpcClient {
+        NonblockingRpcClient::new_with_timeout_and_commitment(endpoint.to_string(), self.rpc_timeout, CommitmentConfig::default())
     }
 
     pub fn validate(&self) -> Result<()> {
         // Validate private key format (basic check)
-        if self.buyer_private_key.len() < 32 {
+        if self.buyer_private_key.as_ref().is_some_and(|key| key.len() < 32) {
             return Err(anyhow!("Invalid private key format"));
         }
 
@@ -172,15 +1212,390 @@ impl Config {
             return Err(anyhow!("Invalid Solana RPC endpoint URL"));
         }
 
+        if !self.send_rpc_endpoint.starts_with("http") {
+            return Err(anyhow!("Invalid send RPC endpoint URL"));
+        }
+
+        if let Some(endpoint) = self.send_endpoints.iter().find(|endpoint| !endpoint.starts_with("http")) {
+            return Err(anyhow!("Invalid SEND_ENDPOINTS URL '{}'", endpoint));
+        }
+
+        if self.fanout_send && self.send_endpoints.is_empty() {
+            return Err(anyhow!("FANOUT_SEND needs at least one SEND_ENDPOINTS entry besides the send RPC endpoint"));
+        }
+
+        if self.rpc_timeout.is_zero() {
+            return Err(anyhow!("RPC timeout must be positive"));
+        }
+        if self.startup_retries > 0 && self.startup_backoff.is_zero() {
+            return Err(anyhow!("STARTUP_BACKOFF_MS must be positive when STARTUP_RETRIES is set"));
+        }
+
+        if self.stream_events.is_empty() {
+            return Err(anyhow!("At least one stream event must be enabled"));
+        }
+
+        if self.max_buyers_ahead.is_some() && !self.stream_events.contains(&PumpFunEvent::Buy) {
+            return Err(anyhow!("MAX_BUYERS_AHEAD needs buy in STREAM_EVENTS to count buyers"));
+        }
+
+        if let Some(policy) = &self.average_down {
+            if policy.dip_percentage <= 0.0 || policy.dip_percentage >= self.stop_loss_percentage {
+                return Err(anyhow!("AVERAGE_DOWN_DIP_PERCENTAGE must be positive and below STOP_LOSS_PERCENTAGE"));
+            }
+            if policy.buy_amount_sol <= 0.0 {
+                return Err(anyhow!("AVERAGE_DOWN_BUY_SOL must be positive"));
+            }
+        }
+
+        if let Some(policy) = &self.auto_follow {
+            if !self.enable_copy_trading {
+                return Err(anyhow!("ENABLE_AUTO_FOLLOW needs ENABLE_COPY_TRADING"));
+            }
+            if policy.max_traders == 0 || policy.recent_window == 0 {
+                return Err(anyhow!("AUTO_FOLLOW_MAX_TRADERS and AUTO_FOLLOW_RECENT_WINDOW must be positive"));
+            }
+        }
+
+        if let Some(policy) = &self.top_up {
+            match &self.funding_private_key {
+                None => return Err(anyhow!("ENABLE_TOP_UP needs FUNDING_PRIVATE_KEY_PATH")),
+                Some(key) if Some(key) == self.buyer_private_key.as_ref() => {
+                    return Err(anyhow!("FUNDING_PRIVATE_KEY_PATH must be a different wallet from the buyer"));
+                }
+                Some(_) => {}
+            }
+            if policy.threshold_lamports == 0 || policy.amount_lamports == 0 {
+                return Err(anyhow!("TOP_UP_THRESHOLD_SOL and TOP_UP_AMOUNT_SOL must be positive"));
+            }
+            if policy.max_per_window_lamports < policy.amount_lamports || policy.window.is_zero() {
+                return Err(anyhow!("TOP_UP_MAX_SOL_PER_WINDOW must cover at least one TOP_UP_AMOUNT_SOL over a positive TOP_UP_WINDOW_SECONDS"));
+            }
+        }
+
+        if self.max_transaction_accounts == 0 {
+            return Err(anyhow!("Max transaction accounts must be positive"));
+        }
+
+        if self.pump_swap_poll_interval.is_zero() {
+            return Err(anyhow!("PumpSwap poll interval must be positive"));
+        }
+
+        if self.max_gas_price_lamports == 0 {
+            return Err(anyhow!("Max gas price must be positive"));
+        }
+
+        if self.priority_fee_ceiling_micro_lamports < self.priority_fee_floor_micro_lamports {
+            return Err(anyhow!("Priority fee ceiling must be at least the floor"));
+        }
+
+        if self.dynamic_priority_fee && !self.priority_fee_estimate_url.starts_with("http") {
+            return Err(anyhow!("Priority fee estimate URL must be an HTTP(S) URL"));
+        }
+
+        if self.price_refresh_interval.is_zero() {
+            return Err(anyhow!("Price refresh interval must be positive"));
+        }
+
+        if self.price_backoff_max < self.price_refresh_interval {
+            return Err(anyhow!("PRICE_BACKOFF_MAX_SECONDS must be at least PRICE_REFRESH_SECONDS"));
+        }
+
+        if !(0.0..1.0).contains(&self.price_backoff_jitter) {
+            return Err(anyhow!("PRICE_BACKOFF_JITTER must be between 0 and 1"));
+        }
+
+        if self.fetches_social_links() && self.metadata_fetch_timeout.is_zero() {
+            return Err(anyhow!("METADATA_FETCH_TIMEOUT_MS must be positive"));
+        }
+
+        if self.price_connect_timeout.is_zero() || self.price_read_timeout.is_zero() || self.price_timeout.is_zero() {
+            return Err(anyhow!("PRICE_CONNECT_TIMEOUT_MS, PRICE_READ_TIMEOUT_MS and PRICE_TIMEOUT_MS must be positive"));
+        }
+        if self.migration_sol_price_usd.is_some_and(|price| price <= 0.0) {
+            return Err(anyhow!("MIGRATION_SOL_PRICE_USD must be positive"));
+        }
+
+        self.price_http().build_client()?;
+
+        if self.price_sources.is_empty() {
+            return Err(anyhow!("At least one price source must be configured"));
+        }
+
+        if self.price_source_strategy == PriceSourceStrategy::Median && self.price_sources.len() < 3 {
+            return Err(anyhow!("The median price strategy needs at least three PRICE_SOURCES to reject an outlier"));
+        }
+
         // Validate numeric values
         if self.market_cap_threshold_usd <= 0.0 {
             return Err(anyhow!("Market cap threshold must be positive"));
         }
 
+        if self.min_curve_sol < 0.0 {
+            return Err(anyhow!("MIN_CURVE_SOL must not be negative"));
+        }
+
+        if self.max_market_cap_usd.is_some_and(|max| max <= self.market_cap_threshold_usd) {
+            return Err(anyhow!("Max market cap must be above the market cap threshold"));
+        }
+
+        if self.market_cap_threshold_sol.is_some_and(|min| min <= 0.0) {
+            return Err(anyhow!("MARKET_CAP_THRESHOLD_SOL must be positive"));
+        }
+
+        if let (MarketCapLimit::Sol(min), Some(MarketCapLimit::Sol(max))) = (self.min_market_cap(), self.max_market_cap()) {
+            if max <= min {
+                return Err(anyhow!("MAX_MARKET_CAP_SOL must be above the SOL market cap threshold"));
+            }
+        }
+
+        if self.initial_market_cap_min_sol <= 0.0 || self.initial_market_cap_max_sol <= self.initial_market_cap_min_sol {
+            return Err(anyhow!("INITIAL_MARKET_CAP_MAX_SOL must be above a positive INITIAL_MARKET_CAP_MIN_SOL"));
+        }
+
+        // Two instructions sharing a discriminator means one of them is never recognized
+        let named = self.discriminators.named();
+        for (i, (name, value)) in named.iter().enumerate() {
+            if let Some((other, _)) = named[i + 1..].iter().find(|(_, other)| other == value) {
+                return Err(anyhow!("The {} and {} discriminators are both {}", name, other, hex::encode(value)));
+            }
+        }
+        for alternate in &self.discriminators.alternate_creates {
+            if let Some((name, _)) = named.iter().find(|(name, value)| *name != "create" && value == alternate) {
+                return Err(anyhow!("The alternate create discriminator {} is also the {} one", hex::encode(alternate), name));
+            }
+        }
+
+        if self.reserve_sol < 0.0 {
+            return Err(anyhow!("RESERVE_SOL cannot be negative"));
+        }
+
         if self.buy_amount_sol <= 0.0 {
             return Err(anyhow!("Buy amount must be positive"));
         }
 
+        if self.buy_slippage_percentage < 0.0 {
+            return Err(anyhow!("Buy slippage cannot be negative"));
+        }
+
+        if self.max_send_slippage_percentage.is_some_and(|pct| pct < 0.0) {
+            return Err(anyhow!("MAX_SEND_SLIPPAGE_PERCENTAGE cannot be negative"));
+        }
+
+        if !(0.0..100.0).contains(&self.sell_slippage_percentage) {
+            return Err(anyhow!("Sell slippage must be at least 0 and below 100"));
+        }
+
+        if self.pending_buy_timeout.is_zero() {
+            return Err(anyhow!("Pending buy timeout must be positive"));
+        }
+
+        if self.max_price_impact_pct <= 0.0 {
+            return Err(anyhow!("Max price impact must be positive"));
+        }
+
+        if self.holder_velocity_delay.is_some_and(|delay| delay > MAX_HOLDER_VELOCITY_DELAY) {
+            return Err(anyhow!(
+                "HOLDER_VELOCITY_DELAY_MS cannot exceed {}ms; the delay is added to every buy",
+                MAX_HOLDER_VELOCITY_DELAY.as_millis()
+            ));
+        }
+
+        if self.min_holder_velocity < 0.0 {
+            return Err(anyhow!("Minimum holder velocity cannot be negative"));
+        }
+
+        if self.rug_drop_percentage <= 0.0 || self.rug_drop_percentage > 100.0 {
+            return Err(anyhow!("Rug drop percentage must be between 0 and 100"));
+        }
+
+        if self.max_concurrent_buys == 0 {
+            return Err(anyhow!("Max concurrent buys must be at least 1"));
+        }
+
+        if self.same_block_snipe && !(self.enable_jito && self.enable_same_block_execution) {
+            return Err(anyhow!("SAME_BLOCK_SNIPE requires ENABLE_JITO and ENABLE_SAME_BLOCK_EXECUTION"));
+        }
+        // Same-block snipes skip the metadata fetch, so every one would be refused
+        if self.same_block_snipe && !self.required_social_links.is_empty() {
+            return Err(anyhow!("REQUIRED_SOCIAL_LINKS cannot be used with SAME_BLOCK_SNIPE, which skips the metadata fetch"));
+        }
+
+        if self.telegram_bot_token.is_some() != self.telegram_chat_id.is_some() {
+            return Err(anyhow!("TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID must be set together"));
+        }
+
+        if self.enable_notifications
+            && self.notify_webhook_url.is_none()
+            && self.discord_webhook_url.is_none()
+            && self.telegram_bot_token.is_none()
+        {
+            return Err(anyhow!("ENABLE_NOTIFICATIONS needs a webhook, Discord or Telegram destination"));
+        }
+
+        if self.max_tracked_tokens == 0 {
+            return Err(anyhow!("Max tracked tokens must be at least 1"));
+        }
+
+        if self.min_dev_buy_sol < 0.0 {
+            return Err(anyhow!("Minimum dev buy cannot be negative"));
+        }
+
+        if self.max_session_drawdown_sol.is_some_and(|limit| limit <= 0.0) {
+            return Err(anyhow!("Max session drawdown must be positive"));
+        }
+
+        if self.max_session_loss_usd.is_some_and(|limit| limit <= 0.0) {
+            return Err(anyhow!("Max session loss must be positive"));
+        }
+
+        if self.max_session_fees_sol.is_some_and(|budget| budget <= 0.0) {
+            return Err(anyhow!("Max session fees must be positive"));
+        }
+
+        if let Some(max_dev_buy_sol) = self.max_dev_buy_sol {
+            if max_dev_buy_sol < self.min_dev_buy_sol {
+                return Err(anyhow!("Maximum dev buy must be at least the minimum dev buy"));
+            }
+        }
+
         Ok(())
     }
 }
+
+/// A single setting in a config file. Lists are joined with commas, matching
+/// how list-valued environment variables such as `STREAM_EVENTS` are written.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ConfigValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    fn into_setting(self) -> String {
+        match self {
+            ConfigValue::Bool(value) => value.to_string(),
+            ConfigValue::Integer(value) => value.to_string(),
+            ConfigValue::Float(value) => value.to_string(),
+            ConfigValue::String(value) => value,
+            ConfigValue::List(values) => values
+                .into_iter()
+                .map(ConfigValue::into_setting)
+                .collect::<Vec<_>>()
+                .join(","),
+        }
+    }
+}
+
+/// Parses a comma-separated list of base58 public keys.
+fn parse_pubkey_list(name: &str, text: &str) -> Result<Vec<Pubkey>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| entry.parse::<Pubkey>().map_err(|_| anyhow!("Invalid {} entry '{}'", name, entry)))
+        .collect()
+}
+
+/// Reads a flat TOML or YAML file into settings keyed by upper-cased name.
+fn parse_config_file(path: &Path) -> Result<HashMap<String, String>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+    let values: HashMap<String, ConfigValue> = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text)
+            .with_context(|| format!("Invalid TOML in {}", path.display()))?,
+        Some("yaml" | "yml") => serde_yaml::from_str(&text)
+            .with_context(|| format!("Invalid YAML in {}", path.display()))?,
+        _ => return Err(anyhow!("Config file {} must end in .toml, .yaml or .yml", path.display())),
+    };
+
+    Ok(values
+        .into_iter()
+        .map(|(key, value)| (key.to_uppercase(), value.into_setting()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_toml_and_yaml_files_parse_to_the_same_settings() {
+        let toml = write_config("config.toml", r#"
+            buy_amount_sol = 0.05
+            DRY_RUN = true
+            max_concurrent_buys = 2
+            stream_events = ["create", "migrate"]
+            grpc_endpoint = "https://grpc.example.com"
+        "#);
+        let yaml = write_config("config.yaml", "
+buy_amount_sol: 0.05
+DRY_RUN: true
+max_concurrent_buys: 2
+stream_events: [create, migrate]
+grpc_endpoint: https://grpc.example.com
+");
+
+        let from_toml = parse_config_file(&toml).unwrap();
+        assert_eq!(from_toml.get("BUY_AMOUNT_SOL").unwrap(), "0.05");
+        assert_eq!(from_toml.get("DRY_RUN").unwrap(), "true");
+        assert_eq!(from_toml.get("MAX_CONCURRENT_BUYS").unwrap(), "2");
+        assert_eq!(from_toml.get("STREAM_EVENTS").unwrap(), "create,migrate");
+        assert_eq!(parse_config_file(&yaml).unwrap(), from_toml);
+
+        assert!(parse_config_file(&write_config("config.json", "{}")).is_err());
+        assert!(parse_config_file(&write_config("nested.toml", "[jito]\ntip = 1")).is_err());
+    }
+
+    #[test]
+    fn test_sources_fill_config_and_earlier_sources_win() {
+        let file: HashMap<String, String> = [
+            ("BUYER_PRIVATE_KEY_PATH", "key"),
+            ("GRPC_ENDPOINT", "https://grpc.example.com"),
+            ("GRPC_AUTH_TOKEN", "token"),
+            ("SOLANA_RPC_ENDPOINT", "https://rpc.example.com"),
+            ("BUY_AMOUNT_SOL", "0.05"),
+            ("MAX_CONCURRENT_BUYS", "2"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let overrides = HashMap::from([("BUY_AMOUNT_SOL".to_string(), "0.1".to_string())]);
+
+        let config = Config::from_source(|key| overrides.get(key).or_else(|| file.get(key)).cloned()).unwrap();
+        assert_eq!(config.buy_amount_sol, 0.1);
+        assert_eq!(config.max_concurrent_buys, 2);
+        assert_eq!(config.solana_rpc_endpoint, "https://rpc.example.com");
+        assert_eq!(config.buyer_private_key.as_deref(), Some("key"));
+
+        // A bot handed its signer needs no key of its own
+        let without_key = Config::from_source(|key| file.get(key).filter(|_| key != "BUYER_PRIVATE_KEY_PATH").cloned()).unwrap();
+        assert_eq!(without_key.buyer_private_key, None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_legacy_slippage_fills_the_unset_side() {
+        let values: HashMap<&str, &str> = HashMap::from([
+            ("GRPC_ENDPOINT", "https://grpc.example.com"),
+            ("GRPC_AUTH_TOKEN", "token"),
+            ("SOLANA_RPC_ENDPOINT", "https://rpc.example.com"),
+            ("MAX_SLIPPAGE_PERCENTAGE", "15"),
+            ("SELL_SLIPPAGE_PERCENTAGE", "30"),
+        ]);
+
+        let config = Config::from_source(|key| values.get(key).map(|value| value.to_string())).unwrap();
+        assert_eq!(config.max_slippage_percentage, 15.0);
+        assert_eq!((config.buy_slippage_percentage, config.sell_slippage_percentage), (15.0, 30.0));
+    }
+}
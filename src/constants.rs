@@ -4,9 +4,21 @@ use std::str::FromStr;
 // PumpFun program ID (verified current as of 2024)
 pub const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 
+// Signs every PumpFun migrate transaction, so it doubles as a migration filter
+pub const PUMP_FUN_MIGRATION_AUTHORITY: &str = "39azUYFWPz3VHgKCf3VChUwbpURdCHRxjWVowf5jUJjg";
+
+// PumpSwap AMM program ID (destination of PumpFun migrations)
+pub const PUMP_SWAP_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+pub const PUMP_SWAP_PROTOCOL_FEE_RECIPIENT: &str = "62qc2CNXwrYqQScmEdiZFFAnJR262PxWEuNQtxfafNgV";
+pub const PUMP_SWAP_FEE_BASIS_POINTS: u64 = 25; // 0.20% LP + 0.05% protocol
+pub const PUMPFUN_FEE_BASIS_POINTS: u64 = 100; // protocol + creator fee, charged on top of the curve cost
+
 // Constants
 pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
 pub const TOTAL_SUPPLY: u64 = 1_000_000_000;
+pub const TOKEN_DECIMALS: u8 = 6;
+pub const TOKEN_ACCOUNT_RENT_LAMPORTS: u64 = 2_039_280; // Rent-exempt minimum of a 165-byte SPL token account
+pub const SLOT_MILLIS: u64 = 400; // Target slot time
 
 // Updated bonding curve constants (2024 - Season 2)
 pub const INITIAL_VIRTUAL_SOL: f64 = 30.0;
@@ -20,6 +32,7 @@ pub const CREATOR_REVENUE_SHARE: f64 = 0.01; // 1% revenue share for creators
 // Jito configuration for ultra-fast transactions
 pub const JITO_TIP_ACCOUNT: &str = "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY";
 pub const JITO_FEE_ACCOUNT: &str = "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL";
+pub const JITO_BLOCK_ENGINE_URL: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
 
 // Risk management constants
 pub const MAX_SLIPPAGE_PERCENTAGE: f64 = 20.0;
@@ -31,6 +44,7 @@ pub const KNOWN_GLOBAL: &str = "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf";
 pub const KNOWN_EVENT_AUTH: &str = "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1";
 pub const KNOWN_SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
 pub const KNOWN_TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
 pub const KNOWN_METADATA_PROGRAM: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
 pub const KNOWN_ATA_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
 pub const KNOWN_COMPUTE_BUDGET: &str = "ComputeBudget111111111111111111111111111111";
@@ -42,8 +56,9 @@ pub const FEE_RECIPIENT: &str = "G5UZAVbAf46s7cKWoyKu8kYTip9DGTpbLZ2qa9Aq69dP";
 // Updated discriminators (2024)
 pub const CREATE_DISCRIMINATOR: [u8; 8] = [0x18, 0x1e, 0xc8, 0x28, 0x05, 0x1c, 0x07, 0x77];
 pub const PUMPFUN_BUY_DISCRIMINATOR: [u8; 8] = [0x66, 0x06, 0x3d, 0x12, 0x01, 0xda, 0xeb, 0xea];
-pub const PUMPFUN_SELL_DISCRIMINATOR: [u8; 8] = [0x33, 0xe6, 0x85, 0x4a, 0x5a, 0x2d, 0x07, 0x1a];
+pub const PUMPFUN_SELL_DISCRIMINATOR: [u8; 8] = [0x33, 0xe6, 0x85, 0xa4, 0x01, 0x7f, 0x83, 0xad];
 pub const PUMPFUN_CLOSE_DISCRIMINATOR: [u8; 8] = [0x41, 0x13, 0x77, 0x1f, 0x4c, 0x0e, 0x8a, 0x2b];
+pub const PUMPFUN_MIGRATE_DISCRIMINATOR: [u8; 8] = [0x9b, 0xea, 0xe7, 0x92, 0xec, 0x9e, 0xa2, 0x1e];
 
 // Copy trading discriminators
 pub const COPY_TRADE_DISCRIMINATOR: [u8; 8] = [0x52, 0x8a, 0x9c, 0x3d, 0x1e, 0x4f, 0x7b, 0x2c];
@@ -1,6 +1,9 @@
+use crate::bounded_map::{BoundedMap, DEFAULT_MAX_TRACKED_TOKENS};
 use crate::error::{Result, SniperError};
-use solana_sdk::pubkey::Pubkey;
-use std::collections::HashMap;
+use crate::pumpfun::{ObservedTrade, PumpFunEvent};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signature};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
 use std::time::{Duration, Instant};
 use tracing::{info, warn, error};
 use serde::{Deserialize, Serialize};
@@ -24,6 +27,8 @@ pub struct CopyTradeConfig {
     pub copy_percentage: f64, // Percentage of trader's position to copy
     pub max_copy_amount_sol: f64,
     pub cooldown_between_copies: Duration,
+    pub max_trade_history: usize,
+    pub seen_signature_ttl: Duration, // How long a source transaction is remembered for dedup
 }
 
 impl Default for CopyTradeConfig {
@@ -35,6 +40,27 @@ impl Default for CopyTradeConfig {
             copy_percentage: 0.1, // Copy 10% of trader's position
             max_copy_amount_sol: 0.01, // Max 0.01 SOL per copy
             cooldown_between_copies: Duration::from_secs(5),
+            max_trade_history: 10_000,
+            seen_signature_ttl: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Per-trader settings that take precedence over `CopyTradeConfig`. Fields
+/// left as `None` fall back to the global config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraderOverrides {
+    pub copy_percentage: Option<f64>,
+    pub max_copy_amount_sol: Option<f64>,
+    pub enabled: bool, // Disabled traders stay followed but are never copied
+}
+
+impl Default for TraderOverrides {
+    fn default() -> Self {
+        Self {
+            copy_percentage: None,
+            max_copy_amount_sol: None,
+            enabled: true,
         }
     }
 }
@@ -42,8 +68,10 @@ impl Default for CopyTradeConfig {
 pub struct CopyTradingEngine {
     config: CopyTradeConfig,
     followed_traders: HashMap<Pubkey, TraderProfile>,
+    trader_overrides: HashMap<Pubkey, TraderOverrides>,
     recent_copies: HashMap<Pubkey, Instant>,
-    trade_history: Vec<TradeRecord>,
+    seen_signatures: HashMap<Signature, Instant>,
+    trade_history: VecDeque<TradeRecord>,
 }
 
 #[derive(Debug, Clone)]
@@ -62,13 +90,79 @@ pub enum TradeAction {
     Sell,
 }
 
+impl TradeAction {
+    pub fn from_event(event: PumpFunEvent) -> Option<Self> {
+        match event {
+            PumpFunEvent::Buy => Some(TradeAction::Buy),
+            PumpFunEvent::Sell => Some(TradeAction::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// Where the SOL size of a followed trader's trade is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyAmountSource {
+    /// What the trader's buy actually paid into the bonding curve, from the
+    /// transaction's inner system transfers. Sells move no SOL through the
+    /// system program, so they, and buys without a transfer, use the limit.
+    Transfer,
+    /// The instruction's own limit: a buy's `max_sol_cost`, which includes
+    /// the trader's slippage, or a sell's `min_sol_output`.
+    Limit,
+}
+
+impl FromStr for CopyAmountSource {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "transfer" => Ok(CopyAmountSource::Transfer),
+            "limit" => Ok(CopyAmountSource::Limit),
+            other => Err(SniperError::Config(format!(
+                "Unknown copy amount source '{}', expected transfer or limit", other
+            ))),
+        }
+    }
+}
+
+/// A followed trader's buy or sell, sized from the trader's own transaction.
+#[derive(Debug, Clone)]
+pub struct FollowedTrade {
+    pub action: TradeAction,
+    pub token: Pubkey,
+    pub amount_sol: f64,
+    /// Raw tokens bought or sold.
+    pub token_amount: u64,
+}
+
+impl FollowedTrade {
+    /// Sizes `trade` by `source`. `transferred_lamports` is what the trader's
+    /// inner system transfers paid into the bonding curve, 0 if none were seen.
+    pub fn new(trade: &ObservedTrade, transferred_lamports: u64, source: CopyAmountSource) -> Option<Self> {
+        let action = TradeAction::from_event(trade.event)?;
+        let lamports = match (source, &action) {
+            (CopyAmountSource::Transfer, TradeAction::Buy) if transferred_lamports > 0 => transferred_lamports,
+            _ => trade.sol_limit_lamports,
+        };
+        Some(Self {
+            action,
+            token: trade.mint,
+            amount_sol: lamports as f64 / LAMPORTS_PER_SOL as f64,
+            token_amount: trade.token_amount,
+        })
+    }
+}
+
 impl CopyTradingEngine {
     pub fn new(config: CopyTradeConfig) -> Self {
         Self {
             config,
             followed_traders: HashMap::new(),
+            trader_overrides: HashMap::new(),
             recent_copies: HashMap::new(),
-            trade_history: Vec::new(),
+            seen_signatures: HashMap::new(),
+            trade_history: VecDeque::new(),
         }
     }
 
@@ -102,39 +196,81 @@ impl CopyTradingEngine {
         }
     }
 
-    pub fn should_copy_trade(&mut self, trader: &Pubkey, token: &Pubkey, action: &TradeAction, amount_sol: f64) -> Result<bool> {
+    /// Sets copy settings for one trader, replacing any earlier overrides.
+    pub fn set_trader_overrides(&mut self, trader: Pubkey, overrides: TraderOverrides) {
+        info!("Set copy overrides for trader {}: {:?}", trader, overrides);
+        self.trader_overrides.insert(trader, overrides);
+    }
+
+    /// Returns a trader to the global copy settings.
+    pub fn clear_trader_overrides(&mut self, trader: &Pubkey) {
+        if self.trader_overrides.remove(trader).is_some() {
+            info!("Cleared copy overrides for trader {}", trader);
+        }
+    }
+
+    pub fn trader_overrides(&self, trader: &Pubkey) -> Option<&TraderOverrides> {
+        self.trader_overrides.get(trader)
+    }
+
+    /// Decides whether to mirror the trader's transaction `source_signature`,
+    /// returning the SOL to copy it with, or `None` to leave it. The same
+    /// transaction can arrive from several endpoints during failover, so each
+    /// source signature is only ever considered once.
+    pub fn should_copy_trade(&mut self, trader: &Pubkey, source_signature: &Signature, token: &Pubkey, action: &TradeAction, amount_sol: f64) -> Result<Option<f64>> {
         // Check if trader is being followed
         let profile = match self.followed_traders.get(trader) {
             Some(profile) => profile,
-            None => return Ok(false),
+            None => return Ok(None),
         };
 
+        // Check replay of an already seen source transaction
+        let ttl = self.config.seen_signature_ttl;
+        self.seen_signatures.retain(|_, seen_at| seen_at.elapsed() < ttl);
+        if self.seen_signatures.insert(*source_signature, Instant::now()).is_some() {
+            warn!("Skipping duplicate copy of {} from {}", source_signature, trader);
+            return Ok(None);
+        }
+
         // Check cooldown
         if let Some(last_copy) = self.recent_copies.get(token) {
             if last_copy.elapsed() < self.config.cooldown_between_copies {
-                return Ok(false);
+                return Ok(None);
             }
         }
 
         // Check if trader meets criteria
         if profile.success_rate < self.config.min_success_rate {
-            return Ok(false);
+            return Ok(None);
         }
 
         if profile.reputation_score < self.config.min_reputation_score {
-            return Ok(false);
+            return Ok(None);
         }
 
-        // Calculate copy amount
-        let copy_amount = (amount_sol * self.config.copy_percentage).min(self.config.max_copy_amount_sol);
+        // Calculate copy amount, preferring this trader's own settings
+        let overrides = self.trader_overrides.get(trader);
+        if overrides.is_some_and(|overrides| !overrides.enabled) {
+            return Ok(None);
+        }
+        let copy_percentage = overrides
+            .and_then(|overrides| overrides.copy_percentage)
+            .unwrap_or(self.config.copy_percentage);
+        let max_copy_amount_sol = overrides
+            .and_then(|overrides| overrides.max_copy_amount_sol)
+            .unwrap_or(self.config.max_copy_amount_sol);
+        let copy_amount = (amount_sol * copy_percentage).min(max_copy_amount_sol);
         
         if copy_amount <= 0.0 {
-            return Ok(false);
+            return Ok(None);
         }
 
         // Record the copy trade
         self.recent_copies.insert(*token, Instant::now());
-        self.trade_history.push(TradeRecord {
+        if self.trade_history.len() >= self.config.max_trade_history.max(1) {
+            self.trade_history.pop_front();
+        }
+        self.trade_history.push_back(TradeRecord {
             trader: *trader,
             token: *token,
             action: action.clone(),
@@ -146,7 +282,7 @@ impl CopyTradingEngine {
         info!("Copying trade from {}: {:?} {} SOL worth of {}", 
               trader, action, copy_amount, token);
         
-        Ok(true)
+        Ok(Some(copy_amount))
     }
 
     pub fn update_trade_result(&mut self, trader: &Pubkey, token: &Pubkey, success: bool) {
@@ -172,6 +308,13 @@ impl CopyTradingEngine {
         }
     }
 
+    /// Drops trade records and copy cooldowns older than `max_age`.
+    pub fn cleanup_old_records(&mut self, max_age: Duration) {
+        self.trade_history.retain(|record| record.timestamp.elapsed() < max_age);
+        self.recent_copies.retain(|_, copied_at| copied_at.elapsed() < max_age);
+        self.seen_signatures.retain(|_, seen_at| seen_at.elapsed() < max_age);
+    }
+
     fn calculate_reputation_score(&self, profile: &TraderProfile) -> f64 {
         let success_weight = 0.6;
         let activity_weight = 0.2;
@@ -245,6 +388,10 @@ pub struct TraderAnalysis {
 
 pub struct TraderDiscovery {
     known_good_traders: Vec<Pubkey>,
+    min_success_rate: f64,
+    min_trades: u32,
+    /// Every trade seen per trader, across all the batches fed in so far
+    trader_stats: BoundedMap<Pubkey, TraderStats>,
 }
 
 impl TraderDiscovery {
@@ -254,27 +401,269 @@ impl TraderDiscovery {
                 // Add known successful traders here
                 // These would be discovered through analysis of successful trades
             ],
+            min_success_rate: 0.7,
+            min_trades: 10,
+            trader_stats: BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS),
         }
     }
 
-    pub fn discover_traders_from_transactions(&mut self, transactions: &[TransactionData]) -> Vec<Pubkey> {
-        let mut trader_stats: HashMap<Pubkey, TraderStats> = HashMap::new();
+    /// Only traders above both thresholds are discovered.
+    pub fn with_thresholds(mut self, min_success_rate: f64, min_trades: u32) -> Self {
+        self.min_success_rate = min_success_rate;
+        self.min_trades = min_trades;
+        self
+    }
 
+    /// Adds `transactions` to each trader's running record and returns the
+    /// traders in this batch whose record now clears both thresholds.
+    pub fn discover_traders_from_transactions(&mut self, transactions: &[TransactionData]) -> Vec<Pubkey> {
+        let mut seen = Vec::new();
         for tx in transactions {
-            if let Some(trader) = &tx.trader {
-                let stats = trader_stats.entry(*trader).or_insert(TraderStats::new());
-                stats.add_trade(tx.success, tx.profit);
+            let Some(trader) = tx.trader else { continue };
+            match self.trader_stats.get_mut(&trader) {
+                Some(stats) => stats.add_trade(tx.success, tx.profit),
+                None => {
+                    let mut stats = TraderStats::new();
+                    stats.add_trade(tx.success, tx.profit);
+                    self.trader_stats.insert(trader, stats);
+                }
+            }
+            if !seen.contains(&trader) {
+                seen.push(trader);
             }
         }
 
         // Find traders with good performance
-        trader_stats.into_iter()
-            .filter(|(_, stats)| stats.success_rate > 0.7 && stats.total_trades > 10)
-            .map(|(trader, _)| trader)
+        seen.into_iter()
+            .filter(|trader| {
+                self.trader_stats.get(trader).is_some_and(|stats| {
+                    stats.success_rate() > self.min_success_rate && stats.total_trades > self.min_trades
+                })
+            })
             .collect()
     }
 }
 
+/// Thresholds for `TraderAutoManager`.
+#[derive(Debug, Clone)]
+pub struct AutoFollowConfig {
+    pub min_success_rate: f64, // Discovered traders must beat this success rate...
+    pub min_trades: u32,       // ...over more than this many trades
+    pub unfollow_success_rate: f64, // Followed traders whose recent success rate drops below this are dropped
+    pub recent_window: usize,  // Trades per trader the recent success rate is taken over
+    pub min_recent_trades: usize, // Recent trades needed before a trader can be dropped
+    pub max_traders: usize,    // Discovered traders followed at once, on top of the pinned ones
+}
+
+impl Default for AutoFollowConfig {
+    fn default() -> Self {
+        Self {
+            min_success_rate: 0.7,
+            min_trades: 10,
+            unfollow_success_rate: 0.5,
+            recent_window: 20,
+            min_recent_trades: 5,
+            max_traders: 5,
+        }
+    }
+}
+
+/// A change `TraderAutoManager` made to the follow list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FollowChange {
+    Followed { trader: Pubkey, reputation_score: f64 },
+    /// The trader's recent success rate fell below the unfollow floor.
+    Unfollowed { trader: Pubkey, recent_success_rate: f64 },
+    /// Dropped to make room for a stronger trader while at `max_traders_to_follow`.
+    Evicted { trader: Pubkey, replaced_by: Pubkey },
+}
+
+/// Keeps a `CopyTradingEngine`'s follow list tuned from the trades seen on the
+/// stream: traders that `TraderDiscovery` picks out are followed, and followed
+/// traders whose recent record slips are dropped. When the list is full, a
+/// new trader only gets in by out-scoring the weakest one followed. Pinned
+/// traders, the hand-picked ones, are never dropped or evicted.
+pub struct TraderAutoManager {
+    config: AutoFollowConfig,
+    discovery: TraderDiscovery,
+    recent_trades: BoundedMap<Pubkey, VecDeque<(bool, f64)>>,
+    /// SOL paid and raw tokens still held per (trader, token), for pricing sells
+    open_buys: BoundedMap<(Pubkey, Pubkey), (f64, u64)>,
+    pinned: HashSet<Pubkey>,
+}
+
+impl TraderAutoManager {
+    pub fn new(config: AutoFollowConfig) -> Self {
+        let discovery = TraderDiscovery::new().with_thresholds(config.min_success_rate, config.min_trades);
+        Self {
+            config,
+            discovery,
+            recent_trades: BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS),
+            open_buys: BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS),
+            pinned: HashSet::new(),
+        }
+    }
+
+    /// Traders the manager leaves followed whatever their record.
+    pub fn with_pinned(mut self, traders: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.pinned.extend(traders);
+        self
+    }
+
+    /// Turns one streamed trade into an outcome: buys open or add to the
+    /// trader's cost in the token, and a sell closes the share of it that was
+    /// sold, a win if it returned more SOL than it cost. Sells of tokens whose
+    /// buy was never seen carry no outcome.
+    pub fn record_trade(&mut self, engine: &mut CopyTradingEngine, trader: Pubkey, trade: &FollowedTrade) -> Vec<FollowChange> {
+        let key = (trader, trade.token);
+        match trade.action {
+            TradeAction::Buy => {
+                match self.open_buys.get_mut(&key) {
+                    Some((cost_sol, tokens)) => {
+                        *cost_sol += trade.amount_sol;
+                        *tokens += trade.token_amount;
+                    }
+                    None => {
+                        self.open_buys.insert(key, (trade.amount_sol, trade.token_amount));
+                    }
+                }
+                Vec::new()
+            }
+            TradeAction::Sell => {
+                let Some((cost_sol, tokens)) = self.open_buys.get_mut(&key).filter(|(_, tokens)| *tokens > 0) else {
+                    return Vec::new();
+                };
+                let sold = trade.token_amount.min(*tokens);
+                let cost_of_sold = *cost_sol * sold as f64 / *tokens as f64;
+                *cost_sol -= cost_of_sold;
+                *tokens -= sold;
+                if *tokens == 0 {
+                    self.open_buys.remove(&key);
+                }
+                let profit = trade.amount_sol - cost_of_sold;
+                self.observe(engine, &[TransactionData {
+                    trader: Some(trader),
+                    token: trade.token,
+                    success: profit > 0.0,
+                    profit,
+                }])
+            }
+        }
+    }
+
+    /// Success rate over the trader's last `recent_window` trades, if any were seen.
+    pub fn recent_success_rate(&self, trader: &Pubkey) -> Option<f64> {
+        let trades = self.recent_trades.get(trader).filter(|trades| !trades.is_empty())?;
+        Some(trades.iter().filter(|(success, _)| *success).count() as f64 / trades.len() as f64)
+    }
+
+    /// Feeds a batch of streamed trades through the follow rules and returns
+    /// every change made to `engine`'s follow list.
+    pub fn observe(&mut self, engine: &mut CopyTradingEngine, transactions: &[TransactionData]) -> Vec<FollowChange> {
+        for tx in transactions {
+            let Some(trader) = tx.trader else { continue };
+            let window = self.config.recent_window.max(1);
+            match self.recent_trades.get_mut(&trader) {
+                Some(trades) => {
+                    if trades.len() >= window {
+                        trades.pop_front();
+                    }
+                    trades.push_back((tx.success, tx.profit));
+                }
+                None => {
+                    self.recent_trades.insert(trader, VecDeque::from([(tx.success, tx.profit)]));
+                }
+            }
+        }
+
+        let mut changes = self.unfollow_slipping(engine);
+        for trader in self.discovery.discover_traders_from_transactions(transactions) {
+            if !engine.followed_traders.contains_key(&trader) {
+                changes.extend(self.try_follow(engine, trader));
+            }
+        }
+        changes
+    }
+
+    fn unfollow_slipping(&self, engine: &mut CopyTradingEngine) -> Vec<FollowChange> {
+        let slipping: Vec<(Pubkey, f64)> = engine.followed_traders
+            .keys()
+            .filter(|trader| !self.pinned.contains(trader))
+            .filter_map(|trader| {
+                let trades = self.recent_trades.get(trader)?;
+                if trades.len() < self.config.min_recent_trades {
+                    return None;
+                }
+                let rate = self.recent_success_rate(trader)?;
+                (rate < self.config.unfollow_success_rate).then_some((*trader, rate))
+            })
+            .collect();
+
+        slipping
+            .into_iter()
+            .map(|(trader, recent_success_rate)| {
+                info!("Unfollowing trader {}: recent success rate {:.0}% below the {:.0}% floor",
+                      trader, recent_success_rate * 100.0, self.config.unfollow_success_rate * 100.0);
+                engine.remove_trader(&trader);
+                FollowChange::Unfollowed { trader, recent_success_rate }
+            })
+            .collect()
+    }
+
+    fn try_follow(&self, engine: &mut CopyTradingEngine, trader: Pubkey) -> Vec<FollowChange> {
+        let Some(trades) = self.recent_trades.get(&trader) else {
+            return Vec::new();
+        };
+        let profitable_trades = trades.iter().filter(|(success, _)| *success).count() as u32;
+        let mut profile = TraderProfile {
+            wallet_address: trader,
+            success_rate: profitable_trades as f64 / trades.len() as f64,
+            total_trades: trades.len() as u32,
+            profitable_trades,
+            average_profit: trades.iter().map(|(_, profit)| profit).sum::<f64>() / trades.len() as f64,
+            last_activity: Instant::now(),
+            reputation_score: 0.0,
+        };
+        profile.reputation_score = engine.calculate_reputation_score(&profile);
+
+        // Check the engine's own criteria first so nobody is evicted for a trader it would refuse
+        if profile.success_rate < engine.config.min_success_rate || profile.reputation_score < engine.config.min_reputation_score {
+            info!("Not following discovered trader {}: success rate {:.0}%, reputation {:.2}",
+                  trader, profile.success_rate * 100.0, profile.reputation_score);
+            return Vec::new();
+        }
+
+        let mut changes = Vec::new();
+        let discovered = engine.followed_traders.keys().filter(|trader| !self.pinned.contains(trader)).count();
+        if engine.followed_traders.len() >= engine.config.max_traders_to_follow || discovered >= self.config.max_traders {
+            let weakest = engine.followed_traders
+                .iter()
+                .filter(|(trader, _)| !self.pinned.contains(trader))
+                .min_by(|a, b| a.1.reputation_score.total_cmp(&b.1.reputation_score))
+                .map(|(weakest, profile)| (*weakest, profile.reputation_score));
+            match weakest {
+                Some((weakest, score)) if score < profile.reputation_score => {
+                    info!("Evicting trader {} (reputation {:.2}) to follow {} (reputation {:.2})",
+                          weakest, score, trader, profile.reputation_score);
+                    engine.remove_trader(&weakest);
+                    changes.push(FollowChange::Evicted { trader: weakest, replaced_by: trader });
+                }
+                _ => return changes,
+            }
+        }
+
+        let reputation_score = profile.reputation_score;
+        match engine.add_trader(trader, profile) {
+            Ok(()) => {
+                info!("Auto-followed trader {} (reputation {:.2})", trader, reputation_score);
+                changes.push(FollowChange::Followed { trader, reputation_score });
+            }
+            Err(e) => info!("Not following discovered trader {}: {}", trader, e),
+        }
+        changes
+    }
+}
+
 #[derive(Debug)]
 struct TraderStats {
     total_trades: u32,
@@ -320,26 +709,256 @@ pub struct TransactionData {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_copy_trading_engine() {
-        let config = CopyTradeConfig::default();
-        let mut engine = CopyTradingEngine::new(config);
-        
-        let trader = Pubkey::new_unique();
-        let profile = TraderProfile {
-            wallet_address: trader,
+    /// A followed trader whose record clears the default copy criteria.
+    fn proven_trader(wallet_address: Pubkey) -> TraderProfile {
+        TraderProfile {
+            wallet_address,
             success_rate: 0.8,
             total_trades: 100,
             profitable_trades: 80,
             average_profit: 0.05,
             last_activity: Instant::now(),
             reputation_score: 0.9,
-        };
+        }
+    }
 
-        assert!(engine.add_trader(trader, profile).is_ok());
+    #[test]
+    fn test_copy_trading_engine() {
+        let config = CopyTradeConfig::default();
+        let mut engine = CopyTradingEngine::new(config);
         
+        let trader = Pubkey::new_unique();
+        assert!(engine.add_trader(trader, proven_trader(trader)).is_ok());
+        
+        // 10% of the trader's 0.1 SOL
         let token = Pubkey::new_unique();
-        let should_copy = engine.should_copy_trade(&trader, &token, &TradeAction::Buy, 0.1);
-        assert!(should_copy.is_ok() && should_copy.unwrap());
+        let copy_amount = engine.should_copy_trade(&trader, &Signature::new_unique(), &token, &TradeAction::Buy, 0.1).unwrap();
+        assert!((copy_amount.unwrap() - 0.01).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_trader_overrides_take_precedence() {
+        let config = CopyTradeConfig {
+            cooldown_between_copies: Duration::ZERO,
+            ..CopyTradeConfig::default()
+        };
+        let mut engine = CopyTradingEngine::new(config);
+        let (trusted, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for trader in [trusted, other] {
+            engine.add_trader(trader, proven_trader(trader)).unwrap();
+        }
+        engine.set_trader_overrides(trusted, TraderOverrides {
+            copy_percentage: Some(0.5),
+            max_copy_amount_sol: Some(1.0),
+            ..TraderOverrides::default()
+        });
+        let last_copy = |engine: &mut CopyTradingEngine, trader: &Pubkey| {
+            engine.should_copy_trade(trader, &Signature::new_unique(), &Pubkey::new_unique(), &TradeAction::Buy, 1.0).unwrap().unwrap()
+        };
+
+        // 50% of 1 SOL under the override, the global 0.01 SOL cap otherwise
+        assert_eq!(last_copy(&mut engine, &trusted), 0.5);
+        assert_eq!(last_copy(&mut engine, &other), 0.01);
+
+        engine.clear_trader_overrides(&trusted);
+        assert!(engine.trader_overrides(&trusted).is_none());
+        assert_eq!(last_copy(&mut engine, &trusted), 0.01);
+
+        engine.set_trader_overrides(other, TraderOverrides { enabled: false, ..TraderOverrides::default() });
+        assert!(engine.should_copy_trade(&other, &Signature::new_unique(), &Pubkey::new_unique(), &TradeAction::Buy, 1.0).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_copy_is_sized_from_the_traders_buy() {
+        use crate::pumpfun::{build_buy_instruction, parse_trade, BuyParams, PumpFunAccounts};
+
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: PumpFunAccounts {
+                mint: Pubkey::new_unique(),
+                bonding_curve: Pubkey::new_unique(),
+                associated_bonding_curve: Pubkey::new_unique(),
+                creator_vault: Pubkey::new_unique(),
+            },
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000_000,
+            max_sol_cost_lamports: 2 * LAMPORTS_PER_SOL,
+        };
+        let instruction = build_buy_instruction(&params);
+        let keys: Vec<Pubkey> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+        let trade = parse_trade(&instruction.data, &keys).unwrap();
+
+        // The limit carries the trader's slippage; the transfer is what they paid
+        let limit = FollowedTrade::new(&trade, LAMPORTS_PER_SOL, CopyAmountSource::Limit).unwrap();
+        assert_eq!(limit.amount_sol, 2.0);
+        let paid = FollowedTrade::new(&trade, LAMPORTS_PER_SOL, CopyAmountSource::Transfer).unwrap();
+        assert_eq!((paid.token, paid.amount_sol, paid.token_amount), (params.accounts.mint, 1.0, 1_000_000));
+        assert_eq!(FollowedTrade::new(&trade, 0, CopyAmountSource::Transfer).unwrap().amount_sol, 2.0);
+
+        let mut engine = CopyTradingEngine::new(CopyTradeConfig {
+            max_copy_amount_sol: 10.0,
+            ..CopyTradeConfig::default()
+        });
+        engine.add_trader(params.buyer, proven_trader(params.buyer)).unwrap();
+        let copy_amount = engine.should_copy_trade(&params.buyer, &Signature::new_unique(), &paid.token, &paid.action, paid.amount_sol);
+        assert_eq!(copy_amount.unwrap(), Some(0.1));
+        assert_eq!(engine.trade_history.back().unwrap().amount_sol, 0.1);
+    }
+
+    #[test]
+    fn test_same_source_transaction_is_copied_once() {
+        let config = CopyTradeConfig {
+            cooldown_between_copies: Duration::ZERO,
+            ..CopyTradeConfig::default()
+        };
+        let mut engine = CopyTradingEngine::new(config);
+        let trader = Pubkey::new_unique();
+        engine.add_trader(trader, proven_trader(trader)).unwrap();
+
+        // The same trader transaction delivered by two endpoints
+        let (source, token) = (Signature::new_unique(), Pubkey::new_unique());
+        assert!(engine.should_copy_trade(&trader, &source, &token, &TradeAction::Buy, 0.1).unwrap().is_some());
+        assert!(engine.should_copy_trade(&trader, &source, &token, &TradeAction::Buy, 0.1).unwrap().is_none());
+        assert_eq!(engine.trade_history.len(), 1);
+
+        // A different transaction for the same token is still copied
+        assert!(engine.should_copy_trade(&trader, &Signature::new_unique(), &token, &TradeAction::Buy, 0.1).unwrap().is_some());
+    }
+
+    fn trades(trader: Pubkey, wins: usize, losses: usize) -> Vec<TransactionData> {
+        (0..wins + losses)
+            .map(|i| TransactionData {
+                trader: Some(trader),
+                token: Pubkey::new_unique(),
+                success: i < wins,
+                profit: if i < wins { 0.1 } else { -0.1 },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_follow_and_evict() {
+        let mut engine = CopyTradingEngine::new(CopyTradeConfig {
+            min_reputation_score: 0.5,
+            max_traders_to_follow: 2,
+            ..CopyTradeConfig::default()
+        });
+        let mut manager = TraderAutoManager::new(AutoFollowConfig {
+            recent_window: 30,
+            ..AutoFollowConfig::default()
+        });
+        let (a, b, strong, weak) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Too few trades to be discovered
+        assert!(manager.observe(&mut engine, &trades(a, 5, 0)).is_empty());
+
+        let batch = [trades(a, 10, 2), trades(b, 15, 5)].concat();
+        let changes = manager.observe(&mut engine, &batch);
+        assert_eq!(changes.len(), 2);
+        assert!(engine.followed_traders.contains_key(&a) && engine.followed_traders.contains_key(&b));
+
+        // At capacity, a trader weaker than everyone followed is not let in
+        assert!(manager.observe(&mut engine, &trades(weak, 9, 3)).is_empty());
+        assert!(!engine.followed_traders.contains_key(&weak));
+
+        // A stronger one evicts the weakest
+        let changes = manager.observe(&mut engine, &trades(strong, 30, 0));
+        assert_eq!(changes[0], FollowChange::Evicted { trader: b, replaced_by: strong });
+        assert!(matches!(changes[1], FollowChange::Followed { trader, .. } if trader == strong));
+        assert_eq!(engine.followed_traders.len(), 2);
+
+        // A followed trader on a losing streak is dropped
+        let changes = manager.observe(&mut engine, &trades(a, 0, 14));
+        assert!(matches!(changes[0], FollowChange::Unfollowed { trader, recent_success_rate } if trader == a && recent_success_rate < 0.5));
+        assert!(!engine.followed_traders.contains_key(&a));
+    }
+
+    #[test]
+    fn test_discovery_spans_batches() {
+        let mut engine = CopyTradingEngine::new(CopyTradeConfig {
+            min_reputation_score: 0.0,
+            ..CopyTradeConfig::default()
+        });
+        let mut manager = TraderAutoManager::new(AutoFollowConfig::default());
+        let trader = Pubkey::new_unique();
+
+        // Six winning trades per batch: neither batch clears ten on its own
+        assert!(manager.observe(&mut engine, &trades(trader, 6, 0)).is_empty());
+        let changes = manager.observe(&mut engine, &trades(trader, 6, 0));
+        assert!(matches!(changes[..], [FollowChange::Followed { trader: followed, .. }] if followed == trader));
+    }
+
+    #[test]
+    fn test_round_trips_become_outcomes() {
+        let mut engine = CopyTradingEngine::new(CopyTradeConfig {
+            min_reputation_score: 0.0,
+            ..CopyTradeConfig::default()
+        });
+        let mut manager = TraderAutoManager::new(AutoFollowConfig { min_trades: 1, ..AutoFollowConfig::default() });
+        let (trader, token) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let trade = |action, amount_sol, token_amount| FollowedTrade { action, token, amount_sol, token_amount };
+
+        // A sell with no buy seen says nothing about the trader
+        manager.record_trade(&mut engine, trader, &trade(TradeAction::Sell, 1.0, 100));
+        assert!(manager.recent_success_rate(&trader).is_none());
+
+        manager.record_trade(&mut engine, trader, &trade(TradeAction::Buy, 1.0, 100));
+        // Half the tokens for 0.6 SOL against a 0.5 SOL cost, then the rest for 0.4
+        manager.record_trade(&mut engine, trader, &trade(TradeAction::Sell, 0.6, 50));
+        assert_eq!(manager.recent_success_rate(&trader), Some(1.0));
+        manager.record_trade(&mut engine, trader, &trade(TradeAction::Sell, 0.4, 50));
+        assert_eq!(manager.recent_success_rate(&trader), Some(0.5));
+        assert!(manager.open_buys.is_empty());
+    }
+
+    #[test]
+    fn test_pinned_traders_are_kept() {
+        let mut engine = CopyTradingEngine::new(CopyTradeConfig {
+            min_success_rate: 0.0,
+            min_reputation_score: 0.0,
+            max_traders_to_follow: 2,
+            ..CopyTradeConfig::default()
+        });
+        let (pinned, discovered, stronger) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        engine.add_trader(pinned, TraderProfile {
+            success_rate: 0.0,
+            total_trades: 0,
+            profitable_trades: 0,
+            average_profit: 0.0,
+            reputation_score: 0.0,
+            ..proven_trader(pinned)
+        }).unwrap();
+        let mut manager = TraderAutoManager::new(AutoFollowConfig::default()).with_pinned([pinned]);
+
+        // The pinned trader's losing streak and weak score cost it nothing
+        assert!(manager.observe(&mut engine, &trades(pinned, 0, 10)).is_empty());
+        manager.observe(&mut engine, &trades(discovered, 11, 2));
+        let changes = manager.observe(&mut engine, &trades(stronger, 30, 0));
+        assert_eq!(changes[0], FollowChange::Evicted { trader: discovered, replaced_by: stronger });
+        assert!(engine.followed_traders.contains_key(&pinned) && engine.followed_traders.contains_key(&stronger));
+    }
+
+    #[test]
+    fn test_trade_history_is_capped() {
+        let config = CopyTradeConfig {
+            max_trade_history: 2,
+            cooldown_between_copies: Duration::ZERO,
+            ..CopyTradeConfig::default()
+        };
+        let mut engine = CopyTradingEngine::new(config);
+        let trader = Pubkey::new_unique();
+        engine.add_trader(trader, proven_trader(trader)).unwrap();
+
+        let tokens: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for token in &tokens {
+            assert!(engine.should_copy_trade(&trader, &Signature::new_unique(), token, &TradeAction::Buy, 0.1).unwrap().is_some());
+        }
+
+        let history: Vec<Pubkey> = engine.trade_history.iter().map(|r| r.token).collect();
+        assert_eq!(history, tokens[1..]);
+
+        engine.cleanup_old_records(Duration::ZERO);
+        assert!(engine.trade_history.is_empty());
     }
 }
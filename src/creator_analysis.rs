@@ -0,0 +1,256 @@
+use crate::constants::*;
+use crate::error::{Result, SniperError};
+use crate::pumpfun::discriminators;
+use crate::risk_management::top_holder_percentage;
+use crate::scam_detection::{ScamDetector, ScamRecommendation, TokenMetadata, TradingData};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+use spl_associated_token_account::get_associated_token_address;
+use std::str::FromStr;
+use std::time::Instant;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct CreatorToken {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatorTokenScore {
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub scam_score: f64,
+    pub recommendation: ScamRecommendation,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatorReport {
+    pub creator: Pubkey,
+    pub tokens: Vec<CreatorTokenScore>,
+    pub average_scam_score: f64,
+    pub recommendation: ScamRecommendation,
+}
+
+/// Vets a creator wallet by replaying its recent PumpFun launches through the
+/// `ScamDetector`. Nothing is traded; this only reads chain history.
+pub struct CreatorAnalyzer {
+    rpc_client: RpcClient,
+    pump_fun_program_id: Pubkey,
+}
+
+impl CreatorAnalyzer {
+    pub fn new(rpc_endpoint: String) -> Result<Self> {
+        Ok(Self {
+            rpc_client: RpcClient::new(rpc_endpoint),
+            pump_fun_program_id: Pubkey::from_str(PUMP_FUN_PROGRAM_ID)?,
+        })
+    }
+
+    /// Finds PumpFun tokens created by `creator` among its last `signature_limit` transactions.
+    pub fn fetch_creator_tokens(&self, creator: &Pubkey, signature_limit: usize) -> Result<Vec<CreatorToken>> {
+        let signatures = self.rpc_client
+            .get_signatures_for_address_with_config(
+                creator,
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(signature_limit),
+                    ..Default::default()
+                },
+            )
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to get creator signatures: {}", e)))?;
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let mut tokens = Vec::new();
+        for status in signatures.iter().filter(|s| s.err.is_none()) {
+            let signature = status.signature.parse()
+                .map_err(|e| SniperError::Transaction(format!("Invalid signature {}: {}", status.signature, e)))?;
+
+            let encoded = match self.rpc_client.get_transaction_with_config(&signature, config) {
+                Ok(encoded) => encoded,
+                Err(e) => {
+                    warn!("Skipping transaction {}: {}", status.signature, e);
+                    continue;
+                }
+            };
+
+            let Some(transaction) = encoded.transaction.transaction.decode() else {
+                continue;
+            };
+
+            // v0 creates can reference accounts through lookup tables; those
+            // follow the static keys, writable before readonly
+            let mut account_keys = transaction.message.static_account_keys().to_vec();
+            if let Some(OptionSerializer::Some(loaded)) = encoded.transaction.meta.as_ref().map(|meta| &meta.loaded_addresses) {
+                for address in loaded.writable.iter().chain(&loaded.readonly) {
+                    account_keys.push(Pubkey::from_str(address)?);
+                }
+            }
+            for instruction in transaction.message.instructions() {
+                if account_keys.get(instruction.program_id_index as usize) != Some(&self.pump_fun_program_id) {
+                    continue;
+                }
+
+                if discriminators().matched_create(&instruction.data).is_none() || instruction.accounts.len() < 3 {
+                    continue;
+                }
+
+                let (Some(mint), Some(bonding_curve)) = (
+                    account_keys.get(instruction.accounts[0] as usize),
+                    account_keys.get(instruction.accounts[2] as usize),
+                ) else {
+                    continue;
+                };
+
+                let (name, symbol, uri) = parse_create_args(&instruction.data[8..]).unwrap_or_default();
+                tokens.push(CreatorToken {
+                    mint: *mint,
+                    bonding_curve: *bonding_curve,
+                    name,
+                    symbol,
+                    uri,
+                });
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    pub async fn analyze_creator(
+        &self,
+        detector: &ScamDetector,
+        creator: &Pubkey,
+        signature_limit: usize,
+    ) -> Result<CreatorReport> {
+        let tokens = self.fetch_creator_tokens(creator, signature_limit)?;
+        info!("Found {} PumpFun tokens created by {}", tokens.len(), creator);
+
+        let mut scores = Vec::new();
+        for token in &tokens {
+            let metadata = TokenMetadata {
+                mint: token.mint,
+                name: token.name.clone(),
+                symbol: token.symbol.clone(),
+                description: String::new(),
+                image_uri: token.uri.clone(),
+                creator: *creator,
+                creation_time: Instant::now(),
+                initial_supply: TOTAL_SUPPLY,
+                decimals: TOKEN_DECIMALS,
+                authorities: None,
+                socials: None,
+            };
+            let trading_data = fetch_trading_data(&self.rpc_client, &token.mint, &token.bonding_curve);
+
+            let analysis = detector.analyze_token(&metadata, &trading_data).await;
+            scores.push(CreatorTokenScore {
+                mint: token.mint,
+                name: token.name.clone(),
+                symbol: token.symbol.clone(),
+                scam_score: analysis.scam_score,
+                recommendation: analysis.recommendation,
+            });
+        }
+
+        let average_scam_score = if scores.is_empty() {
+            0.0
+        } else {
+            scores.iter().map(|s| s.scam_score).sum::<f64>() / scores.len() as f64
+        };
+
+        Ok(CreatorReport {
+            creator: *creator,
+            tokens: scores,
+            average_scam_score,
+            recommendation: ScamRecommendation::from_score(average_scam_score),
+        })
+    }
+}
+
+/// Samples a token's on-chain liquidity and holder distribution for scoring.
+/// Volume and price history aren't available from RPC and are left at zero.
+pub fn fetch_trading_data(rpc_client: &RpcClient, mint: &Pubkey, bonding_curve: &Pubkey) -> TradingData {
+    let liquidity = rpc_client
+        .get_balance(bonding_curve)
+        .map(|lamports| lamports as f64 / LAMPORTS_PER_SOL as f64)
+        .unwrap_or(0.0);
+
+    // Largest accounts caps at 20, which is enough to spot thin holder bases
+    let largest_accounts = rpc_client
+        .get_token_largest_accounts(mint)
+        .unwrap_or_default();
+    let holder_count = largest_accounts.len() as u32;
+
+    // Concentration among the top 10, not counting tokens still in the bonding curve
+    let balances: Vec<(Pubkey, u64)> = largest_accounts
+        .iter()
+        .filter_map(|account| Some((Pubkey::from_str(&account.address).ok()?, account.amount.amount.parse().ok()?)))
+        .collect();
+    let curve_token_account = get_associated_token_address(bonding_curve, mint);
+    let top_holder_percentage = rpc_client
+        .get_token_supply(mint)
+        .ok()
+        .and_then(|supply| supply.amount.parse().ok())
+        .map(|supply| top_holder_percentage(&balances, &[curve_token_account], 10, supply));
+
+    TradingData {
+        mint: *mint,
+        liquidity,
+        volume_24h: 0.0,
+        price_change_24h: 0.0,
+        holder_count,
+        top_holder_percentage,
+        transaction_count: 0,
+        market_cap: 0.0,
+        last_update: Instant::now(),
+    }
+}
+
+/// Decodes the Borsh-encoded `name`, `symbol` and `uri` arguments of a create instruction.
+pub fn parse_create_args(data: &[u8]) -> Option<(String, String, String)> {
+    let mut offset = 0;
+    let mut read_string = || -> Option<String> {
+        let len_bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        offset += 4;
+        let value = String::from_utf8(data.get(offset..offset + len)?.to_vec()).ok()?;
+        offset += len;
+        Some(value)
+    };
+
+    let name = read_string()?;
+    let symbol = read_string()?;
+    let uri = read_string()?;
+    Some((name, symbol, uri))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_create_args() {
+        let mut data = Vec::new();
+        for value in ["Token", "TKN", "https://example.com/meta.json"] {
+            data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            data.extend_from_slice(value.as_bytes());
+        }
+
+        let (name, symbol, uri) = parse_create_args(&data).unwrap();
+        assert_eq!(name, "Token");
+        assert_eq!(symbol, "TKN");
+        assert_eq!(uri, "https://example.com/meta.json");
+
+        assert!(parse_create_args(&data[..6]).is_none());
+    }
+}
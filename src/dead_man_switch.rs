@@ -0,0 +1,148 @@
+use crate::error::SniperError;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// What to do once the bot has lost sight of the chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeadManAction {
+    Alert,
+    CloseAll,
+}
+
+impl FromStr for DeadManAction {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "alert" => Ok(DeadManAction::Alert),
+            "close" | "close_all" | "close-all" => Ok(DeadManAction::CloseAll),
+            other => Err(SniperError::Config(format!("Unknown dead man's switch action '{}', expected alert or close_all", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadManSwitchConfig {
+    pub timeout: Duration, // Trip after this long without a Geyser message
+    pub action: DeadManAction,
+}
+
+impl Default for DeadManSwitchConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(120),
+            action: DeadManAction::CloseAll,
+        }
+    }
+}
+
+/// Protects held positions when monitoring itself fails. Every Geyser message
+/// counts as a heartbeat; if none arrive for `timeout` the switch trips. RPC
+/// answering doesn't count, since positions can't be managed without the
+/// stream. A tripped switch stays tripped, even once messages flow again,
+/// until it is explicitly [`reset`](Self::reset).
+pub struct DeadManSwitch {
+    config: DeadManSwitchConfig,
+    last_heartbeat: Instant,
+    tripped: bool,
+}
+
+impl DeadManSwitch {
+    pub fn new(config: DeadManSwitchConfig, at: Instant) -> Self {
+        Self {
+            config,
+            last_heartbeat: at,
+            tripped: false,
+        }
+    }
+
+    pub fn heartbeat(&mut self, at: Instant) {
+        if self.tripped && at.saturating_duration_since(self.last_heartbeat) >= self.config.timeout {
+            info!("💓 Geyser messages are arriving again; the dead man's switch stays tripped until reset");
+        }
+        self.last_heartbeat = self.last_heartbeat.max(at);
+    }
+
+    /// Re-arms a tripped switch, counting `at` as the last heartbeat.
+    pub fn reset(&mut self, at: Instant) {
+        if self.tripped {
+            info!("💓 Dead man's switch reset and re-armed");
+        }
+        self.last_heartbeat = self.last_heartbeat.max(at);
+        self.tripped = false;
+    }
+
+    /// Returns the configured action the first time the heartbeat has been
+    /// missing for longer than the timeout.
+    pub fn check(&mut self, at: Instant) -> Option<DeadManAction> {
+        let silent_for = at.saturating_duration_since(self.last_heartbeat);
+        if self.tripped || silent_for < self.config.timeout {
+            return None;
+        }
+
+        self.tripped = true;
+        error!("☠️ DEAD MAN'S SWITCH - no Geyser message for {:?} (action: {:?})",
+               silent_for, self.config.action);
+        Some(self.config.action)
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Whether held positions should be sold until connectivity returns.
+    pub fn should_close_positions(&self) -> bool {
+        self.tripped && self.config.action == DeadManAction::CloseAll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_once_after_silence() {
+        let start = Instant::now();
+        let mut switch = DeadManSwitch::new(DeadManSwitchConfig::default(), start);
+
+        assert_eq!(switch.check(start + Duration::from_secs(60)), None);
+        switch.heartbeat(start + Duration::from_secs(60));
+        assert_eq!(switch.check(start + Duration::from_secs(150)), None);
+
+        assert_eq!(switch.check(start + Duration::from_secs(180)), Some(DeadManAction::CloseAll));
+        assert!(switch.should_close_positions());
+        assert_eq!(switch.check(start + Duration::from_secs(240)), None);
+
+        // Messages flowing again don't re-arm it: it stays tripped until reset
+        switch.heartbeat(start + Duration::from_secs(250));
+        assert!(switch.is_tripped());
+        assert!(switch.should_close_positions());
+        assert_eq!(switch.check(start + Duration::from_secs(260)), None);
+
+        switch.reset(start + Duration::from_secs(270));
+        assert!(!switch.is_tripped());
+        assert_eq!(switch.check(start + Duration::from_secs(300)), None);
+        assert_eq!(switch.check(start + Duration::from_secs(390)), Some(DeadManAction::CloseAll));
+    }
+
+    #[test]
+    fn test_alert_only_keeps_positions() {
+        let start = Instant::now();
+        let mut switch = DeadManSwitch::new(DeadManSwitchConfig {
+            timeout: Duration::from_secs(30),
+            action: DeadManAction::Alert,
+        }, start);
+
+        assert_eq!(switch.check(start + Duration::from_secs(30)), Some(DeadManAction::Alert));
+        assert!(switch.is_tripped());
+        assert!(!switch.should_close_positions());
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!("alert".parse::<DeadManAction>().unwrap(), DeadManAction::Alert);
+        assert_eq!("Close_All".parse::<DeadManAction>().unwrap(), DeadManAction::CloseAll);
+        assert!("panic".parse::<DeadManAction>().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +15,19 @@ pub enum SniperError {
     #[error("Transaction error: {0}")]
     Transaction(String),
 
+    /// A transaction tried to create an account that already exists, e.g. two
+    /// of our own buys racing to open the same token account.
+    #[error("Account already exists: {0}")]
+    AccountAlreadyExists(String),
+
+    /// Jito rejected a bundle and strict mode kept it from falling back to regular RPC.
+    #[error("Jito bundle rejected: {0}")]
+    BundleRejected(String),
+
+    /// A sent transaction was never confirmed, without failing on chain.
+    #[error("Transaction not landed: {0}")]
+    NotLanded(String),
+
     #[error("Price fetch error: {0}")]
     PriceFetch(String),
 
@@ -31,6 +45,217 @@ pub enum SniperError {
 
     #[error("Generic error: {0}")]
     Generic(#[from] anyhow::Error),
+
+    #[error("{source}{}", format_context(.mint, .signature, .slot))]
+    WithContext {
+        source: Box<SniperError>,
+        mint: Option<Pubkey>,
+        signature: Option<Signature>,
+        slot: Option<u64>,
+    },
+}
+
+fn format_context(mint: &Option<Pubkey>, signature: &Option<Signature>, slot: &Option<u64>) -> String {
+    let mut parts = Vec::new();
+    if let Some(mint) = mint {
+        parts.push(format!("mint: {}", mint));
+    }
+    if let Some(signature) = signature {
+        parts.push(format!("signature: {}", signature));
+    }
+    if let Some(slot) = slot {
+        parts.push(format!("slot: {}", slot));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", parts.join(", "))
+    }
+}
+
+impl SniperError {
+    /// Wraps the error with context, or fills in the existing context. Context
+    /// set closer to the failure wins over context added further up.
+    fn with_context(self, mint: Option<Pubkey>, signature: Option<Signature>, slot: Option<u64>) -> Self {
+        match self {
+            SniperError::WithContext { source, mint: m, signature: s, slot: sl } => SniperError::WithContext {
+                source,
+                mint: m.or(mint),
+                signature: s.or(signature),
+                slot: sl.or(slot),
+            },
+            other => SniperError::WithContext {
+                source: Box::new(other),
+                mint,
+                signature,
+                slot,
+            },
+        }
+    }
+
+    pub fn mint(&self) -> Option<Pubkey> {
+        match self {
+            SniperError::WithContext { mint, .. } => *mint,
+            _ => None,
+        }
+    }
+
+    pub fn signature(&self) -> Option<Signature> {
+        match self {
+            SniperError::WithContext { signature, .. } => *signature,
+            _ => None,
+        }
+    }
+
+    pub fn is_account_already_exists(&self) -> bool {
+        match self {
+            SniperError::AccountAlreadyExists(_) => true,
+            SniperError::WithContext { source, .. } => source.is_account_already_exists(),
+            _ => false,
+        }
+    }
+
+    pub fn is_bundle_rejected(&self) -> bool {
+        match self {
+            SniperError::BundleRejected(_) => true,
+            SniperError::WithContext { source, .. } => source.is_bundle_rejected(),
+            _ => false,
+        }
+    }
+
+    pub fn is_not_landed(&self) -> bool {
+        match self {
+            SniperError::NotLanded(_) => true,
+            SniperError::WithContext { source, .. } => source.is_not_landed(),
+            _ => false,
+        }
+    }
+
+    /// Whether the failure may clear up on its own, e.g. an RPC or gRPC
+    /// endpoint that is briefly down, so the operation is worth retrying.
+    /// Bad config, rejected credentials and malformed data are fatal.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            SniperError::SolanaClient(_) | SniperError::NotLanded(_) | SniperError::PriceFetch(_) | SniperError::Io(_) => true,
+            SniperError::Grpc(status) => matches!(
+                status.code(),
+                tonic::Code::Unavailable
+                    | tonic::Code::DeadlineExceeded
+                    | tonic::Code::ResourceExhausted
+                    | tonic::Code::Aborted
+                    | tonic::Code::Internal
+                    | tonic::Code::Unknown
+            ),
+            SniperError::Http(e) => e.is_timeout() || e.is_connect(),
+            SniperError::WithContext { source, .. } => source.is_recoverable(),
+            _ => false,
+        }
+    }
+}
+
+/// Turns a failed RPC health check into a `SniperError`: a rejected API key or
+/// an unknown method is a configuration problem, anything else is treated as
+/// the endpoint being temporarily unavailable.
+pub fn classify_rpc_health_error(role: &str, error: &impl std::fmt::Display) -> SniperError {
+    let message = format!("{} RPC is unhealthy: {}", role, error);
+    let lowercase = message.to_lowercase();
+    if ["401", "403", "unauthorized", "forbidden", "invalid api key", "method not found"]
+        .iter()
+        .any(|pattern| lowercase.contains(pattern))
+    {
+        SniperError::Config(message)
+    } else {
+        SniperError::SolanaClient(message)
+    }
+}
+
+/// Turns a failed send or confirmation into a `SniperError`, telling an
+/// account that already exists apart from other client errors.
+pub fn classify_client_error(context: &str, error: &impl std::fmt::Display) -> SniperError {
+    let message = format!("{}: {}", context, error);
+    let lowercase = message.to_lowercase();
+    if ["already in use", "accountalreadyinuse", "account already exists"]
+        .iter()
+        .any(|pattern| lowercase.contains(pattern))
+    {
+        SniperError::AccountAlreadyExists(message)
+    } else {
+        SniperError::SolanaClient(message)
+    }
+}
+
+/// Attaches the mint, signature or slot an operation was working on to its error.
+pub trait ErrorContext<T> {
+    fn with_mint(self, mint: &Pubkey) -> Result<T>;
+    fn with_signature(self, signature: &Signature) -> Result<T>;
+    fn with_slot(self, slot: u64) -> Result<T>;
+}
+
+impl<T, E: Into<SniperError>> ErrorContext<T> for std::result::Result<T, E> {
+    fn with_mint(self, mint: &Pubkey) -> Result<T> {
+        self.map_err(|e| e.into().with_context(Some(*mint), None, None))
+    }
+
+    fn with_signature(self, signature: &Signature) -> Result<T> {
+        self.map_err(|e| e.into().with_context(None, Some(*signature), None))
+    }
+
+    fn with_slot(self, slot: u64) -> Result<T> {
+        self.map_err(|e| e.into().with_context(None, None, Some(slot)))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, SniperError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_context() {
+        let mint = Pubkey::new_unique();
+        let signature = Signature::default();
+
+        let result: Result<()> = Err(SniperError::Transaction("send failed".to_string()));
+        let error = result.with_signature(&signature).with_mint(&mint).unwrap_err();
+
+        assert_eq!(error.mint(), Some(mint));
+        assert_eq!(error.signature(), Some(signature));
+        assert_eq!(
+            error.to_string(),
+            format!("Transaction error: send failed (mint: {}, signature: {})", mint, signature)
+        );
+        assert!(matches!(error, SniperError::WithContext { ref source, .. } if matches!(**source, SniperError::Transaction(_))));
+    }
+
+    #[test]
+    fn test_account_already_exists_is_classified() {
+        // What a losing create looks like when two buys race for one token account
+        let collision = "Transaction simulation failed: Error processing Instruction 2: custom program error: 0x0; \
+                         Program log: Create Account: account Address { address: 9xQe, base: None } already in use";
+        let error = classify_client_error("Failed to send buy transaction", &collision);
+        assert!(matches!(error, SniperError::AccountAlreadyExists(_)));
+
+        let result: Result<()> = Err(error);
+        assert!(result.with_mint(&Pubkey::new_unique()).unwrap_err().is_account_already_exists());
+
+        let error = classify_client_error("Failed to send buy transaction", &"Blockhash not found");
+        assert!(matches!(error, SniperError::SolanaClient(_)));
+        assert!(!error.is_account_already_exists());
+    }
+
+    #[test]
+    fn test_fatal_and_recoverable_errors() {
+        let down = classify_rpc_health_error("Read", &"error sending request: connection refused");
+        assert!(down.is_recoverable());
+        let rejected = classify_rpc_health_error("Send", &"HTTP status client error (401 Unauthorized)");
+        assert!(matches!(rejected, SniperError::Config(_)));
+        assert!(!rejected.is_recoverable());
+
+        assert!(SniperError::Grpc(tonic::Status::unavailable("connect")).is_recoverable());
+        assert!(!SniperError::Grpc(tonic::Status::unauthenticated("x-token")).is_recoverable());
+        let result: Result<()> = Err(down);
+        assert!(result.with_mint(&Pubkey::new_unique()).unwrap_err().is_recoverable());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::constants::*;
+use crate::error::{Result, SniperError};
+use parking_lot::RwLock;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::info;
+
+// PumpFun Global account layout offsets (after the 8-byte discriminator):
+// initialized: bool, authority: Pubkey, fee_recipient: Pubkey, five u64 reserves/fees,
+// withdraw_authority: Pubkey, enable_migrate: bool, pool_migration_fee: u64,
+// creator_fee_basis_points: u64, fee_recipients: [Pubkey; 7]
+const GLOBAL_FEE_RECIPIENT_OFFSET: usize = 41;
+const GLOBAL_FEE_RECIPIENTS_OFFSET: usize = 162;
+const GLOBAL_FEE_RECIPIENTS_COUNT: usize = 7;
+
+/// Reads every fee recipient from PumpFun Global account data: the primary
+/// recipient plus the rotation list on newer program versions. Default
+/// (all-zero) entries are skipped.
+pub fn parse_global_fee_recipients(data: &[u8]) -> Result<Vec<Pubkey>> {
+    let read_pubkey = |offset: usize| -> Option<Pubkey> {
+        let bytes: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+        Some(Pubkey::new_from_array(bytes))
+    };
+
+    let primary = read_pubkey(GLOBAL_FEE_RECIPIENT_OFFSET).ok_or_else(|| {
+        SniperError::Transaction(format!("Global account too short: {} bytes", data.len()))
+    })?;
+
+    let mut recipients = vec![primary];
+    for i in 0..GLOBAL_FEE_RECIPIENTS_COUNT {
+        match read_pubkey(GLOBAL_FEE_RECIPIENTS_OFFSET + i * 32) {
+            Some(recipient) if recipient != Pubkey::default() && !recipients.contains(&recipient) => {
+                recipients.push(recipient);
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    Ok(recipients)
+}
+
+/// Chooses the fee recipient for each buy. A configured override always wins;
+/// otherwise buys rotate across the recipients read from the Global account,
+/// falling back to the built-in `FEE_RECIPIENT` until those are loaded.
+pub struct FeeRecipientResolver {
+    override_recipient: Option<Pubkey>,
+    recipients: RwLock<Vec<Pubkey>>,
+    next_index: AtomicUsize,
+}
+
+impl FeeRecipientResolver {
+    pub fn new(override_recipient: Option<Pubkey>) -> Result<Self> {
+        Ok(Self {
+            override_recipient,
+            recipients: RwLock::new(vec![Pubkey::from_str(FEE_RECIPIENT)?]),
+            next_index: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn update_from_global(&self, global_account_data: &[u8]) -> Result<()> {
+        let recipients = parse_global_fee_recipients(global_account_data)?;
+        info!("Loaded {} fee recipient(s) from global account", recipients.len());
+        *self.recipients.write() = recipients;
+        Ok(())
+    }
+
+    pub fn resolve(&self) -> Pubkey {
+        if let Some(recipient) = self.override_recipient {
+            return recipient;
+        }
+
+        let recipients = self.recipients.read();
+        let index = self.next_index.fetch_add(1, Ordering::Relaxed) % recipients.len();
+        recipients[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global_data(primary: Pubkey, rotation: &[Pubkey]) -> Vec<u8> {
+        let mut data = vec![0u8; GLOBAL_FEE_RECIPIENTS_OFFSET + GLOBAL_FEE_RECIPIENTS_COUNT * 32];
+        data[GLOBAL_FEE_RECIPIENT_OFFSET..GLOBAL_FEE_RECIPIENT_OFFSET + 32].copy_from_slice(primary.as_ref());
+        for (i, recipient) in rotation.iter().enumerate() {
+            let offset = GLOBAL_FEE_RECIPIENTS_OFFSET + i * 32;
+            data[offset..offset + 32].copy_from_slice(recipient.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_resolves_from_global_account() {
+        let primary = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let resolver = FeeRecipientResolver::new(None).unwrap();
+        assert_eq!(resolver.resolve(), Pubkey::from_str(FEE_RECIPIENT).unwrap());
+
+        resolver.update_from_global(&global_data(primary, &[second])).unwrap();
+        let resolved: Vec<_> = (0..4).map(|_| resolver.resolve()).collect();
+        assert!(resolved.contains(&primary));
+        assert!(resolved.contains(&second));
+        assert!(resolved.iter().all(|r| *r == primary || *r == second));
+    }
+
+    #[test]
+    fn test_override_wins() {
+        let override_recipient = Pubkey::new_unique();
+        let resolver = FeeRecipientResolver::new(Some(override_recipient)).unwrap();
+        resolver.update_from_global(&global_data(Pubkey::new_unique(), &[])).unwrap();
+        assert_eq!(resolver.resolve(), override_recipient);
+    }
+
+    #[test]
+    fn test_short_global_account() {
+        assert!(parse_global_fee_recipients(&[0u8; 40]).is_err());
+        // Older layout without the rotation list still yields the primary recipient
+        let primary = Pubkey::new_unique();
+        let data = global_data(primary, &[]);
+        assert_eq!(parse_global_fee_recipients(&data[..73]).unwrap(), vec![primary]);
+    }
+}
@@ -0,0 +1,140 @@
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// When and how much the funding wallet sends to the buyer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopUpPolicy {
+    pub threshold_lamports: u64, // Top up once the buyer holds less than this
+    pub amount_lamports: u64,
+    /// Most that may be sent within any one `window`, however often the
+    /// balance dips; a top-up is shrunk to fit what is left.
+    pub max_per_window_lamports: u64,
+    pub window: Duration,
+}
+
+impl Default for TopUpPolicy {
+    fn default() -> Self {
+        Self {
+            threshold_lamports: 100_000_000,
+            amount_lamports: 500_000_000,
+            max_per_window_lamports: 1_000_000_000,
+            window: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Keeps a long-running buyer wallet funded from a separate wallet. Top-ups
+/// sent within the last window are remembered so the per-window cap holds
+/// even if the buyer keeps draining.
+pub struct FundingWallet {
+    keypair: Keypair,
+    policy: TopUpPolicy,
+    sent: VecDeque<(Instant, u64)>,
+}
+
+impl FundingWallet {
+    pub fn new(keypair: Keypair, policy: TopUpPolicy) -> Self {
+        Self {
+            keypair,
+            policy,
+            sent: VecDeque::new(),
+        }
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn policy(&self) -> &TopUpPolicy {
+        &self.policy
+    }
+
+    /// Lamports sent within the window ending at `now`.
+    pub fn sent_in_window(&mut self, now: Instant) -> u64 {
+        while let Some((sent_at, _)) = self.sent.front() {
+            if now.duration_since(*sent_at) < self.policy.window {
+                break;
+            }
+            self.sent.pop_front();
+        }
+        self.sent.iter().map(|(_, lamports)| lamports).sum()
+    }
+
+    /// How much to send a buyer holding `balance_lamports`: nothing while it
+    /// is at or above the threshold or once the window's cap is spent,
+    /// otherwise the configured amount, cut down to what the cap still allows.
+    pub fn top_up_amount(&mut self, balance_lamports: u64, now: Instant) -> Option<u64> {
+        if balance_lamports >= self.policy.threshold_lamports {
+            return None;
+        }
+        let allowed = self.policy.max_per_window_lamports.saturating_sub(self.sent_in_window(now));
+        Some(self.policy.amount_lamports.min(allowed)).filter(|lamports| *lamports > 0)
+    }
+
+    /// A transfer of `lamports` from the funding wallet to `buyer`, paid for
+    /// and signed by the funding wallet.
+    pub fn top_up_transaction(&self, buyer: &Pubkey, lamports: u64, recent_blockhash: Hash) -> Transaction {
+        let funder = self.keypair.pubkey();
+        Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&funder, buyer, lamports)],
+            Some(&funder),
+            &[&self.keypair],
+            recent_blockhash,
+        )
+    }
+
+    /// Counts a top-up against the window's cap. Charged as it is sent rather
+    /// than once it lands, since one whose send errors out may land anyway.
+    pub fn record(&mut self, lamports: u64, now: Instant) {
+        self.sent.push_back((now, lamports));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_program;
+
+    #[test]
+    fn test_top_up_below_threshold_within_cap() {
+        let policy = TopUpPolicy {
+            threshold_lamports: 100,
+            amount_lamports: 400,
+            max_per_window_lamports: 600,
+            window: Duration::from_secs(60),
+        };
+        let mut funding = FundingWallet::new(Keypair::new(), policy);
+        let buyer = Pubkey::new_unique();
+        let now = Instant::now();
+
+        assert_eq!(funding.top_up_amount(100, now), None);
+        assert_eq!(funding.top_up_amount(99, now), Some(400));
+
+        let transaction = funding.top_up_transaction(&buyer, 400, Hash::new_unique());
+        let message = &transaction.message;
+        assert_eq!(message.account_keys[0], funding.pubkey());
+        assert_eq!(message.instructions.len(), 1);
+        let instruction = &message.instructions[0];
+        assert_eq!(message.account_keys[instruction.program_id_index as usize], system_program::id());
+        assert_eq!(
+            instruction.accounts.iter().map(|&i| message.account_keys[i as usize]).collect::<Vec<_>>(),
+            [funding.pubkey(), buyer]
+        );
+        assert_eq!(instruction.data, system_instruction::transfer(&funding.pubkey(), &buyer, 400).data);
+        assert!(transaction.verify().is_ok());
+
+        // The cap shrinks the next top-up, then stops them until the window passes
+        funding.record(400, now);
+        assert_eq!(funding.top_up_amount(0, now), Some(200));
+        funding.record(200, now);
+        assert_eq!(funding.top_up_amount(0, now + Duration::from_secs(59)), None);
+        assert_eq!(funding.top_up_amount(0, now + Duration::from_secs(60)), Some(400));
+    }
+}
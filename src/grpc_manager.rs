@@ -1,13 +1,58 @@
+use crate::bounded_map::BoundedMap;
 use crate::error::{Result, SniperError};
 use crate::geyser::*;
+use parking_lot::Mutex;
+use solana_sdk::signature::Signature;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time;
 use tonic::transport::{Channel, ClientTlsConfig};
 use tonic::Request;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+/// How transaction filters name the account they watch. Providers differ in
+/// what `account_include` matches: on some it is any transaction that merely
+/// lists the account, which lets unrelated traffic through. `Required` puts the
+/// account in `account_required` instead, for providers that support it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeyserFilterMode {
+    Include,
+    Required,
+}
+
+impl GeyserFilterMode {
+    /// A non-vote, successful-transaction filter on `account`.
+    pub fn transaction_filter(&self, account: &str) -> SubscribeRequestFilterTransactions {
+        let (account_include, account_required) = match self {
+            GeyserFilterMode::Include => (vec![account.to_string()], Vec::new()),
+            GeyserFilterMode::Required => (Vec::new(), vec![account.to_string()]),
+        };
+        SubscribeRequestFilterTransactions {
+            vote: false,
+            failed: false,
+            account_include,
+            account_required,
+        }
+    }
+}
+
+impl FromStr for GeyserFilterMode {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "include" => Ok(GeyserFilterMode::Include),
+            "required" => Ok(GeyserFilterMode::Required),
+            other => Err(SniperError::Config(format!(
+                "Unknown Geyser filter mode '{}', expected include or required", other
+            ))),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct GrpcEndpoint {
@@ -18,11 +63,17 @@ pub struct GrpcEndpoint {
     pub enabled: bool,
 }
 
+/// How many recently forwarded updates are remembered to drop the copies
+/// other connections deliver of them.
+const SEEN_UPDATES_CAPACITY: usize = 10_000;
+
 #[derive(Debug)]
 pub struct GrpcConnection {
     pub endpoint: GrpcEndpoint,
     pub client: GeyserClient<Channel>,
-    pub last_health_check: Instant,
+    /// When the connection last delivered a message, shared with its stream
+    /// task so it can be bumped without locking every connection
+    pub last_health_check: Arc<Mutex<Instant>>,
     pub is_healthy: bool,
     pub connection_id: u32,
 }
@@ -33,6 +84,41 @@ pub struct GrpcManager {
     tx_sender: broadcast::Sender<SubscribeResponse>,
     health_check_interval: Duration,
     failover_threshold: Duration,
+    /// What every connection's stream subscribes to
+    subscription: SubscribeRequest,
+    /// Reconnect unhealthy endpoints on each health check and restart their
+    /// streams once they answer again
+    auto_reconnect: bool,
+    stream_tasks: StreamTasks,
+    seen_updates: SeenUpdates,
+}
+
+/// The stream task of each connection, so no connection ever runs two.
+type StreamTasks = Arc<Mutex<HashMap<u32, JoinHandle<()>>>>;
+
+/// Updates already forwarded from any connection.
+type SeenUpdates = Arc<Mutex<BoundedMap<UpdateKey, ()>>>;
+
+/// What identifies an update that every connection delivers a copy of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum UpdateKey {
+    Transaction { signature: Signature, slot: u64 },
+    Status { signature: Signature, slot: u64 },
+}
+
+impl UpdateKey {
+    /// The transaction or status update `response` carries, if any. Other
+    /// updates, such as slots, are harmless to repeat and have no key.
+    fn of(response: &SubscribeResponse) -> Option<Self> {
+        if let Some(update) = &response.transaction {
+            let signature = update.transaction.as_ref()?.signatures.first()?;
+            let signature = Signature::try_from(signature.as_slice()).ok()?;
+            return Some(UpdateKey::Transaction { signature, slot: update.slot });
+        }
+        let update = response.transaction_status.as_ref()?;
+        let signature = Signature::try_from(update.signature.as_slice()).ok()?;
+        Some(UpdateKey::Status { signature, slot: update.slot })
+    }
 }
 
 impl GrpcManager {
@@ -45,6 +131,34 @@ impl GrpcManager {
             tx_sender,
             health_check_interval: Duration::from_secs(30),
             failover_threshold: Duration::from_secs(60),
+            subscription: SubscribeRequest::default(),
+            auto_reconnect: true,
+            stream_tasks: Arc::new(Mutex::new(HashMap::new())),
+            seen_updates: Arc::new(Mutex::new(BoundedMap::new(SEEN_UPDATES_CAPACITY))),
+        }
+    }
+
+    pub fn with_subscription(mut self, request: SubscribeRequest) -> Self {
+        self.subscription = request;
+        self
+    }
+
+    /// Turns reconnecting recovered endpoints on or off (default: on). When
+    /// off, an endpoint marked unhealthy stays out of rotation for good.
+    pub fn with_auto_reconnect(mut self, enabled: bool) -> Self {
+        self.auto_reconnect = enabled;
+        self
+    }
+
+    fn supervisor(&self) -> StreamSupervisor {
+        StreamSupervisor {
+            connections: Arc::clone(&self.connections),
+            stream_tasks: Arc::clone(&self.stream_tasks),
+            seen_updates: Arc::clone(&self.seen_updates),
+            tx_sender: self.tx_sender.clone(),
+            subscription: self.subscription.clone(),
+            failover_threshold: self.failover_threshold,
+            auto_reconnect: self.auto_reconnect,
         }
     }
 
@@ -82,13 +196,7 @@ impl GrpcManager {
     }
 
     async fn create_connection(&self, endpoint: GrpcEndpoint, connection_id: u32) -> Result<GrpcConnection> {
-        let channel = Channel::from_shared(endpoint.url.clone())
-            .map_err(|e| SniperError::Grpc(tonic::Status::from_error(e)))?
-            .connect()
-            .await
-            .map_err(|e| SniperError::Grpc(tonic::Status::from_error(e)))?;
-
-        let mut client = GeyserClient::new(channel);
+        let client = connect(&endpoint.url).await?;
         
         // Test connection with a simple request
         let test_request = SubscribeRequest {
@@ -103,71 +211,36 @@ impl GrpcManager {
         Ok(GrpcConnection {
             endpoint,
             client,
-            last_health_check: Instant::now(),
+            last_health_check: Arc::new(Mutex::new(Instant::now())),
             is_healthy: true,
             connection_id,
         })
     }
 
     async fn start_health_check_task(&self) {
-        let connections = Arc::clone(&self.connections);
+        let supervisor = self.supervisor();
         let health_check_interval = self.health_check_interval;
-        let failover_threshold = self.failover_threshold;
 
         tokio::spawn(async move {
             let mut interval = time::interval(health_check_interval);
             
             loop {
                 interval.tick().await;
-                
-                let mut connections_guard = connections.write().await;
-                let mut to_remove = Vec::new();
-                
-                for (id, connection) in connections_guard.iter_mut() {
-                    // Simple health check - if we haven't received data recently, mark as unhealthy
-                    if connection.last_health_check.elapsed() > failover_threshold {
-                        connection.is_healthy = false;
-                        warn!("gRPC connection {} marked as unhealthy", id);
-                    }
-                }
-                
-                // Remove unhealthy connections
-                for id in to_remove {
-                    connections_guard.remove(&id);
-                }
+                supervisor.check_health().await;
             }
         });
     }
 
     async fn start_subscription_tasks(&self) {
-        let connections = Arc::clone(&self.connections);
-        let tx_sender = self.tx_sender.clone();
-
-        tokio::spawn(async move {
-            let mut connections_guard = connections.read().await;
-            
-            for (id, connection) in connections_guard.iter() {
-                if connection.is_healthy {
-                    let tx_sender_clone = tx_sender.clone();
-                    let connection_id = connection.connection_id;
-                    
-                    tokio::spawn(async move {
-                        if let Err(e) = Self::handle_connection_stream(connection_id, tx_sender_clone).await {
-                            error!("Connection {} stream error: {}", connection_id, e);
-                        }
-                    });
-                }
-            }
-        });
-    }
-
-    async fn handle_connection_stream(
-        connection_id: u32,
-        tx_sender: broadcast::Sender<SubscribeResponse>,
-    ) -> Result<()> {
-        // This would be implemented to handle the actual stream
-        // For now, it's a placeholder
-        Ok(())
+        let supervisor = self.supervisor();
+        let healthy: Vec<u32> = self.connections.read().await
+            .iter()
+            .filter(|(_, connection)| connection.is_healthy)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in healthy {
+            supervisor.start_stream(id).await;
+        }
     }
 
     pub async fn subscribe(&self, request: SubscribeRequest) -> Result<()> {
@@ -220,8 +293,8 @@ impl GrpcManager {
                 url: conn.endpoint.url.clone(),
                 priority: conn.endpoint.priority,
                 is_healthy: conn.is_healthy,
-                last_health_check: conn.last_health_check,
-                uptime: conn.last_health_check.elapsed(),
+                last_health_check: *conn.last_health_check.lock(),
+                uptime: conn.last_health_check.lock().elapsed(),
             })
             .collect()
     }
@@ -250,6 +323,7 @@ impl GrpcManager {
             
             let mut connections = self.connections.write().await;
             connections.remove(&(index as u32));
+            self.supervisor().stop_stream(index as u32);
             
             info!("Removed gRPC endpoint: {}", url);
         }
@@ -278,6 +352,142 @@ impl GrpcManager {
     }
 }
 
+async fn connect(url: &str) -> Result<GeyserClient<Channel>> {
+    let channel = Channel::from_shared(url.to_string())
+        .map_err(|e| SniperError::Grpc(tonic::Status::from_error(e)))?
+        .connect()
+        .await
+        .map_err(|e| SniperError::Grpc(tonic::Status::from_error(e)))?;
+    Ok(GeyserClient::new(channel))
+}
+
+/// What the health check task needs to mark connections unhealthy, bring
+/// recovered ones back and keep exactly one stream running per healthy one.
+#[derive(Clone)]
+struct StreamSupervisor {
+    connections: Arc<RwLock<HashMap<u32, GrpcConnection>>>,
+    stream_tasks: StreamTasks,
+    seen_updates: SeenUpdates,
+    tx_sender: broadcast::Sender<SubscribeResponse>,
+    subscription: SubscribeRequest,
+    failover_threshold: Duration,
+    auto_reconnect: bool,
+}
+
+impl StreamSupervisor {
+    /// Marks connections that have gone quiet unhealthy and stops their
+    /// streams. With auto-reconnect on, unhealthy connections are dialed again,
+    /// and those that answer are marked healthy with a fresh stream. Returns the
+    /// recovered connections.
+    async fn check_health(&self) -> Vec<u32> {
+        let mut unhealthy = Vec::new();
+        {
+            let mut connections = self.connections.write().await;
+            for (id, connection) in connections.iter_mut() {
+                if !connection.is_healthy {
+                    unhealthy.push((*id, connection.endpoint.url.clone()));
+                } else if connection.last_health_check.lock().elapsed() > self.failover_threshold {
+                    // Simple health check - if we haven't received data recently, mark as unhealthy
+                    connection.is_healthy = false;
+                    self.stop_stream(*id);
+                    warn!("gRPC connection {} marked as unhealthy", id);
+                }
+            }
+        }
+        if !self.auto_reconnect {
+            return Vec::new();
+        }
+
+        // Dialed without holding the lock, so a slow endpoint does not stall the others
+        let mut recovered = Vec::new();
+        for (id, url) in unhealthy {
+            let client = match connect(&url).await {
+                Ok(client) => client,
+                Err(e) => {
+                    debug!("gRPC connection {} still down: {}", id, e);
+                    continue;
+                }
+            };
+            if let Some(connection) = self.connections.write().await.get_mut(&id) {
+                connection.client = client;
+                connection.is_healthy = true;
+                *connection.last_health_check.lock() = Instant::now();
+                info!("gRPC connection {} recovered, restarting its stream", id);
+                recovered.push(id);
+            }
+        }
+        for id in &recovered {
+            self.start_stream(*id).await;
+        }
+        recovered
+    }
+
+    /// Starts the stream of a healthy connection unless one is already running.
+    /// Returns whether a stream was started.
+    async fn start_stream(&self, connection_id: u32) -> bool {
+        let (client, last_message_at) = match self.connections.read().await.get(&connection_id) {
+            Some(connection) if connection.is_healthy => (connection.client.clone(), Arc::clone(&connection.last_health_check)),
+            _ => return false,
+        };
+        let mut tasks = self.stream_tasks.lock();
+        if tasks.get(&connection_id).is_some_and(|task| !task.is_finished()) {
+            return false;
+        }
+        let supervisor = self.clone();
+        tasks.insert(connection_id, tokio::spawn(async move {
+            if let Err(e) = supervisor.handle_connection_stream(connection_id, client, last_message_at).await {
+                error!("Connection {} stream error: {}", connection_id, e);
+            }
+        }));
+        true
+    }
+
+    /// Records `response` as forwarded; false if a copy of it already was.
+    fn is_first_delivery(&self, response: &SubscribeResponse) -> bool {
+        let Some(key) = UpdateKey::of(response) else {
+            return true;
+        };
+        let mut seen = self.seen_updates.lock();
+        if seen.contains_key(&key) {
+            return false;
+        }
+        seen.insert(key, ());
+        true
+    }
+
+    fn stop_stream(&self, connection_id: u32) {
+        if let Some(task) = self.stream_tasks.lock().remove(&connection_id) {
+            task.abort();
+        }
+    }
+
+    /// Forwards a connection's stream to the broadcast channel, counting each
+    /// message as a sign of health. A transaction or status another
+    /// connection already forwarded is dropped, so each reaches the bot once.
+    async fn handle_connection_stream(
+        &self,
+        connection_id: u32,
+        mut client: GeyserClient<Channel>,
+        last_message_at: Arc<Mutex<Instant>>,
+    ) -> Result<()> {
+        let mut stream = client
+            .subscribe(Request::new(self.subscription.clone()))
+            .await
+            .map_err(SniperError::Grpc)?
+            .into_inner();
+        while let Some(response) = stream.message().await.map_err(SniperError::Grpc)? {
+            *last_message_at.lock() = Instant::now();
+            if !self.is_first_delivery(&response) {
+                continue;
+            }
+            // Fails only while nobody is listening, which drops the message either way
+            let _ = self.tx_sender.send(response);
+        }
+        info!("Connection {} stream ended", connection_id);
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct ConnectionStats {
     pub connection_id: u32,
@@ -367,4 +577,75 @@ mod tests {
         assert!(endpoint.enabled);
         assert_eq!(endpoint.priority, 1);
     }
+
+    #[cfg(feature = "mock-geyser")]
+    #[tokio::test]
+    async fn test_recovered_endpoint_restarts_its_stream() {
+        let url = crate::mock_geyser::serve(vec![SubscribeResponse::default()]).await;
+        let endpoint = GrpcEndpoint { url, auth_token: String::new(), priority: 0, weight: 1.0, enabled: true };
+        let manager = GrpcManager::new(vec![endpoint.clone()]);
+        let mut receiver = manager.get_message_receiver();
+        let connection = manager.create_connection(endpoint, 0).await.unwrap();
+        manager.connections.write().await.insert(0, connection);
+
+        // One stream per connection, however often it is asked for
+        let mut supervisor = manager.supervisor();
+        assert!(supervisor.start_stream(0).await);
+        assert!(!supervisor.start_stream(0).await);
+        time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+
+        // Gone quiet: marked unhealthy and its stream stopped
+        supervisor.failover_threshold = Duration::ZERO;
+        time::sleep(Duration::from_millis(5)).await;
+        assert!(supervisor.check_health().await.is_empty());
+        assert!(!manager.connections.read().await[&0].is_healthy);
+        assert!(!manager.stream_tasks.lock().contains_key(&0));
+
+        // Answering again: healthy, with a new stream delivering messages
+        assert_eq!(supervisor.check_health().await, [0]);
+        assert!(manager.connections.read().await[&0].is_healthy);
+        time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+
+        // Without auto-reconnect an unhealthy endpoint stays down
+        supervisor.auto_reconnect = false;
+        time::sleep(Duration::from_millis(5)).await;
+        assert!(supervisor.check_health().await.is_empty());
+        assert!(supervisor.check_health().await.is_empty());
+        assert!(!manager.connections.read().await[&0].is_healthy);
+    }
+
+    #[cfg(feature = "mock-geyser")]
+    #[tokio::test]
+    async fn test_updates_from_every_connection_are_forwarded_once() {
+        let transaction = |slot| SubscribeResponse {
+            transaction: Some(TransactionUpdate {
+                slot,
+                transaction: Some(Transaction {
+                    signatures: vec![vec![7; 64]],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let manager = GrpcManager::new(Vec::new());
+        let mut receiver = manager.get_message_receiver();
+        for id in 0..2 {
+            let url = crate::mock_geyser::serve(vec![transaction(5), transaction(6)]).await;
+            let endpoint = GrpcEndpoint { url, auth_token: String::new(), priority: 0, weight: 1.0, enabled: true };
+            let connection = manager.create_connection(endpoint, id).await.unwrap();
+            manager.connections.write().await.insert(id, connection);
+        }
+
+        let supervisor = manager.supervisor();
+        assert!(supervisor.start_stream(0).await && supervisor.start_stream(1).await);
+
+        // The same signature in a new slot is a separate update
+        let mut slots = Vec::new();
+        while let Ok(Ok(response)) = time::timeout(Duration::from_millis(500), receiver.recv()).await {
+            slots.push(response.transaction.unwrap().slot);
+        }
+        slots.sort();
+        assert_eq!(slots, [5, 6]);
+    }
 }
@@ -1,65 +1,114 @@
 use crate::constants::*;
 use crate::error::{Result, SniperError};
+use crate::priority_fee::finalize_instructions;
+use crate::pumpfun::{build_buy_instruction, BuyParams};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use parking_lot::RwLock;
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
+    hash::Hash,
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::Signature,
     signer::Signer,
+    system_instruction,
     transaction::Transaction,
 };
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, warn, error};
 
 pub struct JitoClient {
     rpc_client: RpcClient,
+    http_client: reqwest::Client,
+    block_engine_url: String,
     tip_account: Pubkey,
-    fee_account: Pubkey,
     enabled: bool,
+    strict: bool,
+}
+
+/// What became of a transaction's Jito bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleStatus {
+    /// Jito accepted the tipped bundle.
+    Accepted,
+    /// Jito rejected the bundle and the transaction went out over regular RPC
+    /// instead, untipped.
+    FellBack { reason: String },
+    /// Jito was disabled or not worth tipping for this trade.
+    NotUsed,
+}
+
+/// A transaction sent through `JitoManager`, with the tip that actually went
+/// with it (0 unless the bundle was accepted).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JitoSubmission {
+    pub signature: Signature,
+    pub tip_lamports: u64,
+    pub bundle_status: BundleStatus,
 }
 
 impl JitoClient {
     pub fn new(rpc_endpoint: String, enabled: bool) -> Result<Self> {
         let rpc_client = RpcClient::new(rpc_endpoint);
         let tip_account = Pubkey::from_str(JITO_TIP_ACCOUNT)?;
-        let fee_account = Pubkey::from_str(JITO_FEE_ACCOUNT)?;
 
         Ok(Self {
             rpc_client,
+            http_client: reqwest::Client::new(),
+            block_engine_url: JITO_BLOCK_ENGINE_URL.to_string(),
             tip_account,
-            fee_account,
             enabled,
+            strict: false,
         })
     }
 
+    /// Sends bundles to `block_engine_url` instead of the mainnet block engine.
+    pub fn with_block_engine(mut self, block_engine_url: String) -> Self {
+        self.block_engine_url = block_engine_url;
+        self
+    }
+
+    /// In strict mode a rejected bundle fails the send with
+    /// `SniperError::BundleRejected` instead of falling back to regular RPC,
+    /// where the transaction could land in a later block.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sends `transaction` to the block engine bundled with a tip transfer
+    /// from its fee payer, signed by `signers` into a transaction of its own.
+    /// A rejected bundle is handled as in `send_bundle`.
     pub async fn send_transaction_with_jito<T: Signer>(
         &self,
         transaction: &Transaction,
         signers: &[&T],
         tip_lamports: u64,
-    ) -> Result<Signature> {
+    ) -> Result<(Signature, BundleStatus)> {
         if !self.enabled {
-            return self.send_regular_transaction(transaction, signers).await;
+            let signature = self.send_regular_transaction(transaction, signers).await?;
+            return Ok((signature, BundleStatus::NotUsed));
         }
 
-        // Create Jito tip instruction
-        let tip_instruction = self.create_tip_instruction(tip_lamports)?;
-        
-        // Clone transaction and add tip instruction
-        let mut jito_transaction = transaction.clone();
-        jito_transaction.message.instructions.push(tip_instruction);
-
-        // Send transaction with Jito
-        match self.rpc_client.send_and_confirm_transaction(jito_transaction) {
-            Ok(signature) => {
-                info!("Transaction sent with Jito tip: {} lamports", tip_lamports);
-                Ok(signature)
-            }
-            Err(e) => {
-                warn!("Jito transaction failed, falling back to regular: {}", e);
-                self.send_regular_transaction(transaction, signers).await
-            }
-        }
+        let tip_transaction = self.tip_transaction(transaction, signers, tip_lamports)?;
+        info!("Bundling a {} lamport Jito tip", tip_lamports);
+        self.send_bundle(transaction, &tip_transaction).await
+    }
+
+    /// Signs a tip transfer from `transaction`'s fee payer on the same
+    /// blockhash, so the tip expires with the transaction it pays for.
+    fn tip_transaction<T: Signer>(&self, transaction: &Transaction, signers: &[&T], tip_lamports: u64) -> Result<Transaction> {
+        let payer = transaction.message.account_keys.first()
+            .ok_or_else(|| SniperError::Transaction("Transaction has no fee payer to tip from".to_string()))?;
+        let mut tip_transaction = Transaction::new_with_payer(&[self.tip_transfer(payer, tip_lamports)], Some(payer));
+        tip_transaction
+            .try_sign(signers, transaction.message.recent_blockhash)
+            .map_err(|e| SniperError::Transaction(format!("Failed to sign Jito tip: {}", e)))?;
+        Ok(tip_transaction)
     }
 
     async fn send_regular_transaction<T: Signer>(
@@ -72,18 +121,72 @@ impl JitoClient {
             .map_err(|e| SniperError::SolanaClient(format!("Regular transaction failed: {}", e)))
     }
 
-    fn create_tip_instruction(&self, tip_lamports: u64) -> Result<Instruction> {
-        Ok(Instruction {
-            program_id: solana_sdk::system_program::ID,
-            accounts: vec![
-                AccountMeta::new(self.tip_account, false),
-                AccountMeta::new(self.fee_account, false),
-            ],
-            data: vec![
-                2, // Transfer instruction
-                0, 0, 0, 0, 0, 0, 0, 0, // Placeholder for amount
-            ],
-        })
+    /// Sends `transaction` and `tip_transaction` to the block engine as one
+    /// bundle, so the tip is only paid if the transaction lands with it. A
+    /// rejected bundle fails with `SniperError::BundleRejected` in strict mode;
+    /// otherwise `transaction` goes out alone over regular RPC, untipped.
+    pub async fn send_bundle(&self, transaction: &Transaction, tip_transaction: &Transaction) -> Result<(Signature, BundleStatus)> {
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        match self.post_bundle(&[transaction, tip_transaction]).await {
+            Ok(bundle_id) => {
+                info!("Bundle {} accepted by the Jito block engine", bundle_id);
+                Ok((signature, BundleStatus::Accepted))
+            }
+            Err(e) if self.strict => {
+                warn!("Jito bundle rejected, not falling back (strict mode): {}", e);
+                Err(SniperError::BundleRejected(e.to_string()))
+            }
+            Err(e) => {
+                warn!("Jito bundle rejected, falling back to regular: {}", e);
+                let signature = self.send_without_bundle(transaction)?;
+                Ok((signature, BundleStatus::FellBack { reason: e.to_string() }))
+            }
+        }
+    }
+
+    /// Sends `transaction` over regular RPC without waiting for it to confirm;
+    /// the bundle path leaves confirmation to the caller.
+    pub fn send_without_bundle(&self, transaction: &Transaction) -> Result<Signature> {
+        self.rpc_client
+            .send_transaction(transaction)
+            .map_err(|e| SniperError::SolanaClient(format!("Regular transaction failed: {}", e)))
+    }
+
+    /// Posts `transactions` to the block engine's `sendBundle` method and
+    /// returns the bundle id it assigns.
+    async fn post_bundle(&self, transactions: &[&Transaction]) -> Result<String> {
+        let encoded = transactions
+            .iter()
+            .map(|transaction| bincode::serialize(transaction).map(|bytes| STANDARD.encode(bytes)))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| SniperError::Transaction(format!("Failed to serialize bundle: {}", e)))?;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [encoded, { "encoding": "base64" }],
+        });
+
+        let response: Value = self.http_client
+            .post(&self.block_engine_url)
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(SniperError::Transaction(format!("sendBundle failed: {}", error)));
+        }
+        response["result"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| SniperError::Transaction(format!("sendBundle returned no bundle id: {}", response)))
+    }
+
+    /// A transfer of `tip_lamports` from `payer` to the Jito tip account, to be
+    /// signed into its own transaction and bundled behind the one it pays for.
+    pub fn tip_transfer(&self, payer: &Pubkey, tip_lamports: u64) -> Instruction {
+        system_instruction::transfer(payer, &self.tip_account, tip_lamports)
     }
 
     pub fn calculate_optimal_tip(&self, priority_fee: u64, urgency: UrgencyLevel) -> u64 {
@@ -101,6 +204,10 @@ impl JitoClient {
     pub fn is_jito_enabled(&self) -> bool {
         self.enabled
     }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -113,8 +220,13 @@ pub enum UrgencyLevel {
 
 pub struct JitoConfig {
     pub enabled: bool,
+    pub block_engine_url: String,
     pub default_tip_lamports: u64,
     pub max_tip_lamports: u64,
+    pub max_tip_fraction_of_buy: f64, // Tip never exceeds this fraction of the buy size
+    pub min_tip_lamports: u64,        // Below this, Jito is skipped entirely
+    pub strict: bool,                 // Fail sends whose bundle is rejected instead of falling back
+    pub congestion_sample_interval: Duration,
     pub tip_strategy: TipStrategy,
 }
 
@@ -135,8 +247,13 @@ impl Default for JitoConfig {
     fn default() -> Self {
         Self {
             enabled: true,
+            block_engine_url: JITO_BLOCK_ENGINE_URL.to_string(),
             default_tip_lamports: 10000, // 0.00001 SOL
             max_tip_lamports: 100000,    // 0.0001 SOL
+            max_tip_fraction_of_buy: 0.05, // 5% of buy size
+            min_tip_lamports: 1000,      // 0.000001 SOL
+            strict: false,
+            congestion_sample_interval: Duration::from_secs(10),
             tip_strategy: TipStrategy::Dynamic(DynamicTipConfig {
                 base_tip: 5000,
                 network_congestion_multiplier: 1.5,
@@ -146,34 +263,172 @@ impl Default for JitoConfig {
     }
 }
 
+// Median priority fee (micro-lamports per CU) that maps to congestion level 1.0
+const BASELINE_PRIORITY_FEE_MICRO_LAMPORTS: f64 = 10_000.0;
+
+/// Maps recent prioritization fees to a congestion level in the 0.1–10 range.
+///
+/// The level is the median non-zero fee divided by
+/// `BASELINE_PRIORITY_FEE_MICRO_LAMPORTS`, so a median of 10k µlamports/CU is
+/// normal (1.0), 100k+ is maximum congestion (10.0), and 1k or less is idle
+/// (0.1). Slots with no paid fees are ignored; if none paid, the network is idle.
+pub fn congestion_from_prioritization_fees(fees: &[u64]) -> f64 {
+    let mut paid: Vec<u64> = fees.iter().copied().filter(|fee| *fee > 0).collect();
+    if paid.is_empty() {
+        return 0.1;
+    }
+
+    paid.sort_unstable();
+    let median = paid[paid.len() / 2] as f64;
+
+    (median / BASELINE_PRIORITY_FEE_MICRO_LAMPORTS).clamp(0.1, 10.0)
+}
+
 pub struct JitoManager {
     config: JitoConfig,
     client: JitoClient,
-    network_congestion: f64,
+    network_congestion: Arc<RwLock<f64>>,
 }
 
 impl JitoManager {
     pub fn new(rpc_endpoint: String, config: JitoConfig) -> Result<Self> {
-        let client = JitoClient::new(rpc_endpoint, config.enabled)?;
+        let client = JitoClient::new(rpc_endpoint, config.enabled)?
+            .with_strict(config.strict)
+            .with_block_engine(config.block_engine_url.clone());
         
         Ok(Self {
             config,
             client,
-            network_congestion: 1.0, // Default congestion level
+            network_congestion: Arc::new(RwLock::new(1.0)), // Default congestion level
         })
     }
 
+    /// Spawns a background task that samples recent prioritization fees every
+    /// `congestion_sample_interval` and feeds the result into the tip calculation.
+    pub fn start_congestion_monitor(&self, rpc_endpoint: String) {
+        let network_congestion = Arc::clone(&self.network_congestion);
+        let sample_interval = self.config.congestion_sample_interval;
+
+        tokio::spawn(async move {
+            let rpc_client = NonblockingRpcClient::new(rpc_endpoint);
+            let mut interval = tokio::time::interval(sample_interval);
+
+            loop {
+                interval.tick().await;
+
+                match rpc_client.get_recent_prioritization_fees(&[]).await {
+                    Ok(samples) => {
+                        let fees: Vec<u64> = samples.iter().map(|s| s.prioritization_fee).collect();
+                        let level = congestion_from_prioritization_fees(&fees);
+                        *network_congestion.write() = level;
+                        info!("Measured network congestion level: {:.2}", level);
+                    }
+                    Err(e) => {
+                        warn!("Failed to sample prioritization fees: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
     pub async fn send_priority_transaction<T: Signer>(
         &self,
         transaction: &Transaction,
         signers: &[&T],
         urgency: UrgencyLevel,
+        buy_amount_sol: f64,
     ) -> Result<Signature> {
-        let tip_amount = self.calculate_tip_amount(urgency);
-        
-        self.client
-            .send_transaction_with_jito(transaction, signers, tip_amount)
-            .await
+        let submission = self
+            .send_priority_transaction_with_tip(transaction, signers, urgency, buy_amount_sol)
+            .await?;
+        Ok(submission.signature)
+    }
+
+    /// Same as `send_priority_transaction`, also returning the tip paid and
+    /// what became of the bundle.
+    pub async fn send_priority_transaction_with_tip<T: Signer>(
+        &self,
+        transaction: &Transaction,
+        signers: &[&T],
+        urgency: UrgencyLevel,
+        buy_amount_sol: f64,
+    ) -> Result<JitoSubmission> {
+        match self.calculate_capped_tip(urgency, buy_amount_sol) {
+            Some(tip_amount) => {
+                info!("Effective Jito tip: {} lamports (buy size {} SOL)", tip_amount, buy_amount_sol);
+                let (signature, bundle_status) = self.client
+                    .send_transaction_with_jito(transaction, signers, tip_amount)
+                    .await?;
+                let tip_lamports = if bundle_status == BundleStatus::Accepted { tip_amount } else { 0 };
+                Ok(JitoSubmission { signature, tip_lamports, bundle_status })
+            }
+            None => {
+                info!("Jito tip below {} lamports floor for {} SOL buy, sending without Jito",
+                      self.config.min_tip_lamports, buy_amount_sol);
+                let signature = self.client.send_regular_transaction(transaction, signers).await?;
+                Ok(JitoSubmission { signature, tip_lamports: 0, bundle_status: BundleStatus::NotUsed })
+            }
+        }
+    }
+
+    /// Sends a signed `transaction` bundled with `tip`, its separately signed
+    /// tip transfer and the lamports it moves. Without a tip (Jito disabled or
+    /// the tip under its floor) the transaction goes out alone over regular RPC.
+    pub async fn send_bundle_with_tip(&self, transaction: &Transaction, tip: Option<(&Transaction, u64)>) -> Result<JitoSubmission> {
+        match tip.filter(|_| self.client.is_jito_enabled()) {
+            Some((tip_transaction, tip_amount)) => {
+                info!("Bundling a {} lamport Jito tip", tip_amount);
+                let (signature, bundle_status) = self.client.send_bundle(transaction, tip_transaction).await?;
+                let tip_lamports = if bundle_status == BundleStatus::Accepted { tip_amount } else { 0 };
+                Ok(JitoSubmission { signature, tip_lamports, bundle_status })
+            }
+            None => {
+                let signature = self.client.send_without_bundle(transaction)?;
+                Ok(JitoSubmission { signature, tip_lamports: 0, bundle_status: BundleStatus::NotUsed })
+            }
+        }
+    }
+
+    /// A transfer of `tip_lamports` from `payer` to the Jito tip account.
+    pub fn tip_transfer(&self, payer: &Pubkey, tip_lamports: u64) -> Instruction {
+        self.client.tip_transfer(payer, tip_lamports)
+    }
+
+    /// Builds a PumpFun buy from `params` and sends it with a tip sized to `buy_amount_sol`.
+    pub async fn send_pumpfun_buy<T: Signer>(
+        &self,
+        params: &BuyParams,
+        signers: &[&T],
+        recent_blockhash: Hash,
+        urgency: UrgencyLevel,
+        buy_amount_sol: f64,
+    ) -> Result<Signature> {
+        let transaction = Transaction::new_signed_with_payer(
+            &finalize_instructions(vec![build_buy_instruction(params)]),
+            Some(&params.buyer),
+            signers,
+            recent_blockhash,
+        );
+        self.send_priority_transaction(&transaction, signers, urgency, buy_amount_sol).await
+    }
+
+    /// Caps the strategy tip at `max_tip_fraction_of_buy` of the buy size and at
+    /// `max_tip_lamports`. Returns `None` when the capped tip falls below
+    /// `min_tip_lamports`, meaning Jito is not worth using for this trade.
+    pub fn calculate_capped_tip(&self, urgency: UrgencyLevel, buy_amount_sol: f64) -> Option<u64> {
+        let buy_amount_lamports = buy_amount_sol * LAMPORTS_PER_SOL as f64;
+        let buy_cap = (buy_amount_lamports * self.config.max_tip_fraction_of_buy) as u64;
+
+        let tip = self
+            .calculate_tip_amount(urgency)
+            .min(buy_cap)
+            .min(self.config.max_tip_lamports);
+
+        if tip < self.config.min_tip_lamports {
+            None
+        } else {
+            Some(tip)
+        }
     }
 
     fn calculate_tip_amount(&self, urgency: UrgencyLevel) -> u64 {
@@ -181,7 +436,7 @@ impl JitoManager {
             TipStrategy::Fixed(amount) => *amount,
             TipStrategy::Dynamic(config) => {
                 let base_tip = config.base_tip;
-                let congestion_multiplier = self.network_congestion * config.network_congestion_multiplier;
+                let congestion_multiplier = *self.network_congestion.read() * config.network_congestion_multiplier;
                 let urgency_multiplier = match urgency {
                     UrgencyLevel::Low => 1.0,
                     UrgencyLevel::Medium => 1.5,
@@ -196,13 +451,14 @@ impl JitoManager {
     }
 
     pub fn update_network_congestion(&mut self, congestion_level: f64) {
-        self.network_congestion = congestion_level.clamp(0.1, 10.0);
-        info!("Updated network congestion level: {:.2}", self.network_congestion);
+        let level = congestion_level.clamp(0.1, 10.0);
+        *self.network_congestion.write() = level;
+        info!("Updated network congestion level: {:.2}", level);
     }
 
     pub fn get_network_stats(&self) -> NetworkStats {
         NetworkStats {
-            congestion_level: self.network_congestion,
+            congestion_level: *self.network_congestion.read(),
             jito_enabled: self.client.is_jito_enabled(),
             recommended_tip: self.calculate_tip_amount(UrgencyLevel::Medium),
         }
@@ -247,12 +503,15 @@ pub struct JitoBundle {
 }
 
 impl JitoBundle {
-    pub fn send(&self, client: &JitoClient) -> Result<Vec<Signature>> {
+    /// Sends each transaction with its own tip, paid by its fee payer and
+    /// signed by `signers`.
+    pub async fn send<T: Signer>(&self, client: &JitoClient, signers: &[&T]) -> Result<Vec<Signature>> {
         let mut signatures = Vec::new();
         
         for transaction in &self.transactions {
-            let signature = client
-                .send_transaction_with_jito(transaction, &[], self.tip_lamports)?;
+            let (signature, _) = client
+                .send_transaction_with_jito(transaction, signers, self.tip_lamports)
+                .await?;
             signatures.push(signature);
         }
         
@@ -263,6 +522,8 @@ impl JitoBundle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_sdk::signature::Keypair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     #[test]
     fn test_jito_config() {
@@ -271,6 +532,169 @@ mod tests {
         assert_eq!(config.default_tip_lamports, 10000);
     }
 
+    #[test]
+    fn test_tip_capped_by_buy_size() {
+        let config = JitoConfig::default();
+        let mut manager = JitoManager::new(
+            "https://api.mainnet-beta.solana.com".to_string(),
+            config,
+        ).unwrap();
+
+        // Max congestion × critical urgency would exceed the max tip uncapped
+        manager.update_network_congestion(10.0);
+        let raw_tip = manager.calculate_tip_amount(UrgencyLevel::Critical);
+        assert_eq!(raw_tip, 100000);
+
+        // 0.001 SOL buy at 5% caps the tip at 50_000 lamports
+        let tip = manager.calculate_capped_tip(UrgencyLevel::Critical, 0.001).unwrap();
+        assert_eq!(tip, 50000);
+
+        // Low congestion × low urgency stays under the buy cap
+        manager.update_network_congestion(1.0);
+        let tip = manager.calculate_capped_tip(UrgencyLevel::Low, 1.0).unwrap();
+        assert_eq!(tip, manager.calculate_tip_amount(UrgencyLevel::Low));
+    }
+
+    #[test]
+    fn test_tip_below_floor_skips_jito() {
+        let config = JitoConfig::default();
+        let mut manager = JitoManager::new(
+            "https://api.mainnet-beta.solana.com".to_string(),
+            config,
+        ).unwrap();
+        manager.update_network_congestion(10.0);
+
+        // 0.00001 SOL buy at 5% is 500 lamports, under the 1000 lamport floor
+        assert!(manager.calculate_capped_tip(UrgencyLevel::Critical, 0.00001).is_none());
+    }
+
+    /// Serves a block engine that answers every `sendBundle` with a JSON-RPC error.
+    async fn rejecting_block_engine() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                // Read the whole request before answering, so the client isn't reset mid-send
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    let complete = text.find("\r\n\r\n").map_or(false, |end| {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|n| n.trim().parse().unwrap_or(0)))
+                            .unwrap_or(0);
+                        request.len() >= end + 4 + length
+                    });
+                    if read == 0 || complete {
+                        break;
+                    }
+                }
+                let body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"bundle dropped"}}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    // The blocking client parks its worker thread while it waits, which a
+    // current-thread runtime can't do
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rejected_bundle_strict_and_fallback() {
+        let block_engine_url = rejecting_block_engine().await;
+        // The block engine rejects the bundle, and the mock RPC answers nothing
+        let failing_client = || JitoClient {
+            rpc_client: RpcClient::new_mock("fails".to_string()),
+            ..JitoClient::new(String::new(), true).unwrap().with_block_engine(block_engine_url.clone())
+        };
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+
+        let strict = failing_client().with_strict(true);
+        let error = strict.send_transaction_with_jito(&transaction, &[&payer], 10_000).await.unwrap_err();
+        assert!(error.is_bundle_rejected());
+        assert!(error.to_string().contains("bundle dropped"));
+
+        // Without strict mode the send moves on to regular RPC, and fails there
+        let fallback = failing_client();
+        let error = fallback.send_transaction_with_jito(&transaction, &[&payer], 10_000).await.unwrap_err();
+        assert!(!error.is_bundle_rejected());
+        assert!(error.to_string().contains("Regular transaction failed"));
+    }
+
+    #[test]
+    fn test_tip_transaction_is_signed_by_the_fee_payer() {
+        let client = JitoClient::new(String::new(), true).unwrap();
+        let payer = Keypair::new();
+        let blockhash = Hash::new_unique();
+        let transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            blockhash,
+        );
+
+        let tip_transaction = client.tip_transaction(&transaction, &[&payer], 25_000).unwrap();
+        assert_eq!(tip_transaction.message.account_keys[0], payer.pubkey());
+        assert_eq!(tip_transaction.message.recent_blockhash, blockhash);
+        assert!(tip_transaction.verify().is_ok());
+
+        // A transaction without a fee payer has nobody to tip from
+        let no_signers: &[&Keypair] = &[];
+        assert!(client.tip_transaction(&Transaction::default(), no_signers, 25_000).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unreachable_block_engine_strict_and_fallback() {
+        // Nothing listens on the block engine port, and the mock RPC answers nothing
+        let failing_client = || JitoClient {
+            rpc_client: RpcClient::new_mock("fails".to_string()),
+            ..JitoClient::new(String::new(), true).unwrap().with_block_engine("http://127.0.0.1:1".to_string())
+        };
+        let transaction = Transaction::default();
+        let tip_transaction = Transaction::default();
+
+        let strict = failing_client().with_strict(true);
+        let error = strict.send_bundle(&transaction, &tip_transaction).await.unwrap_err();
+        assert!(error.is_bundle_rejected());
+
+        // Without strict mode the buy goes out alone over regular RPC, and fails there
+        let fallback = failing_client();
+        let error = fallback.send_bundle(&transaction, &tip_transaction).await.unwrap_err();
+        assert!(!error.is_bundle_rejected());
+        assert!(error.to_string().contains("Regular transaction failed"));
+    }
+
+    #[test]
+    fn test_tip_transfer_pays_the_tip_account() {
+        let client = JitoClient::new(String::new(), true).unwrap();
+        let payer = Pubkey::new_unique();
+        let instruction = client.tip_transfer(&payer, 25_000);
+        assert_eq!(instruction.program_id, solana_sdk::system_program::ID);
+        assert_eq!(instruction.accounts[0].pubkey, payer);
+        assert_eq!(instruction.accounts[1].pubkey, Pubkey::from_str(JITO_TIP_ACCOUNT).unwrap());
+    }
+
+    #[test]
+    fn test_congestion_from_prioritization_fees() {
+        assert_eq!(congestion_from_prioritization_fees(&[]), 0.1);
+        assert_eq!(congestion_from_prioritization_fees(&[0, 0, 0]), 0.1);
+        assert_eq!(congestion_from_prioritization_fees(&[0, 5_000, 10_000, 20_000]), 1.0);
+        assert_eq!(congestion_from_prioritization_fees(&[1_000_000]), 10.0);
+    }
+
     #[test]
     fn test_tip_calculation() {
         let config = JitoConfig::default();
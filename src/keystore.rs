@@ -0,0 +1,146 @@
+use crate::error::{Result, SniperError};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_sdk::signature::Keypair;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// PBKDF2 rounds for newly encrypted keypairs.
+pub const KEYSTORE_ITERATIONS: u32 = 600_000;
+
+const KDF: &str = "pbkdf2-sha256";
+const SALT_LEN: usize = 16;
+
+/// A keypair encrypted with AES-256-GCM under a key derived from a password
+/// with PBKDF2-HMAC-SHA256, stored as JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedKeypair {
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, key.as_mut());
+    key
+}
+
+fn decode(field: &str, value: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| SniperError::Config(format!("Encrypted keypair has an invalid {}: {}", field, e)))
+}
+
+/// Encrypts `keypair` under `password`, returning the JSON to write to disk.
+pub fn encrypt_keypair(keypair: &Keypair, password: &str, iterations: u32) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(password, &salt, iterations);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_ref()));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let secret = Zeroizing::new(keypair.to_bytes());
+    let ciphertext = cipher
+        .encrypt(&nonce, secret.as_ref())
+        .map_err(|_| SniperError::Config("Failed to encrypt keypair".to_string()))?;
+
+    Ok(serde_json::to_string_pretty(&EncryptedKeypair {
+        kdf: KDF.to_string(),
+        iterations,
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })?)
+}
+
+/// Whether `contents` looks like an encrypted keypair rather than a base58 key.
+pub fn is_encrypted(contents: &str) -> bool {
+    serde_json::from_str::<EncryptedKeypair>(contents.trim()).is_ok()
+}
+
+/// Decrypts a keypair produced by `encrypt_keypair`. The decrypted secret only
+/// lives in memory and is wiped once the `Keypair` is built.
+pub fn decrypt_keypair(contents: &str, password: &str) -> Result<Keypair> {
+    let encrypted: EncryptedKeypair = serde_json::from_str(contents.trim())?;
+    if encrypted.kdf != KDF {
+        return Err(SniperError::Config(format!("Unsupported keypair KDF '{}'", encrypted.kdf)));
+    }
+
+    let salt = decode("salt", &encrypted.salt)?;
+    let nonce = decode("nonce", &encrypted.nonce)?;
+    let ciphertext = decode("ciphertext", &encrypted.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(SniperError::Config("Encrypted keypair has an invalid nonce".to_string()));
+    }
+
+    let key = derive_key(password, &salt, encrypted.iterations);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_ref()));
+    let secret = Zeroizing::new(
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| SniperError::Config("Wrong password or corrupted keypair file".to_string()))?,
+    );
+
+    Keypair::from_bytes(&secret).map_err(|e| SniperError::Config(format!("Invalid decrypted keypair: {}", e)))
+}
+
+/// Loads the buyer keypair from `BUYER_PRIVATE_KEY_PATH`, which is either a
+/// base58 secret key or a path to a file holding one. Encrypted files are
+/// decrypted with `password`, or with a password read from the terminal.
+pub fn load_keypair(value: &str, password: Option<&str>) -> Result<Keypair> {
+    let contents = if Path::new(value).is_file() {
+        Zeroizing::new(std::fs::read_to_string(value)?)
+    } else {
+        Zeroizing::new(value.to_string())
+    };
+
+    if is_encrypted(&contents) {
+        let password = match password {
+            Some(password) => Zeroizing::new(password.to_string()),
+            None => Zeroizing::new(rpassword::prompt_password("🔐 Keypair password: ")?),
+        };
+        return decrypt_keypair(&contents, &password);
+    }
+
+    let secret = Zeroizing::new(bs58::decode(contents.trim()).into_vec()?);
+    Keypair::from_bytes(&secret).map_err(|e| SniperError::SolanaClient(format!("Invalid private key: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Signer;
+
+    // Keeps the tests fast; real files use `KEYSTORE_ITERATIONS`
+    const TEST_ITERATIONS: u32 = 1_000;
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let keypair = Keypair::new();
+        let encrypted = encrypt_keypair(&keypair, "hunter2", TEST_ITERATIONS).unwrap();
+
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains(&keypair.to_base58_string()));
+        assert_eq!(decrypt_keypair(&encrypted, "hunter2").unwrap().pubkey(), keypair.pubkey());
+        assert!(decrypt_keypair(&encrypted, "hunter3").is_err());
+    }
+
+    #[test]
+    fn test_load_keypair_from_value_or_file() {
+        let keypair = Keypair::new();
+        assert_eq!(load_keypair(&keypair.to_base58_string(), None).unwrap().pubkey(), keypair.pubkey());
+
+        let path = std::env::temp_dir().join(format!("keypair-{}.json", keypair.pubkey()));
+        std::fs::write(&path, encrypt_keypair(&keypair, "hunter2", TEST_ITERATIONS).unwrap()).unwrap();
+        let loaded = load_keypair(path.to_str().unwrap(), Some("hunter2")).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.pubkey(), keypair.pubkey());
+    }
+}
@@ -0,0 +1,146 @@
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tracing::info;
+
+const MAX_SAMPLES: usize = 1000;
+
+/// Rolling window of latency samples with percentile queries.
+pub struct LatencyHistogram {
+    samples: VecDeque<Duration>,
+    max_samples: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+        }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        if self.samples.len() >= self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    pub fn summary(&self) -> Option<LatencySummary> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+
+        Some(LatencySummary {
+            count: sorted.len(),
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Per-buy timing breakdown, measured from receipt of the create update.
+#[derive(Debug, Clone, Copy)]
+pub struct BuyTimings {
+    pub blockhash_fetch: Duration,
+    pub build_and_sign: Duration,
+    pub to_submit: Duration,
+    pub to_confirm: Option<Duration>,
+}
+
+/// Latency histograms for the buy path: create received → buy submitted and
+/// create received → buy confirmed.
+pub struct LatencyTracker {
+    submit: Mutex<LatencyHistogram>,
+    confirm: Mutex<LatencyHistogram>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self {
+            submit: Mutex::new(LatencyHistogram::new(MAX_SAMPLES)),
+            confirm: Mutex::new(LatencyHistogram::new(MAX_SAMPLES)),
+        }
+    }
+
+    pub fn record(&self, timings: &BuyTimings) {
+        self.submit.lock().record(timings.to_submit);
+        if let Some(to_confirm) = timings.to_confirm {
+            self.confirm.lock().record(to_confirm);
+        }
+    }
+
+    pub fn submit_summary(&self) -> Option<LatencySummary> {
+        self.submit.lock().summary()
+    }
+
+    pub fn confirm_summary(&self) -> Option<LatencySummary> {
+        self.confirm.lock().summary()
+    }
+
+    pub fn log_summaries(&self) {
+        if let Some(summary) = self.submit_summary() {
+            info!("⏱️ Create → submit ({} buys): p50 {:?} | p90 {:?} | p99 {:?} | max {:?}",
+                  summary.count, summary.p50, summary.p90, summary.p99, summary.max);
+        }
+        if let Some(summary) = self.confirm_summary() {
+            info!("⏱️ Create → confirm ({} buys): p50 {:?} | p90 {:?} | p99 {:?} | max {:?}",
+                  summary.count, summary.p50, summary.p90, summary.p99, summary.max);
+        }
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::new(MAX_SAMPLES);
+        assert!(histogram.summary().is_none());
+
+        for ms in 1..=100 {
+            histogram.record(Duration::from_millis(ms));
+        }
+
+        let summary = histogram.summary().unwrap();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.p50, Duration::from_millis(51));
+        assert_eq!(summary.p90, Duration::from_millis(90));
+        assert_eq!(summary.p99, Duration::from_millis(99));
+        assert_eq!(summary.max, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_histogram_window_is_bounded() {
+        let mut histogram = LatencyHistogram::new(3);
+        for ms in [100, 1, 2, 3] {
+            histogram.record(Duration::from_millis(ms));
+        }
+
+        let summary = histogram.summary().unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.max, Duration::from_millis(3));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bounded_map;
 pub mod config;
 pub mod constants;
 pub mod error;
@@ -8,22 +9,76 @@ pub mod copy_trading;
 pub mod jito_integration;
 pub mod grpc_manager;
 pub mod scam_detection;
+pub mod scam_guard;
 pub mod bonding_curve;
 pub mod same_block_execution;
 pub mod migration_detector;
+pub mod mint_lock;
+pub mod notifier;
+pub mod buy_gate;
+pub mod keystore;
+pub mod creator_analysis;
+pub mod dead_man_switch;
+pub mod position_manager;
+pub mod latency;
+pub mod metrics;
+pub mod fee_recipient;
+pub mod pump_swap;
+pub mod rug_monitor;
+pub mod pumpfun;
+pub mod pnl;
+pub mod priority_fee;
+pub mod send_fanout;
+pub mod signer;
+pub mod status_watcher;
+pub mod trade_journal;
+pub mod buyer_window;
+pub mod position_store;
+pub mod funding;
+pub mod social_links;
+#[cfg(test)]
+pub(crate) mod mock_geyser;
+#[cfg(test)]
+pub(crate) mod mock_signer;
 
+pub use bounded_map::BoundedMap;
 pub use config::Config;
 pub use error::{Result, SniperError};
-pub use price_cache::PriceCache;
+pub use price_cache::{PriceCache, PriceHttpConfig};
 pub use sniper::SniperBot;
-pub use risk_management::{RiskManager, RiskConfig, RiskMetrics};
-pub use copy_trading::{CopyTradingEngine, CopyTradeConfig, TraderProfile};
-pub use jito_integration::{JitoManager, JitoConfig, UrgencyLevel};
+pub use risk_management::{RiskManager, RiskConfig, RiskMetrics, HolderConcentrationChecker};
+pub use copy_trading::{AutoFollowConfig, CopyAmountSource, CopyTradingEngine, CopyTradeConfig, FollowChange, FollowedTrade, TraderAutoManager, TraderOverrides, TraderProfile};
+pub use jito_integration::{BundleStatus, JitoManager, JitoConfig, JitoSubmission, UrgencyLevel};
 pub use grpc_manager::{GrpcManager, GrpcEndpoint};
-pub use scam_detection::{ScamDetector, TokenMetadata, ScamAnalysis};
+pub use scam_detection::{ScamDetector, TokenMetadata, ScamAnalysis, MintAuthorities};
+pub use scam_guard::{ScamGuard, ScamGuardConfig};
 pub use bonding_curve::{BondingCurveCalculator, BondingCurveState};
-pub use same_block_execution::{SameBlockExecutor, SameBlockSniper, SnipeConfig};
-pub use migration_detector::{MigrationDetector, Season2Features, MigrationEvent, PumpSwapMonitor};
+pub use same_block_execution::{GasCeilingAction, SameBlockExecutor, SameBlockSnipe, SameBlockSniper, SnipeConfig, SnipeOutcome};
+pub use migration_detector::{MigrationDetector, Season2Features, MigrationEvent, MigrationInstruction, PumpSwapMonitor};
+pub use mint_lock::MintLocks;
+pub use notifier::{Notifications, Notifier, TradeEvent};
+pub use buy_gate::{BuyCandidate, BuyGate, BuyGateKind, GateDecision};
+pub use creator_analysis::{CreatorAnalyzer, CreatorReport};
+pub use position_manager::{PositionManager, PositionConfig, Position, ExitReason, MigrationPolicy, AverageDownPolicy};
+pub use latency::{LatencyTracker, LatencySummary};
+pub use fee_recipient::FeeRecipientResolver;
+pub use pump_swap::{PumpSwapPool, PumpSwapSwap};
+pub use rug_monitor::{RugMonitor, RugMonitorConfig, RugReaction};
+pub use dead_man_switch::{DeadManAction, DeadManSwitch, DeadManSwitchConfig};
+pub use signer::{LocalSigner, TransactionSigner};
+pub use status_watcher::{StatusWatcher, StreamedStatus};
+pub use pnl::{FeeBreakdown, FeeKind, PnlTracker, SessionEvent, SessionLimits};
+pub use priority_fee::{
+    finalize_instructions, parse_helius_estimate, PriorityFeeEscalation, PriorityFeeEstimator, PriorityFeeLevel,
+    PriorityFeeSchedule, PriorityFeeSource, PriorityFeeSourceKind,
+};
+pub use send_fanout::{fanout_send, TransactionSender};
+pub use trade_journal::{JournalEntry, TradeJournal, TradeSide};
+pub use funding::{FundingWallet, TopUpPolicy};
+pub use social_links::{SocialLinkFetcher, SocialLinkKind, SocialLinks};
+pub use buyer_window::BuyerWindow;
+pub use position_store::PositionStore;
+pub use pumpfun::{build_buy_instruction, build_sell_instruction, program_keys, BuyParams, ProgramKeys, PumpFunAccounts, SellParams};
 
 // Generated protobuf code
 pub mod geyser {
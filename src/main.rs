@@ -1,4 +1,17 @@
-use solana_pumpfun_sniper::{config::Config, sniper::SniperBot};
+use solana_pumpfun_sniper::{
+    bonding_curve::{BondingCurveCalculator, BondingCurveState},
+    config::Config,
+    creator_analysis::CreatorAnalyzer,
+    keystore::{encrypt_keypair, load_keypair, KEYSTORE_ITERATIONS},
+    metrics,
+    price_cache::PriceCache,
+    pumpfun::bonding_curve_address,
+    scam_detection::ScamDetector,
+    sniper::SniperBot,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
 use tracing::{error, info};
 use tracing_subscriber;
 
@@ -7,10 +20,23 @@ async fn main() {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
-    info!("🚀 Starting Solana PumpFun Sniper Bot...");
+    // `--config <file>` loads a TOML/YAML file; environment variables still override it
+    let mut args: Vec<String> = std::env::args().collect();
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(index) if index + 1 < args.len() => Some(args.drain(index..=index + 1).nth(1).unwrap()),
+        Some(_) => {
+            error!("❌ Usage: --config <path to .toml or .yaml file>");
+            std::process::exit(1);
+        }
+        None => None,
+    };
 
     // Load configuration
-    let config = match Config::from_env() {
+    let loaded = match &config_path {
+        Some(path) => Config::from_file(path),
+        None => Config::from_env(),
+    };
+    let config = match loaded {
         Ok(config) => {
             info!("✅ Configuration loaded successfully");
             config
@@ -21,10 +47,53 @@ async fn main() {
         }
     };
 
+    if args.get(1).map(String::as_str) == Some("analyze-creator") {
+        run_analyze_creator(&config, &args[2..]).await;
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("simulate-buy") {
+        run_simulate_buy(&config, &args[2..]).await;
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("encrypt-keypair") {
+        run_encrypt_keypair(&config, &args[2..]);
+        return;
+    }
+
+    info!("🚀 Starting Solana PumpFun Sniper Bot...");
+
     // Create and run sniper bot
+    let metrics_addr = config.metrics_addr;
     match SniperBot::new(config) {
         Ok(bot) => {
-            if let Err(e) = bot.run().await {
+            let bot = Arc::new(bot);
+            if let Some(addr) = metrics_addr {
+                let listener = match tokio::net::TcpListener::bind(addr).await {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        error!("❌ Failed to bind the metrics endpoint to {}: {}", addr, e);
+                        std::process::exit(1);
+                    }
+                };
+                info!("📊 Serving metrics on http://{}", addr);
+                let metrics_bot = Arc::clone(&bot);
+                tokio::spawn(async move {
+                    if let Err(e) = metrics::serve(listener, move || metrics_bot.render_metrics()).await {
+                        error!("❌ Metrics endpoint stopped: {}", e);
+                    }
+                });
+            }
+            let result = tokio::select! {
+                result = Arc::clone(&bot).run() => result,
+                _ = tokio::signal::ctrl_c() => {
+                    info!("🛑 Shutting down...");
+                    Ok(())
+                }
+            };
+            bot.log_session_summary();
+            if let Err(e) = result {
                 error!("❌ Sniper bot error: {}", e);
                 std::process::exit(1);
             }
@@ -35,3 +104,157 @@ async fn main() {
         }
     }
 }
+
+/// `simulate-buy --mint <mint> --amounts 0.1,0.5,1.0` — simulates buys of each
+/// size against the token's live bonding curve, without sending anything.
+async fn run_simulate_buy(config: &Config, args: &[String]) {
+    const USAGE: &str = "❌ Usage: simulate-buy --mint <mint> --amounts 0.1,0.5,1.0";
+    let flag = |name: &str| args.iter().position(|arg| arg == name).and_then(|index| args.get(index + 1));
+
+    let Some(Ok(mint)) = flag("--mint").map(|mint| Pubkey::from_str(mint)) else {
+        error!("{}", USAGE);
+        std::process::exit(1);
+    };
+    let amounts: Option<Vec<f64>> = flag("--amounts").and_then(|amounts| {
+        amounts.split(',').map(|amount| amount.trim().parse().ok().filter(|amount: &f64| *amount > 0.0)).collect()
+    });
+    let Some(amounts) = amounts.filter(|amounts| !amounts.is_empty()) else {
+        error!("{}", USAGE);
+        std::process::exit(1);
+    };
+
+    let rpc_client = config.rpc_client(&config.solana_rpc_endpoint);
+    let bonding_curve = bonding_curve_address(&mint);
+    let curve = match rpc_client.get_account_data(&bonding_curve).map_err(|e| e.to_string())
+        .and_then(|data| BondingCurveState::from_account_data(&data).map_err(|e| e.to_string()))
+    {
+        Ok(curve) => curve,
+        Err(e) => {
+            error!("❌ Failed to read bonding curve {}: {}", bonding_curve, e);
+            std::process::exit(1);
+        }
+    };
+
+    let price_cache = match PriceCache::from_config(config) {
+        Ok(price_cache) => price_cache,
+        Err(e) => {
+            error!("❌ Failed to create price cache: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let sol_price_usd = match price_cache.refresh().await {
+        Ok(price) => price,
+        Err(e) => {
+            error!("❌ Failed to fetch SOL price: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut calculator = BondingCurveCalculator::new(sol_price_usd);
+    println!("Mint: {} | Market cap: ${:.2} | SOL: ${:.2}", mint, curve.get_market_cap(sol_price_usd), sol_price_usd);
+    calculator.track_curve(&mint, curve);
+    match calculator.compare_buys(&mint, &amounts) {
+        Ok(simulations) => {
+            println!("{:>10} {:>18} {:>12} {:>16}", "SOL", "Tokens", "Impact", "Market cap");
+            for (amount, simulation) in amounts.iter().zip(&simulations) {
+                println!(
+                    "{:>10.4} {:>18.2} {:>11.2}% {:>16.2}",
+                    amount, simulation.tokens_received, simulation.price_impact * 100.0, simulation.new_market_cap
+                );
+            }
+        }
+        Err(e) => {
+            error!("❌ Buy simulation failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `encrypt-keypair <output_path>` — writes the `BUYER_PRIVATE_KEY_PATH` key
+/// to an encrypted file that can replace it, using `KEYPAIR_PASSWORD` or a
+/// password typed at the prompt.
+fn run_encrypt_keypair(config: &Config, args: &[String]) {
+    let Some(output) = args.first() else {
+        error!("❌ Usage: encrypt-keypair <output_path>");
+        std::process::exit(1);
+    };
+
+    let Some(key) = config.buyer_private_key.as_deref() else {
+        error!("❌ BUYER_PRIVATE_KEY_PATH environment variable not set");
+        std::process::exit(1);
+    };
+    let keypair = match load_keypair(key, config.keypair_password.as_deref()) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            error!("❌ Failed to load keypair: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let password = match &config.keypair_password {
+        Some(password) => password.clone(),
+        None => {
+            let password = rpassword::prompt_password("New keypair password: ").unwrap_or_default();
+            let confirmation = rpassword::prompt_password("Repeat password: ").unwrap_or_default();
+            if password.is_empty() || password != confirmation {
+                error!("❌ Passwords are empty or do not match");
+                std::process::exit(1);
+            }
+            password
+        }
+    };
+
+    let written = encrypt_keypair(&keypair, &password, KEYSTORE_ITERATIONS)
+        .and_then(|encrypted| Ok(std::fs::write(output, encrypted)?));
+    match written {
+        Ok(()) => info!("🔐 Encrypted keypair written to {}; point BUYER_PRIVATE_KEY_PATH at it", output),
+        Err(e) => {
+            error!("❌ Failed to write encrypted keypair: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `analyze-creator <creator_pubkey> [signature_limit]` — simulate-only scam
+/// analysis over a creator's recent PumpFun launches.
+async fn run_analyze_creator(config: &Config, args: &[String]) {
+    let creator = match args.first().map(|arg| Pubkey::from_str(arg)) {
+        Some(Ok(creator)) => creator,
+        _ => {
+            error!("❌ Usage: analyze-creator <creator_pubkey> [signature_limit]");
+            std::process::exit(1);
+        }
+    };
+    let signature_limit = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(100);
+
+    let analyzer = match CreatorAnalyzer::new(config.solana_rpc_endpoint.clone()) {
+        Ok(analyzer) => analyzer,
+        Err(e) => {
+            error!("❌ Failed to create creator analyzer: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let detector = ScamDetector::new();
+    match analyzer.analyze_creator(&detector, &creator, signature_limit).await {
+        Ok(report) => {
+            println!("Creator: {}", report.creator);
+            for token in &report.tokens {
+                println!(
+                    "  {} {} ({}) score {:.2} -> {:?}",
+                    token.mint, token.name, token.symbol, token.scam_score, token.recommendation
+                );
+            }
+            println!(
+                "Tokens analyzed: {} | Average scam score: {:.2} | Verdict: {:?}",
+                report.tokens.len(),
+                report.average_scam_score,
+                report.recommendation
+            );
+        }
+        Err(e) => {
+            error!("❌ Creator analysis failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
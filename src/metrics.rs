@@ -0,0 +1,67 @@
+use crate::error::Result;
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::debug;
+
+/// Appends one gauge, with its help and type lines, in the Prometheus text format.
+pub fn gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Answers every HTTP request on `listener` with the text `render` returns
+/// at that moment, whatever the path. Runs until the listener fails.
+pub async fn serve(listener: TcpListener, render: impl Fn() -> String + Send + Sync + 'static) -> Result<()> {
+    let render = Arc::new(render);
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        let render = Arc::clone(&render);
+        tokio::spawn(async move {
+            // The request itself is not needed, only that one arrived
+            let mut request = [0u8; 1024];
+            if stream.read(&mut request).await.is_err() {
+                return;
+            }
+            let body = render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                debug!("Failed to answer metrics request from {}: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_gauge_format() {
+        let mut out = String::new();
+        gauge(&mut out, "sniper_open_positions", "Positions currently held", 2.0);
+        assert_eq!(out, "# HELP sniper_open_positions Positions currently held\n# TYPE sniper_open_positions gauge\nsniper_open_positions 2\n");
+    }
+
+    #[tokio::test]
+    async fn test_serves_the_rendered_metrics() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, || "sniper_open_positions 1\n".to_string()));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\nsniper_open_positions 1\n"));
+    }
+}
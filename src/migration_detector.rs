@@ -1,6 +1,13 @@
+use crate::bounded_map::{BoundedMap, DEFAULT_MAX_TRACKED_TOKENS};
+use crate::constants::*;
 use crate::error::{Result, SniperError};
+use crate::price_cache::PriceCache;
+use crate::pump_swap::PoolUpdate;
+use crate::pumpfun::discriminators;
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{info, warn, error};
 use serde::{Deserialize, Serialize};
@@ -15,16 +22,50 @@ pub struct MigrationEvent {
     pub creator_address: Pubkey,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum MigrationType {
     Instant,        // New Season 2 instant migration
     Traditional,    // Old migration with fees
     PumpSwap,      // Migration to PumpSwap platform
 }
 
+// Account positions in the PumpFun `migrate` instruction
+const MIGRATE_MINT_INDEX: usize = 2;
+const MIGRATE_BONDING_CURVE_INDEX: usize = 3;
+const MIGRATE_POOL_INDEX: usize = 9;
+
+/// Accounts of interest from an on-chain PumpFun migrate instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MigrationInstruction {
+    pub migration_type: MigrationType,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub pool: Pubkey,
+}
+
+/// Decodes a PumpFun migrate instruction given its data and its resolved
+/// account keys (in instruction order). Returns `None` for any other instruction.
+pub fn parse_migration_instruction(data: &[u8], accounts: &[Pubkey]) -> Option<MigrationInstruction> {
+    let discriminators = discriminators();
+    let migration_type = if data.starts_with(&discriminators.migrate) {
+        MigrationType::PumpSwap
+    } else if data.starts_with(&discriminators.instant_migration) {
+        MigrationType::Instant
+    } else {
+        return None;
+    };
+
+    Some(MigrationInstruction {
+        migration_type,
+        mint: *accounts.get(MIGRATE_MINT_INDEX)?,
+        bonding_curve: *accounts.get(MIGRATE_BONDING_CURVE_INDEX)?,
+        pool: *accounts.get(MIGRATE_POOL_INDEX)?,
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct MigrationDetector {
-    migration_events: HashMap<Pubkey, MigrationEvent>,
+    migration_events: BoundedMap<Pubkey, MigrationEvent>,
     pump_swap_program_id: Pubkey,
     migration_threshold: f64,
     last_check: Instant,
@@ -32,17 +73,22 @@ pub struct MigrationDetector {
 
 impl MigrationDetector {
     pub fn new() -> Result<Self> {
-        // PumpSwap program ID (needs to be verified)
-        let pump_swap_program_id = Pubkey::from_str("PumpSwap1111111111111111111111111111111111")?;
+        let pump_swap_program_id = Pubkey::from_str(PUMP_SWAP_PROGRAM_ID)?;
         
         Ok(Self {
-            migration_events: HashMap::new(),
+            migration_events: BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS),
             pump_swap_program_id,
             migration_threshold: 0.95, // 95% of bonding curve completed
             last_check: Instant::now(),
         })
     }
 
+    /// Caps how many migration events are kept; the oldest are dropped first.
+    pub fn with_max_tracked_tokens(mut self, max_tokens: usize) -> Self {
+        self.migration_events.set_capacity(max_tokens);
+        self
+    }
+
     pub fn detect_migration(&mut self, token_mint: &Pubkey, bonding_curve_state: &BondingCurveState) -> Option<MigrationEvent> {
         // Check if token is ready for migration (Season 2 criteria)
         if self.is_ready_for_migration(bonding_curve_state) {
@@ -64,6 +110,25 @@ impl MigrationDetector {
         None
     }
 
+    /// Records a migration observed on chain. Unlike `detect_migration`, the
+    /// PumpSwap pool address comes straight from the migrate instruction.
+    pub fn record_migration(&mut self, migration: &MigrationInstruction, liquidity_migrated: f64) -> MigrationEvent {
+        let migration_event = MigrationEvent {
+            token_mint: migration.mint,
+            migration_time: Instant::now(),
+            migration_type: migration.migration_type,
+            liquidity_migrated,
+            pump_swap_address: Some(migration.pool),
+            creator_address: Pubkey::default(), // Not part of the migrate instruction
+        };
+
+        self.migration_events.insert(migration.mint, migration_event.clone());
+        info!("🚀 On-chain migration for token {} - PumpSwap pool {} ({:.2} SOL)",
+              migration.mint, migration.pool, liquidity_migrated);
+
+        migration_event
+    }
+
     fn is_ready_for_migration(&self, bonding_curve_state: &BondingCurveState) -> bool {
         // Season 2 criteria: Instant migration when bonding curve is complete
         // This is a simplified check - in reality, we'd need to monitor the actual migration events
@@ -95,9 +160,15 @@ impl MigrationDetector {
     }
 
     pub fn cleanup_old_events(&mut self, max_age: Duration) {
+        self.cleanup_old_events_except(max_age, |_| false);
+    }
+
+    /// Like `cleanup_old_events`, but never drops mints for which `keep` holds,
+    /// e.g. tokens the bot still has a position in.
+    pub fn cleanup_old_events_except(&mut self, max_age: Duration, keep: impl Fn(&Pubkey) -> bool) {
         let now = Instant::now();
-        self.migration_events.retain(|_, event| {
-            now.duration_since(event.migration_time) < max_age
+        self.migration_events.retain(|mint, event| {
+            keep(mint) || now.duration_since(event.migration_time) < max_age
         });
     }
 }
@@ -139,7 +210,7 @@ impl BondingCurveState {
 
 pub struct PumpSwapMonitor {
     migration_detector: MigrationDetector,
-    pump_swap_tokens: HashMap<Pubkey, PumpSwapToken>,
+    pump_swap_tokens: BoundedMap<Pubkey, PumpSwapToken>,
 }
 
 #[derive(Debug, Clone)]
@@ -156,7 +227,7 @@ impl PumpSwapMonitor {
     pub fn new() -> Result<Self> {
         Ok(Self {
             migration_detector: MigrationDetector::new()?,
-            pump_swap_tokens: HashMap::new(),
+            pump_swap_tokens: BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS),
         })
     }
 
@@ -183,18 +254,52 @@ impl PumpSwapMonitor {
     pub fn get_all_pump_swap_tokens(&self) -> Vec<&PumpSwapToken> {
         self.pump_swap_tokens.values().collect()
     }
+
+    /// Applies a poll of a token's pool: a priced pool refreshes the token, a
+    /// closed one stops it being tracked.
+    pub fn apply_pool_update(&mut self, update: &PoolUpdate) {
+        match *update {
+            PoolUpdate::Priced { mint, price_sol, liquidity_sol } => {
+                if let Some(token) = self.pump_swap_tokens.get_mut(&mint) {
+                    token.price = price_sol;
+                    token.liquidity = liquidity_sol;
+                    token.last_update = Instant::now();
+                }
+            }
+            PoolUpdate::Closed { mint } => {
+                if let Some(token) = self.pump_swap_tokens.remove(&mint) {
+                    info!("PumpSwap pool {} for {} is gone, no longer tracking it", token.pump_swap_address, mint);
+                }
+            }
+        }
+    }
+}
+
+/// `sol` in USD at the price in `price_cache`; `None` without a cache or
+/// while it has no price yet.
+fn sol_to_usd(price_cache: Option<&PriceCache>, sol: f64) -> Option<f64> {
+    let price = price_cache?.get();
+    (price > 0.0).then(|| sol * price)
 }
 
 pub struct CreatorRevenueTracker {
     creator_revenues: HashMap<Pubkey, CreatorRevenue>,
+    price_cache: Option<Arc<PriceCache>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct CreatorRevenue {
     pub creator_address: Pubkey,
     pub total_revenue: f64,
+    /// Payouts valued at the SOL price when they were made. Payouts that
+    /// arrived before a price was known are left out, not counted as zero.
+    pub total_revenue_usd: f64,
+    /// How many payouts `total_revenue_usd` covers.
+    pub priced_payouts: u32,
     pub tokens_created: u32,
     pub average_revenue_per_token: f64,
+    /// Average over the priced payouts; `None` until one has a price.
+    pub average_revenue_per_token_usd: Option<f64>,
     pub last_payout: Instant,
     pub revenue_share_percentage: f64,
 }
@@ -203,25 +308,45 @@ impl CreatorRevenueTracker {
     pub fn new() -> Self {
         Self {
             creator_revenues: HashMap::new(),
+            price_cache: None,
         }
     }
 
+    /// Values revenue in USD as well, at the prices in `price_cache`.
+    pub fn with_price_cache(mut self, price_cache: Arc<PriceCache>) -> Self {
+        self.price_cache = Some(price_cache);
+        self
+    }
+
     pub fn track_creator_revenue(&mut self, creator: Pubkey, revenue: f64, token_mint: Pubkey) {
+        let revenue_usd = sol_to_usd(self.price_cache.as_deref(), revenue);
         let creator_revenue = self.creator_revenues.entry(creator).or_insert(CreatorRevenue {
             creator_address: creator,
             total_revenue: 0.0,
+            total_revenue_usd: 0.0,
+            priced_payouts: 0,
             tokens_created: 0,
             average_revenue_per_token: 0.0,
+            average_revenue_per_token_usd: None,
             last_payout: Instant::now(),
             revenue_share_percentage: 0.05, // 5% default revenue share
         });
 
         creator_revenue.total_revenue += revenue;
+        if let Some(usd) = revenue_usd {
+            creator_revenue.total_revenue_usd += usd;
+            creator_revenue.priced_payouts += 1;
+            creator_revenue.average_revenue_per_token_usd =
+                Some(creator_revenue.total_revenue_usd / creator_revenue.priced_payouts as f64);
+        }
         creator_revenue.tokens_created += 1;
         creator_revenue.average_revenue_per_token = creator_revenue.total_revenue / creator_revenue.tokens_created as f64;
         creator_revenue.last_payout = Instant::now();
 
-        info!("Creator {} earned {} SOL from token {}", creator, revenue, token_mint);
+        match revenue_usd {
+            Some(usd) => info!("Creator {} earned {} SOL (${:.2}) from token {}", creator, revenue, usd, token_mint),
+            None => info!("Creator {} earned {} SOL from token {}", creator, revenue, token_mint),
+        }
     }
 
     pub fn get_creator_revenue(&self, creator: &Pubkey) -> Option<&CreatorRevenue> {
@@ -238,6 +363,7 @@ impl CreatorRevenueTracker {
 pub struct Season2Features {
     migration_monitor: PumpSwapMonitor,
     creator_tracker: CreatorRevenueTracker,
+    price_cache: Option<Arc<PriceCache>>,
     instant_migration_enabled: bool,
     zero_migration_fees: bool,
 }
@@ -247,11 +373,37 @@ impl Season2Features {
         Ok(Self {
             migration_monitor: PumpSwapMonitor::new()?,
             creator_tracker: CreatorRevenueTracker::new(),
+            price_cache: None,
             instant_migration_enabled: true,
             zero_migration_fees: true,
         })
     }
 
+    /// Reports creator revenue and migrated liquidity in USD as well as SOL,
+    /// at the prices in `price_cache`.
+    pub fn with_price_cache(mut self, price_cache: Arc<PriceCache>) -> Self {
+        self.creator_tracker.price_cache = Some(Arc::clone(&price_cache));
+        self.price_cache = Some(price_cache);
+        self
+    }
+
+    pub fn with_max_tracked_tokens(mut self, max_tokens: usize) -> Self {
+        let monitor = &mut self.migration_monitor;
+        monitor.migration_detector.migration_events.set_capacity(max_tokens);
+        monitor.pump_swap_tokens.set_capacity(max_tokens);
+        self
+    }
+
+    /// Forgets migrations older than `max_age`, except for mints in `keep`,
+    /// along with the PumpSwap tokens they pointed at.
+    pub fn cleanup_old_events(&mut self, max_age: Duration, keep: &[Pubkey]) {
+        let monitor = &mut self.migration_monitor;
+        monitor.migration_detector.cleanup_old_events_except(max_age, |mint| keep.contains(mint));
+
+        let detector = &monitor.migration_detector;
+        monitor.pump_swap_tokens.retain(|mint, _| detector.is_token_migrated(mint));
+    }
+
     pub fn process_token_update(&mut self, token_mint: &Pubkey, bonding_curve_state: &BondingCurveState) -> Option<MigrationEvent> {
         // Monitor for Season 2 instant migrations
         if self.instant_migration_enabled {
@@ -260,6 +412,54 @@ impl Season2Features {
         None
     }
 
+    /// Records an on-chain migrate instruction and applies the resulting event.
+    pub fn process_migration_instruction(&mut self, migration: &MigrationInstruction, liquidity_migrated: f64) -> MigrationEvent {
+        let migration_event = self.migration_monitor.migration_detector.record_migration(migration, liquidity_migrated);
+        self.handle_migration_event(&migration_event);
+        migration_event
+    }
+
+    pub fn is_token_migrated(&self, token_mint: &Pubkey) -> bool {
+        self.migration_monitor.migration_detector.is_token_migrated(token_mint)
+    }
+
+    /// Every tracked `(mint, pool)` pair, for the PumpSwap poller.
+    pub fn pump_swap_pools(&self) -> Vec<(Pubkey, Pubkey)> {
+        self.migration_monitor
+            .get_all_pump_swap_tokens()
+            .iter()
+            .map(|token| (token.mint, token.pump_swap_address))
+            .collect()
+    }
+
+    pub fn apply_pool_updates(&mut self, updates: &[PoolUpdate]) {
+        for update in updates {
+            self.migration_monitor.apply_pool_update(update);
+        }
+    }
+
+    /// The PumpSwap pool a migrated token now trades in, if known.
+    pub fn pump_swap_pool(&self, token_mint: &Pubkey) -> Option<Pubkey> {
+        if !self.is_token_migrated(token_mint) {
+            return None;
+        }
+        self.migration_monitor
+            .get_pump_swap_token(token_mint)
+            .map(|token| token.pump_swap_address)
+    }
+
+    /// The SOL price per whole token of a migrated token, from the last poll
+    /// of its PumpSwap pool. `None` until a poll has priced it.
+    pub fn pump_swap_price(&self, token_mint: &Pubkey) -> Option<f64> {
+        if !self.is_token_migrated(token_mint) {
+            return None;
+        }
+        self.migration_monitor
+            .get_pump_swap_token(token_mint)
+            .map(|token| token.price)
+            .filter(|price| *price > 0.0)
+    }
+
     pub fn handle_migration_event(&mut self, migration_event: &MigrationEvent) {
         // Track creator revenue from migration
         if migration_event.liquidity_migrated > 0.0 {
@@ -289,11 +489,13 @@ impl Season2Features {
         let migration_events = self.migration_monitor.migration_detector.get_migration_events();
         let pump_swap_tokens = self.migration_monitor.get_all_pump_swap_tokens();
         let top_creators = self.creator_tracker.get_top_creators(10);
+        let total_liquidity_migrated = pump_swap_tokens.iter().map(|t| t.liquidity).sum();
 
         MigrationStats {
             total_migrations: migration_events.len(),
             pump_swap_tokens_count: pump_swap_tokens.len(),
-            total_liquidity_migrated: pump_swap_tokens.iter().map(|t| t.liquidity).sum(),
+            total_liquidity_migrated,
+            total_liquidity_migrated_usd: sol_to_usd(self.price_cache.as_deref(), total_liquidity_migrated),
             top_creators_count: top_creators.len(),
             instant_migration_enabled: self.instant_migration_enabled,
             zero_migration_fees: self.zero_migration_fees,
@@ -306,6 +508,8 @@ pub struct MigrationStats {
     pub total_migrations: usize,
     pub pump_swap_tokens_count: usize,
     pub total_liquidity_migrated: f64,
+    /// `total_liquidity_migrated` at the current SOL price; `None` without one.
+    pub total_liquidity_migrated_usd: Option<f64>,
     pub top_creators_count: usize,
     pub instant_migration_enabled: bool,
     pub zero_migration_fees: bool,
@@ -324,6 +528,68 @@ mod tests {
         assert!(migration.is_some());
     }
 
+    #[test]
+    fn test_parse_migration_instruction() {
+        let accounts: Vec<Pubkey> = (0..24).map(|_| Pubkey::new_unique()).collect();
+        let mut data = PUMPFUN_MIGRATE_DISCRIMINATOR.to_vec();
+
+        let migration = parse_migration_instruction(&data, &accounts).unwrap();
+        assert_eq!(migration.migration_type, MigrationType::PumpSwap);
+        assert_eq!(migration.mint, accounts[2]);
+        assert_eq!(migration.bonding_curve, accounts[3]);
+        assert_eq!(migration.pool, accounts[9]);
+
+        data[..8].copy_from_slice(&INSTANT_MIGRATION_DISCRIMINATOR);
+        let migration = parse_migration_instruction(&data, &accounts).unwrap();
+        assert_eq!(migration.migration_type, MigrationType::Instant);
+
+        assert!(parse_migration_instruction(&data, &accounts[..9]).is_none());
+        assert!(parse_migration_instruction(&CREATE_DISCRIMINATOR, &accounts).is_none());
+    }
+
+    #[test]
+    fn test_on_chain_migration_feeds_season2() {
+        let mut season2 = Season2Features::new().unwrap();
+        let accounts: Vec<Pubkey> = (0..24).map(|_| Pubkey::new_unique()).collect();
+        let migration = parse_migration_instruction(&PUMPFUN_MIGRATE_DISCRIMINATOR, &accounts).unwrap();
+
+        let event = season2.process_migration_instruction(&migration, 85.0);
+        assert_eq!(event.pump_swap_address, Some(accounts[9]));
+        assert!(season2.is_token_migrated(&accounts[2]));
+        assert_eq!(season2.pump_swap_pool(&accounts[2]), Some(accounts[9]));
+        assert_eq!(season2.get_migration_stats().pump_swap_tokens_count, 1);
+        assert_eq!(season2.pump_swap_pools(), [(accounts[2], accounts[9])]);
+
+        // Polled pools refresh the token, and closed ones are dropped
+        season2.apply_pool_updates(&[PoolUpdate::Priced { mint: accounts[2], price_sol: 4e-7, liquidity_sol: 90.0 }]);
+        assert_eq!(season2.get_migration_stats().total_liquidity_migrated, 90.0);
+        season2.apply_pool_updates(&[PoolUpdate::Closed { mint: accounts[2] }]);
+        assert_eq!(season2.pump_swap_pool(&accounts[2]), None);
+    }
+
+    #[test]
+    fn test_migration_tracking_is_bounded() {
+        let mut season2 = Season2Features::new().unwrap().with_max_tracked_tokens(2);
+        let migrations: Vec<MigrationInstruction> = (0..3)
+            .map(|_| {
+                let accounts: Vec<Pubkey> = (0..24).map(|_| Pubkey::new_unique()).collect();
+                parse_migration_instruction(&PUMPFUN_MIGRATE_DISCRIMINATOR, &accounts).unwrap()
+            })
+            .collect();
+        for migration in &migrations {
+            season2.process_migration_instruction(migration, 85.0);
+        }
+
+        assert!(!season2.is_token_migrated(&migrations[0].mint));
+        assert_eq!(season2.get_migration_stats().total_migrations, 2);
+
+        // Cleanup keeps held tokens routable even when their events are old
+        season2.cleanup_old_events(Duration::ZERO, &[migrations[2].mint]);
+        assert!(!season2.is_token_migrated(&migrations[1].mint));
+        assert_eq!(season2.pump_swap_pool(&migrations[2].mint), Some(migrations[2].pool));
+        assert_eq!(season2.get_migration_stats().pump_swap_tokens_count, 1);
+    }
+
     #[test]
     fn test_creator_revenue_tracking() {
         let mut tracker = CreatorRevenueTracker::new();
@@ -334,5 +600,45 @@ mod tests {
         
         assert_eq!(revenue.total_revenue, 1.0);
         assert_eq!(revenue.tokens_created, 1);
+        assert_eq!((revenue.total_revenue_usd, revenue.priced_payouts), (0.0, 0));
+        assert_eq!(revenue.average_revenue_per_token_usd, None);
+    }
+
+    #[test]
+    fn test_revenue_and_liquidity_in_usd() {
+        let price_cache = Arc::new(PriceCache::new());
+        let mut season2 = Season2Features::new().unwrap().with_price_cache(Arc::clone(&price_cache));
+        let accounts: Vec<Pubkey> = (0..24).map(|_| Pubkey::new_unique()).collect();
+        let migration = parse_migration_instruction(&PUMPFUN_MIGRATE_DISCRIMINATOR, &accounts).unwrap();
+
+        // Without a price only SOL is reported
+        let event = season2.process_migration_instruction(&migration, 80.0);
+        let stats = season2.get_migration_stats();
+        assert_eq!((stats.total_liquidity_migrated, stats.total_liquidity_migrated_usd), (80.0, None));
+        let revenue = season2.creator_tracker.get_creator_revenue(&event.creator_address).unwrap();
+        assert_eq!((revenue.total_revenue, revenue.priced_payouts), (0.8, 0));
+
+        price_cache.set(150.0);
+        assert_eq!(season2.get_migration_stats().total_liquidity_migrated_usd, Some(12_000.0));
+
+        let mut tracker = CreatorRevenueTracker::new().with_price_cache(Arc::clone(&price_cache));
+        let creator = Pubkey::new_unique();
+        tracker.track_creator_revenue(creator, 1.0, Pubkey::new_unique());
+        price_cache.set(200.0);
+        tracker.track_creator_revenue(creator, 2.0, Pubkey::new_unique());
+        let revenue = tracker.get_creator_revenue(&creator).unwrap();
+        assert_eq!(revenue.total_revenue_usd, 550.0);
+        assert_eq!(revenue.average_revenue_per_token_usd, Some(275.0));
+
+        // A payout made before the price was known doesn't wipe out the USD total
+        let late_creator = Pubkey::new_unique();
+        let late_price_cache = Arc::new(PriceCache::new());
+        let mut tracker = CreatorRevenueTracker::new().with_price_cache(Arc::clone(&late_price_cache));
+        tracker.track_creator_revenue(late_creator, 1.0, Pubkey::new_unique());
+        late_price_cache.set(150.0);
+        tracker.track_creator_revenue(late_creator, 2.0, Pubkey::new_unique());
+        let revenue = tracker.get_creator_revenue(&late_creator).unwrap();
+        assert_eq!((revenue.total_revenue, revenue.total_revenue_usd, revenue.priced_payouts), (3.0, 300.0, 1));
+        assert_eq!(revenue.average_revenue_per_token_usd, Some(300.0));
     }
 }
@@ -0,0 +1,103 @@
+use parking_lot::Mutex;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// Tracks which mints are currently being processed so the same token is never
+/// handled twice at once, while unrelated tokens proceed concurrently. Mints are
+/// spread across shards to keep contention on any single lock low.
+pub struct MintLocks {
+    shards: Vec<Mutex<HashSet<Pubkey>>>,
+}
+
+impl MintLocks {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARD_COUNT)
+    }
+
+    pub fn with_shards(shard_count: usize) -> Self {
+        Self {
+            shards: (0..shard_count.max(1)).map(|_| Mutex::new(HashSet::new())).collect(),
+        }
+    }
+
+    fn shard(&self, mint: &Pubkey) -> &Mutex<HashSet<Pubkey>> {
+        let index = mint.as_ref()[0] as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Claims `mint` for processing. Returns `None` if it is already claimed.
+    /// The claim is released when the returned guard is dropped.
+    pub fn try_lock(&self, mint: &Pubkey) -> Option<MintGuard<'_>> {
+        if self.shard(mint).lock().insert(*mint) {
+            Some(MintGuard { locks: self, mint: *mint })
+        } else {
+            None
+        }
+    }
+
+    pub fn is_locked(&self, mint: &Pubkey) -> bool {
+        self.shard(mint).lock().contains(mint)
+    }
+}
+
+impl Default for MintLocks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct MintGuard<'a> {
+    locks: &'a MintLocks,
+    mint: Pubkey,
+}
+
+impl Drop for MintGuard<'_> {
+    fn drop(&mut self) {
+        self.locks.shard(&self.mint).lock().remove(&self.mint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+
+    #[test]
+    fn test_same_mint_is_exclusive() {
+        let locks = MintLocks::new();
+        let mint = Pubkey::new_unique();
+
+        let guard = locks.try_lock(&mint).unwrap();
+        assert!(locks.try_lock(&mint).is_none());
+
+        drop(guard);
+        assert!(!locks.is_locked(&mint));
+        assert!(locks.try_lock(&mint).is_some());
+    }
+
+    #[test]
+    fn test_different_mints_process_in_parallel() {
+        let locks = Arc::new(MintLocks::new());
+        let barrier = Arc::new(Barrier::new(2));
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let locks = Arc::clone(&locks);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    let mint = Pubkey::new_unique();
+                    let _guard = locks.try_lock(&mint).expect("distinct mint should not be locked");
+                    // Both threads must hold their lock at the same time to pass the barrier
+                    barrier.wait();
+                    assert!(locks.is_locked(&mint));
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
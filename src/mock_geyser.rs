@@ -0,0 +1,208 @@
+use crate::constants::*;
+use crate::pumpfun::creator_vault;
+use crate::geyser::{self, SubscribeResponse};
+#[cfg(feature = "mock-geyser")]
+use crate::geyser::{
+    geyser_server::{Geyser, GeyserServer},
+    SubscribeRequest,
+};
+#[cfg(feature = "mock-geyser")]
+use futures::Stream;
+use solana_sdk::pubkey::Pubkey;
+#[cfg(feature = "mock-geyser")]
+use std::pin::Pin;
+use std::str::FromStr;
+#[cfg(feature = "mock-geyser")]
+use tokio::net::TcpListener;
+#[cfg(feature = "mock-geyser")]
+use tokio_stream::wrappers::TcpListenerStream;
+#[cfg(feature = "mock-geyser")]
+use tonic::{transport::Server, Request, Response, Status};
+
+/// In-process Geyser service for driving `SniperBot::run` in tests. Streams a
+/// fixed script of responses to every subscriber, then ends the stream. The
+/// Geyser server stubs it needs are only generated with the `mock-geyser` feature.
+#[cfg(feature = "mock-geyser")]
+pub struct MockGeyser {
+    responses: Vec<SubscribeResponse>,
+}
+
+#[cfg(feature = "mock-geyser")]
+#[tonic::async_trait]
+impl Geyser for MockGeyser {
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<SubscribeResponse, Status>> + Send>>;
+
+    async fn subscribe(&self, _request: Request<SubscribeRequest>) -> Result<Response<Self::SubscribeStream>, Status> {
+        let responses = self.responses.clone().into_iter().map(Ok);
+        Ok(Response::new(Box::pin(futures::stream::iter(responses))))
+    }
+}
+
+/// Starts a mock Geyser server on a random local port and returns the endpoint
+/// to use as `GRPC_ENDPOINT`.
+#[cfg(feature = "mock-geyser")]
+pub async fn serve(responses: Vec<SubscribeResponse>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(GeyserServer::new(MockGeyser { responses }))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    endpoint
+}
+
+/// Accounts of a scripted create transaction.
+pub struct CreateFixture {
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    pub creator_vault: Pubkey,
+    mint_authority: Pubkey,
+    metadata: Pubkey,
+}
+
+impl CreateFixture {
+    pub fn new() -> Self {
+        let creator = Pubkey::new_unique();
+        Self {
+            creator,
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: creator_vault(&creator),
+            mint_authority: Pubkey::new_unique(),
+            metadata: Pubkey::new_unique(),
+        }
+    }
+
+    /// A legacy-message PumpFun create in which the creator deposits
+    /// `dev_buy_lamports` into the bonding curve.
+    pub fn response(&self, dev_buy_lamports: u64) -> SubscribeResponse {
+        let static_keys = [
+            self.creator,                                        // 0
+            self.mint,                                           // 1
+            self.bonding_curve,                                  // 2
+            self.associated_bonding_curve,                       // 3
+            Pubkey::from_str(KNOWN_GLOBAL).unwrap(),             // 4
+            solana_sdk::system_program::ID,                      // 5
+            Pubkey::from_str(KNOWN_TOKEN_PROGRAM).unwrap(),      // 6
+            self.mint_authority,                                 // 7
+            Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap(),      // 8
+            Pubkey::from_str(KNOWN_EVENT_AUTH).unwrap(),         // 9
+            self.metadata,                                       // 10
+            Pubkey::from_str(KNOWN_METADATA_PROGRAM).unwrap(),   // 11
+            Pubkey::from_str(KNOWN_ATA_PROGRAM).unwrap(),        // 12
+            Pubkey::from_str(KNOWN_RENT).unwrap(),               // 13
+        ];
+        self.build(&static_keys, &[], &[], dev_buy_lamports)
+    }
+
+    /// The same create as a v0 message: the mint, curve accounts and metadata
+    /// are loaded through lookup tables, so the create's account indices point
+    /// past the static keys.
+    pub fn v0_response(&self, dev_buy_lamports: u64) -> SubscribeResponse {
+        let static_keys = [
+            self.creator,                                        // 0
+            self.mint_authority,                                 // 1
+            solana_sdk::system_program::ID,                      // 2
+            Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap(),      // 3
+        ];
+        let loaded_writable = [
+            self.mint,                                           // 4
+            self.bonding_curve,                                  // 5
+            self.associated_bonding_curve,                       // 6
+            self.metadata,                                       // 7
+        ];
+        let loaded_readonly = [
+            Pubkey::from_str(KNOWN_GLOBAL).unwrap(),             // 8
+            Pubkey::from_str(KNOWN_TOKEN_PROGRAM).unwrap(),      // 9
+            Pubkey::from_str(KNOWN_EVENT_AUTH).unwrap(),         // 10
+            Pubkey::from_str(KNOWN_METADATA_PROGRAM).unwrap(),   // 11
+            Pubkey::from_str(KNOWN_ATA_PROGRAM).unwrap(),        // 12
+            Pubkey::from_str(KNOWN_RENT).unwrap(),               // 13
+        ];
+        self.build(&static_keys, &loaded_writable, &loaded_readonly, dev_buy_lamports)
+    }
+
+    /// Lays out the create and the dev deposit over the given keys, indexing
+    /// static keys first, then loaded writable, then loaded readonly addresses.
+    fn build(
+        &self,
+        static_keys: &[Pubkey],
+        loaded_writable: &[Pubkey],
+        loaded_readonly: &[Pubkey],
+        dev_buy_lamports: u64,
+    ) -> SubscribeResponse {
+        let all_keys: Vec<Pubkey> = [static_keys, loaded_writable, loaded_readonly].concat();
+        let index_of = |key: Pubkey| all_keys.iter().position(|k| *k == key).unwrap() as u8;
+
+        let create_accounts = [
+            self.mint,
+            self.mint_authority,
+            self.bonding_curve,
+            self.associated_bonding_curve,
+            Pubkey::from_str(KNOWN_GLOBAL).unwrap(),
+            Pubkey::from_str(KNOWN_METADATA_PROGRAM).unwrap(),
+            self.metadata,
+            self.creator,
+            solana_sdk::system_program::ID,
+            Pubkey::from_str(KNOWN_TOKEN_PROGRAM).unwrap(),
+            Pubkey::from_str(KNOWN_ATA_PROGRAM).unwrap(),
+            Pubkey::from_str(KNOWN_RENT).unwrap(),
+            Pubkey::from_str(KNOWN_EVENT_AUTH).unwrap(),
+            Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap(),
+        ]
+        .into_iter()
+        .map(index_of)
+        .collect();
+
+        let to_bytes = |keys: &[Pubkey]| -> Vec<Vec<u8>> { keys.iter().map(|key| key.to_bytes().to_vec()).collect() };
+
+        // System program Transfer: u32 variant index 2, then u64 lamports
+        let mut transfer_data = 2u32.to_le_bytes().to_vec();
+        transfer_data.extend_from_slice(&dev_buy_lamports.to_le_bytes());
+
+        let create = geyser::Instruction {
+            program_id_index: index_of(Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap()) as u32,
+            accounts: create_accounts,
+            data: CREATE_DISCRIMINATOR.to_vec(),
+            ..Default::default()
+        };
+        let dev_deposit = geyser::Instruction {
+            program_id_index: index_of(solana_sdk::system_program::ID) as u32,
+            accounts: vec![index_of(self.creator), index_of(self.bonding_curve)],
+            data: transfer_data,
+            ..Default::default()
+        };
+
+        SubscribeResponse {
+            transaction: Some(geyser::TransactionUpdate {
+                transaction: Some(geyser::Transaction {
+                    message: Some(geyser::Message {
+                        account_keys: to_bytes(static_keys),
+                        instructions: vec![create],
+                        ..Default::default()
+                    }),
+                    meta: Some(geyser::Meta {
+                        inner_instructions: vec![geyser::InnerInstructions {
+                            instructions: vec![dev_deposit],
+                            ..Default::default()
+                        }],
+                        loaded_writable_addresses: to_bytes(loaded_writable),
+                        loaded_readonly_addresses: to_bytes(loaded_readonly),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
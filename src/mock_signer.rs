@@ -0,0 +1,68 @@
+use crate::error::{Result, SniperError};
+use crate::signer::TransactionSigner;
+use async_trait::async_trait;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+
+/// Stand-in for a remote signer in tests. Signs with a throwaway keypair,
+/// counts every request, and can be told to refuse like an offline device.
+pub struct MockSigner {
+    keypair: Keypair,
+    refuse: bool,
+    requests: AtomicUsize,
+    rendezvous: Option<Arc<Barrier>>,
+}
+
+impl MockSigner {
+    pub fn new() -> Self {
+        Self {
+            keypair: Keypair::new(),
+            refuse: false,
+            requests: AtomicUsize::new(0),
+            rendezvous: None,
+        }
+    }
+
+    pub fn refusing() -> Self {
+        Self { refuse: true, ..Self::new() }
+    }
+
+    /// Blocks the thread of every sign, the way a blocking RPC call would,
+    /// until `concurrent` signs are under way at once. Callers that only run
+    /// one at a time never get past it.
+    pub fn rendezvous(concurrent: usize) -> Self {
+        Self { rendezvous: Some(Arc::new(Barrier::new(concurrent))), ..Self::new() }
+    }
+
+    /// How many transactions the bot has asked this signer to sign.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for MockSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign(&self, transaction: &mut Transaction) -> Result<()> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if let Some(rendezvous) = &self.rendezvous {
+            rendezvous.wait();
+        }
+        if self.refuse {
+            return Err(SniperError::Transaction("Mock signer refused to sign".to_string()));
+        }
+
+        let recent_blockhash = transaction.message.recent_blockhash;
+        transaction
+            .try_sign(&[&self.keypair], recent_blockhash)
+            .map_err(|e| SniperError::Transaction(format!("Failed to sign transaction: {}", e)))
+    }
+}
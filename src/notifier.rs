@@ -0,0 +1,320 @@
+use crate::config::Config;
+use crate::error::{Result, SniperError};
+use crate::position_manager::ExitReason;
+use crate::scam_detection::ScamRecommendation;
+use async_trait::async_trait;
+use serde_json::json;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How long a single notification may take before it is abandoned.
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Simulation log lines included in a message; the tail holds any failure.
+const MAX_MESSAGE_LOG_LINES: usize = 20;
+
+/// Trade-loop events worth telling an operator about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeEvent {
+    TargetAcquired {
+        mint: Pubkey,
+        market_cap_usd: f64,
+    },
+    BuyConfirmed {
+        mint: Pubkey,
+        signature: Signature,
+        sol_spent: f64,
+    },
+    Sold {
+        mint: Pubkey,
+        signature: Signature,
+        reason: ExitReason,
+        pnl_sol: f64,
+    },
+    ScamFlagged {
+        mint: Pubkey,
+        scam_score: f64,
+        recommendation: ScamRecommendation,
+    },
+    /// A circuit breaker stopped or unwound trading, e.g. the session loss
+    /// limit or the dead man's switch.
+    CircuitBreaker {
+        reason: String,
+    },
+    FeeBudgetExceeded {
+        fees_sol: f64,
+        budget_sol: f64,
+    },
+    /// The funding wallet topped up a buyer wallet running low.
+    WalletToppedUp {
+        signature: Signature,
+        amount_sol: f64,
+        balance_sol: f64,
+    },
+    /// A dry-run buy was simulated against the RPC instead of being sent.
+    DryRunSimulated {
+        mint: Pubkey,
+        error: Option<String>,
+        units_consumed: Option<u64>,
+        logs: Vec<String>,
+    },
+}
+
+impl TradeEvent {
+    /// Plain-text summary with Solscan links, readable in any chat client.
+    pub fn message(&self) -> String {
+        match self {
+            TradeEvent::TargetAcquired { mint, market_cap_usd } => format!(
+                "🎯 Target acquired: {}\nMarket cap: ${:.2}\n{}",
+                mint, market_cap_usd, token_link(mint)
+            ),
+            TradeEvent::BuyConfirmed { mint, signature, sol_spent } => format!(
+                "✅ Bought {} for {:.4} SOL\n{}\n{}",
+                mint, sol_spent, token_link(mint), tx_link(signature)
+            ),
+            TradeEvent::Sold { mint, signature, reason, pnl_sol } => format!(
+                "🚪 Sold {} ({})\nPnL: {:+.4} SOL\n{}\n{}",
+                mint, reason, pnl_sol, token_link(mint), tx_link(signature)
+            ),
+            TradeEvent::ScamFlagged { mint, scam_score, recommendation } => format!(
+                "🚨 Scam detection flagged {}: score {:.2} ({:?})\n{}",
+                mint, scam_score, recommendation, token_link(mint)
+            ),
+            TradeEvent::CircuitBreaker { reason } => format!("🛑 Circuit breaker tripped: {}", reason),
+            TradeEvent::FeeBudgetExceeded { fees_sol, budget_sol } => format!(
+                "💸 Fees and tips paid this session: {:.4} SOL, over the {:.4} SOL budget", fees_sol, budget_sol
+            ),
+            TradeEvent::WalletToppedUp { signature, amount_sol, balance_sol } => format!(
+                "⛽ Topped up the buyer wallet with {:.4} SOL (balance was {:.4} SOL)\n{}",
+                amount_sol, balance_sol, tx_link(signature)
+            ),
+            TradeEvent::DryRunSimulated { mint, error, units_consumed, logs } => {
+                let outcome = match error {
+                    Some(error) => format!("failed: {}", error),
+                    None => "succeeded".to_string(),
+                };
+                let units = units_consumed.map_or_else(|| "unknown".to_string(), |units| units.to_string());
+                let tail = &logs[logs.len().saturating_sub(MAX_MESSAGE_LOG_LINES)..];
+                format!(
+                    "🧪 Dry-run buy simulation for {} {}\nCompute units: {}\n{}\n{}",
+                    mint, outcome, units, tail.join("\n"), token_link(mint)
+                )
+            }
+        }
+    }
+}
+
+fn token_link(mint: &Pubkey) -> String {
+    format!("https://solscan.io/token/{}", mint)
+}
+
+fn tx_link(signature: &Signature) -> String {
+    format!("https://solscan.io/tx/{}", signature)
+}
+
+/// Delivers a formatted notification somewhere off-box.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, message: &str) -> Result<()>;
+}
+
+async fn post_json(client: &reqwest::Client, url: &str, body: serde_json::Value) -> Result<()> {
+    client
+        .post(url)
+        .json(&body)
+        .timeout(NOTIFY_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// POSTs `{"text": message}` to any HTTP endpoint.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        Self { client: reqwest::Client::new(), url }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, message: &str) -> Result<()> {
+        post_json(&self.client, &self.url, json!({ "text": message })).await
+    }
+}
+
+/// Posts to a Discord channel through an incoming webhook URL.
+pub struct DiscordNotifier {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    pub fn new(webhook_url: String) -> Self {
+        Self { client: reqwest::Client::new(), webhook_url }
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    async fn notify(&self, message: &str) -> Result<()> {
+        post_json(&self.client, &self.webhook_url, json!({ "content": message })).await
+    }
+}
+
+/// Sends messages to a Telegram chat through the Bot API.
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(bot_token: String, chat_id: String) -> Self {
+        Self { client: reqwest::Client::new(), bot_token, chat_id }
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn notify(&self, message: &str) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        post_json(&self.client, &url, json!({
+            "chat_id": self.chat_id,
+            "text": message,
+            "disable_web_page_preview": true,
+        }))
+        .await
+        .map_err(|e| match e {
+            // Never log the bot token, which is part of the URL
+            SniperError::Http(e) => SniperError::Http(e.without_url()),
+            other => other,
+        })
+    }
+}
+
+/// Fans trade events out to every configured notifier. Each delivery runs on
+/// its own task, so a slow or failing endpoint never holds up trading.
+#[derive(Clone, Default)]
+pub struct Notifications {
+    notifiers: Vec<Arc<dyn Notifier>>,
+}
+
+impl Notifications {
+    pub fn new(notifiers: Vec<Arc<dyn Notifier>>) -> Self {
+        Self { notifiers }
+    }
+
+    /// Builds every destination configured in `config`, or none when
+    /// `ENABLE_NOTIFICATIONS` is off.
+    pub fn from_config(config: &Config) -> Self {
+        if !config.enable_notifications {
+            return Self::default();
+        }
+
+        let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+        if let Some(url) = &config.notify_webhook_url {
+            notifiers.push(Arc::new(WebhookNotifier::new(url.clone())));
+        }
+        if let Some(url) = &config.discord_webhook_url {
+            notifiers.push(Arc::new(DiscordNotifier::new(url.clone())));
+        }
+        if let (Some(bot_token), Some(chat_id)) = (&config.telegram_bot_token, &config.telegram_chat_id) {
+            notifiers.push(Arc::new(TelegramNotifier::new(bot_token.clone(), chat_id.clone())));
+        }
+        info!("🔔 Sending trade notifications to {} destination(s)", notifiers.len());
+        Self::new(notifiers)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    pub fn send(&self, event: TradeEvent) {
+        if self.notifiers.is_empty() {
+            return;
+        }
+
+        let message = Arc::new(event.message());
+        for notifier in &self.notifiers {
+            let notifier = Arc::clone(notifier);
+            let message = Arc::clone(&message);
+            tokio::spawn(async move {
+                match tokio::time::timeout(NOTIFY_TIMEOUT, notifier.notify(&message)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => warn!("Failed to send notification: {}", e),
+                    Err(_) => warn!("Notification timed out after {:?}", NOTIFY_TIMEOUT),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    struct RecordingNotifier {
+        messages: Arc<Mutex<Vec<String>>>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, message: &str) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            self.messages.lock().push(message.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_messages_include_links_and_pnl() {
+        let mint = Pubkey::new_unique();
+        let signature = Signature::default();
+
+        let message = TradeEvent::Sold { mint, signature, reason: ExitReason::TakeProfit, pnl_sol: 0.25 }.message();
+        assert!(message.contains("take profit"));
+        assert!(message.contains("+0.2500 SOL"));
+        assert!(message.contains(&format!("https://solscan.io/token/{}", mint)));
+        assert!(message.contains(&format!("https://solscan.io/tx/{}", signature)));
+
+        let message = TradeEvent::TargetAcquired { mint, market_cap_usd: 12_345.678 }.message();
+        assert!(message.contains("$12345.68"));
+
+        let logs: Vec<String> = (0..30).map(|line| format!("Program log: line {}", line)).collect();
+        let message = TradeEvent::DryRunSimulated {
+            mint,
+            error: Some("Error processing Instruction 3: custom program error: 0x1771".to_string()),
+            units_consumed: Some(41_200),
+            logs,
+        }
+        .message();
+        assert!(message.contains("failed: Error processing Instruction 3"));
+        assert!(message.contains("Compute units: 41200"));
+        assert!(message.contains("line 29"));
+        assert!(!message.contains("line 9\n"));
+    }
+
+    #[tokio::test]
+    async fn test_send_does_not_wait_for_slow_notifiers() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let notifications = Notifications::new(vec![Arc::new(RecordingNotifier {
+            messages: Arc::clone(&messages),
+            delay: Duration::from_millis(50),
+        })]);
+
+        notifications.send(TradeEvent::CircuitBreaker { reason: "session loss limit".to_string() });
+        assert!(messages.lock().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(messages.lock().as_slice(), ["🛑 Circuit breaker tripped: session loss limit"]);
+    }
+}
@@ -0,0 +1,295 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+#[derive(Debug, Clone, Default)]
+pub struct SessionLimits {
+    pub max_drawdown_sol: Option<f64>, // Halt once the session is down this much SOL
+    pub max_loss_usd: Option<f64>,     // Same limit, valued at the current SOL price
+    pub close_positions_on_halt: bool,
+    pub max_fees_sol: Option<f64>,     // Alert once fees and tips paid exceed this
+}
+
+/// What a fee was paid for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeKind {
+    Base,     // Per-signature network fee
+    Priority, // Compute unit price
+    Tip,      // Jito tip
+}
+
+/// Fees and tips paid this session, by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeeBreakdown {
+    pub base_sol: f64,
+    pub priority_sol: f64,
+    pub tip_sol: f64,
+}
+
+impl FeeBreakdown {
+    pub fn total(&self) -> f64 {
+        self.base_sol + self.priority_sol + self.tip_sol
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// The session loss crossed a limit; no further buys will be made.
+    SessionHalted {
+        realized_sol: f64,
+        unrealized_sol: f64,
+        loss_usd: f64,
+    },
+    /// Fees and tips paid crossed `max_fees_sol`. Trading continues.
+    FeeBudgetExceeded {
+        fees_sol: f64,
+        budget_sol: f64,
+    },
+}
+
+/// Tracks the session's profit and loss across every trade: realized PnL of
+/// closed positions, net of fees and tips paid, plus the marked-to-market PnL
+/// of open ones. Once the total loss exceeds `SessionLimits` the session is
+/// halted for good and a `SessionHalted` event is broadcast.
+pub struct PnlTracker {
+    limits: SessionLimits,
+    realized_sol: f64,
+    unrealized_sol: HashMap<Pubkey, f64>,
+    fees: FeeBreakdown,
+    rent_reclaimed_sol: f64,
+    max_buy_compute_unit_price: u64,
+    escalated_buys: u32,
+    fee_budget_exceeded: bool,
+    halted: bool,
+    events: broadcast::Sender<SessionEvent>,
+}
+
+impl PnlTracker {
+    pub fn new(limits: SessionLimits) -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self {
+            limits,
+            realized_sol: 0.0,
+            unrealized_sol: HashMap::new(),
+            fees: FeeBreakdown::default(),
+            rent_reclaimed_sol: 0.0,
+            max_buy_compute_unit_price: 0,
+            escalated_buys: 0,
+            fee_budget_exceeded: false,
+            halted: false,
+            events,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Updates an open position's PnL from its current value.
+    pub fn mark(&mut self, mint: &Pubkey, current_value_sol: f64, entry_sol: f64) {
+        self.unrealized_sol.insert(*mint, current_value_sol - entry_sol);
+    }
+
+    /// Books a closed position's PnL and stops marking it.
+    pub fn realize(&mut self, mint: &Pubkey, exit_value_sol: f64, entry_sol: f64) {
+        self.unrealized_sol.remove(mint);
+        self.realized_sol += exit_value_sol - entry_sol;
+        info!("💰 Session PnL: {:+.4} SOL realized, {:+.4} SOL open", self.realized_sol, self.unrealized());
+    }
+
+    /// Books a fee or tip against realized PnL. Fees count once a transaction
+    /// is sent, whether or not it lands. Returns `FeeBudgetExceeded` the first
+    /// time the session's fees cross `max_fees_sol`.
+    pub fn record_fee(&mut self, kind: FeeKind, sol: f64) -> Option<SessionEvent> {
+        match kind {
+            FeeKind::Base => self.fees.base_sol += sol,
+            FeeKind::Priority => self.fees.priority_sol += sol,
+            FeeKind::Tip => self.fees.tip_sol += sol,
+        }
+        self.realized_sol -= sol;
+
+        let budget_sol = self.limits.max_fees_sol?;
+        let fees_sol = self.fees.total();
+        if self.fee_budget_exceeded || fees_sol < budget_sol {
+            return None;
+        }
+
+        self.fee_budget_exceeded = true;
+        warn!("💸 Fees paid this session ({:.4} SOL) exceeded the {:.4} SOL budget", fees_sol, budget_sol);
+        let event = SessionEvent::FeeBudgetExceeded { fees_sol, budget_sol };
+        let _ = self.events.send(event.clone());
+        Some(event)
+    }
+
+    /// Notes rent returned by closing an emptied token account. Rent is a
+    /// deposit the account hands back, so like the rent paid when a buy opened
+    /// it, it stays out of realized PnL.
+    pub fn record_rent_reclaimed(&mut self, sol: f64) {
+        self.rent_reclaimed_sol += sol;
+    }
+
+    pub fn rent_reclaimed(&self) -> f64 {
+        self.rent_reclaimed_sol
+    }
+
+    /// Notes the compute-unit price a confirmed buy was finally sent at, and
+    /// whether it had to be escalated past the floor to land. The priority fee
+    /// itself is already booked through `record_fee`.
+    pub fn record_buy_compute_unit_price(&mut self, micro_lamports: u64, escalated: bool) {
+        self.max_buy_compute_unit_price = self.max_buy_compute_unit_price.max(micro_lamports);
+        if escalated {
+            self.escalated_buys += 1;
+        }
+    }
+
+    /// Highest compute-unit price a confirmed buy paid this session.
+    pub fn max_buy_compute_unit_price(&self) -> u64 {
+        self.max_buy_compute_unit_price
+    }
+
+    pub fn escalated_buys(&self) -> u32 {
+        self.escalated_buys
+    }
+
+    pub fn total_fees_paid_sol(&self) -> f64 {
+        self.fees.total()
+    }
+
+    pub fn fee_breakdown(&self) -> FeeBreakdown {
+        self.fees
+    }
+
+    pub fn realized(&self) -> f64 {
+        self.realized_sol
+    }
+
+    pub fn unrealized(&self) -> f64 {
+        self.unrealized_sol.values().sum()
+    }
+
+    pub fn total(&self) -> f64 {
+        self.realized_sol + self.unrealized()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn close_positions_on_halt(&self) -> bool {
+        self.limits.close_positions_on_halt
+    }
+
+    /// Halts the session if its loss now exceeds either limit. Returns the
+    /// `SessionHalted` event the first time that happens.
+    pub fn check_limits(&mut self, sol_price_usd: f64) -> Option<SessionEvent> {
+        if self.halted {
+            return None;
+        }
+
+        let loss_sol = (-self.total()).max(0.0);
+        let loss_usd = loss_sol * sol_price_usd;
+        let over_sol = self.limits.max_drawdown_sol.is_some_and(|limit| loss_sol >= limit);
+        let over_usd = self.limits.max_loss_usd.is_some_and(|limit| sol_price_usd > 0.0 && loss_usd >= limit);
+        if !over_sol && !over_usd {
+            return None;
+        }
+
+        self.halted = true;
+        error!("🛑🛑🛑 SESSION HALTED - down {:.4} SOL (${:.2}) | realized {:+.4} SOL | open {:+.4} SOL | no new buys{}",
+               loss_sol, loss_usd, self.realized_sol, self.unrealized(),
+               if self.limits.close_positions_on_halt { ", closing all positions" } else { "" });
+
+        let event = SessionEvent::SessionHalted {
+            realized_sol: self.realized_sol,
+            unrealized_sol: self.unrealized(),
+            loss_usd,
+        };
+        // Nobody may be listening, which is fine
+        let _ = self.events.send(event.clone());
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_halts_on_sol_drawdown() {
+        let mut tracker = PnlTracker::new(SessionLimits {
+            max_drawdown_sol: Some(1.0),
+            ..SessionLimits::default()
+        });
+        let mut events = tracker.subscribe();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        tracker.realize(&a, 0.5, 1.0);
+        tracker.mark(&b, 0.8, 1.0);
+        assert!((tracker.total() + 0.7).abs() < 1e-9);
+        assert!(tracker.check_limits(150.0).is_none());
+
+        // Unrealized losses count toward the limit too
+        tracker.mark(&b, 0.4, 1.0);
+        let event = tracker.check_limits(150.0).unwrap();
+        assert!(tracker.is_halted());
+        assert_eq!(events.try_recv().unwrap(), event);
+
+        // Halting happens once
+        assert!(tracker.check_limits(150.0).is_none());
+    }
+
+    #[test]
+    fn test_halts_on_usd_loss() {
+        let mut tracker = PnlTracker::new(SessionLimits {
+            max_loss_usd: Some(100.0),
+            ..SessionLimits::default()
+        });
+        tracker.realize(&Pubkey::new_unique(), 0.0, 0.5);
+
+        assert!(tracker.check_limits(150.0).is_none());
+        // An unknown SOL price never trips the USD limit
+        assert!(tracker.check_limits(0.0).is_none());
+        assert!(matches!(
+            tracker.check_limits(250.0),
+            Some(SessionEvent::SessionHalted { loss_usd, .. }) if (loss_usd - 125.0).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn test_fees_reduce_realized_pnl_and_alert_once() {
+        let mut tracker = PnlTracker::new(SessionLimits {
+            max_fees_sol: Some(0.01),
+            ..SessionLimits::default()
+        });
+        let mut events = tracker.subscribe();
+
+        tracker.realize(&Pubkey::new_unique(), 1.1, 1.0);
+        assert!(tracker.record_fee(FeeKind::Base, 0.00001).is_none());
+        assert!(tracker.record_fee(FeeKind::Priority, 0.0002).is_none());
+        assert!((tracker.realized() - (0.1 - 0.00021)).abs() < 1e-9);
+
+        let event = tracker.record_fee(FeeKind::Tip, 0.01).unwrap();
+        assert!(matches!(event, SessionEvent::FeeBudgetExceeded { budget_sol, .. } if budget_sol == 0.01));
+        assert_eq!(events.try_recv().unwrap(), event);
+        assert!(tracker.record_fee(FeeKind::Tip, 0.01).is_none());
+
+        let fees = tracker.fee_breakdown();
+        assert!((fees.tip_sol - 0.02).abs() < 1e-9);
+        assert!((tracker.total_fees_paid_sol() - 0.02021).abs() < 1e-9);
+
+        // Reclaimed rent is a returned deposit, not profit
+        let realized = tracker.realized();
+        tracker.record_rent_reclaimed(0.002);
+        assert_eq!(tracker.realized(), realized);
+        assert_eq!(tracker.rent_reclaimed(), 0.002);
+    }
+
+    #[test]
+    fn test_no_limits_never_halts() {
+        let mut tracker = PnlTracker::new(SessionLimits::default());
+        tracker.realize(&Pubkey::new_unique(), 0.0, 100.0);
+        assert!(tracker.check_limits(150.0).is_none());
+        assert!(!tracker.is_halted());
+    }
+}
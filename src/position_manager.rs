@@ -0,0 +1,684 @@
+use crate::constants::TOKEN_DECIMALS;
+use crate::error::SniperError;
+use crate::position_store::PositionStore;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::TransactionStatus;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    pub creator_vault: Pubkey,
+    /// Wallet that created the token.
+    pub creator: Pubkey,
+    pub token_program: Pubkey,
+    pub token_amount: u64, // Raw token units (6 decimals)
+    pub entry_price_sol: f64,
+    pub entry_sol: f64,
+    /// Fees and tips paid on the buys that make up the position, charged
+    /// against it in the journal once it is sold.
+    pub entry_fees_sol: f64,
+    pub opened_at: Instant,
+    pub exiting: bool,
+    /// Buys added to the position after its entry by averaging down.
+    pub average_down_buys: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitReason {
+    StopLoss,
+    TakeProfit,
+    HoldTimeout,
+    RugPull,
+    ScamScore,
+    SessionHalt,
+    DeadManSwitch,
+    Migration,
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::StopLoss => write!(f, "stop loss"),
+            ExitReason::TakeProfit => write!(f, "take profit"),
+            ExitReason::HoldTimeout => write!(f, "hold timeout"),
+            ExitReason::RugPull => write!(f, "rug pull"),
+            ExitReason::ScamScore => write!(f, "scam score"),
+            ExitReason::SessionHalt => write!(f, "session halt"),
+            ExitReason::DeadManSwitch => write!(f, "dead man's switch"),
+            ExitReason::Migration => write!(f, "migration"),
+        }
+    }
+}
+
+/// What to do with a held position when its token migrates to PumpSwap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MigrationPolicy {
+    HoldThrough,
+    SellAll,
+    /// Sells this fraction of the position, in (0, 1], and keeps the rest.
+    SellPartial(f64),
+}
+
+impl MigrationPolicy {
+    /// Raw tokens to sell out of `token_amount`, or `None` to keep holding.
+    pub fn tokens_to_sell(&self, token_amount: u64) -> Option<u64> {
+        let tokens = match self {
+            MigrationPolicy::HoldThrough => return None,
+            MigrationPolicy::SellAll => token_amount,
+            MigrationPolicy::SellPartial(fraction) => (token_amount as f64 * fraction) as u64,
+        };
+        (tokens > 0).then_some(tokens.min(token_amount))
+    }
+}
+
+impl fmt::Display for MigrationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationPolicy::HoldThrough => write!(f, "hold through"),
+            MigrationPolicy::SellAll => write!(f, "sell all"),
+            MigrationPolicy::SellPartial(fraction) => write!(f, "sell {:.0}%", fraction * 100.0),
+        }
+    }
+}
+
+impl FromStr for MigrationPolicy {
+    type Err = SniperError;
+
+    /// Accepts `hold`, `sell_all`, or `sell_partial:<fraction>` such as `sell_partial:0.5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_lowercase().replace('-', "_");
+        if let Some(fraction) = text.strip_prefix("sell_partial:") {
+            return match fraction.trim().parse::<f64>() {
+                Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => Ok(MigrationPolicy::SellPartial(fraction)),
+                _ => Err(SniperError::Config(format!(
+                    "Invalid sell_partial fraction '{}', expected a number in (0, 1]", fraction
+                ))),
+            };
+        }
+        match text.as_str() {
+            "hold" | "hold_through" => Ok(MigrationPolicy::HoldThrough),
+            "sell" | "sell_all" => Ok(MigrationPolicy::SellAll),
+            other => Err(SniperError::Config(format!(
+                "Unknown migration policy '{}', expected hold, sell_all or sell_partial:<fraction>", other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionConfig {
+    pub stop_loss_percentage: f64,   // e.g. 10.0 = exit after a 10% drop
+    pub take_profit_percentage: f64, // e.g. 50.0 = exit after a 50% gain
+    pub max_hold_duration: Option<Duration>,
+    pub average_down: Option<AverageDownPolicy>,
+}
+
+impl Default for PositionConfig {
+    fn default() -> Self {
+        Self {
+            stop_loss_percentage: 10.0,
+            take_profit_percentage: 50.0,
+            max_hold_duration: None,
+            average_down: None,
+        }
+    }
+}
+
+/// Adds to a held position that dipped without reaching its stop loss.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AverageDownPolicy {
+    /// Drop below the blended entry price that triggers a buy, e.g. 5.0 = 5%.
+    pub dip_percentage: f64,
+    pub buy_amount_sol: f64,
+    /// Most buys added to any one position.
+    pub max_buys: u32,
+}
+
+/// Entry cost, raw token amount and entry price per whole token of `position`
+/// once `tokens_bought` more are bought for `sol_spent`.
+pub fn blended_entry(position: &Position, tokens_bought: u64, sol_spent: f64) -> (f64, u64, f64) {
+    let entry_sol = position.entry_sol + sol_spent;
+    let token_amount = position.token_amount.saturating_add(tokens_bought);
+    let whole_tokens = token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+    let entry_price_sol = if whole_tokens > 0.0 { entry_sol / whole_tokens } else { position.entry_price_sol };
+    (entry_sol, token_amount, entry_price_sol)
+}
+
+/// What became of a buy that was not confirmed in time. A buy with no status
+/// can only be written off once its blockhash has expired; until then it may
+/// still land, and a resend on a fresh blockhash could land alongside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StuckBuy {
+    /// No status yet, but its blockhash is still valid so it may yet land
+    InFlight,
+    /// It landed after the wait gave up on it
+    LandedLate,
+    /// It landed after the wait gave up on it, and failed
+    Failed,
+    /// Its blockhash expired with no status recorded, so it can never land
+    Dropped,
+    /// The block height or its status could not be read
+    Unknown,
+}
+
+impl StuckBuy {
+    /// Judges a stuck buy by the current block height and the status found
+    /// for it, read in that order so it can't land unseen in between. The
+    /// status is `Some(None)` when there is none and otherwise says whether
+    /// the buy succeeded; `None` stands for a read that failed.
+    pub fn classify(block_height: Option<u64>, last_valid_block_height: u64, status: Option<Option<bool>>) -> Self {
+        match (status, block_height) {
+            (Some(Some(true)), _) => StuckBuy::LandedLate,
+            (Some(Some(false)), _) => StuckBuy::Failed,
+            (None, _) | (_, None) => StuckBuy::Unknown,
+            (Some(None), Some(height)) if height > last_valid_block_height => StuckBuy::Dropped,
+            (Some(None), Some(_)) => StuckBuy::InFlight,
+        }
+    }
+}
+
+/// When a submitted buy counts as landed and its position as open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuyConfirmationPolicy {
+    pub commitment: CommitmentConfig,
+    /// Confirmations required on top of the commitment; 0 accepts the commitment alone.
+    pub min_confirmations: usize,
+    /// Pending buys that have not landed by then are discarded.
+    pub pending_timeout: Duration,
+}
+
+impl BuyConfirmationPolicy {
+    /// Whether a successful transaction's status meets the policy. Rooted
+    /// transactions report no confirmation count and are past any threshold.
+    pub fn is_satisfied(&self, status: &TransactionStatus) -> bool {
+        status.satisfies_commitment(self.commitment)
+            && status.confirmations.is_none_or(|confirmations| confirmations >= self.min_confirmations)
+    }
+}
+
+/// A buy that was sent but has not met the confirmation policy yet. Its
+/// tokens are not counted as held, so nothing tries to sell them.
+#[derive(Debug, Clone)]
+pub struct PendingBuy {
+    pub position: Position,
+    pub signature: Signature,
+    pub submitted_at: Instant,
+    /// The last block height the buy can land at
+    pub last_valid_block_height: u64,
+}
+
+pub struct PositionManager {
+    config: PositionConfig,
+    positions: HashMap<Pubkey, Position>,
+    pending: HashMap<Pubkey, PendingBuy>,
+    /// Where open positions are saved on every change, if anywhere.
+    store: Option<PositionStore>,
+}
+
+impl PositionManager {
+    pub fn new(config: PositionConfig) -> Self {
+        Self {
+            config,
+            positions: HashMap::new(),
+            pending: HashMap::new(),
+            store: None,
+        }
+    }
+
+    pub fn with_store(mut self, store: PositionStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn store(&self) -> Option<&PositionStore> {
+        self.store.as_ref()
+    }
+
+    /// Takes over positions restored from the store after a restart.
+    pub fn restore(&mut self, positions: Vec<Position>) {
+        for position in positions {
+            info!("♻️ Restored position in {}: {} tokens at {:.10} SOL/token",
+                  position.mint, position.token_amount, position.entry_price_sol);
+            self.positions.insert(position.mint, position);
+        }
+        self.persist();
+    }
+
+    /// Writes the open positions to the store. A failed write is logged and
+    /// retried with the next change; trading carries on regardless.
+    fn persist(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let positions: Vec<Position> = self.positions.values().cloned().collect();
+        if let Err(e) = store.save(&positions) {
+            warn!("Could not save positions to {}: {}", store.path().display(), e);
+        }
+    }
+
+    /// Records a sent buy as pending. A resend replaces the earlier attempt
+    /// and restarts its timeout.
+    pub fn add_pending(&mut self, position: Position, signature: Signature, submitted_at: Instant, last_valid_block_height: u64) {
+        self.pending.insert(position.mint, PendingBuy { position, signature, submitted_at, last_valid_block_height });
+    }
+
+    /// Opens the position of a pending buy that met the confirmation policy.
+    /// Its hold time starts now. Returns `false` if nothing was pending.
+    pub fn confirm_pending(&mut self, mint: &Pubkey) -> bool {
+        match self.pending.remove(mint) {
+            Some(pending) => {
+                self.open_position(Position { opened_at: Instant::now(), ..pending.position });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Discards a pending buy that failed or never landed.
+    pub fn drop_pending(&mut self, mint: &Pubkey) -> Option<PendingBuy> {
+        self.pending.remove(mint)
+    }
+
+    /// Pending buys submitted more than `timeout` ago. They are left pending:
+    /// only the chain can tell whether one may still land.
+    pub fn stale_pending(&self, timeout: Duration) -> Vec<PendingBuy> {
+        self.pending
+            .values()
+            .filter(|pending| pending.submitted_at.elapsed() >= timeout)
+            .cloned()
+            .collect()
+    }
+
+    pub fn pending_buys(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn open_position(&mut self, position: Position) {
+        info!("📈 Opened position in {} at {:.10} SOL/token", position.mint, position.entry_price_sol);
+        self.positions.insert(position.mint, position);
+        self.persist();
+    }
+
+    pub fn close_position(&mut self, mint: &Pubkey) -> Option<Position> {
+        let closed = self.positions.remove(mint);
+        if closed.is_some() {
+            self.persist();
+        }
+        closed
+    }
+
+    pub fn get_position(&self, mint: &Pubkey) -> Option<&Position> {
+        self.positions.get(mint)
+    }
+
+    /// Positions held in tokens from `creator`, including any being exited.
+    pub fn positions_by_creator(&self, creator: &Pubkey) -> usize {
+        self.positions.values().filter(|position| position.creator == *creator).count()
+    }
+
+    pub fn open_positions(&self) -> Vec<Position> {
+        self.positions.values().filter(|p| !p.exiting).cloned().collect()
+    }
+
+    /// Decides whether a position should be exited at `current_price_sol`.
+    /// Price-based exits take precedence; the hold timeout only fires if
+    /// neither stop loss nor take profit has.
+    pub fn evaluate(&self, position: &Position, current_price_sol: f64) -> Option<ExitReason> {
+        if position.exiting || position.entry_price_sol <= 0.0 {
+            return None;
+        }
+
+        let change_percentage = (current_price_sol - position.entry_price_sol) / position.entry_price_sol * 100.0;
+
+        if change_percentage <= -self.config.stop_loss_percentage {
+            return Some(ExitReason::StopLoss);
+        }
+
+        if change_percentage >= self.config.take_profit_percentage {
+            return Some(ExitReason::TakeProfit);
+        }
+
+        if let Some(max_hold_duration) = self.config.max_hold_duration {
+            if position.opened_at.elapsed() >= max_hold_duration {
+                return Some(ExitReason::HoldTimeout);
+            }
+        }
+
+        None
+    }
+
+    /// Whether `position` has dipped far enough below its entry price to buy
+    /// more under the averaging-down policy, while staying above its stop
+    /// loss and under the policy's buy cap.
+    pub fn should_average_down(&self, position: &Position, current_price_sol: f64) -> bool {
+        let Some(policy) = self.config.average_down else {
+            return false;
+        };
+        if position.exiting || position.entry_price_sol <= 0.0 || position.average_down_buys >= policy.max_buys {
+            return false;
+        }
+        let change_percentage = (current_price_sol - position.entry_price_sol) / position.entry_price_sol * 100.0;
+        change_percentage <= -policy.dip_percentage && change_percentage > -self.config.stop_loss_percentage
+    }
+
+    /// Folds an averaging-down buy of `tokens_bought` for `sol_spent`, which
+    /// paid `fees_sol` in fees and tips, into a held position, blending its
+    /// entry cost and price, and clears the exiting flag that guarded the buy.
+    /// Returns the position as it now stands.
+    pub fn average_down(&mut self, mint: &Pubkey, tokens_bought: u64, sol_spent: f64, fees_sol: f64) -> Option<Position> {
+        let position = self.positions.get_mut(mint)?;
+        let (entry_sol, token_amount, entry_price_sol) = blended_entry(position, tokens_bought, sol_spent);
+        position.entry_sol = entry_sol;
+        position.entry_fees_sol += fees_sol;
+        position.token_amount = token_amount;
+        position.entry_price_sol = entry_price_sol;
+        position.average_down_buys += 1;
+        position.exiting = false;
+        let averaged = position.clone();
+        self.persist();
+        Some(averaged)
+    }
+
+    /// Replaces an open position's estimated token amount and entry cost with
+    /// what the wallet actually received and spent, re-deriving its entry
+    /// price from them. Returns the position as it now stands.
+    pub fn settle_token_amount(&mut self, mint: &Pubkey, token_amount: u64, sol_spent: f64) -> Option<Position> {
+        let position = self.positions.get_mut(mint)?;
+        let whole_tokens = token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+        position.token_amount = token_amount;
+        position.entry_sol = sol_spent;
+        if whole_tokens > 0.0 {
+            position.entry_price_sol = sol_spent / whole_tokens;
+        }
+        let settled = position.clone();
+        self.persist();
+        Some(settled)
+    }
+
+    /// Raw tokens held per mint, across every open position including ones
+    /// being sold.
+    pub fn holdings(&self) -> HashMap<Pubkey, u64> {
+        self.positions.values().map(|position| (position.mint, position.token_amount)).collect()
+    }
+
+    /// Marks a position as exiting so the same exit is not triggered twice
+    /// while its sell is in flight. Returns `false` if it was already exiting.
+    pub fn mark_exiting(&mut self, mint: &Pubkey) -> bool {
+        match self.positions.get_mut(mint) {
+            Some(position) if !position.exiting => {
+                position.exiting = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Books a sale of `tokens_sold` out of a position that stays open. Its entry
+    /// cost shrinks in proportion and its exiting flag is cleared; selling
+    /// everything left closes it. Returns the position as it now stands.
+    pub fn reduce_position(&mut self, mint: &Pubkey, tokens_sold: u64) -> Option<Position> {
+        let position = self.positions.get_mut(mint)?;
+        let remaining = position.token_amount.saturating_sub(tokens_sold);
+        if remaining == 0 {
+            return self.close_position(mint)
+                .map(|position| Position { token_amount: 0, entry_sol: 0.0, entry_fees_sol: 0.0, ..position });
+        }
+        let kept = remaining as f64 / position.token_amount as f64;
+        position.entry_sol *= kept;
+        position.entry_fees_sol *= kept;
+        position.token_amount = remaining;
+        position.exiting = false;
+        let reduced = position.clone();
+        self.persist();
+        Some(reduced)
+    }
+
+    /// Clears the exiting flag after a failed sell so the exit can be retried.
+    pub fn clear_exiting(&mut self, mint: &Pubkey) {
+        if let Some(position) = self.positions.get_mut(mint) {
+            position.exiting = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::TransactionConfirmationStatus;
+
+    fn position(opened_at: Instant) -> Position {
+        Position {
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000_000,
+            entry_price_sol: 1.0,
+            entry_sol: 0.01,
+            entry_fees_sol: 0.0,
+            opened_at,
+            exiting: false,
+            average_down_buys: 0,
+        }
+    }
+
+    #[test]
+    fn test_price_exits() {
+        let manager = PositionManager::new(PositionConfig::default());
+        let position = position(Instant::now());
+
+        assert_eq!(manager.evaluate(&position, 0.85), Some(ExitReason::StopLoss));
+        assert_eq!(manager.evaluate(&position, 1.6), Some(ExitReason::TakeProfit));
+        assert_eq!(manager.evaluate(&position, 1.1), None);
+    }
+
+    #[test]
+    fn test_hold_timeout() {
+        let config = PositionConfig {
+            max_hold_duration: Some(Duration::from_secs(60)),
+            ..PositionConfig::default()
+        };
+        let mut manager = PositionManager::new(config);
+        let expired = position(Instant::now() - Duration::from_secs(61));
+
+        assert_eq!(manager.evaluate(&expired, 1.1), Some(ExitReason::HoldTimeout));
+        // Price exits win over the timeout
+        assert_eq!(manager.evaluate(&expired, 0.5), Some(ExitReason::StopLoss));
+        assert_eq!(ExitReason::HoldTimeout.to_string(), "hold timeout");
+
+        let mint = expired.mint;
+        manager.open_position(expired);
+        assert!(manager.mark_exiting(&mint));
+        assert!(!manager.mark_exiting(&mint));
+        assert_eq!(manager.evaluate(manager.get_position(&mint).unwrap(), 1.1), None);
+    }
+
+    #[test]
+    fn test_migration_policy() {
+        assert_eq!("hold".parse::<MigrationPolicy>().unwrap(), MigrationPolicy::HoldThrough);
+        assert_eq!("sell_all".parse::<MigrationPolicy>().unwrap(), MigrationPolicy::SellAll);
+        assert_eq!("sell_partial:0.25".parse::<MigrationPolicy>().unwrap(), MigrationPolicy::SellPartial(0.25));
+        assert!("sell_partial:1.5".parse::<MigrationPolicy>().is_err());
+        assert!("sell_partial:0".parse::<MigrationPolicy>().is_err());
+        assert!("dump".parse::<MigrationPolicy>().is_err());
+
+        assert_eq!(MigrationPolicy::HoldThrough.tokens_to_sell(1_000_000), None);
+        assert_eq!(MigrationPolicy::SellAll.tokens_to_sell(1_000_000), Some(1_000_000));
+        assert_eq!(MigrationPolicy::SellPartial(0.25).tokens_to_sell(1_000_000), Some(250_000));
+        assert_eq!(MigrationPolicy::SellPartial(0.25).tokens_to_sell(3), None);
+
+        let mut manager = PositionManager::new(PositionConfig::default());
+        let held = position(Instant::now());
+        let mint = held.mint;
+        manager.open_position(held);
+        assert!(manager.mark_exiting(&mint));
+
+        let remaining = manager.reduce_position(&mint, 250_000).unwrap();
+        assert_eq!(remaining.token_amount, 750_000);
+        assert!((remaining.entry_sol - 0.0075).abs() < 1e-12);
+        assert!(!remaining.exiting);
+        assert_eq!(remaining.entry_price_sol, 1.0);
+
+        manager.reduce_position(&mint, 750_000).unwrap();
+        assert!(manager.get_position(&mint).is_none());
+    }
+
+    #[test]
+    fn test_blended_entry_after_averaging_down() {
+        // 1 SOL bought 1M tokens; 1 SOL more at half the price buys 2M
+        let held = Position {
+            token_amount: 1_000_000_000_000,
+            entry_price_sol: 0.000_001,
+            entry_sol: 1.0,
+            ..position(Instant::now())
+        };
+        let (entry_sol, token_amount, entry_price_sol) = blended_entry(&held, 2_000_000_000_000, 1.0);
+        assert_eq!((entry_sol, token_amount), (2.0, 3_000_000_000_000));
+        assert!((entry_price_sol - 2.0 / 3_000_000.0).abs() < 1e-15);
+
+        let mut manager = PositionManager::new(PositionConfig {
+            stop_loss_percentage: 30.0,
+            average_down: Some(AverageDownPolicy { dip_percentage: 10.0, buy_amount_sol: 1.0, max_buys: 1 }),
+            ..PositionConfig::default()
+        });
+        let mint = held.mint;
+        manager.open_position(held.clone());
+
+        // Only between the dip and the stop loss
+        assert!(!manager.should_average_down(&held, 0.000_000_95));
+        assert!(manager.should_average_down(&held, 0.000_000_89));
+        assert!(manager.should_average_down(&held, 0.000_000_75));
+        assert!(!manager.should_average_down(&held, 0.000_000_65));
+
+        assert!(manager.mark_exiting(&mint));
+        let averaged = manager.average_down(&mint, 2_000_000_000_000, 1.0, 0.0002).unwrap();
+        assert_eq!((averaged.entry_sol, averaged.average_down_buys, averaged.exiting), (2.0, 1, false));
+        assert_eq!(averaged.entry_fees_sol, held.entry_fees_sol + 0.0002);
+        assert_eq!(averaged.entry_price_sol, entry_price_sol);
+
+        // The cap is reached, and the next dip is measured from the blended price
+        assert!(!manager.should_average_down(&averaged, 0.000_000_5));
+        assert!(manager.should_average_down(&Position { average_down_buys: 0, ..averaged.clone() }, 0.000_000_55));
+    }
+
+    #[test]
+    fn test_positions_by_creator() {
+        let mut manager = PositionManager::new(PositionConfig::default());
+        let creator = Pubkey::new_unique();
+        let first = Position { creator, ..position(Instant::now()) };
+        let second = Position { creator, ..position(Instant::now()) };
+        let (first_mint, second_mint) = (first.mint, second.mint);
+        manager.open_position(first);
+        manager.open_position(second);
+        manager.open_position(position(Instant::now()));
+
+        assert_eq!(manager.positions_by_creator(&creator), 2);
+        // Still held while its sell is in flight
+        manager.mark_exiting(&first_mint);
+        assert_eq!(manager.positions_by_creator(&creator), 2);
+        manager.close_position(&second_mint);
+        assert_eq!(manager.positions_by_creator(&creator), 1);
+        assert_eq!(manager.positions_by_creator(&Pubkey::new_unique()), 0);
+    }
+
+    fn status(confirmations: Option<usize>, level: TransactionConfirmationStatus) -> TransactionStatus {
+        TransactionStatus {
+            slot: 1,
+            confirmations,
+            status: Ok(()),
+            err: None,
+            confirmation_status: Some(level),
+        }
+    }
+
+    #[test]
+    fn test_confirmation_policy() {
+        let policy = BuyConfirmationPolicy {
+            commitment: CommitmentConfig::confirmed(),
+            min_confirmations: 2,
+            pending_timeout: Duration::from_secs(60),
+        };
+        assert!(!policy.is_satisfied(&status(Some(0), TransactionConfirmationStatus::Processed)));
+        assert!(!policy.is_satisfied(&status(Some(1), TransactionConfirmationStatus::Confirmed)));
+        assert!(policy.is_satisfied(&status(Some(2), TransactionConfirmationStatus::Confirmed)));
+        assert!(policy.is_satisfied(&status(None, TransactionConfirmationStatus::Finalized)));
+
+        let finalized = BuyConfirmationPolicy { commitment: CommitmentConfig::finalized(), min_confirmations: 0, ..policy };
+        assert!(!finalized.is_satisfied(&status(Some(30), TransactionConfirmationStatus::Confirmed)));
+        assert!(finalized.is_satisfied(&status(None, TransactionConfirmationStatus::Finalized)));
+    }
+
+    #[test]
+    fn test_pending_buy_transitions() {
+        let mut manager = PositionManager::new(PositionConfig::default());
+        let landed = position(Instant::now() - Duration::from_secs(30));
+        let dropped = position(Instant::now());
+        let (landed_mint, dropped_mint) = (landed.mint, dropped.mint);
+
+        // Pending buys are not positions: nothing evaluates or sells them
+        manager.add_pending(landed, Signature::new_unique(), Instant::now(), 1_150);
+        manager.add_pending(dropped, Signature::new_unique(), Instant::now() - Duration::from_secs(90), 1_150);
+        assert_eq!(manager.pending_buys(), 2);
+        assert!(manager.open_positions().is_empty());
+        assert!(manager.get_position(&landed_mint).is_none());
+
+        // pending -> open, with the hold time counted from confirmation
+        assert!(manager.confirm_pending(&landed_mint));
+        let open = manager.get_position(&landed_mint).unwrap();
+        assert!(open.opened_at.elapsed() < Duration::from_secs(5));
+        assert!(!manager.confirm_pending(&landed_mint));
+
+        // Past the timeout a buy is stale but stays pending until judged dropped
+        let stale = manager.stale_pending(Duration::from_secs(60));
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].position.mint, dropped_mint);
+        assert_eq!(stale[0].last_valid_block_height, 1_150);
+        assert_eq!(manager.pending_buys(), 1);
+
+        // pending -> dropped
+        assert!(manager.drop_pending(&dropped_mint).is_some());
+        assert_eq!(manager.pending_buys(), 0);
+        assert!(!manager.confirm_pending(&dropped_mint));
+        assert_eq!(manager.open_positions().len(), 1);
+
+        let resent = position(Instant::now());
+        let resent_mint = resent.mint;
+        manager.add_pending(resent, Signature::new_unique(), Instant::now(), 1_150);
+        assert!(manager.stale_pending(Duration::from_secs(60)).is_empty());
+        assert!(manager.drop_pending(&resent_mint).is_some());
+        assert!(manager.get_position(&resent_mint).is_none());
+    }
+
+    #[test]
+    fn test_stuck_buy_outcomes() {
+        // No status before the blockhash expires: it may still land
+        assert_eq!(StuckBuy::classify(Some(1_000), 1_150, Some(None)), StuckBuy::InFlight);
+        assert_eq!(StuckBuy::classify(Some(1_150), 1_150, Some(None)), StuckBuy::InFlight);
+        // ... and only once it has expired is it dropped
+        assert_eq!(StuckBuy::classify(Some(1_151), 1_150, Some(None)), StuckBuy::Dropped);
+
+        // A status means it landed, whatever the height
+        assert_eq!(StuckBuy::classify(Some(1_000), 1_150, Some(Some(true))), StuckBuy::LandedLate);
+        assert_eq!(StuckBuy::classify(None, 1_150, Some(Some(true))), StuckBuy::LandedLate);
+        assert_eq!(StuckBuy::classify(Some(1_000), 1_150, Some(Some(false))), StuckBuy::Failed);
+
+        assert_eq!(StuckBuy::classify(None, 1_150, Some(None)), StuckBuy::Unknown);
+        assert_eq!(StuckBuy::classify(Some(2_000), 1_150, None), StuckBuy::Unknown);
+    }
+
+    #[test]
+    fn test_hold_timeout_disabled() {
+        let manager = PositionManager::new(PositionConfig::default());
+        let old = position(Instant::now() - Duration::from_secs(3600));
+        assert_eq!(manager.evaluate(&old, 1.1), None);
+    }
+}
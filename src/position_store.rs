@@ -0,0 +1,207 @@
+use crate::error::{Result, SniperError};
+use crate::position_manager::Position;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// An open position as written to disk. Keys are base58 strings, like the
+/// trade journal's, and the open time is wall-clock so the hold timeout keeps
+/// counting across a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredPosition {
+    pub mint: String,
+    pub bonding_curve: String,
+    pub associated_bonding_curve: String,
+    pub creator_vault: String,
+    pub creator: String,
+    pub token_program: String,
+    /// Raw token amount, in the token's smallest unit.
+    pub token_amount: u64,
+    pub entry_price_sol: f64,
+    pub entry_sol: f64,
+    #[serde(default)]
+    pub entry_fees_sol: f64,
+    pub opened_at: DateTime<Utc>,
+    #[serde(default)]
+    pub average_down_buys: u32,
+}
+
+impl StoredPosition {
+    fn from_position(position: &Position, now: Instant, now_utc: DateTime<Utc>) -> Self {
+        let held = now.saturating_duration_since(position.opened_at);
+        Self {
+            mint: position.mint.to_string(),
+            bonding_curve: position.bonding_curve.to_string(),
+            associated_bonding_curve: position.associated_bonding_curve.to_string(),
+            creator_vault: position.creator_vault.to_string(),
+            creator: position.creator.to_string(),
+            token_program: position.token_program.to_string(),
+            token_amount: position.token_amount,
+            entry_price_sol: position.entry_price_sol,
+            entry_sol: position.entry_sol,
+            entry_fees_sol: position.entry_fees_sol,
+            opened_at: now_utc - chrono::Duration::from_std(held).unwrap_or_default(),
+            average_down_buys: position.average_down_buys,
+        }
+    }
+
+    fn into_position(self, now: Instant, now_utc: DateTime<Utc>) -> Result<Position> {
+        let key = |field: &str, value: &str| {
+            Pubkey::from_str(value)
+                .map_err(|e| SniperError::Config(format!("Invalid {} '{}' in stored position: {}", field, value, e)))
+        };
+        let held = (now_utc - self.opened_at).to_std().unwrap_or_default();
+        Ok(Position {
+            mint: key("mint", &self.mint)?,
+            bonding_curve: key("bonding curve", &self.bonding_curve)?,
+            associated_bonding_curve: key("associated bonding curve", &self.associated_bonding_curve)?,
+            creator_vault: key("creator vault", &self.creator_vault)?,
+            creator: key("creator", &self.creator)?,
+            token_program: key("token program", &self.token_program)?,
+            token_amount: self.token_amount,
+            entry_price_sol: self.entry_price_sol,
+            entry_sol: self.entry_sol,
+            entry_fees_sol: self.entry_fees_sol,
+            opened_at: now.checked_sub(held).unwrap_or(now),
+            exiting: false,
+            average_down_buys: self.average_down_buys,
+        })
+    }
+}
+
+/// Keeps the open positions in a JSON file so a restarted bot can go on
+/// managing them. The whole set is rewritten on every change, through a
+/// temporary file that replaces the old one, so a crash mid-write never
+/// leaves a torn file behind.
+pub struct PositionStore {
+    path: PathBuf,
+}
+
+impl PositionStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save(&self, positions: &[Position]) -> Result<()> {
+        let (now, now_utc) = (Instant::now(), Utc::now());
+        let stored: Vec<StoredPosition> = positions
+            .iter()
+            .map(|position| StoredPosition::from_position(position, now, now_utc))
+            .collect();
+
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(&stored)?)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+
+    /// Reads the positions back; a missing file means none were open.
+    pub fn load(&self) -> Result<Vec<Position>> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let (now, now_utc) = (Instant::now(), Utc::now());
+        serde_json::from_slice::<Vec<StoredPosition>>(&contents)?
+            .into_iter()
+            .map(|stored| stored.into_position(now, now_utc))
+            .collect()
+    }
+}
+
+/// Brings restored positions in line with what the wallet actually holds.
+/// `balance_of` returns the raw on-chain token balance, or `None` when it
+/// could not be read, in which case the stored position is kept as is.
+/// Emptied positions are dropped; any other difference, e.g. a sell that
+/// landed just before the restart, resizes the position at its entry price.
+pub fn reconcile(positions: Vec<Position>, mut balance_of: impl FnMut(&Position) -> Option<u64>) -> Vec<Position> {
+    positions
+        .into_iter()
+        .filter_map(|position| match balance_of(&position) {
+            None => Some(position),
+            Some(0) => None,
+            Some(balance) if balance == position.token_amount => Some(position),
+            Some(balance) => Some(Position {
+                entry_sol: position.entry_sol * balance as f64 / position.token_amount.max(1) as f64,
+                entry_fees_sol: position.entry_fees_sol * balance as f64 / position.token_amount.max(1) as f64,
+                token_amount: balance,
+                ..position
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn position(token_amount: u64, opened_at: Instant) -> Position {
+        Position {
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount,
+            entry_price_sol: 0.000_000_03,
+            entry_sol: 0.5,
+            entry_fees_sol: 0.0,
+            opened_at,
+            exiting: false,
+            average_down_buys: 0,
+        }
+    }
+
+    #[test]
+    fn test_save_load_and_reconcile() {
+        let path = std::env::temp_dir().join(format!("positions_{}.json", Pubkey::new_unique()));
+        let store = PositionStore::new(&path);
+        assert!(store.load().unwrap().is_empty());
+
+        let opened_at = Instant::now() - Duration::from_secs(90);
+        let (held, sold, partly_sold, unknown) = (
+            position(17_000_000_000_000, opened_at),
+            position(1_000, opened_at),
+            position(2_000, opened_at),
+            position(3_000, opened_at),
+        );
+        store.save(&[held.clone(), sold.clone(), partly_sold.clone(), unknown.clone()]).unwrap();
+
+        let loaded = store.load().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.len(), 4);
+        let restored = &loaded[0];
+        assert_eq!((restored.mint, restored.creator, restored.token_program), (held.mint, held.creator, held.token_program));
+        assert_eq!((restored.token_amount, restored.entry_sol, restored.exiting), (held.token_amount, 0.5, false));
+        // The hold time carries over, to within the store's clock resolution
+        let held_for = restored.opened_at.elapsed();
+        assert!(held_for >= Duration::from_secs(89) && held_for < Duration::from_secs(120));
+
+        let balances = HashMap::from([
+            (held.mint, held.token_amount),
+            (sold.mint, 0),
+            (partly_sold.mint, 500),
+        ]);
+        let reconciled = reconcile(loaded, |position| balances.get(&position.mint).copied());
+        let by_mint: HashMap<Pubkey, &Position> = reconciled.iter().map(|position| (position.mint, position)).collect();
+
+        assert_eq!(reconciled.len(), 3);
+        assert!(!by_mint.contains_key(&sold.mint));
+        assert_eq!(by_mint[&held.mint].token_amount, held.token_amount);
+        assert_eq!((by_mint[&partly_sold.mint].token_amount, by_mint[&partly_sold.mint].entry_sol), (500, 0.125));
+        assert_eq!(by_mint[&unknown.mint].token_amount, 3_000);
+    }
+}
@@ -1,10 +1,15 @@
+use crate::config::Config;
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::{join_all, select_ok};
 use parking_lot::RwLock;
+use rand::Rng;
 use serde::Deserialize;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 
 #[derive(Deserialize)]
 struct CoinGeckoResponse {
@@ -16,17 +21,344 @@ struct SolanaPrice {
     usd: f64,
 }
 
+#[derive(Deserialize)]
+struct BinanceTicker {
+    price: String,
+}
+
+#[derive(Deserialize)]
+struct CoinbaseResponse {
+    data: CoinbaseSpot,
+}
+
+#[derive(Deserialize)]
+struct CoinbaseSpot {
+    amount: String,
+}
+
+const FREE_PRICE_URL: &str = "https://api.coingecko.com/api/v3/simple/price?ids=solana&vs_currencies=usd";
+const PRO_PRICE_URL: &str = "https://pro-api.coingecko.com/api/v3/simple/price?ids=solana&vs_currencies=usd";
+const BINANCE_PRICE_URL: &str = "https://api.binance.com/api/v3/ticker/price?symbol=SOLUSDT";
+const COINBASE_PRICE_URL: &str = "https://api.coinbase.com/v2/prices/SOL-USD/spot";
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// Longest wait between refreshes while every source keeps failing.
+const DEFAULT_BACKOFF_MAX: Duration = Duration::from_secs(300);
+/// Fraction by which a backoff delay is randomly stretched or shortened.
+const DEFAULT_BACKOFF_JITTER: f64 = 0.2;
+/// How long any one source may take to answer.
+const SOURCE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Sent unless `PRICE_USER_AGENT` says otherwise; CoinGecko turns away
+/// requests that carry no user agent.
+pub const DEFAULT_USER_AGENT: &str = concat!("solana-pumpfun-sniper/", env!("CARGO_PKG_VERSION"));
+/// Under `Median`, quotes further than this from the median are discarded.
+const MAX_MEDIAN_DEVIATION: f64 = 0.05;
+
+/// Somewhere a SOL/USD price can be fetched from.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn fetch_sol_price(&self, client: &reqwest::Client) -> Result<f64>;
+}
+
+/// CoinGecko's simple price API, on the pro endpoint when `api_key` is set.
+pub struct CoinGeckoSource {
+    api_key: Option<String>,
+}
+
+impl CoinGeckoSource {
+    pub fn new(api_key: Option<String>) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn name(&self) -> &str {
+        "coingecko"
+    }
+
+    async fn fetch_sol_price(&self, client: &reqwest::Client) -> Result<f64> {
+        let request = match &self.api_key {
+            Some(api_key) => client.get(PRO_PRICE_URL).header("x-cg-pro-api-key", api_key),
+            None => client.get(FREE_PRICE_URL),
+        };
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("CoinGecko API returned error status: {}", response.status()));
+        }
+
+        let data: CoinGeckoResponse = response.json().await?;
+        Ok(data.solana.usd)
+    }
+}
+
+/// Binance's SOL/USDT ticker.
+pub struct BinanceSource;
+
+#[async_trait]
+impl PriceSource for BinanceSource {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    async fn fetch_sol_price(&self, client: &reqwest::Client) -> Result<f64> {
+        let ticker: BinanceTicker = client.get(BINANCE_PRICE_URL).send().await?.error_for_status()?.json().await?;
+        ticker.price.parse().map_err(|_| anyhow!("Binance returned an invalid price: {}", ticker.price))
+    }
+}
+
+/// Coinbase's SOL-USD spot price.
+pub struct CoinbaseSource;
+
+#[async_trait]
+impl PriceSource for CoinbaseSource {
+    fn name(&self) -> &str {
+        "coinbase"
+    }
+
+    async fn fetch_sol_price(&self, client: &reqwest::Client) -> Result<f64> {
+        let response: CoinbaseResponse = client.get(COINBASE_PRICE_URL).send().await?.error_for_status()?.json().await?;
+        response.data.amount.parse().map_err(|_| anyhow!("Coinbase returned an invalid price: {}", response.data.amount))
+    }
+}
+
+/// The price sources that can be listed in `PRICE_SOURCES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSourceKind {
+    CoinGecko,
+    Binance,
+    Coinbase,
+}
+
+impl FromStr for PriceSourceKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "coingecko" => Ok(PriceSourceKind::CoinGecko),
+            "binance" => Ok(PriceSourceKind::Binance),
+            "coinbase" => Ok(PriceSourceKind::Coinbase),
+            other => Err(anyhow!("Unknown price source '{}', expected coingecko, binance or coinbase", other)),
+        }
+    }
+}
+
+/// Parses a comma-separated source list such as `coingecko,binance`.
+pub fn parse_price_sources(text: &str) -> Result<Vec<PriceSourceKind>> {
+    let mut sources = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let source = entry.parse()?;
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+    Ok(sources)
+}
+
+/// How the configured sources are combined into one price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSourceStrategy {
+    /// Try sources in order and take the first that answers.
+    Sequential,
+    /// Ask every source at once and take whichever answers first.
+    RaceFirst,
+    /// Ask every source, drop quotes far from the median, and take the median
+    /// of the rest. Guards against one source returning a bad price.
+    Median,
+}
+
+impl FromStr for PriceSourceStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace(['-', '_'], "").as_str() {
+            "sequential" => Ok(PriceSourceStrategy::Sequential),
+            "racefirst" | "race" => Ok(PriceSourceStrategy::RaceFirst),
+            "median" => Ok(PriceSourceStrategy::Median),
+            other => Err(anyhow!("Unknown price source strategy '{}', expected sequential, race_first or median", other)),
+        }
+    }
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// The median of `prices` after discarding any quote more than
+/// `max_deviation` (as a fraction) away from the median of all of them.
+pub fn median_without_outliers(prices: &[f64], max_deviation: f64) -> Option<f64> {
+    let mut sorted: Vec<f64> = prices.iter().copied().filter(|price| price.is_finite() && *price > 0.0).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    let first_pass = median(&sorted);
+    sorted.retain(|price| ((price - first_pass) / first_pass).abs() <= max_deviation);
+    if sorted.is_empty() {
+        return None;
+    }
+    Some(median(&sorted))
+}
+
+/// Refresh schedule for the periodic price update. After each consecutive
+/// failed fetch the wait doubles, up to `max`; the first success drops it back
+/// to `base`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceBackoff {
+    pub base: Duration,
+    pub max: Duration,
+    /// Each delay is scaled by a random factor in `1 ± jitter`, so that
+    /// several bots do not hammer a recovering source in lockstep.
+    pub jitter: f64,
+}
+
+impl PriceBackoff {
+    /// Wait before the next fetch, without jitter.
+    pub fn delay(&self, consecutive_failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(consecutive_failures.min(31));
+        self.base.saturating_mul(factor).min(self.max.max(self.base))
+    }
+
+    /// `delay` scaled by `1 + jitter * (2 * unit - 1)` for a `unit` in `[0, 1]`.
+    fn jittered(&self, consecutive_failures: u32, unit: f64) -> Duration {
+        let spread = self.jitter.clamp(0.0, 1.0) * (2.0 * unit.clamp(0.0, 1.0) - 1.0);
+        self.delay(consecutive_failures).mul_f64(1.0 + spread)
+    }
+
+    fn next_delay(&self, consecutive_failures: u32) -> Duration {
+        let unit = rand::thread_rng().gen::<f64>();
+        self.jittered(consecutive_failures, unit)
+    }
+}
+
+/// How price requests reach the outside world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceHttpConfig {
+    /// Proxy every price request goes through, e.g. `http://proxy.corp:3128`
+    pub proxy: Option<String>,
+    pub user_agent: String,
+    pub connect_timeout: Duration,
+    /// Longest wait for any one read of a response
+    pub read_timeout: Duration,
+    /// Longest a whole request may take, however slowly its reads trickle in
+    pub timeout: Duration,
+}
+
+impl Default for PriceHttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            connect_timeout: SOURCE_TIMEOUT,
+            read_timeout: SOURCE_TIMEOUT,
+            timeout: SOURCE_TIMEOUT,
+        }
+    }
+}
+
+impl PriceHttpConfig {
+    /// Builds the client the price sources are queried with. Fails on a proxy
+    /// URL reqwest cannot use.
+    pub fn build_client(&self) -> Result<reqwest::Client> {
+        self.client_builder()?
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| anyhow!("Could not build the price HTTP client: {}", e).into())
+    }
+
+    /// A client builder with the proxy and user agent set, for other outbound
+    /// requests to add their own timeouts to.
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder> {
+        let mut builder = reqwest::Client::builder().user_agent(self.user_agent.as_str());
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy.as_str())
+                .map_err(|e| anyhow!("Invalid price proxy '{}': {}", proxy, e))?;
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder)
+    }
+}
+
 pub struct PriceCache {
     price: Arc<RwLock<f64>>,
+    sources: Vec<Box<dyn PriceSource>>,
+    strategy: PriceSourceStrategy,
+    client: reqwest::Client,
+    backoff: PriceBackoff,
 }
 
 impl PriceCache {
     pub fn new() -> Self {
+        Self::with_settings(None, DEFAULT_REFRESH_INTERVAL)
+    }
+
+    /// Uses the CoinGecko pro endpoint when `api_key` is set, otherwise the free one.
+    pub fn with_settings(api_key: Option<String>, refresh_interval: Duration) -> Self {
+        Self::with_sources(
+            vec![Box::new(CoinGeckoSource::new(api_key))],
+            PriceSourceStrategy::Sequential,
+            refresh_interval,
+        )
+    }
+
+    pub fn with_sources(
+        sources: Vec<Box<dyn PriceSource>>,
+        strategy: PriceSourceStrategy,
+        refresh_interval: Duration,
+    ) -> Self {
         Self {
             price: Arc::new(RwLock::new(0.0)),
+            sources,
+            strategy,
+            client: PriceHttpConfig::default().build_client().unwrap_or_default(),
+            backoff: PriceBackoff {
+                base: refresh_interval,
+                max: DEFAULT_BACKOFF_MAX,
+                jitter: DEFAULT_BACKOFF_JITTER,
+            },
         }
     }
 
+    pub fn with_backoff(mut self, max: Duration, jitter: f64) -> Self {
+        self.backoff.max = max;
+        self.backoff.jitter = jitter;
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Builds the sources listed in `PRICE_SOURCES`, combined by
+    /// `PRICE_SOURCE_STRATEGY` and queried through the configured proxy.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let sources: Vec<Box<dyn PriceSource>> = config.price_sources
+            .iter()
+            .map(|kind| -> Box<dyn PriceSource> {
+                match kind {
+                    PriceSourceKind::CoinGecko => Box::new(CoinGeckoSource::new(config.coingecko_api_key.clone())),
+                    PriceSourceKind::Binance => Box::new(BinanceSource),
+                    PriceSourceKind::Coinbase => Box::new(CoinbaseSource),
+                }
+            })
+            .collect();
+        let client = config.price_http().build_client()?;
+        Ok(Self::with_sources(sources, config.price_source_strategy, config.price_refresh_interval)
+            .with_backoff(config.price_backoff_max, config.price_backoff_jitter)
+            .with_client(client))
+    }
+
     pub fn get(&self) -> f64 {
         *self.price.read()
     }
@@ -35,54 +367,92 @@ impl PriceCache {
         *self.price.write() = price;
     }
 
-    async fn fetch_sol_price() -> Result<f64> {
-        let client = reqwest::Client::new();
-        let response = client
-            .get("https://api.coingecko.com/api/v3/simple/price?ids=solana&vs_currencies=usd")
-            .send()
-            .await?;
+    /// Fetches the SOL price once and caches it, for one-off tools that do
+    /// not run the periodic refresh.
+    pub async fn refresh(&self) -> Result<f64> {
+        let price = self.fetch_sol_price().await?;
+        self.set(price);
+        Ok(price)
+    }
 
-        if !response.status().is_success() {
-            return Err(anyhow!("CoinGecko API returned error status: {}", response.status()));
+    async fn fetch_from(&self, source: &dyn PriceSource) -> Result<f64> {
+        let price = source.fetch_sol_price(&self.client).await
+            .map_err(|e| anyhow!("{} price fetch failed: {}", source.name(), e))?;
+        if !price.is_finite() || price <= 0.0 {
+            return Err(anyhow!("{} returned an invalid price for SOL: {}", source.name(), price));
         }
+        debug!("{} SOL price: ${:.2}", source.name(), price);
+        Ok(price)
+    }
 
-        let data: CoinGeckoResponse = response.json().await?;
-        
-        if data.solana.usd == 0.0 {
-            return Err(anyhow!("CoinGecko returned zero price for SOL"));
+    async fn fetch_sol_price(&self) -> Result<f64> {
+        if self.sources.is_empty() {
+            return Err(anyhow!("No price sources configured"));
         }
 
-        Ok(data.solana.usd)
-    }
-
-    pub async fn update_price_periodically(&self) {
-        let price_cache = Arc::new(self.price.clone());
-        
-        // Initial fetch
-        match Self::fetch_sol_price().await {
-            Ok(price) => {
-                *price_cache.write() = price;
-                info!("SOL Price updated: ${:.2}", price);
+        match self.strategy {
+            PriceSourceStrategy::Sequential => {
+                let mut errors = Vec::new();
+                for source in &self.sources {
+                    match self.fetch_from(source.as_ref()).await {
+                        Ok(price) => return Ok(price),
+                        Err(e) => errors.push(e.to_string()),
+                    }
+                }
+                Err(anyhow!("All price sources failed: {}", errors.join("; ")))
             }
-            Err(e) => {
-                error!("CoinGecko price fetch failed: {}. Price not updated.", e);
+            PriceSourceStrategy::RaceFirst => {
+                let fetches = self.sources.iter().map(|source| Box::pin(self.fetch_from(source.as_ref())));
+                let (price, _) = select_ok(fetches).await
+                    .map_err(|e| anyhow!("All price sources failed, last error: {}", e))?;
+                Ok(price)
+            }
+            PriceSourceStrategy::Median => {
+                let results = join_all(self.sources.iter().map(|source| self.fetch_from(source.as_ref()))).await;
+                let mut prices = Vec::new();
+                for result in results {
+                    match result {
+                        Ok(price) => prices.push(price),
+                        Err(e) => warn!("{}", e),
+                    }
+                }
+                let price = median_without_outliers(&prices, MAX_MEDIAN_DEVIATION)
+                    .ok_or_else(|| anyhow!("No price source returned a usable SOL price"))?;
+                if prices.len() > 1 {
+                    debug!("Median SOL price ${:.2} from {} quotes", price, prices.len());
+                }
+                Ok(price)
             }
         }
+    }
 
-        // Periodic updates every 30 seconds
-        let mut interval = time::interval(Duration::from_secs(30));
+    pub async fn update_price_periodically(&self) {
+        let mut consecutive_failures: u32 = 0;
         loop {
-            interval.tick().await;
-            
-            match Self::fetch_sol_price().await {
+            match self.fetch_sol_price().await {
                 Ok(price) => {
-                    *price_cache.write() = price;
+                    self.set(price);
                     info!("SOL Price updated: ${:.2}", price);
+                    if consecutive_failures > 0 {
+                        info!("SOL price feed recovered after {} failed fetches", consecutive_failures);
+                    }
+                    consecutive_failures = 0;
                 }
                 Err(e) => {
-                    error!("CoinGecko price fetch failed: {}. Price not updated.", e);
+                    consecutive_failures = consecutive_failures.saturating_add(1);
+                    error!("SOL price fetch failed: {}. Price not updated.", e);
                 }
             }
+
+            let delay = self.backoff.next_delay(consecutive_failures);
+            if consecutive_failures > 0 {
+                warn!(
+                    "SOL price fetch failed {} times in a row, retrying in {:.1}s",
+                    consecutive_failures,
+                    delay.as_secs_f64()
+                );
+            }
+            time::sleep(delay).await;
         }
     }
 }
@@ -92,3 +462,127 @@ impl Default for PriceCache {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedSource {
+        name: &'static str,
+        price: Option<f64>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl PriceSource for FixedSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn fetch_sol_price(&self, _client: &reqwest::Client) -> Result<f64> {
+            tokio::time::sleep(self.delay).await;
+            self.price.ok_or_else(|| anyhow!("unavailable"))
+        }
+    }
+
+    fn source(name: &'static str, price: Option<f64>, delay_ms: u64) -> Box<dyn PriceSource> {
+        Box::new(FixedSource { name, price, delay: Duration::from_millis(delay_ms) })
+    }
+
+    fn cache(strategy: PriceSourceStrategy, sources: Vec<Box<dyn PriceSource>>) -> PriceCache {
+        PriceCache::with_sources(sources, strategy, DEFAULT_REFRESH_INTERVAL)
+    }
+
+    #[test]
+    fn test_backoff_doubles_on_failures_and_caps() {
+        let backoff = PriceBackoff {
+            base: Duration::from_secs(30),
+            max: Duration::from_secs(300),
+            jitter: 0.2,
+        };
+        let schedule: Vec<u64> = (0..7).map(|failures| backoff.delay(failures).as_secs()).collect();
+        assert_eq!(schedule, [30, 60, 120, 240, 300, 300, 300]);
+        // A long outage never overflows
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(300));
+
+        // Jitter stays within 20% either way
+        let jittered_secs = |unit| backoff.jittered(2, unit).as_secs_f64().round();
+        assert_eq!(jittered_secs(0.0), 96.0);
+        assert_eq!(jittered_secs(0.5), 120.0);
+        assert_eq!(jittered_secs(1.0), 144.0);
+        for _ in 0..100 {
+            let delay = backoff.next_delay(4);
+            assert!(delay >= Duration::from_secs(240) && delay <= Duration::from_secs(360));
+        }
+    }
+
+    #[test]
+    fn test_median_without_outliers() {
+        assert_eq!(median_without_outliers(&[150.0, 151.0, 149.0], 0.05), Some(150.0));
+        // A source quoting far off the others is ignored
+        assert_eq!(median_without_outliers(&[150.0, 152.0, 15.0, 151.0], 0.05), Some(151.0));
+        assert_eq!(median_without_outliers(&[150.0], 0.05), Some(150.0));
+        assert_eq!(median_without_outliers(&[], 0.05), None);
+        assert_eq!(median_without_outliers(&[0.0, f64::NAN], 0.05), None);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_falls_through_to_next_source() {
+        let prices = cache(PriceSourceStrategy::Sequential, vec![
+            source("down", None, 0),
+            source("slow", Some(150.0), 50),
+            source("fast", Some(151.0), 0),
+        ]);
+        assert_eq!(prices.refresh().await.unwrap(), 150.0);
+        assert_eq!(prices.get(), 150.0);
+    }
+
+    #[tokio::test]
+    async fn test_race_first_takes_fastest_answer() {
+        let prices = cache(PriceSourceStrategy::RaceFirst, vec![
+            source("slow", Some(150.0), 200),
+            source("down", None, 0),
+            source("fast", Some(151.0), 10),
+        ]);
+        assert_eq!(prices.refresh().await.unwrap(), 151.0);
+
+        let all_down = cache(PriceSourceStrategy::RaceFirst, vec![source("a", None, 0), source("b", None, 0)]);
+        assert!(all_down.refresh().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_median_rejects_bad_source() {
+        let prices = cache(PriceSourceStrategy::Median, vec![
+            source("a", Some(150.0), 0),
+            source("b", Some(1.5), 0),
+            source("c", Some(152.0), 0),
+            source("down", None, 0),
+        ]);
+        assert_eq!(prices.refresh().await.unwrap(), 151.0);
+    }
+
+    #[test]
+    fn test_client_builds_with_a_proxy() {
+        let http = PriceHttpConfig {
+            proxy: Some("http://127.0.0.1:3128".to_string()),
+            user_agent: "price-test/1.0".to_string(),
+            ..PriceHttpConfig::default()
+        };
+        assert!(http.build_client().is_ok());
+        assert!(PriceHttpConfig::default().build_client().is_ok());
+
+        let unusable = PriceHttpConfig { proxy: Some("not a proxy url".to_string()), ..http };
+        assert!(unusable.build_client().is_err());
+    }
+
+    #[test]
+    fn test_parse_price_sources() {
+        assert_eq!(
+            parse_price_sources("coingecko, Binance,coinbase,binance").unwrap(),
+            [PriceSourceKind::CoinGecko, PriceSourceKind::Binance, PriceSourceKind::Coinbase]
+        );
+        assert!(parse_price_sources("kraken").is_err());
+        assert_eq!("race_first".parse::<PriceSourceStrategy>().unwrap(), PriceSourceStrategy::RaceFirst);
+        assert_eq!("Median".parse::<PriceSourceStrategy>().unwrap(), PriceSourceStrategy::Median);
+    }
+}
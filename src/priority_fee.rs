@@ -0,0 +1,501 @@
+use crate::error::{Result, SniperError};
+use async_trait::async_trait;
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// How often the dynamic compute-unit price is re-estimated.
+pub const PRIORITY_FEE_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Compute budget every buy and sell is sent with; buys take their price from
+// the configured priority fee schedule instead
+pub const COMPUTE_UNIT_LIMIT: u32 = 400_000;
+pub const COMPUTE_UNIT_PRICE_MICRO_LAMPORTS: u64 = 500_000;
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Priority fee in lamports for `compute_unit_limit` units at the given
+/// price, rounded up as the runtime does.
+pub const fn priority_fee_lamports(compute_unit_limit: u32, compute_unit_price_micro_lamports: u64) -> u64 {
+    (compute_unit_limit as u64 * compute_unit_price_micro_lamports).div_ceil(1_000_000)
+}
+
+/// Compute-unit price schedule for buys. Each buy starts at `floor`, or at the
+/// current estimate when one is sampled, and every resend of an attempt that
+/// failed to land raises the price by `step`, never past `ceiling`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityFeeSchedule {
+    pub floor_micro_lamports: u64,
+    pub step_micro_lamports: u64,
+    pub ceiling_micro_lamports: u64,
+    /// Resends allowed after the first attempt fails to land.
+    pub max_retries: u32,
+}
+
+impl PriorityFeeSchedule {
+    /// Starts tracking a new in-flight buy at the floor price.
+    pub fn start(&self) -> PriorityFeeEscalation {
+        self.start_at(None)
+    }
+
+    /// Starts tracking a new in-flight buy at `estimate`, kept between the
+    /// floor and the ceiling; at the floor without one.
+    pub fn start_at(&self, estimate: Option<u64>) -> PriorityFeeEscalation {
+        let price = estimate.map_or(self.floor_micro_lamports, |estimate| estimate.max(self.floor_micro_lamports));
+        let price = price.min(self.ceiling_micro_lamports);
+        PriorityFeeEscalation {
+            schedule: *self,
+            start_micro_lamports: price,
+            price_micro_lamports: price,
+            retries: 0,
+        }
+    }
+}
+
+/// Escalation state of a single buy attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityFeeEscalation {
+    schedule: PriorityFeeSchedule,
+    start_micro_lamports: u64,
+    price_micro_lamports: u64,
+    retries: u32,
+}
+
+impl PriorityFeeEscalation {
+    /// Price to sign the current attempt with.
+    pub fn price_micro_lamports(&self) -> u64 {
+        self.price_micro_lamports
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    pub fn is_escalated(&self) -> bool {
+        self.price_micro_lamports > self.start_micro_lamports
+    }
+
+    /// Moves on to the next resend and returns its price, or `None` once the
+    /// retry budget is spent. At the ceiling, resends keep the ceiling price.
+    pub fn escalate(&mut self) -> Option<u64> {
+        if self.retries >= self.schedule.max_retries {
+            return None;
+        }
+        self.retries += 1;
+        self.price_micro_lamports = self.price_micro_lamports
+            .saturating_add(self.schedule.step_micro_lamports)
+            .min(self.schedule.ceiling_micro_lamports);
+        Some(self.price_micro_lamports)
+    }
+}
+
+/// Tiers a priority fee estimate can target, named after Helius'
+/// `priorityFeeLevels`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFeeLevel {
+    Min,
+    Low,
+    Medium,
+    High,
+    VeryHigh,
+    UnsafeMax,
+}
+
+impl PriorityFeeLevel {
+    /// The percentile of recent fees the tier stands for.
+    pub fn percentile(&self) -> u8 {
+        match self {
+            PriorityFeeLevel::Min => 0,
+            PriorityFeeLevel::Low => 25,
+            PriorityFeeLevel::Medium => 50,
+            PriorityFeeLevel::High => 75,
+            PriorityFeeLevel::VeryHigh => 95,
+            PriorityFeeLevel::UnsafeMax => 100,
+        }
+    }
+
+    fn helius_key(&self) -> &'static str {
+        match self {
+            PriorityFeeLevel::Min => "min",
+            PriorityFeeLevel::Low => "low",
+            PriorityFeeLevel::Medium => "medium",
+            PriorityFeeLevel::High => "high",
+            PriorityFeeLevel::VeryHigh => "veryHigh",
+            PriorityFeeLevel::UnsafeMax => "unsafeMax",
+        }
+    }
+}
+
+impl FromStr for PriorityFeeLevel {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "min" => Ok(PriorityFeeLevel::Min),
+            "low" => Ok(PriorityFeeLevel::Low),
+            "medium" => Ok(PriorityFeeLevel::Medium),
+            "high" => Ok(PriorityFeeLevel::High),
+            "very_high" | "veryhigh" => Ok(PriorityFeeLevel::VeryHigh),
+            "unsafe_max" | "unsafemax" => Ok(PriorityFeeLevel::UnsafeMax),
+            other => Err(SniperError::Config(format!(
+                "Unknown priority fee level '{}', expected min, low, medium, high, very_high or unsafe_max", other
+            ))),
+        }
+    }
+}
+
+/// Where the dynamic compute-unit price is estimated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFeeSourceKind {
+    /// The standard `getRecentPrioritizationFees` RPC method
+    Rpc,
+    /// Helius' `getPriorityFeeEstimate`
+    Helius,
+}
+
+impl FromStr for PriorityFeeSourceKind {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rpc" => Ok(PriorityFeeSourceKind::Rpc),
+            "helius" => Ok(PriorityFeeSourceKind::Helius),
+            other => Err(SniperError::Config(format!(
+                "Unknown priority fee source '{}', expected rpc or helius", other
+            ))),
+        }
+    }
+}
+
+/// Estimates the compute-unit price a transaction needs to land promptly.
+#[async_trait]
+pub trait PriorityFeeSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Micro-lamports per compute unit for a transaction writing `accounts`.
+    async fn estimate(&self, accounts: &[Pubkey]) -> Result<u64>;
+}
+
+/// Builds the source named by `kind`, querying `endpoint` at `level`.
+pub fn build_priority_fee_source(
+    kind: PriorityFeeSourceKind,
+    endpoint: &str,
+    level: PriorityFeeLevel,
+    timeout: Duration,
+) -> Box<dyn PriorityFeeSource> {
+    match kind {
+        PriorityFeeSourceKind::Rpc => Box::new(RpcPriorityFeeSource {
+            client: NonblockingRpcClient::new_with_timeout(endpoint.to_string(), timeout),
+            level,
+        }),
+        PriorityFeeSourceKind::Helius => Box::new(HeliusPriorityFeeSource {
+            client: reqwest::Client::builder().timeout(timeout).build().unwrap_or_default(),
+            endpoint: endpoint.to_string(),
+            level,
+        }),
+    }
+}
+
+/// The fee at `percentile` of recent per-slot fees, 0 without samples.
+pub fn percentile_fee(fees: &[u64], percentile: u8) -> u64 {
+    let mut sorted = fees.to_vec();
+    sorted.sort_unstable();
+    match sorted.len() {
+        0 => 0,
+        len => sorted[(len - 1) * percentile.min(100) as usize / 100],
+    }
+}
+
+/// Picks the level's percentile out of the fees recent slots paid.
+pub struct RpcPriorityFeeSource {
+    client: NonblockingRpcClient,
+    level: PriorityFeeLevel,
+}
+
+#[async_trait]
+impl PriorityFeeSource for RpcPriorityFeeSource {
+    fn name(&self) -> &'static str {
+        "rpc"
+    }
+
+    async fn estimate(&self, accounts: &[Pubkey]) -> Result<u64> {
+        let samples = self.client
+            .get_recent_prioritization_fees(accounts)
+            .await
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch recent prioritization fees: {}", e)))?;
+        let fees: Vec<u64> = samples.iter().map(|sample| sample.prioritization_fee).collect();
+        Ok(percentile_fee(&fees, self.level.percentile()))
+    }
+}
+
+/// Asks Helius' `getPriorityFeeEstimate` for all of its tiers and takes the
+/// configured one.
+pub struct HeliusPriorityFeeSource {
+    client: reqwest::Client,
+    endpoint: String,
+    level: PriorityFeeLevel,
+}
+
+#[async_trait]
+impl PriorityFeeSource for HeliusPriorityFeeSource {
+    fn name(&self) -> &'static str {
+        "helius"
+    }
+
+    async fn estimate(&self, accounts: &[Pubkey]) -> Result<u64> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getPriorityFeeEstimate",
+            "params": [{
+                "accountKeys": accounts.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "options": { "includeAllPriorityFeeLevels": true },
+            }],
+        });
+        let body = self.client.post(&self.endpoint).json(&request).send().await?.error_for_status()?.bytes().await?;
+        parse_helius_estimate(&body, self.level)
+    }
+}
+
+#[derive(Deserialize)]
+struct HeliusResponse {
+    result: Option<HeliusEstimate>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeliusEstimate {
+    priority_fee_levels: Option<HashMap<String, f64>>,
+    priority_fee_estimate: Option<f64>,
+}
+
+/// Reads `level` out of a `getPriorityFeeEstimate` response, falling back to
+/// the single estimate Helius returns when the tiers are missing.
+pub fn parse_helius_estimate(body: &[u8], level: PriorityFeeLevel) -> Result<u64> {
+    let response: HeliusResponse = serde_json::from_slice(body)?;
+    if let Some(error) = response.error {
+        return Err(SniperError::SolanaClient(format!("Helius priority fee estimate failed: {}", error)));
+    }
+    let estimate = response.result
+        .and_then(|result| {
+            result.priority_fee_levels
+                .and_then(|levels| levels.get(level.helius_key()).copied())
+                .or(result.priority_fee_estimate)
+        })
+        .ok_or_else(|| SniperError::SolanaClient("Helius priority fee estimate has no result".to_string()))?;
+    Ok(estimate.max(0.0).round() as u64)
+}
+
+/// Keeps a recent estimate from a [`PriorityFeeSource`] for buys to start at.
+pub struct PriorityFeeEstimator {
+    source: Box<dyn PriorityFeeSource>,
+    accounts: Vec<Pubkey>,
+    latest: AtomicU64,
+}
+
+impl PriorityFeeEstimator {
+    /// Estimates fees for transactions writing `accounts`, e.g. the PumpFun program.
+    pub fn new(source: Box<dyn PriorityFeeSource>, accounts: Vec<Pubkey>) -> Self {
+        Self {
+            source,
+            accounts,
+            latest: AtomicU64::new(0),
+        }
+    }
+
+    /// The last estimate, `None` until one has been sampled.
+    pub fn latest(&self) -> Option<u64> {
+        Some(self.latest.load(Ordering::Relaxed)).filter(|price| *price > 0)
+    }
+
+    pub async fn refresh(&self) -> Result<u64> {
+        let price = self.source.estimate(&self.accounts).await?;
+        self.latest.store(price, Ordering::Relaxed);
+        Ok(price)
+    }
+
+    /// Re-estimates every [`PRIORITY_FEE_SAMPLE_INTERVAL`]. A failed sample
+    /// keeps the previous estimate.
+    pub async fn update_periodically(&self) {
+        let mut interval = tokio::time::interval(PRIORITY_FEE_SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.refresh().await {
+                Ok(price) => debug!("Estimated compute-unit price from {}: {} µlamports/CU", self.source.name(), price),
+                Err(e) => warn!("Failed to estimate priority fees from {}: {}", self.source.name(), e),
+            }
+        }
+    }
+}
+
+// Leading byte of the compute-budget program's instructions
+const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// Leaves exactly one compute-unit limit and one compute-unit price at the
+/// front of `instructions`, each at the highest value any contributor asked
+/// for. The runtime rejects transactions carrying either instruction twice,
+/// which happens when several builders each prepend their own. Everything
+/// else, other compute-budget instructions included, keeps its order.
+pub fn finalize_instructions(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut unit_limit: Option<u32> = None;
+    let mut unit_price: Option<u64> = None;
+    let mut rest = Vec::with_capacity(instructions.len());
+    for instruction in instructions {
+        if instruction.program_id == compute_budget::id() {
+            match instruction.data.split_first() {
+                Some((&SET_COMPUTE_UNIT_LIMIT_TAG, value)) if value.len() == 4 => {
+                    let units = u32::from_le_bytes(value.try_into().unwrap());
+                    unit_limit = unit_limit.max(Some(units));
+                    continue;
+                }
+                Some((&SET_COMPUTE_UNIT_PRICE_TAG, value)) if value.len() == 8 => {
+                    let price = u64::from_le_bytes(value.try_into().unwrap());
+                    unit_price = unit_price.max(Some(price));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        rest.push(instruction);
+    }
+
+    let mut finalized = Vec::with_capacity(rest.len() + 2);
+    finalized.extend(unit_limit.map(ComputeBudgetInstruction::set_compute_unit_limit));
+    finalized.extend(unit_price.map(ComputeBudgetInstruction::set_compute_unit_price));
+    finalized.extend(rest);
+    finalized
+}
+
+/// The compute-unit price `transaction` pays, in micro-lamports per unit; 0
+/// when it sets none.
+pub fn transaction_compute_unit_price(transaction: &Transaction) -> u64 {
+    let account_keys = &transaction.message.account_keys;
+    transaction.message.instructions
+        .iter()
+        .filter(|instruction| account_keys.get(instruction.program_id_index as usize) == Some(&compute_budget::id()))
+        .filter_map(|instruction| match instruction.data.split_first() {
+            Some((&SET_COMPUTE_UNIT_PRICE_TAG, value)) if value.len() == 8 => Some(u64::from_le_bytes(value.try_into().unwrap())),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{pubkey::Pubkey, system_instruction};
+
+    #[test]
+    fn test_priority_fee_lamports() {
+        assert_eq!(priority_fee_lamports(COMPUTE_UNIT_LIMIT, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS), 200_000);
+        assert_eq!(priority_fee_lamports(1, 1), 1);
+        assert_eq!(priority_fee_lamports(200_000, 0), 0);
+    }
+
+    #[test]
+    fn test_escalates_by_step_up_to_ceiling() {
+        let schedule = PriorityFeeSchedule {
+            floor_micro_lamports: 500_000,
+            step_micro_lamports: 250_000,
+            ceiling_micro_lamports: 1_000_000,
+            max_retries: 3,
+        };
+
+        let mut attempt = schedule.start();
+        assert_eq!(attempt.price_micro_lamports(), 500_000);
+        assert!(!attempt.is_escalated());
+
+        assert_eq!(attempt.escalate(), Some(750_000));
+        assert_eq!(attempt.escalate(), Some(1_000_000));
+        assert_eq!(attempt.escalate(), Some(1_000_000));
+        assert_eq!(attempt.escalate(), None);
+        assert_eq!(attempt.retries(), 3);
+        assert!(attempt.is_escalated());
+
+        // Each buy tracks its own escalation
+        assert_eq!(schedule.start().price_micro_lamports(), 500_000);
+
+        let no_retries = PriorityFeeSchedule { max_retries: 0, ..schedule };
+        assert_eq!(no_retries.start().escalate(), None);
+
+        // An estimate moves the start within the floor and ceiling
+        let mut estimated = schedule.start_at(Some(800_000));
+        assert_eq!(estimated.price_micro_lamports(), 800_000);
+        assert!(!estimated.is_escalated());
+        assert_eq!(estimated.escalate(), Some(1_000_000));
+        assert!(estimated.is_escalated());
+        assert_eq!(schedule.start_at(Some(1_000)).price_micro_lamports(), 500_000);
+        assert_eq!(schedule.start_at(Some(5_000_000)).price_micro_lamports(), 1_000_000);
+    }
+
+    #[test]
+    fn test_helius_estimate_parsing() {
+        let response = br#"{
+            "jsonrpc": "2.0",
+            "result": {
+                "priorityFeeLevels": {
+                    "min": 0.0, "low": 1200.5, "medium": 25000.0,
+                    "high": 180000.0, "veryHigh": 950000.0, "unsafeMax": 12000000.0
+                }
+            },
+            "id": 1
+        }"#;
+        assert_eq!(parse_helius_estimate(response, PriorityFeeLevel::Medium).unwrap(), 25_000);
+        assert_eq!(parse_helius_estimate(response, PriorityFeeLevel::Low).unwrap(), 1_201);
+        assert_eq!(parse_helius_estimate(response, PriorityFeeLevel::VeryHigh).unwrap(), 950_000);
+
+        // Without the tiers, the single estimate stands in for any of them
+        let single = br#"{"jsonrpc": "2.0", "result": {"priorityFeeEstimate": 42000.0}, "id": 1}"#;
+        assert_eq!(parse_helius_estimate(single, PriorityFeeLevel::High).unwrap(), 42_000);
+
+        let error = br#"{"jsonrpc": "2.0", "error": {"code": -32602, "message": "invalid account keys"}, "id": 1}"#;
+        assert!(parse_helius_estimate(error, PriorityFeeLevel::Medium).is_err());
+        assert!(parse_helius_estimate(b"<html>", PriorityFeeLevel::Medium).is_err());
+
+        assert_eq!("very-high".parse::<PriorityFeeLevel>().unwrap(), PriorityFeeLevel::VeryHigh);
+        assert!("urgent".parse::<PriorityFeeLevel>().is_err());
+        assert_eq!(" Helius ".parse::<PriorityFeeSourceKind>().unwrap(), PriorityFeeSourceKind::Helius);
+        assert_eq!(percentile_fee(&[0, 10, 20, 30, 40], 50), 20);
+        assert_eq!(percentile_fee(&[5, 1], 100), 5);
+        assert_eq!(percentile_fee(&[], 75), 0);
+    }
+
+    #[test]
+    fn test_finalize_dedupes_compute_budget() {
+        let transfer = system_instruction::transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+        let tip = system_instruction::transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 2);
+        let heap = ComputeBudgetInstruction::request_heap_frame(64 * 1024);
+
+        // The buy path and a bundler each prepend their own compute budget
+        let finalized = finalize_instructions(vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(750_000),
+            transfer.clone(),
+            ComputeBudgetInstruction::set_compute_unit_price(500_000),
+            heap.clone(),
+            ComputeBudgetInstruction::set_compute_unit_limit(400_000),
+            tip.clone(),
+        ]);
+        assert_eq!(finalized, vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(400_000),
+            ComputeBudgetInstruction::set_compute_unit_price(750_000),
+            transfer.clone(),
+            heap,
+            tip,
+        ]);
+        assert_eq!(finalize_instructions(finalized.clone()), finalized);
+
+        // Nothing is added when no one asked for a compute budget
+        assert_eq!(finalize_instructions(vec![transfer.clone()]), vec![transfer]);
+    }
+}
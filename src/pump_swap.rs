@@ -0,0 +1,428 @@
+use crate::constants::*;
+use crate::error::{Result, SniperError};
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
+use std::str::FromStr;
+use tracing::warn;
+
+// PumpSwap Pool account layout offsets (after the 8-byte discriminator):
+// pool_bump: u8, index: u16, creator, base_mint, quote_mint, lp_mint,
+// pool_base_token_account, pool_quote_token_account: Pubkey, lp_supply: u64,
+// coin_creator: Pubkey (newer pools only)
+const POOL_BASE_MINT_OFFSET: usize = 43;
+const POOL_QUOTE_MINT_OFFSET: usize = 75;
+const POOL_BASE_TOKEN_ACCOUNT_OFFSET: usize = 139;
+const POOL_QUOTE_TOKEN_ACCOUNT_OFFSET: usize = 171;
+const POOL_COIN_CREATOR_OFFSET: usize = 211;
+
+/// A migrated PumpSwap pool, decoded from its on-chain account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PumpSwapPool {
+    pub address: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    /// Set on pools created after creator fees were introduced; their swaps
+    /// take two extra creator-vault accounts.
+    pub coin_creator: Option<Pubkey>,
+}
+
+impl PumpSwapPool {
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Result<Self> {
+        let read_pubkey = |offset: usize| -> Option<Pubkey> {
+            let bytes: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+            Some(Pubkey::new_from_array(bytes))
+        };
+        let too_short = || SniperError::Transaction(format!("PumpSwap pool {} too short: {} bytes", address, data.len()));
+
+        Ok(Self {
+            address,
+            base_mint: read_pubkey(POOL_BASE_MINT_OFFSET).ok_or_else(too_short)?,
+            quote_mint: read_pubkey(POOL_QUOTE_MINT_OFFSET).ok_or_else(too_short)?,
+            pool_base_token_account: read_pubkey(POOL_BASE_TOKEN_ACCOUNT_OFFSET).ok_or_else(too_short)?,
+            pool_quote_token_account: read_pubkey(POOL_QUOTE_TOKEN_ACCOUNT_OFFSET).ok_or_else(too_short)?,
+            coin_creator: read_pubkey(POOL_COIN_CREATOR_OFFSET).filter(|creator| *creator != Pubkey::default()),
+        })
+    }
+}
+
+/// Quotes the SOL received for selling `base_amount_in` raw tokens into a pool
+/// holding `base_reserve` tokens and `quote_reserve` lamports, after swap fees.
+pub fn quote_sell_output(base_amount_in: u64, base_reserve: u64, quote_reserve: u64) -> u64 {
+    let base_in = base_amount_in as u128;
+    let quote_out = quote_reserve as u128 * base_in / (base_reserve as u128 + base_in);
+    let fee = quote_out * PUMP_SWAP_FEE_BASIS_POINTS as u128 / 10_000;
+    (quote_out - fee) as u64
+}
+
+/// SOL per whole token in a pool holding `base_reserve` raw tokens and
+/// `quote_reserve` lamports.
+pub fn pool_price_sol(base_reserve: u64, quote_reserve: u64) -> f64 {
+    if base_reserve == 0 {
+        return 0.0;
+    }
+    let quote_sol = quote_reserve as f64 / LAMPORTS_PER_SOL as f64;
+    let base_tokens = base_reserve as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+    quote_sol / base_tokens
+}
+
+/// Most accounts one `getMultipleAccounts` call may ask for.
+const MAX_ACCOUNTS_PER_READ: usize = 100;
+
+/// Account reads behind `PumpSwapPoller`. Accounts that don't exist come back
+/// as `None`.
+#[async_trait]
+pub trait PoolAccountReader: Send + Sync {
+    async fn read_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>>;
+}
+
+#[async_trait]
+impl PoolAccountReader for NonblockingRpcClient {
+    async fn read_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>> {
+        self.get_multiple_accounts(addresses)
+            .await
+            .map(|accounts| accounts.into_iter().map(|account| account.map(|account| account.data)).collect())
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch PumpSwap accounts: {}", e)))
+    }
+}
+
+/// What one poll learned about a tracked token's pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoolUpdate {
+    /// Price in SOL per whole token, and the SOL held in the pool.
+    Priced { mint: Pubkey, price_sol: f64, liquidity_sol: f64 },
+    /// The pool or one of its reserve accounts no longer exists.
+    Closed { mint: Pubkey },
+}
+
+/// Reads the reserves of migrated tokens' PumpSwap pools so their price and
+/// liquidity stay current without waiting on a trade.
+pub struct PumpSwapPoller<R> {
+    reader: R,
+}
+
+impl<R: PoolAccountReader> PumpSwapPoller<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Polls each `(mint, pool)` pair: every pool account first, then all their
+    /// reserve accounts, in batches of `MAX_ACCOUNTS_PER_READ`. Updates come
+    /// back in the order of `pools`. Pools that fail to read are left out and
+    /// tried again on the next poll.
+    pub async fn poll(&self, pools: &[(Pubkey, Pubkey)]) -> Vec<PoolUpdate> {
+        let pool_addresses: Vec<Pubkey> = pools.iter().map(|(_, pool)| *pool).collect();
+        let pool_accounts = self.read_in_batches(&pool_addresses).await;
+
+        let mut updates: Vec<Option<PoolUpdate>> = vec![None; pools.len()];
+        let mut open = Vec::new();
+        for (index, ((mint, pool_address), account)) in pools.iter().zip(pool_accounts).enumerate() {
+            let Some(account) = account else {
+                continue;
+            };
+            let Some(data) = account.filter(|data| !data.is_empty()) else {
+                updates[index] = Some(PoolUpdate::Closed { mint: *mint });
+                continue;
+            };
+            match PumpSwapPool::from_account_data(*pool_address, &data) {
+                Ok(pool) => open.push((index, pool)),
+                Err(e) => warn!("Failed to poll PumpSwap pool {} for {}: {}", pool_address, mint, e),
+            }
+        }
+
+        let reserve_addresses: Vec<Pubkey> = open
+            .iter()
+            .flat_map(|(_, pool)| [pool.pool_base_token_account, pool.pool_quote_token_account])
+            .collect();
+        let reserves = self.read_in_batches(&reserve_addresses).await;
+        for ((index, _), reserves) in open.iter().zip(reserves.chunks(2)) {
+            let mint = pools[*index].0;
+            let [Some(base), Some(quote)] = reserves else {
+                continue;
+            };
+            let amount = |account: &Option<Vec<u8>>| account.as_deref().and_then(token_account_amount);
+            updates[*index] = Some(match (amount(base), amount(quote)) {
+                (Some(base_reserve), Some(quote_reserve)) => PoolUpdate::Priced {
+                    mint,
+                    price_sol: pool_price_sol(base_reserve, quote_reserve),
+                    liquidity_sol: quote_reserve as f64 / LAMPORTS_PER_SOL as f64,
+                },
+                _ => PoolUpdate::Closed { mint },
+            });
+        }
+        updates.into_iter().flatten().collect()
+    }
+
+    /// Reads `addresses` in order, `MAX_ACCOUNTS_PER_READ` at a time. Every
+    /// account of a batch that failed to read comes back as `None`.
+    async fn read_in_batches(&self, addresses: &[Pubkey]) -> Vec<Option<Option<Vec<u8>>>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for batch in addresses.chunks(MAX_ACCOUNTS_PER_READ) {
+            match self.reader.read_accounts(batch).await {
+                Ok(read) if read.len() == batch.len() => accounts.extend(read.into_iter().map(Some)),
+                Ok(read) => {
+                    warn!("Asked for {} PumpSwap accounts, got {}", batch.len(), read.len());
+                    accounts.extend(batch.iter().map(|_| None));
+                }
+                Err(e) => {
+                    warn!("Failed to read {} PumpSwap accounts: {}", batch.len(), e);
+                    accounts.extend(batch.iter().map(|_| None));
+                }
+            }
+        }
+        accounts
+    }
+}
+
+/// Raw amount held by a token account under either token program. Token-2022
+/// accounts start with the SPL Token layout and append their extensions, so
+/// the base account is read from the leading bytes of both.
+fn token_account_amount(data: &[u8]) -> Option<u64> {
+    let base = data.get(..spl_token::state::Account::LEN)?;
+    spl_token::state::Account::unpack(base)
+        .ok()
+        .map(|account| account.amount)
+}
+
+/// Builder for a PumpSwap sell (base token → SOL) against a migrated pool.
+pub struct PumpSwapSwap {
+    pool: PumpSwapPool,
+    user: Pubkey,
+    protocol_fee_recipient: Pubkey,
+    /// The token program of the base mint; the quote is always classic WSOL
+    base_token_program: Pubkey,
+    base_amount_in: u64,
+    min_quote_amount_out: u64,
+}
+
+impl PumpSwapSwap {
+    pub fn sell(pool: PumpSwapPool, user: Pubkey, base_amount_in: u64, base_token_program: Pubkey) -> Result<Self> {
+        Ok(Self {
+            pool,
+            user,
+            protocol_fee_recipient: Pubkey::from_str(PUMP_SWAP_PROTOCOL_FEE_RECIPIENT)?,
+            base_token_program,
+            base_amount_in,
+            min_quote_amount_out: 0,
+        })
+    }
+
+    /// The user's token account holding the base tokens being sold.
+    pub fn user_base_token_account(&self) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.user, &self.pool.base_mint, &self.base_token_program)
+    }
+
+    pub fn with_min_quote_amount_out(mut self, min_quote_amount_out: u64) -> Self {
+        self.min_quote_amount_out = min_quote_amount_out;
+        self
+    }
+
+    pub fn with_protocol_fee_recipient(mut self, protocol_fee_recipient: Pubkey) -> Self {
+        self.protocol_fee_recipient = protocol_fee_recipient;
+        self
+    }
+
+    /// The user's quote (WSOL) token account the swap pays into. It must exist
+    /// before the swap and be closed afterwards to unwrap the proceeds.
+    pub fn user_quote_token_account(&self) -> Pubkey {
+        get_associated_token_address(&self.user, &self.pool.quote_mint)
+    }
+
+    pub fn instruction(&self) -> Result<Instruction> {
+        let program_id = Pubkey::from_str(PUMP_SWAP_PROGRAM_ID)?;
+        let (global_config, _) = Pubkey::find_program_address(&[b"global_config"], &program_id);
+        let (event_authority, _) = Pubkey::find_program_address(&[b"__event_authority"], &program_id);
+        let protocol_fee_recipient_token_account =
+            get_associated_token_address(&self.protocol_fee_recipient, &self.pool.quote_mint);
+
+        let mut data = PUMPFUN_SELL_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&self.base_amount_in.to_le_bytes());
+        data.extend_from_slice(&self.min_quote_amount_out.to_le_bytes());
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.pool.address, false),
+            AccountMeta::new(self.user, true),
+            AccountMeta::new_readonly(global_config, false),
+            AccountMeta::new_readonly(self.pool.base_mint, false),
+            AccountMeta::new_readonly(self.pool.quote_mint, false),
+            AccountMeta::new(self.user_base_token_account(), false),
+            AccountMeta::new(self.user_quote_token_account(), false),
+            AccountMeta::new(self.pool.pool_base_token_account, false),
+            AccountMeta::new(self.pool.pool_quote_token_account, false),
+            AccountMeta::new_readonly(self.protocol_fee_recipient, false),
+            AccountMeta::new(protocol_fee_recipient_token_account, false),
+            AccountMeta::new_readonly(self.base_token_program, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(event_authority, false),
+            AccountMeta::new_readonly(program_id, false),
+        ];
+
+        // Pools with a coin creator also route a creator fee to the creator's vault
+        if let Some(coin_creator) = self.pool.coin_creator {
+            let (vault_authority, _) =
+                Pubkey::find_program_address(&[b"creator_vault", coin_creator.as_ref()], &program_id);
+            accounts.push(AccountMeta::new(get_associated_token_address(&vault_authority, &self.pool.quote_mint), false));
+            accounts.push(AccountMeta::new_readonly(vault_authority, false));
+        }
+
+        Ok(Instruction { program_id, accounts, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_data(base_mint: Pubkey, coin_creator: Option<Pubkey>) -> Vec<u8> {
+        let mut data = vec![0u8; POOL_COIN_CREATOR_OFFSET + 32];
+        data[POOL_BASE_MINT_OFFSET..POOL_BASE_MINT_OFFSET + 32].copy_from_slice(base_mint.as_ref());
+        data[POOL_QUOTE_MINT_OFFSET..POOL_QUOTE_MINT_OFFSET + 32]
+            .copy_from_slice(spl_token::native_mint::id().as_ref());
+        if let Some(creator) = coin_creator {
+            data[POOL_COIN_CREATOR_OFFSET..].copy_from_slice(creator.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_pool_layouts() {
+        let base_mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+
+        let pool = PumpSwapPool::from_account_data(Pubkey::new_unique(), &pool_data(base_mint, Some(creator))).unwrap();
+        assert_eq!(pool.base_mint, base_mint);
+        assert_eq!(pool.quote_mint, spl_token::native_mint::id());
+        assert_eq!(pool.coin_creator, Some(creator));
+
+        // Older pools end before the coin creator field
+        let data = pool_data(base_mint, None);
+        let legacy = PumpSwapPool::from_account_data(Pubkey::new_unique(), &data[..POOL_COIN_CREATOR_OFFSET]).unwrap();
+        assert_eq!(legacy.coin_creator, None);
+
+        assert!(PumpSwapPool::from_account_data(Pubkey::new_unique(), &data[..100]).is_err());
+    }
+
+    #[test]
+    fn test_sell_instruction_accounts() {
+        let user = Pubkey::new_unique();
+        let base_mint = Pubkey::new_unique();
+        let legacy_data = pool_data(base_mint, None);
+        let legacy = PumpSwapPool::from_account_data(Pubkey::new_unique(), &legacy_data).unwrap();
+
+        let swap = PumpSwapSwap::sell(legacy, user, 5_000, spl_token::id()).unwrap().with_min_quote_amount_out(42);
+        let instruction = swap.instruction().unwrap();
+        assert_eq!(instruction.accounts.len(), 17);
+        assert_eq!(instruction.accounts[1].pubkey, user);
+        assert!(instruction.accounts[1].is_signer);
+        assert_eq!(instruction.accounts[5].pubkey, get_associated_token_address(&user, &base_mint));
+        assert_eq!(instruction.accounts[6].pubkey, swap.user_quote_token_account());
+        assert_eq!((instruction.accounts[11].pubkey, instruction.accounts[12].pubkey), (spl_token::id(), spl_token::id()));
+        assert_eq!(&instruction.data[8..16], &5_000u64.to_le_bytes());
+        assert_eq!(&instruction.data[16..24], &42u64.to_le_bytes());
+
+        let creator_pool = PumpSwapPool::from_account_data(Pubkey::new_unique(), &pool_data(base_mint, Some(user))).unwrap();
+        let instruction = PumpSwapSwap::sell(creator_pool, user, 5_000, spl_token::id()).unwrap().instruction().unwrap();
+        assert_eq!(instruction.accounts.len(), 19);
+
+        // A Token-2022 base is sold from its Token-2022 account, under that program
+        let token_2022 = PumpSwapSwap::sell(legacy, user, 5_000, crate::pumpfun::program_keys().token_2022).unwrap();
+        let instruction = token_2022.instruction().unwrap();
+        assert_eq!(
+            instruction.accounts[5].pubkey,
+            get_associated_token_address_with_program_id(&user, &base_mint, &crate::pumpfun::program_keys().token_2022)
+        );
+        assert_eq!((instruction.accounts[11].pubkey, instruction.accounts[12].pubkey), (crate::pumpfun::program_keys().token_2022, spl_token::id()));
+    }
+
+    #[derive(Default)]
+    struct StubAccounts(std::collections::HashMap<Pubkey, Vec<u8>>, std::sync::atomic::AtomicUsize);
+
+    #[async_trait]
+    impl PoolAccountReader for StubAccounts {
+        async fn read_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>> {
+            assert!(addresses.len() <= MAX_ACCOUNTS_PER_READ);
+            self.1.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(addresses.iter().map(|address| self.0.get(address).cloned()).collect())
+        }
+    }
+
+    fn token_account_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+        let account = spl_token::state::Account {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        data
+    }
+
+    /// A pool of `mint` with its own reserve accounts, holding 200M tokens against 80 SOL.
+    fn add_pool(accounts: &mut StubAccounts, mint: Pubkey) -> (Pubkey, Pubkey) {
+        let (pool_address, base_account, quote_account) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = pool_data(mint, None);
+        data[POOL_BASE_TOKEN_ACCOUNT_OFFSET..POOL_BASE_TOKEN_ACCOUNT_OFFSET + 32].copy_from_slice(base_account.as_ref());
+        data[POOL_QUOTE_TOKEN_ACCOUNT_OFFSET..POOL_QUOTE_TOKEN_ACCOUNT_OFFSET + 32].copy_from_slice(quote_account.as_ref());
+        accounts.0.insert(pool_address, data);
+        accounts.0.insert(base_account, token_account_data(mint, 200_000_000_000_000));
+        accounts.0.insert(quote_account, token_account_data(spl_token::native_mint::id(), 80 * LAMPORTS_PER_SOL));
+        (pool_address, quote_account)
+    }
+
+    #[tokio::test]
+    async fn test_poller_prices_pools_and_drops_closed_ones() {
+        let mint = Pubkey::new_unique();
+        let mut accounts = StubAccounts::default();
+        let (pool_address, quote_account) = add_pool(&mut accounts, mint);
+
+        let gone = Pubkey::new_unique();
+        let poller = PumpSwapPoller::new(accounts);
+        let updates = poller.poll(&[(mint, pool_address), (gone, Pubkey::new_unique())]).await;
+        assert_eq!(updates, [
+            PoolUpdate::Priced { mint, price_sol: 80.0 / 200_000_000.0, liquidity_sol: 80.0 },
+            PoolUpdate::Closed { mint: gone },
+        ]);
+
+        // A drained reserve account closes the pool too
+        let mut poller = poller;
+        poller.reader.0.remove(&quote_account);
+        assert_eq!(poller.poll(&[(mint, pool_address)]).await, [PoolUpdate::Closed { mint }]);
+    }
+
+    #[tokio::test]
+    async fn test_poller_reads_pools_in_batches() {
+        let mut accounts = StubAccounts::default();
+        let pools: Vec<(Pubkey, Pubkey)> = (0..250)
+            .map(|_| {
+                let mint = Pubkey::new_unique();
+                (mint, add_pool(&mut accounts, mint).0)
+            })
+            .collect();
+
+        let poller = PumpSwapPoller::new(accounts);
+        let updates = poller.poll(&pools).await;
+        assert_eq!(updates.len(), 250);
+        assert!(updates.iter().zip(&pools).all(|(update, (mint, _))| matches!(update, PoolUpdate::Priced { mint: priced, .. } if priced == mint)));
+        // 250 pools in 3 reads, then their 500 reserve accounts in 5
+        assert_eq!(poller.reader.1.load(std::sync::atomic::Ordering::Relaxed), 8);
+    }
+
+    #[test]
+    fn test_quote_sell_output() {
+        // 10% of the base reserve against 100 SOL returns 100/11 SOL less 0.25%
+        let quote = quote_sell_output(100, 1_000, 100 * LAMPORTS_PER_SOL);
+        let gross = 100 * LAMPORTS_PER_SOL / 11;
+        assert_eq!(quote, gross - gross * 25 / 10_000);
+    }
+}
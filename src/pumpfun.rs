@@ -0,0 +1,834 @@
+use crate::constants::*;
+use crate::error::SniperError;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// PumpFun instructions the bot can react to on the Geyser stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PumpFunEvent {
+    Create,
+    Buy,
+    Sell,
+    Migrate,
+}
+
+impl PumpFunEvent {
+    /// Classifies an instruction by the installed [`discriminators`].
+    pub fn from_instruction_data(data: &[u8]) -> Option<Self> {
+        discriminators().event(data)
+    }
+}
+
+/// The 8-byte prefixes PumpFun instructions are recognized and built with.
+/// Defaults to the built-in constants; any of them can be overridden from
+/// config when PumpFun changes one, without recompiling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discriminators {
+    pub create: [u8; 8],
+    /// Create discriminators of other PumpFun deployments, matched as well so
+    /// creates keep being seen across a program upgrade.
+    pub alternate_creates: Vec<[u8; 8]>,
+    pub buy: [u8; 8],
+    pub sell: [u8; 8],
+    pub migrate: [u8; 8],
+    pub instant_migration: [u8; 8],
+}
+
+impl Default for Discriminators {
+    fn default() -> Self {
+        Self {
+            create: CREATE_DISCRIMINATOR,
+            alternate_creates: Vec::new(),
+            buy: PUMPFUN_BUY_DISCRIMINATOR,
+            sell: PUMPFUN_SELL_DISCRIMINATOR,
+            migrate: PUMPFUN_MIGRATE_DISCRIMINATOR,
+            instant_migration: INSTANT_MIGRATION_DISCRIMINATOR,
+        }
+    }
+}
+
+impl Discriminators {
+    pub fn event(&self, data: &[u8]) -> Option<PumpFunEvent> {
+        if self.matched_create(data).is_some() {
+            Some(PumpFunEvent::Create)
+        } else if data.starts_with(&self.buy) {
+            Some(PumpFunEvent::Buy)
+        } else if data.starts_with(&self.sell) {
+            Some(PumpFunEvent::Sell)
+        } else if data.starts_with(&self.migrate) || data.starts_with(&self.instant_migration) {
+            Some(PumpFunEvent::Migrate)
+        } else {
+            None
+        }
+    }
+
+    /// The create discriminator `data` starts with, the primary one or an alternate.
+    pub fn matched_create(&self, data: &[u8]) -> Option<[u8; 8]> {
+        std::iter::once(&self.create)
+            .chain(&self.alternate_creates)
+            .find(|create| data.starts_with(*create))
+            .copied()
+    }
+
+    /// The account layout of creates tagged with `discriminator`: the legacy
+    /// `create` one for the primary discriminator and `create_v2`'s for its
+    /// Anchor hash. `None` for any other alternate, whose accounts can't be
+    /// read safely.
+    pub fn create_layout(&self, discriminator: [u8; 8]) -> Option<CreateLayout> {
+        if discriminator == anchor_discriminator("create_v2") {
+            Some(CREATE_V2_LAYOUT)
+        } else if discriminator == self.create {
+            Some(CREATE_LAYOUT)
+        } else {
+            None
+        }
+    }
+
+    /// Every discriminator with its name, for logging. Alternate creates are
+    /// left out.
+    pub fn named(&self) -> [(&'static str, [u8; 8]); 5] {
+        [
+            ("create", self.create),
+            ("buy", self.buy),
+            ("sell", self.sell),
+            ("migrate", self.migrate),
+            ("instant_migration", self.instant_migration),
+        ]
+    }
+
+    /// Discriminators that differ from the Anchor hash of their instruction
+    /// name, as `(name, configured, expected)`. The instant migration has no
+    /// known instruction name and is never reported.
+    pub fn anchor_mismatches(&self) -> Vec<(&'static str, [u8; 8], [u8; 8])> {
+        self.named()
+            .into_iter()
+            .filter(|(name, _)| *name != "instant_migration")
+            .map(|(name, configured)| (name, configured, anchor_discriminator(name)))
+            .filter(|(_, configured, expected)| configured != expected)
+            .collect()
+    }
+
+    /// Checks the PumpFun instructions of a known on-chain transaction. Returns
+    /// the buys and sells among them, or the unrecognized prefixes when none
+    /// of the instructions is a buy or sell under these discriminators.
+    pub fn check_reference<'a>(
+        &self,
+        pump_fun_instructions: impl IntoIterator<Item = &'a [u8]>,
+    ) -> std::result::Result<Vec<PumpFunEvent>, String> {
+        let mut trades = Vec::new();
+        let mut unrecognized = Vec::new();
+        for data in pump_fun_instructions {
+            match self.event(data) {
+                Some(event @ (PumpFunEvent::Buy | PumpFunEvent::Sell)) => trades.push(event),
+                Some(_) => {}
+                None => unrecognized.push(hex::encode(data.get(..8).unwrap_or(data))),
+            }
+        }
+        if trades.is_empty() {
+            return Err(if unrecognized.is_empty() {
+                "no PumpFun buy or sell instruction found".to_string()
+            } else {
+                format!("PumpFun instructions start with {} instead of a known buy or sell discriminator", unrecognized.join(", "))
+            });
+        }
+        Ok(trades)
+    }
+}
+
+/// The first 8 bytes of `sha256("global:<name>")`, which Anchor programs
+/// such as PumpFun use to tag instructions.
+pub fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let hash = solana_sdk::hash::hash(format!("global:{}", name).as_bytes());
+    hash.to_bytes()[..8].try_into().unwrap()
+}
+
+/// Parses a discriminator written as 16 hex digits, optionally `0x`-prefixed.
+pub fn parse_discriminator(text: &str) -> Result<[u8; 8], SniperError> {
+    let digits = text.trim().trim_start_matches("0x");
+    hex::decode(digits)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SniperError::Config(format!("Invalid discriminator '{}', expected 16 hex digits", text)))
+}
+
+static DISCRIMINATORS: OnceLock<Discriminators> = OnceLock::new();
+
+/// Installs the discriminators from config. Only the first call takes effect,
+/// and it must come before anything reads them; returns whether it did.
+pub fn install_discriminators(discriminators: Discriminators) -> bool {
+    DISCRIMINATORS.set(discriminators).is_ok()
+}
+
+/// The discriminators in use: the installed ones, or the built-in constants.
+pub fn discriminators() -> &'static Discriminators {
+    DISCRIMINATORS.get_or_init(Discriminators::default)
+}
+
+impl FromStr for PumpFunEvent {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "create" => Ok(PumpFunEvent::Create),
+            "buy" => Ok(PumpFunEvent::Buy),
+            "sell" => Ok(PumpFunEvent::Sell),
+            "migrate" => Ok(PumpFunEvent::Migrate),
+            other => Err(SniperError::Config(format!("Unknown stream event '{}'", other))),
+        }
+    }
+}
+
+/// Parses a comma-separated event list such as `create,migrate`.
+pub fn parse_events(text: &str) -> Result<Vec<PumpFunEvent>, SniperError> {
+    let mut events = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let event = entry.parse()?;
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+/// The per-token PumpFun accounts a buy or sell instruction operates on.
+#[derive(Debug, Clone, Copy)]
+pub struct PumpFunAccounts {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    pub creator_vault: Pubkey,
+}
+
+/// Account positions within a PumpFun create instruction, which differ
+/// between the create instructions PumpFun has shipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateLayout {
+    pub mint: usize,
+    pub bonding_curve: usize,
+    pub associated_bonding_curve: usize,
+    pub user: usize,
+    pub token_program: usize,
+}
+
+/// The legacy `create`, with the Metaplex metadata accounts ahead of the user.
+pub const CREATE_LAYOUT: CreateLayout = CreateLayout {
+    mint: 0,
+    bonding_curve: 2,
+    associated_bonding_curve: 3,
+    user: 7,
+    token_program: 9,
+};
+
+/// `create_v2`, for Token-2022 mints: no Metaplex accounts, so the user comes
+/// right after `global` and the token program sits where `create` has the user.
+pub const CREATE_V2_LAYOUT: CreateLayout = CreateLayout {
+    mint: 0,
+    bonding_curve: 2,
+    associated_bonding_curve: 3,
+    user: 5,
+    token_program: 7,
+};
+
+// Account positions within a PumpFun buy or sell instruction
+const TRADE_MINT_INDEX: usize = 2;
+const TRADE_BONDING_CURVE_INDEX: usize = 3;
+const TRADE_USER_INDEX: usize = 6;
+
+/// A token's bonding curve account, a PDA of the PumpFun program.
+pub fn bonding_curve_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &program_keys().pump_fun).0
+}
+
+/// The creator's fee vault, a PDA of the PumpFun program.
+pub fn creator_vault(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator-vault", creator.as_ref()], &program_keys().pump_fun).0
+}
+
+/// The accounts of a new token, taken from its create instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CreateAccounts {
+    pub creator: Pubkey,
+    pub accounts: PumpFunAccounts,
+    /// The token program the mint was created under, so buying it needs no lookup
+    pub token_program: Pubkey,
+}
+
+/// Reads a create instruction's accounts, already resolved to keys in
+/// instruction order, by `layout`. Returns `None` if the instruction is too
+/// short or has no token program where `layout` puts it.
+pub fn parse_create_accounts(layout: CreateLayout, accounts: &[Pubkey]) -> Option<CreateAccounts> {
+    let creator = *accounts.get(layout.user)?;
+    Some(CreateAccounts {
+        creator,
+        accounts: PumpFunAccounts {
+            mint: *accounts.get(layout.mint)?,
+            bonding_curve: *accounts.get(layout.bonding_curve)?,
+            associated_bonding_curve: *accounts.get(layout.associated_bonding_curve)?,
+            creator_vault: creator_vault(&creator),
+        },
+        token_program: token_program_for_mint_owner(accounts.get(layout.token_program)?)?,
+    })
+}
+
+/// A buy of exactly `token_amount` raw tokens, paying at most `max_sol_cost_lamports`.
+///
+/// PumpFun's `buy(amount, max_sol_cost)` is exact-out: the program prices
+/// exactly `amount` on the live curve, adds its fee, and fails with
+/// `TooMuchSolRequired` when that total exceeds `max_sol_cost`. There is no
+/// minimum-tokens-out parameter, so slippage protection lives entirely on the
+/// SOL side; see [`size_buy`].
+#[derive(Debug, Clone, Copy)]
+pub struct BuyParams {
+    pub buyer: Pubkey,
+    pub accounts: PumpFunAccounts,
+    pub fee_recipient: Pubkey,
+    pub token_program: Pubkey,
+    pub token_amount: u64,
+    pub max_sol_cost_lamports: u64,
+}
+
+/// A sale of `token_amount` raw tokens for at least `min_sol_output_lamports`.
+#[derive(Debug, Clone, Copy)]
+pub struct SellParams {
+    pub seller: Pubkey,
+    pub accounts: PumpFunAccounts,
+    pub fee_recipient: Pubkey,
+    pub token_program: Pubkey,
+    pub token_amount: u64,
+    pub min_sol_output_lamports: u64,
+}
+
+/// The static PumpFun accounts every buy and sell references, and the
+/// Token-2022 program some of its mints live under.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramKeys {
+    pub pump_fun: Pubkey,
+    pub global: Pubkey,
+    pub event_authority: Pubkey,
+    pub token_2022: Pubkey,
+}
+
+/// Parses the PumpFun constants on first use and returns the same keys after
+/// that, keeping base58 decoding off the buy path.
+pub fn program_keys() -> &'static ProgramKeys {
+    static KEYS: OnceLock<ProgramKeys> = OnceLock::new();
+    KEYS.get_or_init(|| ProgramKeys {
+        pump_fun: Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap(),
+        global: Pubkey::from_str(KNOWN_GLOBAL).unwrap(),
+        event_authority: Pubkey::from_str(KNOWN_EVENT_AUTH).unwrap(),
+        token_2022: Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap(),
+    })
+}
+
+/// Maps a mint account's owner to the token program its ATAs and instructions
+/// must use. PumpFun mints are classic SPL Token today, but Token-2022 mints
+/// are accepted too. Returns `None` for accounts no token program owns.
+pub fn token_program_for_mint_owner(owner: &Pubkey) -> Option<Pubkey> {
+    [spl_token::id(), program_keys().token_2022].into_iter().find(|program| program == owner)
+}
+
+/// Sizes a buy of `buy_amount_sol` against a curve at the given virtual
+/// reserves. Returns the raw token amount the SOL buys at those reserves and
+/// the lamport cap to pass as `max_sol_cost`: the curve cost plus the PumpFun
+/// fee, widened by `slippage_pct` for the curve moving up before we land.
+/// Because the amount is exact-out, the buffer only ever lets us pay more for
+/// the same tokens, never receive fewer.
+pub fn size_buy(virtual_sol: f64, virtual_tokens: f64, buy_amount_sol: f64, slippage_pct: f64) -> (u64, u64) {
+    let k = virtual_sol * virtual_tokens;
+    let tokens_out = virtual_tokens - k / (virtual_sol + buy_amount_sol);
+    let token_amount = (tokens_out * 10f64.powi(TOKEN_DECIMALS as i32)) as u64;
+    let cost_with_fee = buy_amount_sol * (1.0 + PUMPFUN_FEE_BASIS_POINTS as f64 / 10_000.0);
+    let max_sol_cost_lamports = (cost_with_fee * (1.0 + slippage_pct / 100.0) * LAMPORTS_PER_SOL as f64).round() as u64;
+    (token_amount, max_sol_cost_lamports)
+}
+
+/// SOL, PumpFun fee included, that `token_amount` raw tokens cost from a curve
+/// at the given virtual reserves; `None` when the curve holds no more than that.
+pub fn buy_cost_sol(virtual_sol: f64, virtual_tokens: f64, token_amount: u64) -> Option<f64> {
+    let tokens = token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+    if tokens >= virtual_tokens {
+        return None;
+    }
+    let cost = virtual_sol * virtual_tokens / (virtual_tokens - tokens) - virtual_sol;
+    Some(cost * (1.0 + PUMPFUN_FEE_BASIS_POINTS as f64 / 10_000.0))
+}
+
+/// Re-prices a buy of `token_amount`, sized by [`size_buy`] against the
+/// `detected` virtual reserves, at the `live` reserves read right before
+/// signing. Returns the fresh `max_sol_cost`: the live cost widened by
+/// `slippage_pct` for the curve moving before we land, but never over the cap
+/// sized at detection, so a risen price eats into the slippage buffer rather
+/// than stacking on top of it. Errs with how far the cost has risen since
+/// detection, in percent, once that is over `max_move_pct`.
+pub fn reprice_buy(
+    token_amount: u64,
+    detected: (f64, f64),
+    live: (f64, f64),
+    slippage_pct: f64,
+    max_move_pct: f64,
+) -> std::result::Result<u64, f64> {
+    let (Some(detected_cost), Some(live_cost)) = (
+        buy_cost_sol(detected.0, detected.1, token_amount),
+        buy_cost_sol(live.0, live.1, token_amount),
+    ) else {
+        return Err(f64::INFINITY);
+    };
+    let moved_pct = (live_cost / detected_cost - 1.0) * 100.0;
+    if moved_pct > max_move_pct {
+        return Err(moved_pct);
+    }
+    let cap = |cost: f64| (cost * (1.0 + slippage_pct / 100.0) * LAMPORTS_PER_SOL as f64).round() as u64;
+    Ok(cap(live_cost).min(cap(detected_cost)))
+}
+
+/// A buy or sell seen on the stream, decoded from its instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObservedTrade {
+    pub event: PumpFunEvent,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub user: Pubkey,
+    pub token_amount: u64,
+    /// `max_sol_cost` of a buy or `min_sol_output` of a sell.
+    pub sol_limit_lamports: u64,
+}
+
+/// Decodes a PumpFun buy or sell from its data and its accounts, already
+/// resolved to keys. Returns `None` for other instructions or short ones.
+pub fn parse_trade(data: &[u8], accounts: &[Pubkey]) -> Option<ObservedTrade> {
+    let event = PumpFunEvent::from_instruction_data(data)
+        .filter(|event| matches!(event, PumpFunEvent::Buy | PumpFunEvent::Sell))?;
+    let arg = |offset: usize| data.get(offset..offset + 8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    Some(ObservedTrade {
+        event,
+        mint: *accounts.get(TRADE_MINT_INDEX)?,
+        bonding_curve: *accounts.get(TRADE_BONDING_CURVE_INDEX)?,
+        user: *accounts.get(TRADE_USER_INDEX)?,
+        token_amount: arg(8)?,
+        sol_limit_lamports: arg(16)?,
+    })
+}
+
+fn instruction_data(discriminator: &[u8; 8], amount: u64, limit: u64) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&limit.to_le_bytes());
+    data
+}
+
+pub fn build_buy_instruction(params: &BuyParams) -> Instruction {
+    let keys = program_keys();
+    let (pump_fun_pk, global_key, event_authority_key) = (keys.pump_fun, keys.global, keys.event_authority);
+    let accounts = &params.accounts;
+    let buyer_ata = get_associated_token_address_with_program_id(&params.buyer, &accounts.mint, &params.token_program);
+
+    Instruction {
+        program_id: pump_fun_pk,
+        accounts: vec![
+            AccountMeta::new_readonly(global_key, false),
+            AccountMeta::new(params.fee_recipient, false),
+            AccountMeta::new(accounts.mint, false),
+            AccountMeta::new(accounts.bonding_curve, false),
+            AccountMeta::new(accounts.associated_bonding_curve, false),
+            AccountMeta::new(buyer_ata, false),
+            AccountMeta::new(params.buyer, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(params.token_program, false),
+            AccountMeta::new(accounts.creator_vault, false),
+            AccountMeta::new_readonly(event_authority_key, false),
+            AccountMeta::new_readonly(pump_fun_pk, false),
+        ],
+        data: instruction_data(&discriminators().buy, params.token_amount, params.max_sol_cost_lamports),
+    }
+}
+
+pub fn build_sell_instruction(params: &SellParams) -> Instruction {
+    let keys = program_keys();
+    let (pump_fun_pk, global_key, event_authority_key) = (keys.pump_fun, keys.global, keys.event_authority);
+    let accounts = &params.accounts;
+    let seller_ata = get_associated_token_address_with_program_id(&params.seller, &accounts.mint, &params.token_program);
+
+    // Sell orders the creator vault before the token program, unlike buy
+    Instruction {
+        program_id: pump_fun_pk,
+        accounts: vec![
+            AccountMeta::new_readonly(global_key, false),
+            AccountMeta::new(params.fee_recipient, false),
+            AccountMeta::new_readonly(accounts.mint, false),
+            AccountMeta::new(accounts.bonding_curve, false),
+            AccountMeta::new(accounts.associated_bonding_curve, false),
+            AccountMeta::new(seller_ata, false),
+            AccountMeta::new(params.seller, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new(accounts.creator_vault, false),
+            AccountMeta::new_readonly(params.token_program, false),
+            AccountMeta::new_readonly(event_authority_key, false),
+            AccountMeta::new_readonly(pump_fun_pk, false),
+        ],
+        data: instruction_data(&discriminators().sell, params.token_amount, params.min_sol_output_lamports),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accounts() -> PumpFunAccounts {
+        PumpFunAccounts {
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+        }
+    }
+
+    fn metas(instruction: &Instruction) -> Vec<(Pubkey, bool, bool)> {
+        instruction.accounts.iter().map(|m| (m.pubkey, m.is_signer, m.is_writable)).collect()
+    }
+
+    #[test]
+    fn test_event_parsing() {
+        assert_eq!(parse_events("create, Migrate,create").unwrap(), vec![PumpFunEvent::Create, PumpFunEvent::Migrate]);
+        assert!(parse_events("create,swap").is_err());
+        assert!(parse_events("").unwrap().is_empty());
+
+        assert_eq!(PumpFunEvent::from_instruction_data(&CREATE_DISCRIMINATOR), Some(PumpFunEvent::Create));
+        assert_eq!(PumpFunEvent::from_instruction_data(&PUMPFUN_SELL_DISCRIMINATOR), Some(PumpFunEvent::Sell));
+        assert_eq!(PumpFunEvent::from_instruction_data(&PUMPFUN_MIGRATE_DISCRIMINATOR), Some(PumpFunEvent::Migrate));
+        assert_eq!(PumpFunEvent::from_instruction_data(&[0u8; 8]), None);
+    }
+
+    #[test]
+    fn test_discriminator_checks() {
+        let built_in = Discriminators::default();
+        assert_eq!(anchor_discriminator("create"), CREATE_DISCRIMINATOR);
+        assert_eq!(anchor_discriminator("buy"), PUMPFUN_BUY_DISCRIMINATOR);
+        assert!(built_in.anchor_mismatches().is_empty());
+
+        let stale = Discriminators { sell: [1; 8], ..built_in.clone() };
+        assert_eq!(stale.anchor_mismatches(), vec![("sell", [1; 8], PUMPFUN_SELL_DISCRIMINATOR)]);
+        assert_eq!(stale.event(&PUMPFUN_SELL_DISCRIMINATOR), None);
+        assert_eq!(stale.event(&[1; 8]), Some(PumpFunEvent::Sell));
+
+        assert_eq!(parse_discriminator("0x33e685a4017f83ad").unwrap(), PUMPFUN_SELL_DISCRIMINATOR);
+        assert_eq!(parse_discriminator(" 66063d1201daebea ").unwrap(), PUMPFUN_BUY_DISCRIMINATOR);
+        assert!(parse_discriminator("66063d12").is_err());
+        assert!(parse_discriminator("not hex at all!!").is_err());
+
+        // A reference buy confirms the buy discriminator, and a stale one is caught
+        let buy = instruction_data(&PUMPFUN_BUY_DISCRIMINATOR, 1_000, 2_000);
+        assert_eq!(built_in.check_reference([buy.as_slice()]), Ok(vec![PumpFunEvent::Buy]));
+        let stale_buy = Discriminators { buy: [2; 8], ..built_in.clone() };
+        let problem = stale_buy.check_reference([buy.as_slice()]).unwrap_err();
+        assert!(problem.contains("66063d1201daebea"), "{}", problem);
+        assert!(built_in.check_reference([CREATE_DISCRIMINATOR.as_slice()]).is_err());
+    }
+
+    #[test]
+    fn test_alternate_create_discriminator() {
+        let upgraded = [0xd6, 0x90, 0x4c, 0xec, 0x5f, 0x8b, 0x31, 0xb4];
+        let mut data = upgraded.to_vec();
+        data.extend_from_slice(&[4, 0, 0, 0]);
+        assert_eq!(Discriminators::default().event(&data), None);
+
+        let discriminators = Discriminators { alternate_creates: vec![upgraded], ..Discriminators::default() };
+        assert_eq!(discriminators.event(&data), Some(PumpFunEvent::Create));
+        assert_eq!(discriminators.matched_create(&data), Some(upgraded));
+        assert_eq!(discriminators.matched_create(&CREATE_DISCRIMINATOR), Some(CREATE_DISCRIMINATOR));
+        assert_eq!(discriminators.matched_create(&PUMPFUN_BUY_DISCRIMINATOR), None);
+        // Alternates are expected to differ from the Anchor hash
+        assert!(discriminators.anchor_mismatches().is_empty());
+
+        // Each create is read by its own account layout; unknown ones aren't read at all
+        assert_eq!(upgraded, anchor_discriminator("create_v2"));
+        assert_eq!(discriminators.create_layout(upgraded), Some(CREATE_V2_LAYOUT));
+        assert_eq!(discriminators.create_layout(CREATE_DISCRIMINATOR), Some(CREATE_LAYOUT));
+        assert_eq!(discriminators.create_layout([7; 8]), None);
+    }
+
+    #[test]
+    fn test_buy_instruction_layout() {
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: accounts(),
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        let instruction = build_buy_instruction(&params);
+        let keys = program_keys();
+        let (program, global, event_authority) = (keys.pump_fun, keys.global, keys.event_authority);
+
+        assert_eq!(instruction.program_id, program);
+        assert_eq!(metas(&instruction), vec![
+            (global, false, false),
+            (params.fee_recipient, false, true),
+            (params.accounts.mint, false, true),
+            (params.accounts.bonding_curve, false, true),
+            (params.accounts.associated_bonding_curve, false, true),
+            (get_associated_token_address_with_program_id(&params.buyer, &params.accounts.mint, &spl_token::id()), false, true),
+            (params.buyer, true, true),
+            (solana_sdk::system_program::ID, false, false),
+            (spl_token::id(), false, false),
+            (params.accounts.creator_vault, false, true),
+            (event_authority, false, false),
+            (program, false, false),
+        ]);
+        assert_eq!(instruction.data, [
+            &PUMPFUN_BUY_DISCRIMINATOR[..],
+            &1_000u64.to_le_bytes(),
+            &2_000u64.to_le_bytes(),
+        ].concat());
+    }
+
+    #[test]
+    fn test_size_buy_buffers_the_sol_side() {
+        // 1 SOL into a fresh curve buys a fixed token amount; the cap covers
+        // the 1% fee and then the 20% slippage on top of it
+        let (token_amount, max_sol_cost) = size_buy(INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS, 1.0, 20.0);
+        let expected_tokens = INITIAL_VIRTUAL_TOKENS - INITIAL_VIRTUAL_SOL * INITIAL_VIRTUAL_TOKENS / 31.0;
+        assert_eq!(token_amount, (expected_tokens * 1_000_000.0) as u64);
+        assert_eq!(max_sol_cost, 1_212_000_000);
+
+        // Slippage never shrinks the exact-out token amount, it only raises the cap
+        let (tight_amount, tight_cost) = size_buy(INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS, 1.0, 0.0);
+        assert_eq!(tight_amount, token_amount);
+        assert_eq!(tight_cost, 1_010_000_000);
+        assert!(tight_cost > LAMPORTS_PER_SOL, "a zero-slippage cap must still cover the fee");
+
+        // The same SOL buys fewer tokens further up the curve
+        let (later_amount, _) = size_buy(INITIAL_VIRTUAL_SOL + 10.0, INITIAL_VIRTUAL_TOKENS * 30.0 / 40.0, 1.0, 20.0);
+        assert!(later_amount < token_amount);
+    }
+
+    #[test]
+    fn test_reprice_buy_after_the_curve_moves() {
+        let detected = (INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS);
+        let (token_amount, max_sol_cost) = size_buy(detected.0, detected.1, 1.0, 20.0);
+        assert!((buy_cost_sol(detected.0, detected.1, token_amount).unwrap() - 1.01).abs() < 1e-6);
+
+        // Nothing moved: the cap is the one sized at detection
+        let unmoved = reprice_buy(token_amount, detected, detected, 20.0, 20.0).unwrap();
+        assert!(unmoved.abs_diff(max_sol_cost) <= 1_000);
+
+        // Another wallet buys 2 SOL first; the same tokens now cost about 14% more,
+        // which comes out of the buffer instead of raising the cap
+        let k = detected.0 * detected.1;
+        let moved = (detected.0 + 2.0, k / (detected.0 + 2.0));
+        let repriced = reprice_buy(token_amount, detected, moved, 20.0, 20.0).unwrap();
+        assert!(repriced.abs_diff(max_sol_cost) <= 1_000);
+        let moved_pct = reprice_buy(token_amount, detected, moved, 20.0, 10.0).unwrap_err();
+        assert!(moved_pct > 13.0 && moved_pct < 15.0, "moved {}%", moved_pct);
+
+        // A curve that fell back makes the buy cheaper and the cap tighter
+        let fallen = (detected.0 - 0.5, k / (detected.0 - 0.5));
+        assert!(reprice_buy(token_amount, detected, fallen, 20.0, 0.0).unwrap() < max_sol_cost);
+
+        // A curve with fewer tokens left than we want cannot fill the buy
+        assert!(reprice_buy(token_amount, detected, (detected.0, 1.0), 20.0, 20.0).unwrap_err().is_infinite());
+    }
+
+    #[test]
+    fn test_sell_instruction_layout() {
+        let params = SellParams {
+            seller: Pubkey::new_unique(),
+            accounts: accounts(),
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 5_000,
+            min_sol_output_lamports: 7,
+        };
+        let instruction = build_sell_instruction(&params);
+        let keys = program_keys();
+        let (program, global, event_authority) = (keys.pump_fun, keys.global, keys.event_authority);
+
+        assert_eq!(metas(&instruction), vec![
+            (global, false, false),
+            (params.fee_recipient, false, true),
+            (params.accounts.mint, false, false),
+            (params.accounts.bonding_curve, false, true),
+            (params.accounts.associated_bonding_curve, false, true),
+            (get_associated_token_address_with_program_id(&params.seller, &params.accounts.mint, &spl_token::id()), false, true),
+            (params.seller, true, true),
+            (solana_sdk::system_program::ID, false, false),
+            (params.accounts.creator_vault, false, true),
+            (spl_token::id(), false, false),
+            (event_authority, false, false),
+            (program, false, false),
+        ]);
+        assert_eq!(instruction.data, [
+            &PUMPFUN_SELL_DISCRIMINATOR[..],
+            &5_000u64.to_le_bytes(),
+            &7u64.to_le_bytes(),
+        ].concat());
+    }
+
+    #[test]
+    fn test_parse_trade() {
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: accounts(),
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        let instruction = build_buy_instruction(&params);
+        let keys: Vec<Pubkey> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(parse_trade(&instruction.data, &keys), Some(ObservedTrade {
+            event: PumpFunEvent::Buy,
+            mint: params.accounts.mint,
+            bonding_curve: params.accounts.bonding_curve,
+            user: params.buyer,
+            token_amount: 1_000,
+            sol_limit_lamports: 2_000,
+        }));
+
+        let sell = build_sell_instruction(&SellParams {
+            seller: params.buyer,
+            accounts: params.accounts,
+            fee_recipient: params.fee_recipient,
+            token_program: spl_token::id(),
+            token_amount: 5_000,
+            min_sol_output_lamports: 7,
+        });
+        let trade = parse_trade(&sell.data, &keys).unwrap();
+        assert_eq!((trade.event, trade.token_amount, trade.sol_limit_lamports), (PumpFunEvent::Sell, 5_000, 7));
+
+        assert_eq!(parse_trade(&CREATE_DISCRIMINATOR, &keys), None);
+        assert_eq!(parse_trade(&instruction.data[..12], &keys), None);
+        assert_eq!(parse_trade(&instruction.data, &keys[..4]), None);
+    }
+
+    #[test]
+    fn test_token_2022_mints() {
+        assert_eq!(token_program_for_mint_owner(&spl_token::id()), Some(spl_token::id()));
+        assert_eq!(token_program_for_mint_owner(&program_keys().token_2022), Some(program_keys().token_2022));
+        assert_eq!(token_program_for_mint_owner(&solana_sdk::system_program::ID), None);
+
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: accounts(),
+            fee_recipient: Pubkey::new_unique(),
+            token_program: program_keys().token_2022,
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        let instruction = build_buy_instruction(&params);
+
+        // Both the buyer's ATA and the token program meta follow the mint's program
+        let token_2022_ata =
+            get_associated_token_address_with_program_id(&params.buyer, &params.accounts.mint, &program_keys().token_2022);
+        assert_eq!(instruction.accounts[5].pubkey, token_2022_ata);
+        assert_ne!(
+            token_2022_ata,
+            get_associated_token_address_with_program_id(&params.buyer, &params.accounts.mint, &spl_token::id())
+        );
+        assert_eq!(instruction.accounts[8].pubkey, program_keys().token_2022);
+    }
+
+    #[test]
+    fn test_parse_create_accounts() {
+        let mut accounts: Vec<Pubkey> = (0..14).map(|_| Pubkey::new_unique()).collect();
+        accounts[9] = spl_token::id();
+        let create = parse_create_accounts(CREATE_LAYOUT, &accounts).unwrap();
+
+        assert_eq!(create.creator, accounts[7]);
+        assert_eq!(create.accounts.mint, accounts[0]);
+        assert_eq!(create.accounts.bonding_curve, accounts[2]);
+        assert_eq!(create.accounts.associated_bonding_curve, accounts[3]);
+        assert_eq!(create.accounts.creator_vault, creator_vault(&accounts[7]));
+        assert_eq!(create.token_program, spl_token::id());
+        assert!(parse_create_accounts(CREATE_LAYOUT, &accounts[..7]).is_none());
+
+        // Anything but a token program where the layout expects one is misread
+        accounts[9] = Pubkey::new_unique();
+        assert!(parse_create_accounts(CREATE_LAYOUT, &accounts).is_none());
+    }
+
+    #[test]
+    fn test_parse_create_v2_accounts() {
+        // create_v2's accounts as PumpFun's IDL lists them
+        let (mint, bonding_curve, associated_bonding_curve, user) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = vec![
+            mint,
+            Pubkey::new_unique(), // mint_authority
+            bonding_curve,
+            associated_bonding_curve,
+            program_keys().global,
+            user,
+            solana_sdk::system_program::id(),
+            program_keys().token_2022,
+            spl_associated_token_account::id(),
+            Pubkey::new_unique(), // mayhem_program_id
+            Pubkey::new_unique(), // global_params
+            Pubkey::new_unique(), // sol_vault
+            Pubkey::new_unique(), // mayhem_state
+            Pubkey::new_unique(), // mayhem_token_vault
+            program_keys().event_authority,
+            program_keys().pump_fun,
+        ];
+
+        let create = parse_create_accounts(CREATE_V2_LAYOUT, &accounts).unwrap();
+        assert_eq!(create.creator, user);
+        assert_eq!(create.accounts.mint, mint);
+        assert_eq!(create.accounts.bonding_curve, bonding_curve);
+        assert_eq!(create.accounts.associated_bonding_curve, associated_bonding_curve);
+        assert_eq!(create.accounts.creator_vault, creator_vault(&user));
+        assert_eq!(create.token_program, program_keys().token_2022);
+
+        // The legacy layout finds no token program where it expects one
+        assert!(parse_create_accounts(CREATE_LAYOUT, &accounts).is_none());
+    }
+
+    #[test]
+    fn test_program_keys_are_parsed_once() {
+        let keys = program_keys();
+        assert!(std::ptr::eq(keys, program_keys()));
+        assert_eq!(keys.global, Pubkey::from_str(KNOWN_GLOBAL).unwrap());
+        assert_eq!(keys.event_authority, Pubkey::from_str(KNOWN_EVENT_AUTH).unwrap());
+    }
+
+    /// Compares building a buy from the cached keys against re-parsing the
+    /// constants per buy, as the bot used to. Run with `--ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_buy_instruction_build() {
+        const ITERATIONS: u32 = 100_000;
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: accounts(),
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        program_keys();
+
+        let start = std::time::Instant::now();
+        for _ in 0..ITERATIONS {
+            std::hint::black_box(build_buy_instruction(std::hint::black_box(&params)));
+        }
+        let cached = start.elapsed() / ITERATIONS;
+
+        let start = std::time::Instant::now();
+        for _ in 0..ITERATIONS {
+            std::hint::black_box((
+                Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap(),
+                Pubkey::from_str(KNOWN_GLOBAL).unwrap(),
+                Pubkey::from_str(KNOWN_EVENT_AUTH).unwrap(),
+            ));
+            std::hint::black_box(build_buy_instruction(std::hint::black_box(&params)));
+        }
+        let reparsed = start.elapsed() / ITERATIONS;
+
+        assert!(cached < reparsed, "buy instruction: {:?} cached vs {:?} re-parsing keys", cached, reparsed);
+    }
+}
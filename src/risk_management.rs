@@ -1,7 +1,10 @@
 use crate::constants::*;
 use crate::error::{Result, SniperError};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::time::{Duration, Instant};
 use tracing::{warn, info};
 
@@ -13,6 +16,7 @@ pub struct RiskMetrics {
     pub holder_count: u32,
     pub is_honeypot: bool,
     pub rug_pull_score: f64, // 0.0 = safe, 1.0 = high risk
+    pub top_holder_percentage: Option<f64>, // % of supply held by the top holders
     pub creation_time: Instant,
 }
 
@@ -24,6 +28,8 @@ pub struct RiskConfig {
     pub max_slippage_percentage: f64,
     pub max_buy_amount_sol: f64,
     pub cooldown_period: Duration,
+    pub top_holder_count: usize,
+    pub max_top_holder_percentage: f64,
 }
 
 impl Default for RiskConfig {
@@ -35,6 +41,8 @@ impl Default for RiskConfig {
             max_slippage_percentage: MAX_SLIPPAGE_PERCENTAGE,
             max_buy_amount_sol: MAX_BUY_AMOUNT_SOL,
             cooldown_period: Duration::from_secs(30),
+            top_holder_count: 10,
+            max_top_holder_percentage: 50.0,
         }
     }
 }
@@ -90,6 +98,15 @@ impl RiskManager {
             return Ok(false);
         }
 
+        // Check holder concentration
+        if let Some(top_holder_percentage) = metrics.top_holder_percentage {
+            if top_holder_percentage > self.config.max_top_holder_percentage {
+                warn!("Token {} top {} holders own {:.1}% of supply",
+                      mint, self.config.top_holder_count, top_holder_percentage);
+                return Ok(false);
+            }
+        }
+
         // Check for honeypot
         if metrics.is_honeypot {
             warn!("Token {} detected as honeypot", mint);
@@ -132,6 +149,105 @@ impl RiskManager {
     }
 }
 
+/// Percentage of `total_supply` held by the `top_n` largest balances, ignoring
+/// `excluded` accounts such as the bonding curve's token account.
+pub fn top_holder_percentage(
+    balances: &[(Pubkey, u64)],
+    excluded: &[Pubkey],
+    top_n: usize,
+    total_supply: u64,
+) -> f64 {
+    if total_supply == 0 {
+        return 0.0;
+    }
+
+    let mut holders: Vec<u64> = balances
+        .iter()
+        .filter(|(account, _)| !excluded.contains(account))
+        .map(|(_, amount)| *amount)
+        .collect();
+    holders.sort_unstable_by(|a, b| b.cmp(a));
+
+    let held: u128 = holders.iter().take(top_n).map(|amount| *amount as u128).sum();
+    held as f64 / total_supply as f64 * 100.0
+}
+
+/// Number of non-empty token accounts among the mint's largest holders,
+/// ignoring `excluded` accounts such as the bonding curve's token account.
+/// The RPC only returns the top 20, which is plenty for a fresh launch.
+pub async fn count_holders(rpc_client: &NonblockingRpcClient, mint: &Pubkey, excluded: &[Pubkey]) -> Result<usize> {
+    let largest_accounts = rpc_client
+        .get_token_largest_accounts(mint)
+        .await
+        .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch largest accounts for {}: {}", mint, e)))?;
+
+    let mut holders = 0;
+    for account in &largest_accounts {
+        if account.amount.amount == "0" || excluded.contains(&Pubkey::from_str(&account.address)?) {
+            continue;
+        }
+        holders += 1;
+    }
+    Ok(holders)
+}
+
+/// Holders gained per second between two samples taken `elapsed` apart.
+pub fn holder_velocity(first: usize, second: usize, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    (second as f64 - first as f64) / seconds
+}
+
+/// Looks up top-holder concentration over RPC, caching each mint's result briefly
+/// since largest-account queries are expensive and change slowly.
+pub struct HolderConcentrationChecker {
+    rpc_client: RpcClient,
+    top_n: usize,
+    cache_ttl: Duration,
+    cache: HashMap<Pubkey, (f64, Instant)>,
+}
+
+impl HolderConcentrationChecker {
+    pub fn new(rpc_endpoint: String, top_n: usize, cache_ttl: Duration) -> Self {
+        Self {
+            rpc_client: RpcClient::new(rpc_endpoint),
+            top_n,
+            cache_ttl,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn top_holder_percentage(&mut self, mint: &Pubkey, excluded: &[Pubkey]) -> Result<f64> {
+        if let Some((percentage, fetched_at)) = self.cache.get(mint) {
+            if fetched_at.elapsed() < self.cache_ttl {
+                return Ok(*percentage);
+            }
+        }
+
+        let parse_amount = |amount: &str| -> Result<u64> {
+            amount.parse().map_err(|_| SniperError::PriceFetch(format!("Invalid token amount: {}", amount)))
+        };
+
+        let supply = self.rpc_client
+            .get_token_supply(mint)
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch supply for {}: {}", mint, e)))?;
+        let largest_accounts = self.rpc_client
+            .get_token_largest_accounts(mint)
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch largest accounts for {}: {}", mint, e)))?;
+
+        let mut balances = Vec::with_capacity(largest_accounts.len());
+        for account in &largest_accounts {
+            balances.push((Pubkey::from_str(&account.address)?, parse_amount(&account.amount.amount)?));
+        }
+
+        let percentage = top_holder_percentage(&balances, excluded, self.top_n, parse_amount(&supply.amount)?);
+        self.cache.insert(*mint, (percentage, Instant::now()));
+        Ok(percentage)
+    }
+}
+
 pub struct HoneypotDetector {
     suspicious_patterns: Vec<String>,
 }
@@ -184,12 +300,50 @@ mod tests {
             holder_count: 20,
             is_honeypot: false,
             rug_pull_score: 0.1,
+            top_holder_percentage: Some(20.0),
             creation_time: Instant::now(),
         };
 
         assert!(risk_manager.evaluate_token(&mint, &metrics).unwrap());
     }
 
+    #[test]
+    fn test_top_holder_percentage() {
+        let bonding_curve = Pubkey::new_unique();
+        let mut balances = vec![(bonding_curve, 800)];
+        balances.extend((1..=12).map(|amount| (Pubkey::new_unique(), amount * 5)));
+
+        // Top 10 of 5..=60 excluding the curve: 15 + 20 + ... + 60 = 375 of 1000
+        assert_eq!(top_holder_percentage(&balances, &[bonding_curve], 10, 1_000), 37.5);
+        assert_eq!(top_holder_percentage(&balances, &[], 1, 1_000), 80.0);
+        assert_eq!(top_holder_percentage(&balances, &[], 10, 0), 0.0);
+    }
+
+    #[test]
+    fn test_concentrated_holders_rejected() {
+        let mut risk_manager = RiskManager::new(RiskConfig::default());
+        let metrics = RiskMetrics {
+            market_cap: 10000.0,
+            liquidity: 2000.0,
+            volume_24h: 5000.0,
+            holder_count: 20,
+            is_honeypot: false,
+            rug_pull_score: 0.1,
+            top_holder_percentage: Some(75.0),
+            creation_time: Instant::now(),
+        };
+
+        assert!(!risk_manager.evaluate_token(&Pubkey::new_unique(), &metrics).unwrap());
+    }
+
+    #[test]
+    fn test_holder_velocity() {
+        assert_eq!(holder_velocity(2, 12, Duration::from_secs(5)), 2.0);
+        assert_eq!(holder_velocity(2, 2, Duration::from_secs(5)), 0.0);
+        assert!(holder_velocity(5, 3, Duration::from_secs(2)) < 0.0);
+        assert_eq!(holder_velocity(0, 10, Duration::ZERO), 0.0);
+    }
+
     #[test]
     fn test_optimal_buy_amount() {
         let config = RiskConfig::default();
@@ -202,6 +356,7 @@ mod tests {
             holder_count: 20,
             is_honeypot: false,
             rug_pull_score: 0.1,
+            top_holder_percentage: Some(20.0),
             creation_time: Instant::now(),
         };
 
@@ -0,0 +1,149 @@
+use crate::error::SniperError;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// What to do when a held token's reserves collapse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RugReaction {
+    Alert,
+    AutoExit,
+}
+
+impl FromStr for RugReaction {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "alert" => Ok(RugReaction::Alert),
+            "exit" | "auto_exit" | "auto-exit" => Ok(RugReaction::AutoExit),
+            other => Err(SniperError::Config(format!("Unknown rug reaction '{}', expected alert or exit", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RugMonitorConfig {
+    pub drop_percentage: f64, // e.g. 30.0 = alert after losing 30% of SOL reserves
+    pub window: Duration,
+    pub reaction: RugReaction,
+}
+
+impl Default for RugMonitorConfig {
+    fn default() -> Self {
+        Self {
+            drop_percentage: 30.0,
+            window: Duration::from_secs(10),
+            reaction: RugReaction::AutoExit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RugAlert {
+    pub mint: Pubkey,
+    pub peak_sol_reserve: f64,
+    pub current_sol_reserve: f64,
+    pub drop_percentage: f64,
+}
+
+/// Watches the bonding-curve SOL reserves of held tokens and raises an alert
+/// when they fall sharply within a short window, i.e. a dump is in progress.
+pub struct RugMonitor {
+    config: RugMonitorConfig,
+    history: HashMap<Pubkey, VecDeque<(Instant, f64)>>,
+}
+
+impl RugMonitor {
+    pub fn new(config: RugMonitorConfig) -> Self {
+        Self {
+            config,
+            history: HashMap::new(),
+        }
+    }
+
+    pub fn reaction(&self) -> RugReaction {
+        self.config.reaction
+    }
+
+    /// Records a reserve sample taken at `at` and compares it with the highest
+    /// sample still inside the window.
+    pub fn record(&mut self, mint: &Pubkey, sol_reserve: f64, at: Instant) -> Option<RugAlert> {
+        let samples = self.history.entry(*mint).or_default();
+        while let Some((sampled_at, _)) = samples.front() {
+            if at.saturating_duration_since(*sampled_at) > self.config.window {
+                samples.pop_front();
+            } else {
+                break;
+            }
+        }
+        samples.push_back((at, sol_reserve));
+
+        let peak_sol_reserve = samples.iter().map(|(_, reserve)| *reserve).fold(0.0, f64::max);
+        if peak_sol_reserve <= 0.0 {
+            return None;
+        }
+
+        let drop_percentage = (peak_sol_reserve - sol_reserve) / peak_sol_reserve * 100.0;
+        if drop_percentage < self.config.drop_percentage {
+            return None;
+        }
+
+        warn!("🚨 Possible rug on {}: SOL reserves fell {:.1}% ({:.3} → {:.3} SOL) within {:?}",
+              mint, drop_percentage, peak_sol_reserve, sol_reserve, self.config.window);
+        Some(RugAlert {
+            mint: *mint,
+            peak_sol_reserve,
+            current_sol_reserve: sol_reserve,
+            drop_percentage,
+        })
+    }
+
+    /// Drops the reserve history for a mint once its position is closed.
+    pub fn forget(&mut self, mint: &Pubkey) {
+        self.history.remove(mint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_within_window_alerts() {
+        let mut monitor = RugMonitor::new(RugMonitorConfig::default());
+        let mint = Pubkey::new_unique();
+        let start = Instant::now();
+
+        assert!(monitor.record(&mint, 50.0, start).is_none());
+        assert!(monitor.record(&mint, 45.0, start + Duration::from_secs(2)).is_none());
+
+        let alert = monitor.record(&mint, 30.0, start + Duration::from_secs(4)).unwrap();
+        assert_eq!(alert.peak_sol_reserve, 50.0);
+        assert_eq!(alert.drop_percentage, 40.0);
+    }
+
+    #[test]
+    fn test_slow_bleed_outside_window_is_ignored() {
+        let mut monitor = RugMonitor::new(RugMonitorConfig::default());
+        let mint = Pubkey::new_unique();
+        let start = Instant::now();
+
+        // Each step is a 20% drop, but the 50 SOL peak ages out before reserves reach 30
+        assert!(monitor.record(&mint, 50.0, start).is_none());
+        assert!(monitor.record(&mint, 40.0, start + Duration::from_secs(8)).is_none());
+        assert!(monitor.record(&mint, 32.0, start + Duration::from_secs(16)).is_none());
+
+        monitor.forget(&mint);
+        assert!(monitor.record(&mint, 1.0, start + Duration::from_secs(17)).is_none());
+    }
+
+    #[test]
+    fn test_rug_reaction_parsing() {
+        assert_eq!("alert".parse::<RugReaction>().unwrap(), RugReaction::Alert);
+        assert_eq!("EXIT".parse::<RugReaction>().unwrap(), RugReaction::AutoExit);
+        assert!("panic".parse::<RugReaction>().is_err());
+    }
+}
@@ -1,5 +1,10 @@
 use crate::error::{Result, SniperError};
-use solana_client::rpc_client::RpcClient;
+use crate::jito_integration::{BundleStatus, JitoManager, JitoSubmission, UrgencyLevel};
+use crate::priority_fee::{finalize_instructions, priority_fee_lamports, transaction_compute_unit_price};
+use crate::pumpfun::{build_buy_instruction, BuyParams};
+use parking_lot::Mutex;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_config::RpcBlockConfig;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     hash::Hash,
@@ -9,16 +14,20 @@ use solana_sdk::{
     signer::Signer,
     transaction::Transaction,
 };
+use solana_transaction_status::TransactionDetails;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 
 pub struct SameBlockExecutor {
-    rpc_client: RpcClient,
+    rpc_client: Arc<NonblockingRpcClient>,
     pending_transactions: Arc<RwLock<HashMap<Signature, PendingTransaction>>>,
     block_tracker: BlockTracker,
     execution_queue: ExecutionQueue,
+    config: ExecutorConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -29,9 +38,34 @@ pub struct PendingTransaction {
     pub created_at: Instant,
     pub priority: ExecutionPriority,
     pub retry_count: u32,
+    pub last_valid_block_height: u64,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl PendingTransaction {
+    pub fn is_expired(&self, current_block_height: u64) -> bool {
+        current_block_height > self.last_valid_block_height
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    pub blockhash_refresh_interval: Duration,
+    pub max_queue_size: usize,
+    pub max_retries: u32,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            blockhash_refresh_interval: Duration::from_millis(400), // ~1 slot
+            max_queue_size: 1000,
+            max_retries: 3,
+        }
+    }
+}
+
+// Variant order defines the ordering: Low < Medium < High < Critical
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ExecutionPriority {
     Low,
     Medium,
@@ -39,26 +73,47 @@ pub enum ExecutionPriority {
     Critical,
 }
 
+#[derive(Debug, Clone, Copy)]
+struct RecentBlockhash {
+    hash: Hash,
+    last_valid_block_height: u64,
+    fetched_at: Instant,
+}
+
+/// How long a Geyser slot update keeps RPC slot polling switched off. Past
+/// this the stream is presumed to have stopped sending slots.
+const STREAM_SLOT_MAX_AGE: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
 pub struct BlockTracker {
     current_block: Arc<RwLock<u64>>,
+    last_stream_slot_at: Arc<RwLock<Option<Instant>>>,
+    current_block_height: Arc<RwLock<u64>>,
     block_hash_cache: Arc<RwLock<HashMap<u64, Hash>>>,
+    recent_blockhash: Arc<RwLock<Option<RecentBlockhash>>>,
+    blockhash_refresh_interval: Duration,
     last_update: Arc<RwLock<Instant>>,
 }
 
 impl BlockTracker {
-    pub fn new() -> Self {
+    pub fn new(blockhash_refresh_interval: Duration) -> Self {
         Self {
             current_block: Arc::new(RwLock::new(0)),
+            last_stream_slot_at: Arc::new(RwLock::new(None)),
+            current_block_height: Arc::new(RwLock::new(0)),
             block_hash_cache: Arc::new(RwLock::new(HashMap::new())),
+            recent_blockhash: Arc::new(RwLock::new(None)),
+            blockhash_refresh_interval,
             last_update: Arc::new(RwLock::new(Instant::now())),
         }
     }
 
-    pub async fn update_current_block(&self, rpc_client: &RpcClient) -> Result<u64> {
+    pub async fn update_current_block(&self, rpc_client: &NonblockingRpcClient) -> Result<u64> {
         match rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await {
             Ok(slot) => {
+                // Never step back behind a slot the stream already reported
                 let mut current_block = self.current_block.write().await;
-                *current_block = slot;
+                *current_block = (*current_block).max(slot);
                 
                 let mut last_update = self.last_update.write().await;
                 *last_update = Instant::now();
@@ -77,7 +132,76 @@ impl BlockTracker {
         *self.current_block.read().await
     }
 
-    pub async fn get_block_hash(&self, slot: u64, rpc_client: &RpcClient) -> Result<Hash> {
+    /// Records a slot pushed by the Geyser stream. Stream slots arrive ahead of
+    /// anything RPC polling can see, so while they keep coming the poller
+    /// leaves the current slot alone.
+    pub async fn record_stream_slot(&self, slot: u64) {
+        let mut current_block = self.current_block.write().await;
+        *current_block = (*current_block).max(slot);
+        *self.last_stream_slot_at.write().await = Some(Instant::now());
+        *self.last_update.write().await = Instant::now();
+    }
+
+    /// Whether the stream has reported a slot recently enough to skip RPC polling.
+    pub async fn has_fresh_stream_slot(&self) -> bool {
+        self.last_stream_slot_at
+            .read()
+            .await
+            .is_some_and(|at| at.elapsed() < STREAM_SLOT_MAX_AGE)
+    }
+
+    /// The most up-to-date slot available right now: the stream's while it
+    /// keeps reporting, otherwise a fresh RPC read, which also updates the
+    /// tracker.
+    pub async fn freshest_slot(&self, rpc_client: &NonblockingRpcClient) -> Result<u64> {
+        if !self.has_fresh_stream_slot().await {
+            self.update_current_block(rpc_client).await?;
+        }
+        Ok(self.get_current_block().await)
+    }
+
+    pub async fn update_block_height(&self, rpc_client: &NonblockingRpcClient) -> Result<u64> {
+        let block_height = rpc_client
+            .get_block_height_with_commitment(CommitmentConfig::processed())
+            .await
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to get block height: {}", e)))?;
+
+        *self.current_block_height.write().await = block_height;
+        Ok(block_height)
+    }
+
+    pub async fn get_current_block_height(&self) -> u64 {
+        *self.current_block_height.read().await
+    }
+
+    /// Returns a recent blockhash suitable for signing new transactions, along with
+    /// the last block height at which it is still valid. The hash is reused until
+    /// `blockhash_refresh_interval` elapses.
+    pub async fn get_recent_blockhash(&self, rpc_client: &NonblockingRpcClient) -> Result<(Hash, u64)> {
+        {
+            let cached = self.recent_blockhash.read().await;
+            if let Some(recent) = *cached {
+                if recent.fetched_at.elapsed() < self.blockhash_refresh_interval {
+                    return Ok((recent.hash, recent.last_valid_block_height));
+                }
+            }
+        }
+
+        let (hash, last_valid_block_height) = rpc_client
+            .get_latest_blockhash_with_commitment(CommitmentConfig::processed())
+            .await
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to get latest blockhash: {}", e)))?;
+
+        *self.recent_blockhash.write().await = Some(RecentBlockhash {
+            hash,
+            last_valid_block_height,
+            fetched_at: Instant::now(),
+        });
+
+        Ok((hash, last_valid_block_height))
+    }
+
+    pub async fn get_block_hash(&self, slot: u64, rpc_client: &NonblockingRpcClient) -> Result<Hash> {
         // Check cache first
         {
             let cache = self.block_hash_cache.read().await;
@@ -86,9 +210,20 @@ impl BlockTracker {
             }
         }
 
-        // Fetch from RPC
-        match rpc_client.get_block_hash_with_commitment(slot, CommitmentConfig::processed()).await {
-            Ok(Some(hash)) => {
+        // Fetch from RPC, without the block's transactions or rewards
+        let config = RpcBlockConfig {
+            transaction_details: Some(TransactionDetails::None),
+            rewards: Some(false),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+            ..RpcBlockConfig::default()
+        };
+        let block = rpc_client
+            .get_block_with_config(slot, config)
+            .await
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to get block hash: {}", e)))?;
+        match Hash::from_str(&block.blockhash) {
+            Ok(hash) => {
                 // Cache the result
                 let mut cache = self.block_hash_cache.write().await;
                 cache.insert(slot, hash);
@@ -101,12 +236,12 @@ impl BlockTracker {
                 
                 Ok(hash)
             }
-            Ok(None) => Err(SniperError::SolanaClient("Block hash not found".to_string())),
-            Err(e) => Err(SniperError::SolanaClient(format!("Failed to get block hash: {}", e))),
+            Err(e) => Err(SniperError::SolanaClient(format!("Invalid block hash for slot {}: {}", slot, e))),
         }
     }
 }
 
+#[derive(Clone)]
 pub struct ExecutionQueue {
     queue: Arc<RwLock<Vec<PendingTransaction>>>,
     max_queue_size: usize,
@@ -127,13 +262,30 @@ impl ExecutionQueue {
             return Err(SniperError::Generic(anyhow::anyhow!("Execution queue is full")));
         }
 
-        // Insert based on priority
-        let insert_index = queue.binary_search_by(|tx| {
-            tx.priority.cmp(&transaction.priority).reverse()
-        }).unwrap_or_else(|i| i);
+        Self::insert_sorted(&mut queue, transaction);
+        Ok(())
+    }
 
+    // Keep the queue sorted ascending by priority so `pop` yields the highest
+    // priority first. Inserting before equal priorities keeps FIFO order within a level.
+    fn insert_sorted(queue: &mut Vec<PendingTransaction>, transaction: PendingTransaction) {
+        let insert_index = queue.partition_point(|tx| tx.priority < transaction.priority);
         queue.insert(insert_index, transaction);
-        Ok(())
+    }
+
+    /// Swaps `old_signature` for `replacement` under a single lock so no other task
+    /// can observe the queue with both or neither entry. Returns `false` if the old
+    /// transaction is no longer queued.
+    pub async fn replace_transaction(&self, old_signature: &Signature, replacement: PendingTransaction) -> bool {
+        let mut queue = self.queue.write().await;
+        match queue.iter().position(|tx| tx.signature == *old_signature) {
+            Some(index) => {
+                queue.remove(index);
+                Self::insert_sorted(&mut queue, replacement);
+                true
+            }
+            None => false,
+        }
     }
 
     pub async fn get_next_transaction(&self) -> Option<PendingTransaction> {
@@ -157,18 +309,20 @@ impl ExecutionQueue {
 }
 
 impl SameBlockExecutor {
-    pub fn new(rpc_client: RpcClient) -> Self {
+    pub fn new(rpc_client: NonblockingRpcClient, config: ExecutorConfig) -> Self {
         Self {
-            rpc_client,
+            rpc_client: Arc::new(rpc_client),
             pending_transactions: Arc::new(RwLock::new(HashMap::new())),
-            block_tracker: BlockTracker::new(),
-            execution_queue: ExecutionQueue::new(1000),
+            block_tracker: BlockTracker::new(config.blockhash_refresh_interval),
+            execution_queue: ExecutionQueue::new(config.max_queue_size),
+            config,
         }
     }
 
-    pub async fn initialize(&mut self) -> Result<()> {
+    pub async fn initialize(&self) -> Result<()> {
         // Initialize block tracker
         self.block_tracker.update_current_block(&self.rpc_client).await?;
+        self.block_tracker.update_block_height(&self.rpc_client).await?;
         
         // Start background tasks
         self.start_block_tracker_task().await;
@@ -179,7 +333,7 @@ impl SameBlockExecutor {
 
     async fn start_block_tracker_task(&self) {
         let block_tracker = self.block_tracker.clone();
-        let rpc_client = self.rpc_client.clone();
+        let rpc_client = Arc::clone(&self.rpc_client);
         
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(100));
@@ -187,8 +341,15 @@ impl SameBlockExecutor {
             loop {
                 interval.tick().await;
                 
-                if let Err(e) = block_tracker.update_current_block(&rpc_client).await {
-                    error!("Block tracker error: {}", e);
+                // Fall back to polling the slot only when the stream isn't supplying it
+                if !block_tracker.has_fresh_stream_slot().await {
+                    if let Err(e) = block_tracker.update_current_block(&rpc_client).await {
+                        error!("Block tracker error: {}", e);
+                    }
+                }
+
+                if let Err(e) = block_tracker.update_block_height(&rpc_client).await {
+                    error!("Block height tracker error: {}", e);
                 }
             }
         });
@@ -198,7 +359,8 @@ impl SameBlockExecutor {
         let execution_queue = self.execution_queue.clone();
         let pending_transactions = self.pending_transactions.clone();
         let block_tracker = self.block_tracker.clone();
-        let rpc_client = self.rpc_client.clone();
+        let rpc_client = Arc::clone(&self.rpc_client);
+        let max_retries = self.config.max_retries;
         
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(50));
@@ -207,9 +369,18 @@ impl SameBlockExecutor {
                 interval.tick().await;
                 
                 let current_block = block_tracker.get_current_block().await;
+                let current_block_height = block_tracker.get_current_block_height().await;
                 
                 // Process transactions for current block
                 while let Some(mut pending_tx) = execution_queue.get_next_transaction().await {
+                    // Drop transactions whose blockhash has expired; they can never land
+                    if pending_tx.is_expired(current_block_height) {
+                        warn!("Dropping expired transaction {} (last valid block height {}, current {})",
+                              pending_tx.signature, pending_tx.last_valid_block_height, current_block_height);
+                        pending_transactions.write().await.remove(&pending_tx.signature);
+                        continue;
+                    }
+
                     if pending_tx.target_block <= current_block {
                         // Execute transaction
                         match Self::execute_transaction(&rpc_client, &pending_tx).await {
@@ -225,7 +396,7 @@ impl SameBlockExecutor {
                                 
                                 // Retry logic
                                 pending_tx.retry_count += 1;
-                                if pending_tx.retry_count < 3 {
+                                if pending_tx.retry_count < max_retries {
                                     // Re-queue with higher priority
                                     pending_tx.priority = ExecutionPriority::High;
                                     if let Err(e) = execution_queue.add_transaction(pending_tx).await {
@@ -250,11 +421,12 @@ impl SameBlockExecutor {
     }
 
     async fn execute_transaction(
-        rpc_client: &RpcClient,
+        rpc_client: &NonblockingRpcClient,
         pending_tx: &PendingTransaction,
     ) -> Result<Signature> {
         rpc_client
             .send_and_confirm_transaction(&pending_tx.transaction)
+            .await
             .map_err(|e| SniperError::SolanaClient(format!("Transaction execution failed: {}", e)))
     }
 
@@ -266,17 +438,18 @@ impl SameBlockExecutor {
         target_block_offset: u64,
     ) -> Result<Signature> {
         let current_block = self.block_tracker.get_current_block().await;
+        // The target slot is only used for timing; the target block does not exist yet,
+        // so the transaction is signed with the latest valid blockhash instead.
         let target_block = current_block + target_block_offset;
         
-        // Get fresh blockhash for target block
-        let blockhash = self.block_tracker.get_block_hash(target_block, &self.rpc_client).await?;
+        let (blockhash, last_valid_block_height) =
+            self.block_tracker.get_recent_blockhash(&self.rpc_client).await?;
         
-        // Update transaction with fresh blockhash
+        // Sign transaction with the recent blockhash
         let mut updated_transaction = transaction;
-        updated_transaction.message.recent_blockhash = blockhash;
-        
-        // Sign transaction
-        updated_transaction.sign(signers, blockhash);
+        updated_transaction
+            .try_sign(signers, blockhash)
+            .map_err(|e| SniperError::Transaction(format!("Failed to sign scheduled transaction: {}", e)))?;
         
         let signature = updated_transaction.signatures[0];
         
@@ -287,18 +460,100 @@ impl SameBlockExecutor {
             created_at: Instant::now(),
             priority,
             retry_count: 0,
+            last_valid_block_height,
         };
         
-        // Add to execution queue
-        self.execution_queue.add_transaction(pending_tx).await?;
-        
         // Track pending transaction
-        self.pending_transactions.write().await.insert(signature, pending_tx);
+        self.pending_transactions.write().await.insert(signature, pending_tx.clone());
+        
+        // Add to execution queue, untracking it again if the queue turns it away
+        if let Err(e) = self.execution_queue.add_transaction(pending_tx).await {
+            self.pending_transactions.write().await.remove(&signature);
+            return Err(e);
+        }
         
         info!("Scheduled transaction for block {}: {}", target_block, signature);
         Ok(signature)
     }
 
+    /// Cancels a queued transaction and queues `new_transaction` in its place for the
+    /// next slot. Only a transaction that has not been sent yet can be cancelled; once
+    /// the queue hands it out it may still land, so it is no longer replaceable. The
+    /// replacement must pay a higher compute-unit price than the original, or it is
+    /// rejected and the original stays queued; it is signed here with a recent
+    /// blockhash and keeps at least `High` priority.
+    pub async fn replace_transaction<T: Signer>(
+        &self,
+        old_signature: &Signature,
+        new_transaction: Transaction,
+        signers: &[&T],
+    ) -> Result<Signature> {
+        let (blockhash, last_valid_block_height) =
+            self.block_tracker.get_recent_blockhash(&self.rpc_client).await?;
+
+        let mut replacement_transaction = new_transaction;
+        replacement_transaction
+            .try_sign(signers, blockhash)
+            .map_err(|e| SniperError::Transaction(format!("Failed to sign replacement for {}: {}", old_signature, e)))?;
+        let signature = replacement_transaction.signatures[0];
+        let current_block = self.block_tracker.get_current_block().await;
+
+        // Hold the pending lock across the queue swap so both maps change together
+        let mut pending_transactions = self.pending_transactions.write().await;
+        let old_pending = pending_transactions.get(old_signature).cloned().ok_or_else(|| {
+            SniperError::Transaction(format!("No pending transaction {} to replace", old_signature))
+        })?;
+
+        let old_price = transaction_compute_unit_price(&old_pending.transaction);
+        let new_price = transaction_compute_unit_price(&replacement_transaction);
+        if new_price <= old_price {
+            return Err(SniperError::Transaction(format!(
+                "Replacement for {} pays {} µlamports/CU, not above the original's {}", old_signature, new_price, old_price
+            )));
+        }
+
+        let replacement = PendingTransaction {
+            signature,
+            transaction: replacement_transaction,
+            target_block: current_block + 1,
+            created_at: Instant::now(),
+            priority: old_pending.priority.max(ExecutionPriority::High),
+            retry_count: 0,
+            last_valid_block_height,
+        };
+
+        if !self.execution_queue.replace_transaction(old_signature, replacement.clone()).await {
+            return Err(SniperError::Transaction(format!(
+                "Transaction {} is already in flight and cannot be replaced", old_signature
+            )));
+        }
+
+        pending_transactions.remove(old_signature);
+        pending_transactions.insert(signature, replacement);
+
+        info!("Replaced transaction {} with {} for block {}", old_signature, signature, current_block + 1);
+        Ok(signature)
+    }
+
+    pub async fn current_block(&self) -> u64 {
+        self.block_tracker.get_current_block().await
+    }
+
+    pub async fn record_stream_slot(&self, slot: u64) {
+        self.block_tracker.record_stream_slot(slot).await;
+    }
+
+    /// Re-reads the slot, preferring the stream, for checks made right
+    /// before a send.
+    pub async fn freshest_slot(&self) -> Result<u64> {
+        self.block_tracker.freshest_slot(&self.rpc_client).await
+    }
+
+    /// A recent blockhash and the last block height it is valid at.
+    pub async fn recent_blockhash(&self) -> Result<(Hash, u64)> {
+        self.block_tracker.get_recent_blockhash(&self.rpc_client).await
+    }
+
     pub async fn get_execution_stats(&self) -> ExecutionStats {
         let pending_count = self.pending_transactions.read().await.len();
         let queue_size = self.execution_queue.get_queue_size().await;
@@ -336,9 +591,14 @@ pub struct SameBlockSniper {
 #[derive(Debug, Clone)]
 pub struct SnipeConfig {
     pub max_slippage: f64,
+    /// Most a snipe may pay in priority fees, in lamports.
     pub max_gas_price: u64,
+    pub gas_ceiling_action: GasCeilingAction,
     pub target_block_offset: u64,
     pub priority: ExecutionPriority,
+    /// Re-read the slot right before sending and aim from that, instead of
+    /// trusting the tracker's estimate, which lags when slot polling is slow.
+    pub verify_target_slot: bool,
 }
 
 impl Default for SnipeConfig {
@@ -346,57 +606,313 @@ impl Default for SnipeConfig {
         Self {
             max_slippage: 0.05, // 5%
             max_gas_price: 1000000, // 0.001 SOL
+            gas_ceiling_action: GasCeilingAction::Skip,
             target_block_offset: 1, // Next block
             priority: ExecutionPriority::Critical,
+            verify_target_slot: true,
         }
     }
 }
 
 impl SameBlockSniper {
-    pub fn new(rpc_client: RpcClient, snipe_config: SnipeConfig) -> Self {
+    pub fn new(rpc_client: NonblockingRpcClient, snipe_config: SnipeConfig) -> Self {
+        Self::with_executor_config(rpc_client, snipe_config, ExecutorConfig::default())
+    }
+
+    pub fn with_executor_config(
+        rpc_client: NonblockingRpcClient,
+        snipe_config: SnipeConfig,
+        executor_config: ExecutorConfig,
+    ) -> Self {
         Self {
-            executor: SameBlockExecutor::new(rpc_client),
+            executor: SameBlockExecutor::new(rpc_client, executor_config),
             snipe_config,
         }
     }
 
-    pub async fn initialize(&mut self) -> Result<()> {
+    pub async fn initialize(&self) -> Result<()> {
         self.executor.initialize().await
     }
 
+    pub fn snipe_config(&self) -> &SnipeConfig {
+        &self.snipe_config
+    }
+
+    /// Schedules a PumpFun buy built from `params`, paid for by the buyer,
+    /// unless the window behind the create seen in `seen_slot` has passed.
+    pub async fn snipe_pumpfun_buy<T: Signer>(
+        &self,
+        params: &BuyParams,
+        signers: &[&T],
+        seen_slot: u64,
+    ) -> Result<Option<Signature>> {
+        self.snipe_token(vec![build_buy_instruction(params)], signers, &params.buyer, seen_slot).await
+    }
+
+    /// Schedules `instructions` for the block behind the create seen in
+    /// `seen_slot`. With `verify_target_slot`, the slot is re-read first so a
+    /// stale estimate doesn't hide a missed window; if the read fails, the
+    /// estimate is used as is. Returns `None`, without scheduling anything,
+    /// when the window has already passed.
     pub async fn snipe_token<T: Signer>(
         &self,
         instructions: Vec<Instruction>,
         signers: &[&T],
         fee_payer: &Pubkey,
-    ) -> Result<Signature> {
-        let current_block = self.executor.block_tracker.get_current_block().await;
-        let target_block = current_block + self.snipe_config.target_block_offset;
-        
-        // Get blockhash for target block
-        let blockhash = self.executor.block_tracker.get_block_hash(target_block, &self.executor.rpc_client).await?;
-        
-        // Build transaction
-        let transaction = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(fee_payer),
-            signers,
-            blockhash,
-        );
-        
+        seen_slot: u64,
+    ) -> Result<Option<Signature>> {
+        // Build unsigned transaction; it is signed with a recent blockhash when scheduled
+        let transaction = Transaction::new_with_payer(&finalize_instructions(instructions), Some(fee_payer));
+
+        // The target is counted from the tracker's slot, so bring it up to date first
+        let estimated_slot = self.executor.current_block().await;
+        let freshest_slot = if self.snipe_config.verify_target_slot {
+            self.executor.freshest_slot().await.unwrap_or_else(|e| {
+                warn!("Could not re-read the slot before a snipe, using the estimate: {}", e);
+                estimated_slot
+            })
+        } else {
+            estimated_slot
+        };
+
+        match verify_target_slot(seen_slot, estimated_slot, freshest_slot, self.snipe_config.target_block_offset) {
+            TargetSlot::Missed { current_slot } => {
+                warn!("⌛ Missed the block behind slot {} (now {}), not scheduling the snipe", seen_slot, current_slot);
+                return Ok(None);
+            }
+            TargetSlot::OnTime { target, stale_by } => {
+                if stale_by > 0 {
+                    info!("Slot estimate was {} slot(s) behind, targeting from slot {}", stale_by, target);
+                }
+            }
+        }
+
         // Schedule for same-block execution
         self.executor.schedule_transaction(
             transaction,
             signers,
             self.snipe_config.priority.clone(),
             self.snipe_config.target_block_offset,
-        ).await
+        ).await.map(Some)
+    }
+}
+
+/// What a buy does when its priority fee would exceed `max_gas_price`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasCeilingAction {
+    /// Don't send it at all.
+    Skip,
+    /// Send it at the highest compute unit price the ceiling allows.
+    Clamp,
+}
+
+impl FromStr for GasCeilingAction {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(GasCeilingAction::Skip),
+            "clamp" => Ok(GasCeilingAction::Clamp),
+            other => Err(SniperError::Config(format!(
+                "Unknown gas ceiling action '{}', expected skip or clamp", other
+            ))),
+        }
+    }
+}
+
+/// The compute unit price a transaction of `compute_unit_limit` units may
+/// carry when its priority fee must stay within `max_gas_price` lamports:
+/// `price_micro_lamports` if it fits, else the highest price that does under
+/// `Clamp`, or `None` under `Skip`.
+pub fn gas_capped_price(
+    price_micro_lamports: u64,
+    compute_unit_limit: u32,
+    max_gas_price: u64,
+    action: GasCeilingAction,
+) -> Option<u64> {
+    if priority_fee_lamports(compute_unit_limit, price_micro_lamports) <= max_gas_price {
+        return Some(price_micro_lamports);
+    }
+    match action {
+        GasCeilingAction::Skip => None,
+        GasCeilingAction::Clamp => Some(max_gas_price.saturating_mul(1_000_000) / u64::from(compute_unit_limit.max(1))),
+    }
+}
+
+/// True once the chain has moved more than `target_block_offset` slots past
+/// the slot a create was seen in, i.e. a buy can no longer land right behind it.
+pub fn missed_target_slot(seen_slot: u64, current_slot: u64, target_block_offset: u64) -> bool {
+    current_slot > seen_slot.saturating_add(target_block_offset)
+}
+
+/// Where a same-block buy is aimed, once checked against the freshest slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetSlot {
+    /// Still inside the window; `target` is the slot the buy should land in
+    /// and `stale_by` how many slots the tracker's estimate was behind.
+    OnTime { target: u64, stale_by: u64 },
+    /// The chain is already past the window behind the create.
+    Missed { current_slot: u64 },
+}
+
+/// Recomputes the target of a buy reacting to a create seen in `seen_slot`,
+/// from the tracker's `estimated_slot` and the `freshest_slot` read just
+/// before sending. Whichever is further ahead wins, so a stale estimate can
+/// neither aim the buy at a slot that is gone nor hide a missed window.
+pub fn verify_target_slot(
+    seen_slot: u64,
+    estimated_slot: u64,
+    freshest_slot: u64,
+    target_block_offset: u64,
+) -> TargetSlot {
+    let current_slot = estimated_slot.max(freshest_slot);
+    if missed_target_slot(seen_slot, current_slot, target_block_offset) {
+        return TargetSlot::Missed { current_slot };
+    }
+    TargetSlot::OnTime {
+        target: current_slot.max(seen_slot),
+        stale_by: current_slot - estimated_slot,
+    }
+}
+
+/// Submitted snipes stop being tracked once this many slots behind the
+/// newest target; by then their blockhash has expired and they cannot land.
+const TARGET_SLOT_RETENTION: u64 = 150;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnipeOutcome {
+    /// Sent through Jito; `tip_lamports` is the tip that went with it, 0 if the
+    /// bundle was rejected and the buy fell back to regular RPC.
+    Submitted { signature: Signature, tip_lamports: u64, bundle_status: BundleStatus },
+    /// The target slot passed before the buy was ready; nothing was sent.
+    MissedBlock { seen_slot: u64, current_slot: u64 },
+}
+
+/// Tries to land a buy in the same block as the create it reacts to, directly
+/// behind it. The executor's block tracker supplies the slot the create was
+/// seen in and a warm blockhash; the buy then goes out as a Jito bundle with a
+/// critical-urgency tip transfer so the leader orders it ahead of ordinary buys.
+///
+/// This only works if create→submit fits inside the remainder of the slot
+/// (~400ms, realistically under 100ms end to end). When the tracker shows the
+/// target slot has already passed, the snipe reports `MissedBlock` and the
+/// caller falls back to a regular buy.
+pub struct SameBlockSnipe {
+    sniper: SameBlockSniper,
+    jito: JitoManager,
+    /// Target slot of each submitted snipe, until its landing is recorded.
+    targets: Mutex<HashMap<Signature, u64>>,
+}
+
+impl SameBlockSnipe {
+    pub fn new(sniper: SameBlockSniper, jito: JitoManager) -> Self {
+        Self { sniper, jito, targets: Mutex::new(HashMap::new()) }
+    }
+
+    pub async fn initialize(&self) -> Result<()> {
+        self.sniper.initialize().await
+    }
+
+    /// Starts sampling network congestion from `rpc_endpoint` so snipe tips
+    /// follow it instead of the default level.
+    pub fn start_congestion_monitor(&self, rpc_endpoint: String) {
+        self.jito.start_congestion_monitor(rpc_endpoint);
+    }
+
+    /// The slot the chain is at now, per the slot tracker.
+    pub async fn current_slot(&self) -> u64 {
+        self.sniper.executor.current_block().await
+    }
+
+    /// Feeds a Geyser slot update into the block tracker.
+    pub async fn record_stream_slot(&self, slot: u64) {
+        self.sniper.executor.record_stream_slot(slot).await;
+    }
+
+    /// A blockhash from the tracker's cache, so signing doesn't wait on RPC,
+    /// with the last block height it is valid at.
+    pub async fn recent_blockhash(&self) -> Result<(Hash, u64)> {
+        self.sniper.executor.recent_blockhash().await
+    }
+
+    /// The critical-urgency tip for a `buy_amount_sol` buy, capped to the buy
+    /// size. `None` when it falls under the floor and the buy goes untipped.
+    pub fn bundle_tip(&self, buy_amount_sol: f64) -> Option<u64> {
+        self.jito.calculate_capped_tip(UrgencyLevel::Critical, buy_amount_sol)
+    }
+
+    /// The transfer that pays a `bundle_tip` from `payer`.
+    pub fn tip_transfer(&self, payer: &Pubkey, tip_lamports: u64) -> Instruction {
+        self.jito.tip_transfer(payer, tip_lamports)
+    }
+
+    /// Sends the signed buy through Jito unless the create's slot has passed,
+    /// bundled with `tip`: its signed `tip_transfer` transaction and the tip.
+    /// With `verify_target_slot`, the slot is re-read right before sending so
+    /// a stale estimate neither misaims the buy nor hides a missed window; if
+    /// the read fails, the estimate is used as is. The gas ceiling is the
+    /// caller's to apply, through `gas_capped_price`, before signing.
+    pub async fn snipe(&self, transaction: &Transaction, tip: Option<(&Transaction, u64)>, seen_slot: u64) -> Result<SnipeOutcome> {
+        let config = &self.sniper.snipe_config;
+        let estimated_slot = self.current_slot().await;
+        let freshest_slot = if config.verify_target_slot {
+            self.sniper.executor.freshest_slot().await.unwrap_or_else(|e| {
+                warn!("Could not re-read the slot before a same-block snipe, using the estimate: {}", e);
+                estimated_slot
+            })
+        } else {
+            estimated_slot
+        };
+
+        let target_slot = match verify_target_slot(seen_slot, estimated_slot, freshest_slot, config.target_block_offset) {
+            TargetSlot::Missed { current_slot } => {
+                return Ok(SnipeOutcome::MissedBlock { seen_slot, current_slot });
+            }
+            TargetSlot::OnTime { target, stale_by } => {
+                if stale_by > 0 {
+                    info!("Slot estimate was {} slot(s) behind, retargeted the snipe to slot {}", stale_by, target);
+                }
+                target
+            }
+        };
+
+        let JitoSubmission { signature, tip_lamports, bundle_status } = self.jito
+            .send_bundle_with_tip(transaction, tip)
+            .await?;
+        info!("⚡ Same-block snipe submitted for slot {} (create in {}): {} (bundle {:?})",
+              target_slot, seen_slot, signature, bundle_status);
+
+        let mut targets = self.targets.lock();
+        targets.retain(|_, target| target.saturating_add(TARGET_SLOT_RETENTION) >= target_slot);
+        targets.insert(signature, target_slot);
+        Ok(SnipeOutcome::Submitted { signature, tip_lamports, bundle_status })
+    }
+
+    /// Logs how far a snipe landed from the slot it was aimed at. Returns the
+    /// offset in slots, or `None` if `signature` is not a tracked snipe.
+    pub fn record_landing(&self, signature: &Signature, landed_slot: u64) -> Option<i64> {
+        let target_slot = self.targets.lock().remove(signature)?;
+        let offset = landed_slot as i64 - target_slot as i64;
+        if offset == 0 {
+            info!("🎯 Same-block snipe {} landed in its target slot {}", signature, landed_slot);
+        } else {
+            warn!("🎯 Same-block snipe {} targeted slot {} but landed in {} ({:+} slot(s))",
+                  signature, target_slot, landed_slot, offset);
+        }
+        Some(offset)
+    }
+
+    /// Stops tracking a snipe that never landed.
+    pub fn forget(&self, signature: &Signature) {
+        self.targets.lock().remove(signature);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::signature::Keypair;
 
     #[test]
     fn test_execution_priority() {
@@ -405,10 +921,236 @@ mod tests {
         assert!(ExecutionPriority::Medium > ExecutionPriority::Low);
     }
 
+    #[tokio::test]
+    async fn test_stream_slots_take_over_from_polling() {
+        let tracker = BlockTracker::new(Duration::from_millis(400));
+        assert!(!tracker.has_fresh_stream_slot().await);
+
+        tracker.record_stream_slot(100).await;
+        assert_eq!(tracker.get_current_block().await, 100);
+        assert!(tracker.has_fresh_stream_slot().await);
+
+        // A late, out-of-order update never moves the slot backwards
+        tracker.record_stream_slot(99).await;
+        assert_eq!(tracker.get_current_block().await, 100);
+
+        *tracker.last_stream_slot_at.write().await = Some(Instant::now() - STREAM_SLOT_MAX_AGE);
+        assert!(!tracker.has_fresh_stream_slot().await);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_does_not_leave_the_transaction_pending() {
+        let executor = SameBlockExecutor::new(
+            NonblockingRpcClient::new_mock("succeeds".to_string()),
+            ExecutorConfig { max_queue_size: 0, ..ExecutorConfig::default() },
+        );
+        let payer = Keypair::new();
+        let transaction = Transaction::new_with_payer(&[], Some(&payer.pubkey()));
+
+        let scheduled = executor.schedule_transaction(transaction, &[&payer], ExecutionPriority::High, 0).await;
+        assert!(scheduled.is_err());
+        assert!(executor.pending_transactions.read().await.is_empty());
+    }
+
+    #[test]
+    fn test_execution_queue_pop_order() {
+        let queue = ExecutionQueue::new(10);
+        let priorities = vec![
+            ExecutionPriority::Medium,
+            ExecutionPriority::Critical,
+            ExecutionPriority::Low,
+            ExecutionPriority::High,
+            ExecutionPriority::Medium,
+        ];
+
+        let mut signatures = Vec::new();
+        for priority in priorities {
+            let signature = Signature::new_unique();
+            signatures.push(signature);
+            let pending_tx = PendingTransaction {
+                signature,
+                transaction: Transaction::default(),
+                target_block: 0,
+                created_at: Instant::now(),
+                priority,
+                retry_count: 0,
+                last_valid_block_height: 0,
+            };
+            futures::executor::block_on(queue.add_transaction(pending_tx)).unwrap();
+        }
+
+        let mut popped = Vec::new();
+        while let Some(tx) = futures::executor::block_on(queue.get_next_transaction()) {
+            popped.push((tx.priority, tx.signature));
+        }
+
+        assert_eq!(
+            popped.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>(),
+            vec![
+                ExecutionPriority::Critical,
+                ExecutionPriority::High,
+                ExecutionPriority::Medium,
+                ExecutionPriority::Medium,
+                ExecutionPriority::Low,
+            ]
+        );
+
+        // Equal priorities come out in insertion order
+        assert_eq!(popped[2].1, signatures[0]);
+        assert_eq!(popped[3].1, signatures[4]);
+    }
+
+    fn pending_with_priority(priority: ExecutionPriority) -> PendingTransaction {
+        PendingTransaction {
+            signature: Signature::new_unique(),
+            transaction: Transaction::default(),
+            target_block: 0,
+            created_at: Instant::now(),
+            priority,
+            retry_count: 0,
+            last_valid_block_height: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn test_replace_transaction_under_contention() {
+        let queue = ExecutionQueue::new(10);
+        let original = pending_with_priority(ExecutionPriority::Medium);
+        let old_signature = original.signature;
+        futures::executor::block_on(queue.add_transaction(original)).unwrap();
+        futures::executor::block_on(queue.add_transaction(pending_with_priority(ExecutionPriority::Low))).unwrap();
+
+        // Many tasks race to replace the same transaction; exactly one may win
+        let replacements: Vec<_> = (0..8)
+            .map(|_| pending_with_priority(ExecutionPriority::High))
+            .collect();
+        let results = futures::executor::block_on(futures::future::join_all(
+            replacements.iter().map(|r| queue.replace_transaction(&old_signature, r.clone())),
+        ));
+
+        assert_eq!(results.iter().filter(|replaced| **replaced).count(), 1);
+        assert_eq!(futures::executor::block_on(queue.get_queue_size()), 2);
+        assert!(!futures::executor::block_on(queue.remove_transaction(&old_signature)));
+
+        let next = futures::executor::block_on(queue.get_next_transaction()).unwrap();
+        assert_eq!(next.priority, ExecutionPriority::High);
+    }
+
+    #[tokio::test]
+    async fn test_executor_replacement_must_outbid_and_swaps_atomically() {
+        let executor = SameBlockExecutor::new(
+            NonblockingRpcClient::new_mock("succeeds".to_string()),
+            ExecutorConfig::default(),
+        );
+        let payer = Keypair::new();
+        let priced = |price| Transaction::new_with_payer(
+            &finalize_instructions(vec![ComputeBudgetInstruction::set_compute_unit_price(price)]),
+            Some(&payer.pubkey()),
+        );
+        let old_signature = executor
+            .schedule_transaction(priced(1_000), &[&payer], ExecutionPriority::Medium, 0)
+            .await
+            .unwrap();
+
+        // A replacement that doesn't outbid the original leaves it queued
+        for price in [500, 1_000] {
+            let error = executor.replace_transaction(&old_signature, priced(price), &[&payer]).await.unwrap_err();
+            assert!(error.to_string().contains("not above"));
+        }
+        assert!(executor.pending_transactions.read().await.contains_key(&old_signature));
+        assert_eq!(executor.execution_queue.get_queue_size().await, 1);
+
+        // So does one signed by someone other than its fee payer
+        let stranger = Keypair::new();
+        let error = executor.replace_transaction(&old_signature, priced(2_000), &[&stranger]).await.unwrap_err();
+        assert!(error.to_string().contains("Failed to sign"));
+        assert!(executor.pending_transactions.read().await.contains_key(&old_signature));
+        assert_eq!(executor.execution_queue.get_queue_size().await, 1);
+
+        // Many higher bids race to replace it; exactly one wins, in both maps
+        let signers = [&payer];
+        let replacements = futures::future::join_all(
+            (1..=8).map(|step| executor.replace_transaction(&old_signature, priced(1_000 + step), &signers)),
+        ).await;
+        let winners: Vec<_> = replacements.into_iter().filter_map(|replaced| replaced.ok()).collect();
+        assert_eq!(winners.len(), 1);
+
+        let pending = executor.pending_transactions.read().await;
+        assert_eq!(pending.keys().collect::<Vec<_>>(), vec![&winners[0]]);
+        assert!(transaction_compute_unit_price(&pending[&winners[0]].transaction) > 1_000);
+        drop(pending);
+
+        let queued = executor.execution_queue.get_next_transaction().await.unwrap();
+        assert_eq!(queued.signature, winners[0]);
+        assert_eq!(queued.priority, ExecutionPriority::High);
+        assert!(executor.execution_queue.get_next_transaction().await.is_none());
+    }
+
+    #[test]
+    fn test_pending_transaction_expiry() {
+        let pending_tx = PendingTransaction {
+            signature: Signature::new_unique(),
+            transaction: Transaction::default(),
+            target_block: 0,
+            created_at: Instant::now(),
+            priority: ExecutionPriority::High,
+            retry_count: 0,
+            last_valid_block_height: 150,
+        };
+
+        assert!(!pending_tx.is_expired(150));
+        assert!(pending_tx.is_expired(151));
+    }
+
     #[test]
     fn test_snipe_config() {
         let config = SnipeConfig::default();
         assert_eq!(config.max_slippage, 0.05);
         assert_eq!(config.target_block_offset, 1);
     }
+
+    #[test]
+    fn test_verify_target_slot_corrects_a_stale_estimate() {
+        // Tracker is current: aim at the slot the chain is in
+        assert_eq!(verify_target_slot(100, 100, 100, 1), TargetSlot::OnTime { target: 100, stale_by: 0 });
+
+        // Tracker lagged a slot behind: retarget, still inside the window
+        assert_eq!(verify_target_slot(100, 100, 101, 1), TargetSlot::OnTime { target: 101, stale_by: 1 });
+
+        // Stale estimate says on time, but the chain has already moved past the window
+        assert!(!missed_target_slot(100, 100, 1));
+        assert_eq!(verify_target_slot(100, 100, 103, 1), TargetSlot::Missed { current_slot: 103 });
+
+        // A lagging fresh read never pulls the estimate backwards
+        assert_eq!(verify_target_slot(100, 101, 100, 1), TargetSlot::OnTime { target: 101, stale_by: 0 });
+        assert_eq!(verify_target_slot(100, 102, 100, 1), TargetSlot::Missed { current_slot: 102 });
+    }
+
+    #[test]
+    fn test_gas_capped_price() {
+        // 400k CU at 2 lamports/CU pays 800k lamports, inside a 1M ceiling
+        assert_eq!(gas_capped_price(2_000_000, 400_000, 1_000_000, GasCeilingAction::Skip), Some(2_000_000));
+        assert_eq!(gas_capped_price(2_500_000, 400_000, 1_000_000, GasCeilingAction::Skip), Some(2_500_000));
+
+        // One µlamport/CU more pays a lamport over
+        assert_eq!(gas_capped_price(2_500_001, 400_000, 1_000_000, GasCeilingAction::Skip), None);
+        let clamped = gas_capped_price(4_000_000, 400_000, 1_000_000, GasCeilingAction::Clamp).unwrap();
+        assert_eq!(clamped, 2_500_000);
+        assert!(priority_fee_lamports(400_000, clamped) <= 1_000_000);
+
+        assert_eq!("Clamp".parse::<GasCeilingAction>().unwrap(), GasCeilingAction::Clamp);
+        assert!("raise".parse::<GasCeilingAction>().is_err());
+    }
+
+    #[test]
+    fn test_missed_target_slot() {
+        // Same block only: any slot change is a miss
+        assert!(!missed_target_slot(100, 100, 0));
+        assert!(missed_target_slot(100, 101, 0));
+
+        // Allowing one slot of lag
+        assert!(!missed_target_slot(100, 101, 1));
+        assert!(missed_target_slot(100, 102, 1));
+        assert!(!missed_target_slot(u64::MAX, u64::MAX, 1));
+    }
 }
@@ -1,9 +1,19 @@
+use crate::bounded_map::{BoundedMap, DEFAULT_MAX_TRACKED_TOKENS};
 use crate::error::{Result, SniperError};
+use crate::social_links::SocialLinks;
+use parking_lot::Mutex;
+use regex::{Regex, RegexBuilder};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::program_pack::Pack;
+use spl_token::state::Mint;
 use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use tracing::{info, warn, error};
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenMetadata {
@@ -16,19 +26,52 @@ pub struct TokenMetadata {
     pub creation_time: Instant,
     pub initial_supply: u64,
     pub decimals: u8,
+    /// Read from the mint account; `None` when it was not fetched.
+    #[serde(default)]
+    pub authorities: Option<MintAuthorities>,
+    /// Social links from the off-chain metadata; `None` when it was not fetched.
+    #[serde(default)]
+    pub socials: Option<SocialLinks>,
 }
 
-#[derive(Debug, Clone)]
+/// Who can still mint or freeze a token. Both are `None` once renounced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MintAuthorities {
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl MintAuthorities {
+    /// Reads the authorities from raw mint account data, under either SPL
+    /// Token or Token-2022, whose mints lead with the same base layout.
+    pub fn from_mint_data(data: &[u8]) -> Result<Self> {
+        let base = data
+            .get(..Mint::LEN)
+            .ok_or_else(|| SniperError::Transaction(format!("Invalid mint account: {} bytes", data.len())))?;
+        let mint = Mint::unpack(base)
+            .map_err(|e| SniperError::Transaction(format!("Invalid mint account: {}", e)))?;
+        Ok(Self {
+            mint_authority: mint.mint_authority.into(),
+            freeze_authority: mint.freeze_authority.into(),
+        })
+    }
+
+    pub fn is_renounced(&self) -> bool {
+        self.mint_authority.is_none() && self.freeze_authority.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScamAnalysis {
     pub mint: Pubkey,
     pub scam_score: f64, // 0.0 = safe, 1.0 = definitely scam
     pub risk_factors: Vec<RiskFactor>,
     pub recommendation: ScamRecommendation,
     pub confidence: f64,
-    pub analysis_time: Instant,
+    pub analysis_time: i64, // Unix timestamp (seconds)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskFactor {
     pub factor_type: RiskFactorType,
     pub severity: f64, // 0.0 to 1.0
@@ -36,7 +79,7 @@ pub struct RiskFactor {
     pub evidence: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RiskFactorType {
     SuspiciousName,
     DuplicateMetadata,
@@ -48,9 +91,11 @@ pub enum RiskFactorType {
     MetadataAnomaly,
     SocialMediaRedFlags,
     CodeAnalysis,
+    HolderConcentration,
 }
 
-#[derive(Debug, Clone)]
+// Variant order defines the ordering: Safe < Caution < HighRisk < Avoid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ScamRecommendation {
     Safe,
     Caution,
@@ -58,11 +103,156 @@ pub enum ScamRecommendation {
     Avoid,
 }
 
+impl ScamRecommendation {
+    pub fn from_score(scam_score: f64) -> Self {
+        match scam_score {
+            s if s < 0.2 => ScamRecommendation::Safe,
+            s if s < 0.5 => ScamRecommendation::Caution,
+            s if s < 0.8 => ScamRecommendation::HighRisk,
+            _ => ScamRecommendation::Avoid,
+        }
+    }
+}
+
+impl std::str::FromStr for ScamRecommendation {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "safe" => Ok(ScamRecommendation::Safe),
+            "caution" => Ok(ScamRecommendation::Caution),
+            "high_risk" | "highrisk" => Ok(ScamRecommendation::HighRisk),
+            "avoid" => Ok(ScamRecommendation::Avoid),
+            other => Err(SniperError::Config(format!(
+                "Unknown scam recommendation '{}', expected safe, caution, high_risk or avoid", other
+            ))),
+        }
+    }
+}
+
+/// A user-supplied name/symbol pattern. Matching is case-insensitive for both kinds.
+#[derive(Debug, Clone)]
+pub enum DenyPattern {
+    Substring(String, f64),
+    Regex(Regex, f64),
+}
+
+impl DenyPattern {
+    pub fn substring(pattern: &str, score: f64) -> Self {
+        DenyPattern::Substring(pattern.to_lowercase(), score)
+    }
+
+    pub fn regex(pattern: &str, score: f64) -> Result<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| SniperError::Config(format!("Invalid deny pattern regex '{}': {}", pattern, e)))?;
+        Ok(DenyPattern::Regex(regex, score))
+    }
+
+    /// Parses `pattern=score`; a `re:` prefix marks the pattern as a regex.
+    pub fn parse(entry: &str) -> Result<Self> {
+        let (pattern, score) = entry.rsplit_once('=')
+            .ok_or_else(|| SniperError::Config(format!("Deny pattern '{}' must be pattern=score", entry)))?;
+        let score: f64 = score.trim().parse()
+            .map_err(|_| SniperError::Config(format!("Invalid score in deny pattern '{}'", entry)))?;
+
+        match pattern.trim().strip_prefix("re:") {
+            Some(regex) => Self::regex(regex, score),
+            None => Ok(Self::substring(pattern.trim(), score)),
+        }
+    }
+
+    fn score_if_matches(&self, lowercase_text: &str, text: &str) -> Option<f64> {
+        match self {
+            DenyPattern::Substring(pattern, score) => lowercase_text.contains(pattern.as_str()).then_some(*score),
+            DenyPattern::Regex(regex, score) => regex.is_match(text).then_some(*score),
+        }
+    }
+}
+
+/// Maps Cyrillic and Greek letters that render like Latin ones to their ASCII look-alike.
+fn fold_confusable(c: char) -> Option<char> {
+    let folded = match c {
+        // Cyrillic
+        'а' | 'А' => 'a', 'в' | 'В' => 'b', 'с' | 'С' => 'c', 'ԁ' => 'd',
+        'е' | 'Е' => 'e', 'һ' | 'Һ' | 'н' | 'Н' => 'h', 'і' | 'І' | 'ӏ' => 'i',
+        'ј' | 'Ј' => 'j', 'к' | 'К' => 'k', 'м' | 'М' => 'm', 'о' | 'О' => 'o',
+        'р' | 'Р' => 'p', 'ԛ' => 'q', 'ѕ' | 'Ѕ' => 's', 'т' | 'Т' => 't',
+        'у' | 'У' => 'y', 'х' | 'Х' => 'x', 'ԝ' => 'w',
+        // Greek
+        'α' | 'Α' => 'a', 'Β' | 'β' => 'b', 'ε' | 'Ε' => 'e', 'Ζ' => 'z',
+        'Η' => 'h', 'ι' | 'Ι' => 'i', 'κ' | 'Κ' => 'k', 'Μ' => 'm',
+        'ν' | 'Ν' => 'n', 'ο' | 'Ο' => 'o', 'ρ' | 'Ρ' => 'p', 'τ' | 'Τ' => 't',
+        'υ' | 'Υ' => 'u', 'χ' | 'Χ' => 'x',
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// Normalizes text for pattern matching: NFKC (folds full-width and stylized
+/// letters), then maps confusable Cyrillic/Greek letters to ASCII and lowercases.
+pub fn normalize_for_matching(text: &str) -> String {
+    text.nfkc()
+        .map(|c| fold_confusable(c).unwrap_or(c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn is_latin_letter(c: char) -> bool {
+    c.is_ascii_alphabetic()
+}
+
+fn is_cyrillic_or_greek(c: char) -> bool {
+    matches!(c as u32, 0x0370..=0x03FF | 0x0400..=0x052F)
+}
+
+/// Flags words that mix Latin with Cyrillic/Greek letters, or that are spelled
+/// entirely in confusable letters so they read as plain Latin. Words in genuine
+/// Cyrillic or Greek contain letters with no Latin twin and are not flagged.
+pub fn has_confusable_characters(text: &str) -> bool {
+    text.nfkc().collect::<String>().split_whitespace().any(|word| {
+        let has_latin = word.chars().any(is_latin_letter);
+        let has_foreign = word.chars().any(is_cyrillic_or_greek);
+        if has_latin && has_foreign {
+            return true;
+        }
+
+        has_foreign
+            && word.chars()
+                .filter(|c| c.is_alphabetic())
+                .all(|c| is_latin_letter(c) || fold_confusable(c).is_some())
+    })
+}
+
+/// Parses deny patterns separated by `;` or newlines. Blank entries and `#` comments are skipped.
+pub fn parse_deny_patterns(text: &str) -> Result<Vec<DenyPattern>> {
+    text.split(|c: char| c == ';' || c == '\n')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !entry.starts_with('#'))
+        .map(DenyPattern::parse)
+        .collect()
+}
+
+/// Scores a token's social links: none at all is a stronger signal than links
+/// that only lead to throwaway hosts.
+fn check_social_links(socials: &SocialLinks) -> Option<(f64, Vec<String>)> {
+    if socials.is_empty() {
+        return Some((0.6, vec!["No twitter, telegram or website in metadata".to_string()]));
+    }
+    let throwaway = socials.throwaway_links();
+    (!throwaway.is_empty()).then(|| (0.4, throwaway.iter().map(|link| format!("Throwaway link: {}", link)).collect()))
+}
+
 pub struct ScamDetector {
     known_scam_patterns: HashMap<String, f64>,
+    custom_patterns: Vec<DenyPattern>,
     suspicious_creators: std::collections::HashSet<Pubkey>,
-    analyzed_tokens: HashMap<Pubkey, ScamAnalysis>,
+    /// Locked only to record or read results, so analyses can run concurrently
+    analyzed_tokens: Mutex<BoundedMap<Pubkey, ScamAnalysis>>,
     ml_model: MLModel,
+    audit_log_path: Option<PathBuf>,
+    max_top_holder_percentage: f64,
 }
 
 impl ScamDetector {
@@ -81,13 +271,40 @@ impl ScamDetector {
 
         Self {
             known_scam_patterns: known_patterns,
+            custom_patterns: Vec::new(),
             suspicious_creators: std::collections::HashSet::new(),
-            analyzed_tokens: HashMap::new(),
+            analyzed_tokens: Mutex::new(BoundedMap::new(DEFAULT_MAX_TRACKED_TOKENS)),
             ml_model: MLModel::new(),
+            audit_log_path: None,
+            max_top_holder_percentage: 50.0,
         }
     }
 
-    pub async fn analyze_token(&mut self, metadata: &TokenMetadata, trading_data: &TradingData) -> ScamAnalysis {
+    /// Adds operator-supplied patterns on top of the built-in scam words.
+    pub fn with_deny_patterns(mut self, patterns: Vec<DenyPattern>) -> Self {
+        self.custom_patterns.extend(patterns);
+        self
+    }
+
+    /// Flags tokens whose top holders own more than `percentage` of supply.
+    pub fn with_max_top_holder_percentage(mut self, percentage: f64) -> Self {
+        self.max_top_holder_percentage = percentage;
+        self
+    }
+
+    /// Caps how many past analyses are kept; the oldest are dropped first.
+    pub fn with_max_tracked_tokens(mut self, max_tokens: usize) -> Self {
+        self.analyzed_tokens.get_mut().set_capacity(max_tokens);
+        self
+    }
+
+    /// Appends every analysis as one JSON line to `path`.
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log_path = Some(path.into());
+        self
+    }
+
+    pub async fn analyze_token(&self, metadata: &TokenMetadata, trading_data: &TradingData) -> ScamAnalysis {
         let mut risk_factors = Vec::new();
         let mut total_score = 0.0;
         let mut confidence = 0.0;
@@ -104,6 +321,21 @@ impl ScamDetector {
             confidence += 0.2;
         }
 
+        // Check for look-alike characters used to disguise names
+        if has_confusable_characters(&metadata.name) || has_confusable_characters(&metadata.symbol) {
+            risk_factors.push(RiskFactor {
+                factor_type: RiskFactorType::SuspiciousName,
+                severity: 0.7,
+                description: "Mixed-script or confusable characters in name or symbol".to_string(),
+                evidence: vec![
+                    format!("Name: {} (reads as {})", metadata.name, normalize_for_matching(&metadata.name)),
+                    format!("Symbol: {} (reads as {})", metadata.symbol, normalize_for_matching(&metadata.symbol)),
+                ],
+            });
+            total_score += 0.7 * 0.2;
+            confidence += 0.2;
+        }
+
         // Check creator reputation
         if self.suspicious_creators.contains(&metadata.creator) {
             risk_factors.push(RiskFactor {
@@ -116,6 +348,30 @@ impl ScamDetector {
             confidence += 0.3;
         }
 
+        // A live freeze authority lets the creator stop holders from selling
+        if let Some(freeze_authority) = metadata.authorities.and_then(|a| a.freeze_authority) {
+            risk_factors.push(RiskFactor {
+                factor_type: RiskFactorType::HoneypotPattern,
+                severity: 0.9,
+                description: "Freeze authority not renounced".to_string(),
+                evidence: vec![format!("Freeze authority: {}", freeze_authority)],
+            });
+            total_score += 0.9 * 0.3;
+            confidence += 0.3;
+        }
+
+        // A live mint authority lets the creator inflate the supply
+        if let Some(mint_authority) = metadata.authorities.and_then(|a| a.mint_authority) {
+            risk_factors.push(RiskFactor {
+                factor_type: RiskFactorType::RugPullIndicator,
+                severity: 0.8,
+                description: "Mint authority not renounced".to_string(),
+                evidence: vec![format!("Mint authority: {}", mint_authority)],
+            });
+            total_score += 0.8 * 0.25;
+            confidence += 0.25;
+        }
+
         // Check liquidity patterns
         if let Some(score) = self.check_liquidity_patterns(trading_data) {
             risk_factors.push(RiskFactor {
@@ -140,6 +396,30 @@ impl ScamDetector {
             confidence += 0.2;
         }
 
+        // Check holder concentration
+        if let Some(score) = self.check_holder_concentration(trading_data) {
+            risk_factors.push(RiskFactor {
+                factor_type: RiskFactorType::HolderConcentration,
+                severity: score,
+                description: "Supply concentrated in a few wallets".to_string(),
+                evidence: vec![format!("Top holders: {:.1}% of supply", trading_data.top_holder_percentage.unwrap_or(0.0))],
+            });
+            total_score += score * 0.25;
+            confidence += 0.25;
+        }
+
+        // Linkless launches, or links on throwaway hosts, skew towards scams
+        if let Some((score, evidence)) = metadata.socials.as_ref().and_then(check_social_links) {
+            risk_factors.push(RiskFactor {
+                factor_type: RiskFactorType::SocialMediaRedFlags,
+                severity: score,
+                description: "Missing or throwaway social links".to_string(),
+                evidence,
+            });
+            total_score += score * 0.15;
+            confidence += 0.15;
+        }
+
         // Check metadata anomalies
         if let Some(score) = self.check_metadata_anomalies(metadata) {
             risk_factors.push(RiskFactor {
@@ -169,12 +449,7 @@ impl ScamDetector {
         let scam_score = total_score.min(1.0);
         confidence = confidence.min(1.0);
 
-        let recommendation = match scam_score {
-            s if s < 0.2 => ScamRecommendation::Safe,
-            s if s < 0.5 => ScamRecommendation::Caution,
-            s if s < 0.8 => ScamRecommendation::HighRisk,
-            _ => ScamRecommendation::Avoid,
-        };
+        let recommendation = ScamRecommendation::from_score(scam_score);
 
         let analysis = ScamAnalysis {
             mint: metadata.mint,
@@ -182,23 +457,61 @@ impl ScamDetector {
             risk_factors,
             recommendation,
             confidence,
-            analysis_time: Instant::now(),
+            analysis_time: chrono::Utc::now().timestamp(),
         };
 
-        self.analyzed_tokens.insert(metadata.mint, analysis.clone());
+        if let Some(path) = &self.audit_log_path {
+            if let Err(e) = Self::append_audit_record(path, &analysis) {
+                warn!("Failed to write scam analysis audit record for {}: {}", metadata.mint, e);
+            }
+        }
+
+        self.analyzed_tokens.lock().insert(metadata.mint, analysis.clone());
         analysis
     }
 
-    fn check_name_patterns(&self, name: &str, symbol: &str) -> Option<f64> {
-        let text = format!("{} {}", name.to_lowercase(), symbol.to_lowercase());
-        
-        for (pattern, score) in &self.known_scam_patterns {
-            if text.contains(pattern) {
-                return Some(*score);
+    fn append_audit_record(path: &Path, analysis: &ScamAnalysis) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let line = serde_json::to_string(analysis)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Reads every recorded analysis for `mint` from a JSONL audit log, oldest first.
+    pub fn query_audit_log(path: &Path, mint: &Pubkey) -> Result<Vec<ScamAnalysis>> {
+        let file = std::fs::File::open(path)?;
+        let mut analyses = Vec::new();
+
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let analysis: ScamAnalysis = serde_json::from_str(&line)?;
+            if analysis.mint == *mint {
+                analyses.push(analysis);
             }
         }
+
+        Ok(analyses)
+    }
+
+    fn check_name_patterns(&self, name: &str, symbol: &str) -> Option<f64> {
+        let raw_text = format!("{} {}", name, symbol);
+        // Substring patterns match the normalized form so homoglyph spellings are caught;
+        // regex patterns see the raw text so they can target the look-alikes themselves
+        let text = normalize_for_matching(&raw_text);
         
-        None
+        // Report the most severe match across built-in and custom patterns
+        let built_in = self.known_scam_patterns
+            .iter()
+            .filter(|(pattern, _)| text.contains(pattern.as_str()))
+            .map(|(_, score)| *score);
+        let custom = self.custom_patterns
+            .iter()
+            .filter_map(|pattern| pattern.score_if_matches(&text, &raw_text));
+
+        built_in.chain(custom).reduce(f64::max)
     }
 
     fn check_liquidity_patterns(&self, trading_data: &TradingData) -> Option<f64> {
@@ -227,6 +540,17 @@ impl ScamDetector {
         None
     }
 
+    fn check_holder_concentration(&self, trading_data: &TradingData) -> Option<f64> {
+        let percentage = trading_data.top_holder_percentage?;
+        if percentage <= self.max_top_holder_percentage {
+            return None;
+        }
+
+        // Scale from 0.5 at the threshold up to 1.0 when the top holders own everything
+        let excess = (percentage - self.max_top_holder_percentage) / (100.0 - self.max_top_holder_percentage).max(f64::EPSILON);
+        Some((0.5 + excess.min(1.0) * 0.5).min(1.0))
+    }
+
     fn check_metadata_anomalies(&self, metadata: &TokenMetadata) -> Option<f64> {
         // Check for suspicious metadata
         if metadata.description.len() < 10 {
@@ -249,12 +573,12 @@ impl ScamDetector {
         info!("Added suspicious creator: {}", creator);
     }
 
-    pub fn get_analysis(&self, mint: &Pubkey) -> Option<&ScamAnalysis> {
-        self.analyzed_tokens.get(mint)
+    pub fn get_analysis(&self, mint: &Pubkey) -> Option<ScamAnalysis> {
+        self.analyzed_tokens.lock().get(mint).cloned()
     }
 
     pub fn is_token_safe(&self, mint: &Pubkey) -> bool {
-        if let Some(analysis) = self.analyzed_tokens.get(mint) {
+        if let Some(analysis) = self.analyzed_tokens.lock().get(mint) {
             matches!(analysis.recommendation, ScamRecommendation::Safe | ScamRecommendation::Caution)
         } else {
             true // Assume safe if not analyzed
@@ -262,7 +586,7 @@ impl ScamDetector {
     }
 
     pub fn get_scam_score(&self, mint: &Pubkey) -> Option<f64> {
-        self.analyzed_tokens.get(mint).map(|a| a.scam_score)
+        self.analyzed_tokens.lock().get(mint).map(|a| a.scam_score)
     }
 }
 
@@ -273,6 +597,7 @@ pub struct TradingData {
     pub volume_24h: f64,
     pub price_change_24h: f64,
     pub holder_count: u32,
+    pub top_holder_percentage: Option<f64>,
     pub transaction_count: u32,
     pub market_cap: f64,
     pub last_update: Instant,
@@ -393,7 +718,7 @@ mod tests {
 
     #[test]
     fn test_scam_detector() {
-        let mut detector = ScamDetector::new();
+        let detector = ScamDetector::new();
         
         let metadata = TokenMetadata {
             mint: Pubkey::new_unique(),
@@ -405,6 +730,8 @@ mod tests {
             creation_time: Instant::now(),
             initial_supply: 1000000,
             decimals: 6,
+            authorities: None,
+            socials: None,
         };
         
         let trading_data = TradingData {
@@ -413,6 +740,7 @@ mod tests {
             volume_24h: 5000.0,
             price_change_24h: 10.0,
             holder_count: 50,
+            top_holder_percentage: None,
             transaction_count: 100,
             market_cap: 10000.0,
             last_update: Instant::now(),
@@ -422,6 +750,229 @@ mod tests {
         assert!(analysis.scam_score >= 0.0 && analysis.scam_score <= 1.0);
     }
 
+    #[test]
+    fn test_audit_log_round_trip() {
+        let path = std::env::temp_dir().join(format!("scam_audit_{}.jsonl", Pubkey::new_unique()));
+        let detector = ScamDetector::new().with_audit_log(&path);
+
+        let metadata = TokenMetadata {
+            mint: Pubkey::new_unique(),
+            name: "Fake Token".to_string(),
+            symbol: "FAKE".to_string(),
+            description: "Short".to_string(),
+            image_uri: "https://example.com/image.png".to_string(),
+            creator: Pubkey::new_unique(),
+            creation_time: Instant::now(),
+            initial_supply: 1000000,
+            decimals: 6,
+            authorities: None,
+            socials: None,
+        };
+
+        let trading_data = TradingData {
+            mint: metadata.mint,
+            liquidity: 1000.0,
+            volume_24h: 5000.0,
+            price_change_24h: 10.0,
+            holder_count: 50,
+            top_holder_percentage: None,
+            transaction_count: 100,
+            market_cap: 10000.0,
+            last_update: Instant::now(),
+        };
+
+        let analysis = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+
+        let records = ScamDetector::query_audit_log(&path, &metadata.mint).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].scam_score, analysis.scam_score);
+        assert!(ScamDetector::query_audit_log(&path, &Pubkey::new_unique()).unwrap().is_empty());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_custom_deny_patterns() {
+        // Flags names mixing Cyrillic look-alike letters into Latin text
+        let patterns = parse_deny_patterns("re:[\\p{Cyrillic}]=0.85; # comment\nsolana2=0.4").unwrap();
+        let detector = ScamDetector::new().with_deny_patterns(patterns);
+
+        assert_eq!(detector.check_name_patterns("S\u{043e}l\u{0430}n\u{0430}", "SOL"), Some(0.85));
+        assert_eq!(detector.check_name_patterns("SOLANA2 Inu", "SOL2"), Some(0.4));
+        assert_eq!(detector.check_name_patterns("Solana", "SOL"), None);
+        // Built-ins still apply and the highest score wins
+        assert_eq!(detector.check_name_patterns("Scam solana2", "X"), Some(0.95));
+
+        assert!(DenyPattern::parse("re:[unclosed=0.5").is_err());
+        assert!(DenyPattern::parse("noscore").is_err());
+    }
+
+    #[test]
+    fn test_homoglyph_names_are_normalized() {
+        let detector = ScamDetector::new();
+
+        // Cyrillic ѕ, с, а
+        assert_eq!(normalize_for_matching("\u{0455}\u{0441}\u{0430}m"), "scam");
+        assert_eq!(detector.check_name_patterns("\u{0455}\u{0441}\u{0430}m", "X"), Some(0.95));
+        // Greek ο in "honeypot", and full-width "Ｆａｋｅ"
+        assert_eq!(detector.check_name_patterns("Honeyp\u{03bf}t", "X"), Some(0.95));
+        assert_eq!(detector.check_name_patterns("\u{FF26}\u{FF41}\u{FF4B}\u{FF45}", "X"), Some(0.9));
+    }
+
+    #[test]
+    fn test_confusable_character_detection() {
+        assert!(has_confusable_characters("S\u{043e}lana"));          // mixed Latin/Cyrillic
+        assert!(has_confusable_characters("\u{0455}\u{0441}\u{0430}m")); // disguised Latin word
+        assert!(has_confusable_characters("\u{0420}\u{0415}\u{0420}\u{0415}")); // all-Cyrillic "PEPE"
+        assert!(!has_confusable_characters("Solana Doge"));
+        assert!(!has_confusable_characters("\u{0414}\u{043e}\u{0433}\u{0435}")); // genuine Cyrillic "Доге"
+    }
+
+    #[test]
+    fn test_holder_concentration_factor() {
+        let detector = ScamDetector::new().with_max_top_holder_percentage(40.0);
+        let mut trading_data = TradingData {
+            mint: Pubkey::new_unique(),
+            liquidity: 1000.0,
+            volume_24h: 5000.0,
+            price_change_24h: 10.0,
+            holder_count: 50,
+            top_holder_percentage: Some(40.0),
+            transaction_count: 100,
+            market_cap: 10000.0,
+            last_update: Instant::now(),
+        };
+
+        assert_eq!(detector.check_holder_concentration(&trading_data), None);
+        trading_data.top_holder_percentage = Some(70.0);
+        assert_eq!(detector.check_holder_concentration(&trading_data), Some(0.75));
+        trading_data.top_holder_percentage = Some(100.0);
+        assert_eq!(detector.check_holder_concentration(&trading_data), Some(1.0));
+        trading_data.top_holder_percentage = None;
+        assert_eq!(detector.check_holder_concentration(&trading_data), None);
+    }
+
+    fn mint_data(mint_authority: Option<Pubkey>, freeze_authority: Option<Pubkey>) -> Vec<u8> {
+        let mint = Mint {
+            mint_authority: mint_authority.into(),
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: freeze_authority.into(),
+        };
+        let mut data = vec![0u8; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_mint_authority_factors() {
+        let authority = Pubkey::new_unique();
+        let renounced = MintAuthorities::from_mint_data(&mint_data(None, None)).unwrap();
+        assert!(renounced.is_renounced());
+        let live = MintAuthorities::from_mint_data(&mint_data(Some(authority), Some(authority))).unwrap();
+        assert_eq!(live, MintAuthorities { mint_authority: Some(authority), freeze_authority: Some(authority) });
+        assert!(MintAuthorities::from_mint_data(&[0u8; 10]).is_err());
+
+        // A Token-2022 mint pads to the account size and appends its extensions
+        let mut extended = mint_data(Some(authority), None);
+        extended.resize(165, 0);
+        extended.extend_from_slice(&[1, 0, 0, 0, 0]);
+        let extended = MintAuthorities::from_mint_data(&extended).unwrap();
+        assert_eq!(extended, MintAuthorities { mint_authority: Some(authority), freeze_authority: None });
+
+        let mut metadata = TokenMetadata {
+            mint: Pubkey::new_unique(),
+            name: "Solana Cat".to_string(),
+            symbol: "SCAT".to_string(),
+            description: "A cat on Solana with a long enough description".to_string(),
+            image_uri: "https://example.com/image.png".to_string(),
+            creator: Pubkey::new_unique(),
+            creation_time: Instant::now(),
+            initial_supply: 1000000,
+            decimals: 6,
+            authorities: Some(renounced),
+            socials: None,
+        };
+        let trading_data = TradingData {
+            mint: metadata.mint,
+            liquidity: 1000.0,
+            volume_24h: 5000.0,
+            price_change_24h: 10.0,
+            holder_count: 50,
+            top_holder_percentage: None,
+            transaction_count: 100,
+            market_cap: 10000.0,
+            last_update: Instant::now(),
+        };
+
+        let detector = ScamDetector::new();
+        let clean = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        assert!(!clean.risk_factors.iter().any(|f| matches!(
+            f.factor_type, RiskFactorType::HoneypotPattern | RiskFactorType::RugPullIndicator
+        )));
+
+        metadata.authorities = Some(live);
+        let flagged = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        assert!(flagged.risk_factors.iter().any(|f| matches!(f.factor_type, RiskFactorType::HoneypotPattern)));
+        assert!(flagged.risk_factors.iter().any(|f| matches!(f.factor_type, RiskFactorType::RugPullIndicator)));
+        assert!(flagged.scam_score > clean.scam_score);
+    }
+
+    #[test]
+    fn test_social_link_factors() {
+        let mut metadata = TokenMetadata {
+            mint: Pubkey::new_unique(),
+            name: "Solana Cat".to_string(),
+            symbol: "SCAT".to_string(),
+            description: "A cat on Solana with a long enough description".to_string(),
+            image_uri: "https://example.com/meta.json".to_string(),
+            creator: Pubkey::new_unique(),
+            creation_time: Instant::now(),
+            initial_supply: 1000000,
+            decimals: 6,
+            authorities: None,
+            socials: Some(SocialLinks {
+                twitter: Some("https://x.com/solanacat".to_string()),
+                telegram: Some("https://t.me/solanacat".to_string()),
+                website: None,
+            }),
+        };
+        let trading_data = TradingData {
+            mint: metadata.mint,
+            liquidity: 1000.0,
+            volume_24h: 5000.0,
+            price_change_24h: 10.0,
+            holder_count: 50,
+            top_holder_percentage: None,
+            transaction_count: 100,
+            market_cap: 10000.0,
+            last_update: Instant::now(),
+        };
+        let social_flag = |analysis: &ScamAnalysis| {
+            analysis.risk_factors.iter().find(|f| matches!(f.factor_type, RiskFactorType::SocialMediaRedFlags)).map(|f| f.severity)
+        };
+
+        let detector = ScamDetector::new();
+        let linked = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        assert_eq!(social_flag(&linked), None);
+
+        // Unfetched metadata is not held against the token
+        metadata.socials = None;
+        let unfetched = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        assert_eq!(social_flag(&unfetched), None);
+
+        metadata.socials = Some(SocialLinks { website: Some("https://solanacat.carrd.co".to_string()), ..SocialLinks::default() });
+        let throwaway = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        assert_eq!(social_flag(&throwaway), Some(0.4));
+
+        metadata.socials = Some(SocialLinks::default());
+        let linkless = futures::executor::block_on(detector.analyze_token(&metadata, &trading_data));
+        assert_eq!(social_flag(&linkless), Some(0.6));
+        assert!(linkless.scam_score > linked.scam_score);
+    }
+
     #[test]
     fn test_honeypot_detector() {
         let detector = HoneypotDetector::new();
@@ -436,6 +987,8 @@ mod tests {
             creation_time: Instant::now(),
             initial_supply: 1000000,
             decimals: 6,
+            authorities: None,
+            socials: None,
         };
         
         let trading_data = TradingData {
@@ -444,6 +997,7 @@ mod tests {
             volume_24h: 1000.0,
             price_change_24h: 5.0,
             holder_count: 2,
+            top_holder_percentage: None,
             transaction_count: 10,
             market_cap: 5000.0,
             last_update: Instant::now(),
@@ -0,0 +1,167 @@
+use crate::scam_detection::{ScamAnalysis, ScamDetector, ScamRecommendation, TokenMetadata, TradingData};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct ScamGuardConfig {
+    pub recheck_interval: Duration,
+    pub exit_at: ScamRecommendation, // Exit once a held token scores this or worse
+}
+
+impl Default for ScamGuardConfig {
+    fn default() -> Self {
+        Self {
+            recheck_interval: Duration::from_secs(30),
+            exit_at: ScamRecommendation::HighRisk,
+        }
+    }
+}
+
+struct HeldToken {
+    metadata: TokenMetadata,
+    last_checked: Instant,
+}
+
+/// Keeps scoring tokens after they are bought. Signals such as creator
+/// history or holder concentration often only show up once trading starts,
+/// so each held token is re-run through the `ScamDetector` every
+/// `recheck_interval` and flagged once it reaches `exit_at`.
+pub struct ScamGuard {
+    config: ScamGuardConfig,
+    detector: ScamDetector,
+    held: HashMap<Pubkey, HeldToken>,
+}
+
+impl ScamGuard {
+    pub fn new(detector: ScamDetector, config: ScamGuardConfig) -> Self {
+        Self {
+            config,
+            detector,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Starts watching a token that was just bought.
+    pub fn track(&mut self, metadata: TokenMetadata, at: Instant) {
+        self.held.insert(metadata.mint, HeldToken { metadata, last_checked: at });
+    }
+
+    pub fn is_due(&self, mint: &Pubkey, at: Instant) -> bool {
+        self.held
+            .get(mint)
+            .is_some_and(|token| at.saturating_duration_since(token.last_checked) >= self.config.recheck_interval)
+    }
+
+    /// Re-scores a held token with fresh trading data. Returns the analysis
+    /// when it has reached the exit threshold.
+    pub async fn recheck(&mut self, trading_data: &TradingData, at: Instant) -> Option<ScamAnalysis> {
+        let token = self.held.get_mut(&trading_data.mint)?;
+        token.last_checked = at;
+
+        let analysis = self.detector.analyze_token(&token.metadata, trading_data).await;
+        if analysis.recommendation < self.config.exit_at {
+            info!("🔁 Re-scored {}: {:.2} ({:?})", trading_data.mint, analysis.scam_score, analysis.recommendation);
+            return None;
+        }
+
+        warn!("🚨 {} now scores {:.2} ({:?}) after entry", trading_data.mint, analysis.scam_score, analysis.recommendation);
+        Some(analysis)
+    }
+
+    /// Stops watching a token once its position is closed.
+    pub fn forget(&mut self, mint: &Pubkey) {
+        self.held.remove(mint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::TOTAL_SUPPLY;
+
+    fn metadata(name: &str) -> TokenMetadata {
+        TokenMetadata {
+            mint: Pubkey::new_unique(),
+            name: name.to_string(),
+            symbol: "TKN".to_string(),
+            description: String::new(),
+            image_uri: String::new(),
+            creator: Pubkey::new_unique(),
+            creation_time: Instant::now(),
+            initial_supply: TOTAL_SUPPLY,
+            decimals: 6,
+            authorities: None,
+            socials: None,
+        }
+    }
+
+    fn trading_data(mint: Pubkey, top_holder_percentage: f64) -> TradingData {
+        TradingData {
+            mint,
+            liquidity: 10.0,
+            volume_24h: 0.0,
+            price_change_24h: 0.0,
+            holder_count: 20,
+            top_holder_percentage: Some(top_holder_percentage),
+            transaction_count: 0,
+            market_cap: 0.0,
+            last_update: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_recheck_schedule() {
+        let mut guard = ScamGuard::new(ScamDetector::new(), ScamGuardConfig::default());
+        let token = metadata("Token");
+        let mint = token.mint;
+        let start = Instant::now();
+
+        assert!(!guard.is_due(&mint, start));
+        guard.track(token, start);
+        assert!(!guard.is_due(&mint, start + Duration::from_secs(10)));
+        assert!(guard.is_due(&mint, start + Duration::from_secs(30)));
+
+        guard.forget(&mint);
+        assert!(!guard.is_due(&mint, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_exit_threshold() {
+        let start = Instant::now();
+        let token = metadata("Scam Honeypot");
+        let data = trading_data(token.mint, 95.0);
+        let guard_at = |exit_at| {
+            let mut guard = ScamGuard::new(ScamDetector::new(), ScamGuardConfig {
+                exit_at,
+                ..ScamGuardConfig::default()
+            });
+            guard.track(token.clone(), start);
+            guard
+        };
+
+        // The name scores 0.95 * 0.2, 95% top holders 0.95 * 0.25 and the empty
+        // description 0.6 * 0.1, for 0.4875: Caution
+        let recheck = |exit_at| futures::executor::block_on(guard_at(exit_at).recheck(&data, start));
+        let analysis = recheck(ScamRecommendation::Caution).unwrap();
+        assert_eq!(analysis.recommendation, ScamRecommendation::Caution);
+        assert!((analysis.scam_score - 0.4875).abs() < 1e-9);
+        assert!(recheck(ScamRecommendation::Safe).is_some());
+        assert!(recheck(ScamRecommendation::HighRisk).is_none());
+        assert!(recheck(ScamRecommendation::Avoid).is_none());
+
+        // Untracked mints are never rechecked
+        let untracked = trading_data(Pubkey::new_unique(), 95.0);
+        assert!(futures::executor::block_on(guard_at(ScamRecommendation::Safe).recheck(&untracked, start)).is_none());
+    }
+
+    #[test]
+    fn test_recommendation_ordering_and_parsing() {
+        assert!(ScamRecommendation::Avoid > ScamRecommendation::HighRisk);
+        assert!(ScamRecommendation::HighRisk > ScamRecommendation::Caution);
+        assert_eq!("high-risk".parse::<ScamRecommendation>().unwrap(), ScamRecommendation::HighRisk);
+        assert_eq!("AVOID".parse::<ScamRecommendation>().unwrap(), ScamRecommendation::Avoid);
+        assert!("maybe".parse::<ScamRecommendation>().is_err());
+    }
+}
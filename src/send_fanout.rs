@@ -0,0 +1,143 @@
+use solana_client::client_error::{ClientError, Result as ClientResult};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{signature::Signature, transaction::Transaction};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use tracing::debug;
+
+/// An endpoint a signed transaction can be submitted to.
+pub trait TransactionSender: Send + Sync {
+    fn endpoint(&self) -> String;
+    fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature>;
+}
+
+impl TransactionSender for RpcClient {
+    fn endpoint(&self) -> String {
+        self.url()
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        RpcClient::send_transaction(self, transaction)
+    }
+}
+
+/// Submits the same signed transaction to every sender at once and returns as
+/// soon as one accepts it; the rest finish in the background. The signature
+/// is fixed at signing, so the copies can land at most once between them and
+/// the caller confirms and books a single trade by that signature. Fails with
+/// every endpoint's error only if none accepted the transaction.
+pub fn fanout_send(
+    senders: &[Arc<dyn TransactionSender>],
+    transaction: &Transaction,
+) -> std::result::Result<Signature, Vec<(String, ClientError)>> {
+    let (results_tx, results_rx) = mpsc::channel();
+    for sender in senders {
+        let sender = Arc::clone(sender);
+        let transaction = transaction.clone();
+        let results_tx = results_tx.clone();
+        thread::spawn(move || {
+            let result = sender.send_transaction(&transaction);
+            let _ = results_tx.send((sender.endpoint(), result));
+        });
+    }
+    drop(results_tx);
+
+    let mut errors = Vec::new();
+    for (endpoint, result) in results_rx {
+        match result {
+            Ok(signature) => {
+                debug!("{} accepted {} first of {} endpoints", endpoint, signature, senders.len());
+                return Ok(signature);
+            }
+            Err(e) => errors.push((endpoint, e)),
+        }
+    }
+    Err(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use solana_sdk::{
+        hash::Hash,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+    use std::collections::HashSet;
+    use std::time::{Duration, Instant};
+
+    struct RecordingSender {
+        name: &'static str,
+        accepts: bool,
+        delay: Duration,
+        received: Arc<Mutex<Vec<Signature>>>,
+    }
+
+    impl TransactionSender for RecordingSender {
+        fn endpoint(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+            thread::sleep(self.delay);
+            self.received.lock().push(transaction.signatures[0]);
+            if self.accepts {
+                Ok(transaction.signatures[0])
+            } else {
+                Err(ClientError::from(std::io::Error::other("connection refused")))
+            }
+        }
+    }
+
+    fn signed_transaction() -> Transaction {
+        let payer = Keypair::new();
+        let transfer = system_instruction::transfer(&payer.pubkey(), &Keypair::new().pubkey(), 1);
+        Transaction::new_signed_with_payer(&[transfer], Some(&payer.pubkey()), &[&payer], Hash::new_unique())
+    }
+
+    #[test]
+    fn test_fanout_sends_one_logical_buy() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sender = |name, accepts, delay_ms| -> Arc<dyn TransactionSender> {
+            Arc::new(RecordingSender {
+                name,
+                accepts,
+                delay: Duration::from_millis(delay_ms),
+                received: Arc::clone(&received),
+            })
+        };
+        let transaction = signed_transaction();
+
+        let senders = [sender("down", false, 0), sender("fast", true, 10), sender("slow", true, 100)];
+        let signature = fanout_send(&senders, &transaction).unwrap();
+        assert_eq!(signature, transaction.signatures[0]);
+
+        // Every endpoint gets the same signed transaction, so there is only one
+        // signature to confirm and one buy to book
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received.lock().len() < senders.len() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let received = received.lock();
+        assert_eq!(received.len(), 3);
+        assert_eq!(received.iter().collect::<HashSet<_>>().len(), 1);
+    }
+
+    #[test]
+    fn test_fanout_fails_only_when_every_endpoint_does() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let senders: Vec<Arc<dyn TransactionSender>> = ["a", "b"]
+            .into_iter()
+            .map(|name| -> Arc<dyn TransactionSender> {
+                Arc::new(RecordingSender { name, accepts: false, delay: Duration::ZERO, received: Arc::clone(&received) })
+            })
+            .collect();
+
+        let errors = fanout_send(&senders, &signed_transaction()).unwrap_err();
+        let mut endpoints: Vec<_> = errors.iter().map(|(endpoint, _)| endpoint.as_str()).collect();
+        endpoints.sort();
+        assert_eq!(endpoints, ["a", "b"]);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::error::{Result, SniperError};
+use async_trait::async_trait;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+/// Signs outgoing transactions on behalf of the buyer wallet. The bot only
+/// ever needs the wallet's address and a signature, so keys can live outside
+/// the process, e.g. behind a remote signing service or an HSM.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    /// Signs `transaction` over the blockhash already set in its message.
+    async fn sign(&self, transaction: &mut Transaction) -> Result<()>;
+}
+
+/// Signs with a keypair held in memory, loaded from `BUYER_PRIVATE_KEY_PATH`.
+pub struct LocalSigner {
+    keypair: Keypair,
+}
+
+impl LocalSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Loads a keypair from its base58-encoded secret key.
+    pub fn from_base58(private_key: &str) -> Result<Self> {
+        let private_key_bytes = bs58::decode(private_key).into_vec()?;
+        let keypair = Keypair::from_bytes(&private_key_bytes)
+            .map_err(|e| SniperError::SolanaClient(format!("Invalid private key: {}", e)))?;
+        Ok(Self::new(keypair))
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for LocalSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign(&self, transaction: &mut Transaction) -> Result<()> {
+        let recent_blockhash = transaction.message.recent_blockhash;
+        transaction
+            .try_sign(&[&self.keypair], recent_blockhash)
+            .map_err(|e| SniperError::Transaction(format!("Failed to sign transaction: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_signer::MockSigner;
+    use solana_sdk::{hash::Hash, system_instruction};
+    use std::sync::Arc;
+
+    fn transfer(payer: &Pubkey) -> Transaction {
+        let mut transaction =
+            Transaction::new_with_payer(&[system_instruction::transfer(payer, &Pubkey::new_unique(), 1)], Some(payer));
+        transaction.message.recent_blockhash = Hash::new_unique();
+        transaction
+    }
+
+    #[tokio::test]
+    async fn test_local_signer_signs_over_message_blockhash() {
+        let keypair = Keypair::new();
+        let signer = LocalSigner::from_base58(&keypair.to_base58_string()).unwrap();
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+
+        let mut transaction = transfer(&signer.pubkey());
+        let blockhash = transaction.message.recent_blockhash;
+        signer.sign(&mut transaction).await.unwrap();
+
+        assert!(transaction.is_signed());
+        assert_eq!(transaction.message.recent_blockhash, blockhash);
+        assert!(transaction.verify().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_local_signer_rejects_other_payers() {
+        let signer = LocalSigner::new(Keypair::new());
+        let mut transaction = transfer(&Pubkey::new_unique());
+        assert!(signer.sign(&mut transaction).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_signer_errors_surface() {
+        let mock = Arc::new(MockSigner::refusing());
+        let signer: Arc<dyn TransactionSigner> = mock.clone();
+
+        let mut transaction = transfer(&signer.pubkey());
+        assert!(signer.sign(&mut transaction).await.is_err());
+        assert!(!transaction.is_signed());
+        assert_eq!(mock.requests(), 1);
+    }
+
+    #[test]
+    fn test_invalid_private_key() {
+        assert!(LocalSigner::from_base58("not base58!").is_err());
+        assert!(LocalSigner::from_base58(&bs58::encode([1u8; 10]).into_string()).is_err());
+    }
+}
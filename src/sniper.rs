@@ -1,64 +1,721 @@
 use crate::{
+    bonding_curve::{check_initial_reserves, parse_global_initial_reserves, BondingCurveCalculator, BondingCurveState},
+    buy_gate::{
+        balance_decision, build_gates, check_market_cap_band, evaluate_gates, format_market_cap, required_buy_sol,
+        BuyCandidate, BuyGate, BuyGateKind, CreateStructure, GateDecision, MarketCapLimit,
+    },
+    buyer_window::BuyerWindow,
     config::Config,
     constants::*,
-    error::{Result, SniperError},
+    copy_trading::{CopyTradeConfig, CopyTradingEngine, FollowedTrade, TraderAutoManager, TraderProfile},
+    creator_analysis::{fetch_trading_data, parse_create_args},
+    dead_man_switch::{DeadManSwitch, DeadManSwitchConfig},
+    error::{classify_client_error, classify_rpc_health_error, ErrorContext, Result, SniperError},
+    fee_recipient::FeeRecipientResolver,
+    funding::FundingWallet,
     geyser::*,
+    grpc_manager::GeyserFilterMode,
+    jito_integration::{BundleStatus, JitoConfig, JitoManager},
+    keystore::load_keypair,
+    latency::{BuyTimings, LatencyTracker},
+    metrics,
+    migration_detector::{parse_migration_instruction, Season2Features},
+    pnl::{FeeBreakdown, FeeKind, PnlTracker, SessionEvent, SessionLimits},
+    mint_lock::MintLocks,
+    notifier::{Notifications, TradeEvent},
+    position_manager::{
+        AverageDownPolicy, BuyConfirmationPolicy, ExitReason, PendingBuy, Position, PositionConfig, PositionManager, StuckBuy,
+    },
+    position_store::{reconcile, PositionStore},
     price_cache::PriceCache,
+    priority_fee::{
+        build_priority_fee_source, finalize_instructions, priority_fee_lamports, PriorityFeeEstimator, PriorityFeeSchedule,
+        COMPUTE_UNIT_LIMIT, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS, LAMPORTS_PER_SIGNATURE,
+    },
+    pumpfun::{
+        build_buy_instruction, build_sell_instruction, discriminators, install_discriminators, parse_create_accounts,
+        parse_trade, program_keys, reprice_buy, size_buy, BuyParams, CreateAccounts, ObservedTrade, ProgramKeys,
+        PumpFunAccounts, PumpFunEvent, SellParams,
+    },
+    pump_swap::{pool_price_sol, quote_sell_output, PumpSwapPool, PumpSwapPoller, PumpSwapSwap},
+    rug_monitor::{RugMonitor, RugMonitorConfig, RugReaction},
+    same_block_execution::{gas_capped_price, SameBlockSnipe, SameBlockSniper, SnipeConfig, SnipeOutcome},
+    scam_detection::{MintAuthorities, ScamAnalysis, ScamDetector, TokenMetadata},
+    scam_guard::{ScamGuard, ScamGuardConfig},
+    send_fanout::{fanout_send, TransactionSender},
+    signer::{LocalSigner, TransactionSigner},
+    social_links::{SocialLinkFetcher, SocialLinks},
+    status_watcher::{StatusWatcher, StreamedStatus},
+    trade_journal::{JournalEntry, TradeJournal, TradeSide},
 };
 use anyhow::anyhow;
+use futures::stream::{FuturesUnordered, StreamExt};
 use parking_lot::Mutex;
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig};
 use solana_sdk::{
+    commitment_config::CommitmentConfig,
     compute_budget,
-    instruction::{AccountMeta, Instruction},
+    hash::Hash,
+    instruction::Instruction,
+    program_pack::Pack,
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    signature::Signature,
     system_instruction,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
-use spl_associated_token_account::get_associated_token_address;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    TransactionConfirmationStatus, TransactionStatus, UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinSet};
 use tonic::transport::{Channel, ClientTlsConfig};
 use tonic::Request;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+const POSITION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const LATENCY_SUMMARY_INTERVAL: Duration = Duration::from_secs(60);
+const TRACKED_TOKEN_CLEANUP_INTERVAL: Duration = Duration::from_secs(300);
+const CONNECTIVITY_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const ENTRY_DELAY_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+const PORTFOLIO_VALUATION_INTERVAL: Duration = Duration::from_secs(60);
+const TOP_UP_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Longest wait between startup retries of an unavailable endpoint.
+const STARTUP_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Reads of a bought token's balance before falling back to the estimate, and
+/// the wait between them while the account is not visible yet.
+const TOKEN_BALANCE_READ_ATTEMPTS: u32 = 4;
+const TOKEN_BALANCE_RETRY_DELAY: Duration = Duration::from_millis(250);
+const BUY_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(400);
+/// How often RPC is still asked about a sent transaction the stream has not
+/// reported yet, in case the stream missed it.
+const STREAM_STATUS_FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const PRIORITY_FEE_LAMPORTS: u64 = priority_fee_lamports(COMPUTE_UNIT_LIMIT, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS);
+
+// Names of the Geyser transaction filters; each update lists the ones it matched
+const PUMP_FUN_FILTER: &str = "pump_fun_subscription";
+const OWN_STATUS_FILTER: &str = "own_transaction_status";
+const MIGRATIONS_FILTER: &str = "pump_fun_migrations";
+const COPY_TRADER_FILTER_PREFIX: &str = "copy_trader_";
+
+/// A create more slots than this behind the stream's newest one is a replay,
+/// e.g. of updates buffered across a reconnect.
+const REPLAYED_CREATE_SLOT_LAG: u64 = 10;
 
 pub struct SniperBot {
     config: Config,
     price_cache: Arc<PriceCache>,
     rpc_client: RpcClient,
-    buyer_keypair: Keypair,
-    processing_mutex: Arc<Mutex<()>>,
+    send_rpc_client: RpcClient,
+    /// The same endpoints for work run alongside the stream, which must not
+    /// block the task reading it
+    async_rpc_client: NonblockingRpcClient,
+    async_send_rpc_client: NonblockingRpcClient,
+    /// Every endpoint a buy is submitted to at once; empty unless `FANOUT_SEND` is on.
+    fanout_senders: Vec<Arc<dyn TransactionSender>>,
+    signer: Arc<dyn TransactionSigner>,
+    mint_locks: MintLocks,
+    buy_semaphore: Semaphore,
+    /// When the last buy was submitted, or claimed by a buy still under way,
+    /// for `GLOBAL_BUY_COOLDOWN_MS`.
+    last_buy_at: Mutex<Option<Instant>>,
+    /// Buys under way per creator, for `MAX_POSITIONS_PER_CREATOR`.
+    creator_buys_in_flight: Mutex<HashMap<Pubkey, usize>>,
+    /// Buys seen into fresh mints, for `MAX_BUYERS_AHEAD`; `None` when unset.
+    buyer_window: Option<BuyerWindow>,
+    filtered_transactions: AtomicU64,
+    stream_slot: AtomicU64,
+    position_manager: Mutex<PositionManager>,
+    pnl: Mutex<PnlTracker>,
+    /// The latest bonding curve read per held mint, for mark-to-market.
+    curves: Mutex<BondingCurveCalculator>,
+    latency_tracker: LatencyTracker,
+    fee_recipients: FeeRecipientResolver,
+    priority_fees: PriorityFeeSchedule,
+    /// Live compute-unit price estimate buys start at; `None` unless
+    /// `DYNAMIC_PRIORITY_FEE` is on.
+    priority_fee_estimator: Option<Arc<PriorityFeeEstimator>>,
+    buy_confirmation: BuyConfirmationPolicy,
+    /// Statuses of the bot's own transactions from the stream; `None` unless
+    /// `STREAM_TRANSACTION_STATUSES` is on.
+    stream_statuses: Option<StatusWatcher>,
+    program_keys: &'static ProgramKeys,
+    season2: Mutex<Season2Features>,
+    pump_swap_poller: PumpSwapPoller<NonblockingRpcClient>,
+    rug_monitor: Mutex<RugMonitor>,
+    same_block_snipe: Option<SameBlockSnipe>,
+    /// Sizes and dedups followed traders' trades; `None` unless copy trading is on.
+    copy_engine: Option<Mutex<CopyTradingEngine>>,
+    /// Follows and drops traders by the PumpFun trades on the stream; `None`
+    /// unless `ENABLE_AUTO_FOLLOW` is on.
+    auto_follow: Option<Mutex<TraderAutoManager>>,
+    scam_guard: Option<tokio::sync::Mutex<ScamGuard>>,
+    /// Reads new tokens' social links; `None` unless they are checked.
+    social_links: Option<SocialLinkFetcher>,
+    buy_gates: Vec<Box<dyn BuyGate>>,
+    dead_man_switch: Option<Mutex<DeadManSwitch>>,
+    /// Set by the connectivity check when the send endpoint stopped answering
+    /// but the read endpoint still does, so exits go through the latter.
+    send_endpoint_down: AtomicBool,
+    /// Tops up the buyer wallet when it runs low; `None` unless `ENABLE_TOP_UP` is on.
+    funding: Option<Mutex<FundingWallet>>,
+    notifications: Notifications,
+    trade_journal: Option<TradeJournal>,
+    dry_run_buys: Mutex<Vec<DryRunBuy>>,
+}
+
+/// A buy that was built and signed but not sent because `DRY_RUN` is set.
+#[derive(Debug, Clone)]
+pub struct DryRunBuy {
+    pub mint: Pubkey,
+    pub token_amount: u64,
+    pub max_sol_cost_lamports: u64,
+    pub transaction: Transaction,
+    /// Set when `DRY_RUN_SIMULATE` is on and the RPC answered.
+    pub simulation: Option<DryRunSimulation>,
+}
+
+/// What the RPC reported when a dry-run buy was simulated against live state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunSimulation {
+    /// The transaction or program error, e.g. a custom PumpFun error code.
+    pub error: Option<String>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+impl DryRunSimulation {
+    fn new(error: Option<&TransactionError>, units_consumed: Option<u64>, logs: Option<Vec<String>>) -> Self {
+        Self {
+            error: error.map(ToString::to_string),
+            units_consumed,
+            logs: logs.unwrap_or_default(),
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Where an open position can currently be sold: its PumpFun bonding curve, or
+/// the PumpSwap pool it migrated into.
+enum SellRoute {
+    BondingCurve(BondingCurveState),
+    PumpSwap {
+        pool: PumpSwapPool,
+        base_reserve: u64,
+        quote_reserve: u64,
+    },
+}
+
+impl SellRoute {
+    fn current_price(&self) -> f64 {
+        match self {
+            SellRoute::BondingCurve(curve) => curve.get_current_price(),
+            SellRoute::PumpSwap { base_reserve, quote_reserve, .. } => pool_price_sol(*base_reserve, *quote_reserve),
+        }
+    }
 }
 
 impl SniperBot {
+    /// Builds a bot that signs locally with the key in `BUYER_PRIVATE_KEY_PATH`,
+    /// decrypting it first if it is an encrypted keypair file.
     pub fn new(config: Config) -> Result<Self> {
+        let key = config.buyer_private_key.as_deref().ok_or_else(|| {
+            SniperError::Config("BUYER_PRIVATE_KEY_PATH environment variable not set".to_string())
+        })?;
+        let keypair = load_keypair(key, config.keypair_password.as_deref())?;
+        Self::with_signer(config, Arc::new(LocalSigner::new(keypair)))
+    }
+
+    /// Builds a bot that signs through `signer`, e.g. a remote or hardware signer
+    /// that keeps the wallet's key out of this process.
+    pub fn with_signer(config: Config, signer: Arc<dyn TransactionSigner>) -> Result<Self> {
         config.validate()?;
 
-        let price_cache = Arc::new(PriceCache::new());
-        let rpc_client = RpcClient::new(config.solana_rpc_endpoint.clone());
-        
-        // Parse private key from base58 string
-        let private_key_bytes = bs58::decode(&config.buyer_private_key)
-            .into_vec()
-            .map_err(|e| SniperError::Base58(e))?;
-        
-        let buyer_keypair = Keypair::from_bytes(&private_key_bytes)
-            .map_err(|e| SniperError::SolanaClient(format!("Invalid private key: {}", e)))?;
+        if !install_discriminators(config.discriminators.clone()) && *discriminators() != config.discriminators {
+            warn!("PumpFun discriminators were already installed; ignoring the configured ones");
+        }
+
+        let price_cache = Arc::new(PriceCache::from_config(&config)?);
+        let rpc_client = config.rpc_client(&config.solana_rpc_endpoint);
+        let send_rpc_client = config.rpc_client(&config.send_rpc_endpoint);
+        let async_rpc_client = config.nonblocking_rpc_client(&config.solana_rpc_endpoint);
+        let async_send_rpc_client = config.nonblocking_rpc_client(&config.send_rpc_endpoint);
+        let fanout_senders: Vec<Arc<dyn TransactionSender>> = if config.fanout_send {
+            std::iter::once(&config.send_rpc_endpoint)
+                .chain(&config.send_endpoints)
+                .map(|endpoint| -> Arc<dyn TransactionSender> { Arc::new(config.rpc_client(endpoint)) })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        info!("🌐 RPC timeout: {:?} | retries: {}", config.rpc_timeout, config.rpc_max_retries);
+
+        info!("✅ Buyer's Public Key: {}", signer.pubkey());
+
+        let buy_semaphore = Semaphore::new(config.max_concurrent_buys);
+        let mut position_manager = PositionManager::new(PositionConfig {
+            stop_loss_percentage: config.stop_loss_percentage,
+            take_profit_percentage: config.take_profit_percentage,
+            max_hold_duration: config.max_hold_duration,
+            average_down: config.average_down,
+        });
+        if let Some(path) = &config.positions_path {
+            position_manager = position_manager.with_store(PositionStore::new(path));
+        }
+        let rug_monitor = RugMonitor::new(RugMonitorConfig {
+            drop_percentage: config.rug_drop_percentage,
+            window: config.rug_window,
+            reaction: config.rug_reaction,
+        });
+
+        let same_block_snipe = if config.same_block_snipe {
+            let jito = JitoManager::new(config.send_rpc_endpoint.clone(), JitoConfig {
+                block_engine_url: config.jito_block_engine_url.clone(),
+                default_tip_lamports: config.jito_tip_lamports,
+                max_tip_fraction_of_buy: config.jito_max_tip_fraction_of_buy,
+                min_tip_lamports: config.jito_min_tip_lamports,
+                strict: config.jito_strict,
+                ..JitoConfig::default()
+            })?;
+            let sniper = SameBlockSniper::new(
+                config.nonblocking_rpc_client(&config.solana_rpc_endpoint),
+                SnipeConfig {
+                    target_block_offset: 0,
+                    verify_target_slot: config.same_block_verify_slot,
+                    max_gas_price: config.max_gas_price_lamports,
+                    gas_ceiling_action: config.gas_ceiling_action,
+                    ..SnipeConfig::default()
+                },
+            );
+            Some(SameBlockSnipe::new(sniper, jito))
+        } else {
+            None
+        };
+
+        let scam_guard = match config.scam_recheck_interval {
+            Some(recheck_interval) if config.enable_scam_detection => {
+                let mut detector = ScamDetector::new().with_deny_patterns(config.scam_deny_patterns.clone());
+                if let Some(path) = &config.scam_audit_log_path {
+                    detector = detector.with_audit_log(path);
+                }
+                Some(tokio::sync::Mutex::new(ScamGuard::new(detector, ScamGuardConfig {
+                    recheck_interval,
+                    exit_at: config.scam_exit_recommendation,
+                })))
+            }
+            _ => None,
+        };
+
+        let pnl = PnlTracker::new(SessionLimits {
+            max_drawdown_sol: config.max_session_drawdown_sol,
+            max_loss_usd: config.max_session_loss_usd,
+            close_positions_on_halt: config.close_positions_on_halt,
+            max_fees_sol: config.max_session_fees_sol,
+        });
+
+        let dead_man_switch = config.dead_man_timeout.map(|timeout| {
+            Mutex::new(DeadManSwitch::new(DeadManSwitchConfig {
+                timeout,
+                action: config.dead_man_action,
+            }, Instant::now()))
+        });
+
+        let funding = match (&config.top_up, &config.funding_private_key) {
+            (Some(policy), Some(key)) => {
+                let funding = FundingWallet::new(load_keypair(key, config.keypair_password.as_deref())?, policy.clone());
+                info!("⛽ Buyer top-ups enabled from {}", funding.pubkey());
+                Some(Mutex::new(funding))
+            }
+            _ => None,
+        };
+
+        let social_links = config.fetches_social_links()
+            .then(|| SocialLinkFetcher::new(&config.price_http(), config.metadata_fetch_timeout))
+            .transpose()?;
+
+        let notifications = Notifications::from_config(&config);
+        let trade_journal = config.trade_journal_path.as_ref().map(TradeJournal::new);
+        let buy_gates = build_gates(&config, signer.pubkey(), &notifications);
 
-        info!("✅ Buyer's Public Key: {}", buyer_keypair.pubkey());
+        let fee_recipients = FeeRecipientResolver::new(config.fee_recipient_override)?;
+        // A fixed migration price is a cache that is never refreshed
+        let migration_price = match config.migration_sol_price_usd {
+            Some(price) => {
+                let fixed = PriceCache::new();
+                fixed.set(price);
+                Arc::new(fixed)
+            }
+            None => Arc::clone(&price_cache),
+        };
+        let season2 = Season2Features::new()?
+            .with_max_tracked_tokens(config.max_tracked_tokens)
+            .with_price_cache(migration_price);
+        let pump_swap_poller = PumpSwapPoller::new(config.nonblocking_rpc_client(&config.solana_rpc_endpoint));
+        let priority_fees = PriorityFeeSchedule {
+            floor_micro_lamports: config.priority_fee_floor_micro_lamports,
+            step_micro_lamports: config.priority_fee_step_micro_lamports,
+            ceiling_micro_lamports: config.priority_fee_ceiling_micro_lamports,
+            max_retries: config.buy_land_retries,
+        };
+        let priority_fee_estimator = config.dynamic_priority_fee.then(|| {
+            info!("⛽ Dynamic priority fee: {:?} source at {:?} level", config.priority_fee_source, config.priority_fee_level);
+            let source = build_priority_fee_source(
+                config.priority_fee_source,
+                &config.priority_fee_estimate_url,
+                config.priority_fee_level,
+                config.rpc_timeout,
+            );
+            Arc::new(PriorityFeeEstimator::new(source, vec![program_keys().pump_fun]))
+        });
+        let buy_confirmation = BuyConfirmationPolicy {
+            commitment: config.buy_confirmation_commitment,
+            min_confirmations: config.buy_min_confirmations,
+            pending_timeout: config.pending_buy_budget(),
+        };
 
         Ok(Self {
             config,
             price_cache,
             rpc_client,
-            buyer_keypair,
-            processing_mutex: Arc::new(Mutex::new(())),
+            send_rpc_client,
+            async_rpc_client,
+            async_send_rpc_client,
+            fanout_senders,
+            signer,
+            mint_locks: MintLocks::new(),
+            buy_semaphore,
+            last_buy_at: Mutex::new(None),
+            creator_buys_in_flight: Mutex::new(HashMap::new()),
+            buyer_window: config.max_buyers_ahead
+                .map(|max_buyers_ahead| BuyerWindow::new(max_buyers_ahead, config.max_tracked_tokens)),
+            filtered_transactions: AtomicU64::new(0),
+            stream_slot: AtomicU64::new(0),
+            position_manager: Mutex::new(position_manager),
+            pnl: Mutex::new(pnl),
+            curves: Mutex::new(BondingCurveCalculator::new(0.0).with_max_tracked_tokens(config.max_tracked_tokens)),
+            latency_tracker: LatencyTracker::new(),
+            fee_recipients,
+            priority_fees,
+            priority_fee_estimator,
+            buy_confirmation,
+            stream_statuses: config.stream_transaction_statuses.then(StatusWatcher::new),
+            program_keys: program_keys(),
+            season2: Mutex::new(season2),
+            pump_swap_poller,
+            rug_monitor: Mutex::new(rug_monitor),
+            same_block_snipe,
+            copy_engine: config.enable_copy_trading.then(|| Mutex::new(copy_engine(&config))),
+            auto_follow: config.auto_follow.clone().filter(|_| config.enable_copy_trading).map(|policy| {
+                Mutex::new(TraderAutoManager::new(policy).with_pinned(config.copy_traders.iter().copied()))
+            }),
+            scam_guard,
+            social_links,
+            buy_gates,
+            dead_man_switch,
+            send_endpoint_down: AtomicBool::new(false),
+            funding,
+            notifications,
+            trade_journal,
+            dry_run_buys: Mutex::new(Vec::new()),
         })
     }
 
-    pub async fn run(&self) -> Result<()> {
+    pub fn dry_run_buys(&self) -> Vec<DryRunBuy> {
+        self.dry_run_buys.lock().clone()
+    }
+
+    /// Logs the session's PnL and where its fees went; called on shutdown.
+    pub fn log_session_summary(&self) {
+        let pnl = self.pnl.lock();
+        let fees = pnl.fee_breakdown();
+        info!("📒 Session summary: {:+.4} SOL realized (net of fees), {:+.4} SOL open, {} position(s) held",
+              pnl.realized(), pnl.unrealized(), self.position_manager.lock().open_positions().len());
+        info!("📒 Fees paid: {:.6} SOL total | base {:.6} | priority {:.6} | tips {:.6} | rent reclaimed {:.6}",
+              fees.total(), fees.base_sol, fees.priority_sol, fees.tip_sol, pnl.rent_reclaimed());
+        info!("📒 Buy priority: highest {} µlamports/CU, {} buy(s) escalated past the floor",
+              pnl.max_buy_compute_unit_price(), pnl.escalated_buys());
+        drop(pnl);
+        info!("📒 Open holdings marked at ${:.2}", self.portfolio_value_usd());
+    }
+
+    /// USD value of the tokens the open positions hold. Migrated tokens are
+    /// priced off their PumpSwap pool's last poll, the rest off the latest
+    /// bonding curve read for each; a token with neither price is left out.
+    pub fn portfolio_value_usd(&self) -> f64 {
+        let sol_price_usd = self.price_cache.get();
+        let holdings = self.position_manager.lock().holdings();
+        let mut on_curve = HashMap::new();
+        let mut migrated_value_sol = 0.0;
+        {
+            let season2 = self.season2.lock();
+            for (mint, token_amount) in holdings {
+                let tokens = token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+                match season2.pump_swap_price(&mint) {
+                    Some(price_sol) => migrated_value_sol += price_sol * tokens,
+                    None => {
+                        on_curve.insert(mint, tokens);
+                    }
+                }
+            }
+        }
+        let mut curves = self.curves.lock();
+        curves.update_sol_price(sol_price_usd);
+        curves.calculate_portfolio_value(&on_curve) + migrated_value_sol * sol_price_usd
+    }
+
+    fn log_portfolio_value(&self) {
+        let holdings = self.position_manager.lock().holdings().len();
+        if holdings > 0 {
+            info!("💼 Portfolio: {} holding(s) marked at ${:.2}", holdings, self.portfolio_value_usd());
+        }
+    }
+
+    /// The session's marks in the Prometheus text format, for `METRICS_ADDR`.
+    pub fn render_metrics(&self) -> String {
+        let (open_positions, pending_buys) = {
+            let positions = self.position_manager.lock();
+            (positions.holdings().len(), positions.pending_buys())
+        };
+        let (realized, unrealized, fees) = {
+            let pnl = self.pnl.lock();
+            (pnl.realized(), pnl.unrealized(), pnl.total_fees_paid_sol())
+        };
+        let mut out = String::new();
+        metrics::gauge(&mut out, "sniper_portfolio_value_usd", "Open holdings marked to market, in USD", self.portfolio_value_usd());
+        metrics::gauge(&mut out, "sniper_open_positions", "Positions currently held", open_positions as f64);
+        metrics::gauge(&mut out, "sniper_pending_buys", "Buys sent but not yet confirmed", pending_buys as f64);
+        metrics::gauge(&mut out, "sniper_realized_pnl_sol", "Realized PnL this session, net of fees, in SOL", realized);
+        metrics::gauge(&mut out, "sniper_unrealized_pnl_sol", "Unrealized PnL of open positions, in SOL", unrealized);
+        metrics::gauge(&mut out, "sniper_fees_paid_sol", "Base fees, priority fees and tips paid this session, in SOL", fees);
+        out
+    }
+
+    /// The latest slot reported by the Geyser stream, if any has arrived yet.
+    pub fn latest_stream_slot(&self) -> Option<u64> {
+        Some(self.stream_slot.load(Ordering::Relaxed)).filter(|slot| *slot > 0)
+    }
+
+    /// Session-level events such as `SessionHalted`, for alerting.
+    pub fn session_events(&self) -> tokio::sync::broadcast::Receiver<SessionEvent> {
+        self.pnl.lock().subscribe()
+    }
+
+    /// Forgets migrations older than `tracked_token_max_age`, keeping any token
+    /// the bot still holds so its position can be sold through PumpSwap.
+    fn cleanup_tracked_tokens(&self) {
+        let held: Vec<Pubkey> = self.position_manager.lock().open_positions().iter().map(|p| p.mint).collect();
+        self.season2.lock().cleanup_old_events(self.config.tracked_token_max_age, &held);
+    }
+
+    /// Resolves `stale` pending buys, left behind past the pending timeout,
+    /// that no buy is still waiting on. One is dropped only once its blockhash
+    /// has expired without it landing; one that landed opens its position, and
+    /// any other stays pending for the next pass. Reads each status over
+    /// blocking RPC, so it runs off the stream loop.
+    fn resolve_stale_pending(&self, stale: Vec<PendingBuy>) {
+        for pending in stale {
+            let mint = pending.position.mint;
+            // The buy that sent it is still resolving it
+            if self.mint_locks.is_locked(&mint) {
+                continue;
+            }
+            match self.stuck_buy(&pending.signature, pending.last_valid_block_height) {
+                StuckBuy::LandedLate => {
+                    if self.position_manager.lock().confirm_pending(&mint) {
+                        info!("🕰️ Pending buy {} for {} landed late, opening its position", pending.signature, mint);
+                    }
+                }
+                StuckBuy::Dropped | StuckBuy::Failed => {
+                    if self.position_manager.lock().drop_pending(&mint).is_some() {
+                        warn!("🗑️ Dropped pending buy {} for {}: it can no longer land", pending.signature, mint);
+                    }
+                }
+                StuckBuy::InFlight | StuckBuy::Unknown => {}
+            }
+        }
+    }
+
+    /// Refreshes the price and liquidity of migrated tokens from their PumpSwap
+    /// pools, and stops tracking pools that have closed.
+    async fn poll_pump_swap_pools(&self) {
+        if !self.config.enable_pump_swap_monitoring {
+            return;
+        }
+        let pools = self.season2.lock().pump_swap_pools();
+        if pools.is_empty() {
+            return;
+        }
+        let updates = self.pump_swap_poller.poll(&pools).await;
+        self.season2.lock().apply_pool_updates(&updates);
+    }
+
+    /// Loads the fee recipients from the PumpFun global account, unless one is
+    /// configured, and checks its initial curve reserves. Runs on every start;
+    /// if the account can't be read, buys go to the default fee recipient.
+    fn load_global_account(&self) {
+        match self.rpc_client.get_account_data(&self.program_keys.global) {
+            Ok(data) => {
+                if self.config.fee_recipient_override.is_none() {
+                    if let Err(e) = self.fee_recipients.update_from_global(&data) {
+                        warn!("Failed to parse fee recipients from global account: {}", e);
+                    }
+                }
+                self.check_global_initial_reserves(&data);
+            }
+            Err(e) => warn!("Failed to fetch global account, using default fee recipient: {}", e),
+        }
+    }
+
+    /// Opens the send RPC connection up front so the first buy doesn't pay for
+    /// it. A failure only costs that first buy some time.
+    fn warmup(&self) {
+        let started = Instant::now();
+        if let Err(e) = self.send_rpc_client.get_latest_blockhash() {
+            warn!("Failed to warm up send RPC connection: {}", e);
+        }
+
+        info!("🔥 Warmup finished in {:?}", started.elapsed());
+    }
+
+    /// Logs the market cap a brand-new curve starts at under the built-in
+    /// initial reserves, and complains loudly when it is outside the expected
+    /// range: every market cap the bot computes depends on those reserves.
+    fn check_initial_curve_constants(&self) {
+        match check_initial_reserves(
+            INITIAL_VIRTUAL_SOL,
+            INITIAL_VIRTUAL_TOKENS,
+            self.config.initial_market_cap_min_sol,
+            self.config.initial_market_cap_max_sol,
+        ) {
+            Ok(market_cap_sol) => {
+                let sol_price_usd = self.price_cache.get();
+                if sol_price_usd > 0.0 {
+                    info!("📐 New curves start at a {:.2} SOL (${:.0}) market cap", market_cap_sol, market_cap_sol * sol_price_usd);
+                } else {
+                    info!("📐 New curves start at a {:.2} SOL market cap", market_cap_sol);
+                }
+            }
+            Err(problem) => error!("❌ Bonding curve constants look wrong: {}; market caps will be miscomputed", problem),
+        }
+    }
+
+    /// Waits for the read and send RPC endpoints to hand out a blockhash before
+    /// anything relies on them, so an RPC that is briefly down delays startup
+    /// instead of failing the first buy. Rejected credentials fail at once.
+    /// Dry runs can do without RPC, so they check once and only warn.
+    async fn check_rpc_health(&self) -> Result<()> {
+        let mut clients = vec![("Read", &self.rpc_client)];
+        if self.config.send_rpc_endpoint != self.config.solana_rpc_endpoint {
+            clients.push(("Send", &self.send_rpc_client));
+        }
+        let retries = if self.config.dry_run { 0 } else { self.config.startup_retries };
+        for (role, client) in clients {
+            info!("🩺 Checking {} RPC health...", role.to_lowercase());
+            let started = Instant::now();
+            let health = retry_on_startup(&format!("{} RPC", role), retries, self.config.startup_backoff, || async {
+                client.get_latest_blockhash().map_err(|e| classify_rpc_health_error(role, &e))
+            })
+            .await;
+            match health {
+                Ok(_) => info!("✅ {} RPC healthy after {:?}", role, started.elapsed()),
+                Err(e) if self.config.dry_run => warn!("⚠️ Continuing the dry run without a healthy {} RPC: {}", role.to_lowercase(), e),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Logs the discriminators PumpFun instructions are matched with, and checks
+    /// them against the configured reference trades. A stale discriminator
+    /// fails silently otherwise: the bot just never sees a matching instruction.
+    fn check_discriminators(&self) -> Result<()> {
+        let discriminators = discriminators();
+        for (name, value) in discriminators.named() {
+            info!("🔑 PumpFun {} discriminator: {}", name, hex::encode(value));
+        }
+        for value in &discriminators.alternate_creates {
+            info!("🔑 PumpFun alternate create discriminator: {}", hex::encode(value));
+            if discriminators.create_layout(*value).is_none() {
+                warn!("⚠️ Alternate create discriminator {} has no known account layout; its creates will be skipped",
+                      hex::encode(value));
+            }
+        }
+        for (name, configured, expected) in discriminators.anchor_mismatches() {
+            warn!(
+                "⚠️ PumpFun {} discriminator {} differs from the Anchor hash {}",
+                name,
+                hex::encode(configured),
+                hex::encode(expected)
+            );
+        }
+
+        let pump_fun_program = program_keys().pump_fun;
+        // Most PumpFun trades are v0 transactions, which are only returned
+        // when the version is asked for
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        for signature in &self.config.discriminator_reference_signatures {
+            let encoded = self.rpc_client
+                .get_transaction_with_config(signature, config)
+                .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch reference transaction {}: {}", signature, e)))?;
+            let transaction = encoded.transaction.transaction.decode().ok_or_else(|| {
+                SniperError::Transaction(format!("Failed to decode reference transaction {}", signature))
+            })?;
+            let account_keys = transaction.message.static_account_keys();
+            let pump_fun_instructions = transaction.message.instructions().iter()
+                .filter(|ix| account_keys.get(ix.program_id_index as usize) == Some(&pump_fun_program))
+                .map(|ix| ix.data.as_slice());
+            let trades = discriminators.check_reference(pump_fun_instructions).map_err(|problem| {
+                SniperError::Config(format!("Reference transaction {}: {}", signature, problem))
+            })?;
+            info!("✅ Reference transaction {} matched {:?}", signature, trades);
+        }
+        Ok(())
+    }
+
+    /// Compares the initial reserves PumpFun currently uses, from its Global
+    /// account, with the built-in ones.
+    fn check_global_initial_reserves(&self, global_account_data: &[u8]) {
+        let (virtual_sol, virtual_tokens) = match parse_global_initial_reserves(global_account_data) {
+            Ok(reserves) => reserves,
+            Err(e) => {
+                warn!("Failed to parse initial reserves from global account: {}", e);
+                return;
+            }
+        };
+        if let Err(problem) = check_initial_reserves(
+            virtual_sol,
+            virtual_tokens,
+            self.config.initial_market_cap_min_sol,
+            self.config.initial_market_cap_max_sol,
+        ) {
+            error!("❌ PumpFun global account: {}", problem);
+        }
+        let drift = |on_chain: f64, built_in: f64| ((on_chain - built_in) / built_in).abs() > 0.001;
+        if drift(virtual_sol, INITIAL_VIRTUAL_SOL) || drift(virtual_tokens, INITIAL_VIRTUAL_TOKENS) {
+            error!(
+                "❌ PumpFun now starts curves at {} SOL / {} tokens, but the bot assumes {} SOL / {} tokens; market caps will be miscomputed",
+                virtual_sol, virtual_tokens, INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKENS
+            );
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
         info!("🚀 Starting sniper bot monitoring...");
 
         // Start price cache updates
@@ -67,40 +724,51 @@ impl SniperBot {
             price_cache.update_price_periodically().await;
         });
 
+        if let Some(estimator) = &self.priority_fee_estimator {
+            let estimator = Arc::clone(estimator);
+            tokio::spawn(async move {
+                estimator.update_periodically().await;
+            });
+        }
+
         // Wait for initial price fetch
         tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
 
-        // Connect to gRPC endpoint
-        let channel = Channel::from_shared(self.config.grpc_endpoint.clone())
-            .map_err(|e| SniperError::Grpc(tonic::Status::from_error(e)))?
-            .connect()
-            .await
-            .map_err(|e| SniperError::Grpc(tonic::Status::from_error(e)))?;
+        self.check_rpc_health().await?;
+        self.check_initial_curve_constants();
+        self.check_discriminators()?;
+        self.restore_positions()?;
+        self.load_global_account();
+
+        if self.config.warmup {
+            self.warmup();
+        }
+
+        if let Some(snipe) = &self.same_block_snipe {
+            snipe.initialize().await?;
+            snipe.start_congestion_monitor(self.config.solana_rpc_endpoint.clone());
+            info!("⚡ Same-block snipe mode enabled");
+        }
+
+        // Connect to gRPC endpoint, riding out a brief outage
+        let endpoint = Channel::from_shared(self.config.grpc_endpoint.clone())
+            .map_err(|e| SniperError::Config(format!("Invalid GRPC_ENDPOINT: {}", e)))?;
+        let channel = retry_on_startup("Geyser", self.config.startup_retries, self.config.startup_backoff, || async {
+            endpoint.connect().await.map_err(|e| SniperError::Grpc(tonic::Status::unavailable(e.to_string())))
+        })
+        .await?;
 
         let mut client = GeyserClient::new(channel);
 
-        // Create subscription request
-        let subscription_request = SubscribeRequest {
-            transactions: [(
-                "pump_fun_subscription".to_string(),
-                SubscribeRequestFilterTransactions {
-                    vote: false,
-                    failed: false,
-                    account_include: vec![PUMP_FUN_PROGRAM_ID.to_string()],
-                },
-            )]
-            .into(),
-            transactions_status: [(
-                "pump_fun_status".to_string(),
-                SubscribeRequestFilterTransactions {
-                    vote: false,
-                    failed: false,
-                    account_include: vec![PUMP_FUN_PROGRAM_ID.to_string()],
-                },
-            )]
-            .into(),
-            commitment: CommitmentLevel::Processed as i32,
-        };
+        // Followed traders ride on the same stream as the sniper, under their own filters
+        let copy_traders: &[Pubkey] = if self.config.enable_copy_trading { &self.config.copy_traders } else { &[] };
+        let status_wallet = self.stream_statuses.is_some().then(|| self.signer.pubkey());
+        let subscription_request = subscription_request(
+            &self.config.stream_events,
+            self.config.geyser_filter_mode,
+            copy_traders,
+            status_wallet.as_ref(),
+        );
 
         info!("🔌 Connecting to Geyser: {}", self.config.grpc_endpoint);
         
@@ -111,22 +779,188 @@ impl SniperBot {
             .into_inner();
 
         info!("✅ gRPC Connection Established.");
-        info!("✅ Subscribed. Waiting for 'create' transactions...");
-        info!("🎯 Monitoring for tokens with market cap >= ${:.2}", self.config.market_cap_threshold_usd);
+        info!("✅ Subscribed. Reacting to {:?} instructions...", self.config.stream_events);
+        if !copy_traders.is_empty() {
+            info!("👥 Following {} trader(s) on the same stream", copy_traders.len());
+        }
+        info!("🎯 Monitoring for tokens with market cap >= {}", self.config.min_market_cap());
 
-        // Process incoming transactions
-        while let Some(response) = stream.message().await.map_err(|e| SniperError::Grpc(e))? {
-            if let Some(tx_update) = response.transaction {
-                if let Err(e) = self.process_transaction(tx_update).await {
-                    error!("Error processing transaction: {}", e);
+        // Process incoming transactions on their own tasks so a confirming buy
+        // does not hold up unrelated creates. The buy path makes blocking RPC
+        // calls, so these run on the blocking pool, where they cannot tie up
+        // the runtime workers this loop and the stream are polled on
+        let mut in_flight = JoinSet::new();
+        // Position checks read prices and confirm sells over blocking RPC, so
+        // they run on the blocking pool too; the stream keeps being read and
+        // their streamed statuses arrive. One check runs at a time, and the
+        // dips it finds are bought into alongside later checks.
+        let mut position_checks = JoinSet::new();
+        let mut average_downs = JoinSet::new();
+        // Connectivity checks may sell everything over blocking calls, so they
+        // run on the blocking pool and cannot stall the stream while RPC is slow
+        let mut connectivity_checks = JoinSet::new();
+        let mut stale_resolutions = JoinSet::new();
+        let mut funding_checks = FuturesUnordered::new();
+        let mut pool_polls = FuturesUnordered::new();
+        let mut position_check_interval = tokio::time::interval(POSITION_CHECK_INTERVAL);
+        let mut latency_summary_interval = tokio::time::interval(LATENCY_SUMMARY_INTERVAL);
+        let mut cleanup_interval = tokio::time::interval(TRACKED_TOKEN_CLEANUP_INTERVAL);
+        let mut connectivity_interval = tokio::time::interval(CONNECTIVITY_CHECK_INTERVAL);
+        let mut pump_swap_poll_interval = tokio::time::interval(self.config.pump_swap_poll_interval);
+        let mut portfolio_interval = tokio::time::interval(PORTFOLIO_VALUATION_INTERVAL);
+        let mut top_up_interval = tokio::time::interval(TOP_UP_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = position_check_interval.tick() => {
+                    if position_checks.is_empty() {
+                        let bot = Arc::clone(&self);
+                        position_checks.spawn_blocking(on_blocking_thread(async move { bot.check_positions().await }));
+                    }
+                }
+                _ = latency_summary_interval.tick() => {
+                    self.latency_tracker.log_summaries();
+                }
+                _ = cleanup_interval.tick() => {
+                    self.cleanup_tracked_tokens();
+                    let stale = self.position_manager.lock().stale_pending(self.buy_confirmation.pending_timeout);
+                    if !stale.is_empty() && stale_resolutions.is_empty() {
+                        let bot = Arc::clone(&self);
+                        stale_resolutions.spawn_blocking(move || bot.resolve_stale_pending(stale));
+                    }
+                }
+                _ = connectivity_interval.tick() => {
+                    if connectivity_checks.is_empty() {
+                        let bot = Arc::clone(&self);
+                        connectivity_checks.spawn_blocking(on_blocking_thread(async move { bot.check_connectivity().await }));
+                    }
+                }
+                _ = pump_swap_poll_interval.tick() => {
+                    if pool_polls.is_empty() {
+                        pool_polls.push(self.poll_pump_swap_pools());
+                    }
+                }
+                _ = portfolio_interval.tick() => {
+                    self.log_portfolio_value();
+                }
+                _ = top_up_interval.tick() => {
+                    if funding_checks.is_empty() {
+                        funding_checks.push(self.check_funding());
+                    }
+                }
+                message = stream.message() => {
+                    match message.map_err(|e| SniperError::Grpc(e))? {
+                        Some(response) => {
+                            if let Some(switch) = &self.dead_man_switch {
+                                switch.lock().heartbeat(Instant::now());
+                            }
+                            if let Some(slot_update) = response.slot {
+                                self.record_stream_slot(slot_update.slot).await;
+                            }
+                            if let Some(status_update) = &response.transaction_status {
+                                self.record_streamed_status(status_update);
+                            }
+                            if let Some(tx_update) = response.transaction {
+                                let received_at = Instant::now();
+                                let routes = route_update(&response.filters);
+                                for trader in &routes.copy_traders {
+                                    if let Err(e) = self.handle_followed_trade(trader, &tx_update) {
+                                        log_processing_error(&e);
+                                    }
+                                }
+                                if routes.sniper {
+                                    if let Err(e) = self.observe_traders(&tx_update, &routes.copy_traders) {
+                                        log_processing_error(&e);
+                                    }
+                                    let bot = Arc::clone(&self);
+                                    in_flight.spawn_blocking(on_blocking_thread(async move {
+                                        bot.process_transaction(tx_update, received_at).await
+                                    }));
+                                }
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                Some(joined) = in_flight.join_next(), if !in_flight.is_empty() => {
+                    log_processed(joined);
+                }
+                Some(joined) = position_checks.join_next(), if !position_checks.is_empty() => {
+                    match joined {
+                        Ok(dipped) => {
+                            // Claimed here, so a position never has two dip buys out;
+                            // the buy itself sends over blocking RPC on the blocking pool
+                            for (position, curve) in dipped {
+                                let Some(policy) = self.claim_average_down(&position.mint) else {
+                                    continue;
+                                };
+                                let bot = Arc::clone(&self);
+                                average_downs.spawn_blocking(on_blocking_thread(async move {
+                                    bot.average_down(position, curve, policy).await
+                                }));
+                            }
+                        }
+                        Err(e) => error!("Position check task failed: {}", e),
+                    }
+                }
+                Some(joined) = average_downs.join_next(), if !average_downs.is_empty() => {
+                    if let Err(e) = joined {
+                        error!("Averaging-down task failed: {}", e);
+                    }
+                }
+                Some(joined) = stale_resolutions.join_next(), if !stale_resolutions.is_empty() => {
+                    if let Err(e) = joined {
+                        error!("Stale pending buy resolution failed: {}", e);
+                    }
+                }
+                Some(joined) = connectivity_checks.join_next(), if !connectivity_checks.is_empty() => {
+                    if let Err(e) = joined {
+                        error!("Connectivity check task failed: {}", e);
+                    }
                 }
+                Some(()) = funding_checks.next(), if !funding_checks.is_empty() => {}
+                Some(()) = pool_polls.next(), if !pool_polls.is_empty() => {}
             }
         }
 
+        while position_checks.join_next().await.is_some() {}
+        while average_downs.join_next().await.is_some() {}
+        while connectivity_checks.join_next().await.is_some() {}
+        while stale_resolutions.join_next().await.is_some() {}
+        while funding_checks.next().await.is_some() {}
+        while pool_polls.next().await.is_some() {}
+
+        while let Some(joined) = in_flight.join_next().await {
+            log_processed(joined);
+        }
+
         Ok(())
     }
 
-    async fn process_transaction(&self, tx_update: TransactionUpdate) -> Result<()> {
+    /// Tracks the newest slot the stream has announced and hands it to the
+    /// same-block tracker, which then stops polling RPC for it.
+    async fn record_stream_slot(&self, slot: u64) {
+        let previous = self.stream_slot.fetch_max(slot, Ordering::Relaxed);
+        if slot <= previous {
+            return;
+        }
+        if let Some(snipe) = &self.same_block_snipe {
+            snipe.record_stream_slot(slot).await;
+        }
+    }
+
+    /// Hands a `transactions_status` update to whoever is waiting on that
+    /// signature. Only the bot's own transactions are subscribed to, but a
+    /// status nobody waits for, e.g. of a buy already given up on, is dropped.
+    fn record_streamed_status(&self, update: &TransactionStatusUpdate) {
+        let (Some(statuses), Some((signature, status))) = (&self.stream_statuses, streamed_status(update)) else {
+            return;
+        };
+        if statuses.record(&signature, status) {
+            debug!("📡 Stream saw {} {} in slot {}", signature, if status.failed { "fail" } else { "land" }, status.slot);
+        }
+    }
+
+    async fn process_transaction(&self, tx_update: TransactionUpdate, received_at: Instant) -> Result<()> {
         let tx = tx_update.transaction.ok_or_else(|| {
             SniperError::Transaction("Missing transaction in update".to_string())
         })?;
@@ -139,27 +973,63 @@ impl SniperBot {
             SniperError::Transaction("Missing meta in transaction".to_string())
         })?;
 
-        // Combine all account keys
-        let mut full_account_list = message.account_keys.clone();
-        full_account_list.extend_from_slice(&meta.loaded_writable_addresses);
-        full_account_list.extend_from_slice(&meta.loaded_readonly_addresses);
+        let full_account_list = combined_account_keys(&message, &meta);
+        check_account_count(&full_account_list, self.config.max_transaction_accounts)?;
 
-        // Find PumpFun program index
-        let pump_fun_pk = Pubkey::from_str(PUMP_FUN_PROGRAM_ID)?;
-        let pump_fun_program_index = full_account_list
+        // Find PumpFun program index; transactions without it are simply not relevant
+        let pump_fun_pk = self.program_keys.pump_fun;
+        let pump_fun_program_index = match full_account_list
             .iter()
             .position(|key_bytes| {
                 Pubkey::try_from(key_bytes.as_slice())
                     .map(|pk| pk == pump_fun_pk)
                     .unwrap_or(false)
-            })
-            .ok_or_else(|| SniperError::Transaction("PumpFun program not found in accounts".to_string()))?;
+            }) {
+            Some(index) => index,
+            None => {
+                let filtered = self.filtered_transactions.fetch_add(1, Ordering::Relaxed) + 1;
+                debug!("Skipping transaction without PumpFun program ({} filtered so far)", filtered);
+                return Ok(());
+            }
+        };
+
+        // A create's own transaction carries the dev buy, which is not a rival buyer
+        let creates_token = message.instructions.iter().any(|instruction| {
+            instruction.program_id_index as usize == pump_fun_program_index
+                && PumpFunEvent::from_instruction_data(&instruction.data) == Some(PumpFunEvent::Create)
+        });
 
         // Process instructions
         for instruction in &message.instructions {
-            if instruction.program_id_index as usize == pump_fun_program_index {
-                if instruction.data.starts_with(&CREATE_DISCRIMINATOR) {
-                    self.handle_create_instruction(instruction, &full_account_list, &meta).await?;
+            if instruction.program_id_index as usize != pump_fun_program_index {
+                continue;
+            }
+
+            let event = match PumpFunEvent::from_instruction_data(&instruction.data) {
+                Some(event) if self.config.stream_events.contains(&event) => event,
+                _ => continue,
+            };
+
+            match event {
+                PumpFunEvent::Create => {
+                    let structure = create_structure(&message, &meta, pump_fun_program_index);
+                    self.handle_create_instruction(
+                        instruction,
+                        &full_account_list,
+                        &meta,
+                        structure,
+                        tx_update.slot,
+                        received_at,
+                    ).await?;
+                }
+                PumpFunEvent::Migrate => {
+                    self.handle_migration_instruction(instruction, &full_account_list, &meta).await?;
+                }
+                PumpFunEvent::Buy if !creates_token => {
+                    self.record_rival_buy(instruction, &full_account_list)?;
+                }
+                PumpFunEvent::Buy | PumpFunEvent::Sell => {
+                    debug!("Observed PumpFun {:?} instruction", event);
                 }
             }
         }
@@ -167,269 +1037,3380 @@ impl SniperBot {
         Ok(())
     }
 
-    async fn handle_create_instruction(
-        &self,
-        instruction: &Instruction,
-        full_account_list: &[Vec<u8>],
-        meta: &Meta,
-    ) -> Result<()> {
-        if instruction.accounts.len() < 8 {
+    /// Counts a buy by another wallet into a freshly created mint against
+    /// `MAX_BUYERS_AHEAD`.
+    fn record_rival_buy(&self, instruction: &Instruction, full_account_list: &[Vec<u8>]) -> Result<()> {
+        let Some(window) = &self.buyer_window else {
+            debug!("Observed PumpFun Buy instruction");
+            return Ok(());
+        };
+        let accounts = resolve_instruction_accounts(&instruction.accounts, full_account_list)?;
+        let Some(trade) = parse_trade(&instruction.data, &accounts).filter(|trade| trade.user != self.signer.pubkey()) else {
             return Ok(());
+        };
+        if let Some(buyers) = window.record_buy(&trade.mint, Instant::now()) {
+            debug!("👣 Buyer #{} into {}: {}", buyers, trade.mint, trade.user);
         }
+        Ok(())
+    }
 
-        // Extract account keys
-        let (mint_key, bonding_curve_key, associated_bonding_curve_key, creator_vault_key) = 
-            self.extract_account_keys(instruction, full_account_list)?;
-
-        // Calculate initial SOL deposit
-        let initial_sol_lamports = self.calculate_initial_sol_deposit(
-            instruction,
-            full_account_list,
-            meta,
-            &bonding_curve_key,
-        )?;
+    /// Whether a buy into `mint` still comes early enough under
+    /// `MAX_BUYERS_AHEAD`. Same-block snipes land right behind the create, so
+    /// they are never held back.
+    fn early_enough(&self, mint: &Pubkey, seen_slot: Option<u64>) -> bool {
+        let Some(window) = self.buyer_window.as_ref().filter(|_| seen_slot.is_none()) else {
+            return true;
+        };
+        let admitted = window.admits(mint);
+        window.forget(mint);
+        match admitted {
+            Ok(ahead) => {
+                info!("👣 {} buyer(s) ahead of us in {} (limit {})", ahead, mint, window.max_buyers_ahead());
+                true
+            }
+            Err(ahead) => {
+                info!("⏭️ Skipping {}: {} buyer(s) got in first (limit {})", mint, ahead, window.max_buyers_ahead());
+                false
+            }
+        }
+    }
 
-        if initial_sol_lamports == 0 {
-            return Ok(());
+    /// Logs the PumpFun buys and sells `trader` signed in a transaction that
+    /// matched their copy-trading filter. The filter matches any transaction
+    /// mentioning the wallet, so trades by other users are left out.
+    fn handle_followed_trade(&self, trader: &Pubkey, tx_update: &TransactionUpdate) -> Result<()> {
+        let (source_signature, trades) = self.streamed_trades(tx_update)?;
+        for (trade, followed) in trades.iter().filter(|(trade, _)| trade.user == *trader) {
+            self.copy_followed_trade(trade, followed, &source_signature)?;
         }
+        Ok(())
+    }
 
-        // Calculate market cap
-        let sol_price_usd = self.price_cache.get();
-        if sol_price_usd <= 0.0 {
-            warn!("SOL price not available, skipping transaction");
+    /// Feeds every PumpFun trade in a streamed transaction to the auto
+    /// follower. Traders it followed have no filter of their own, so their
+    /// trades are copied from here; those in `routed` were already handled
+    /// through their filter. Sells count at their `min_sol_output`, so an
+    /// outcome never reads better than the trade was.
+    fn observe_traders(&self, tx_update: &TransactionUpdate, routed: &[Pubkey]) -> Result<()> {
+        let (Some(manager), Some(engine)) = (&self.auto_follow, &self.copy_engine) else {
             return Ok(());
+        };
+        let (source_signature, trades) = self.streamed_trades(tx_update)?;
+        for (trade, followed) in &trades {
+            if trade.user == self.signer.pubkey() {
+                continue;
+            }
+            manager.lock().record_trade(&mut engine.lock(), trade.user, followed);
+            if !routed.contains(&trade.user) && engine.lock().followed_traders.contains_key(&trade.user) {
+                self.copy_followed_trade(trade, followed, &source_signature)?;
+            }
         }
+        Ok(())
+    }
 
-        let sol_deposited_in_sol = initial_sol_lamports as f64 / LAMPORTS_PER_SOL as f64;
-        let k = INITIAL_VIRTUAL_SOL * INITIAL_VIRTUAL_TOKENS;
-        let virtual_sol_after = INITIAL_VIRTUAL_SOL + sol_deposited_in_sol;
-        let virtual_tokens_after = k / virtual_sol_after;
-        let current_price_in_sol = virtual_sol_after / virtual_tokens_after;
-        let current_price_usd = current_price_in_sol * sol_price_usd;
-        let market_cap_usd = current_price_usd * TOTAL_SUPPLY as f64;
-
-        if market_cap_usd >= self.config.market_cap_threshold_usd {
-            let _guard = self.processing_mutex.lock();
-            
-            info!("🎯 TARGET ACQUIRED - Market Cap: ${:.2} | Mint: {}", market_cap_usd, mint_key);
-            info!("🚀 Attempting buy transaction...");
+    /// The PumpFun buys and sells in a streamed transaction, sized by
+    /// `COPY_AMOUNT_SOURCE`, with the transaction's signature.
+    fn streamed_trades(&self, tx_update: &TransactionUpdate) -> Result<(Signature, Vec<(ObservedTrade, FollowedTrade)>)> {
+        let tx = tx_update.transaction.as_ref().ok_or_else(|| {
+            SniperError::Transaction("Missing transaction in update".to_string())
+        })?;
+        let message = tx.message.as_ref().ok_or_else(|| {
+            SniperError::Transaction("Missing message in transaction".to_string())
+        })?;
+        let meta = tx.meta.as_ref().ok_or_else(|| {
+            SniperError::Transaction("Missing meta in transaction".to_string())
+        })?;
+        let full_account_list = combined_account_keys(message, meta);
+        let source_signature = tx.signatures.first()
+            .and_then(|signature| Signature::try_from(signature.as_slice()).ok())
+            .ok_or_else(|| SniperError::Transaction("Missing signature in transaction".to_string()))?;
 
-            self.execute_buy_transaction(
-                &mint_key,
-                &bonding_curve_key,
-                &associated_bonding_curve_key,
-                &creator_vault_key,
-                initial_sol_lamports,
-            ).await?;
+        let mut trades = Vec::new();
+        for (index, instruction) in message.instructions.iter().enumerate() {
+            if account_key(&full_account_list, instruction.program_id_index as usize)? != self.program_keys.pump_fun {
+                continue;
+            }
+            let accounts = resolve_instruction_accounts(&instruction.accounts, &full_account_list)?;
+            let Some(trade) = parse_trade(&instruction.data, &accounts) else {
+                continue;
+            };
+            let transferred_lamports = trade_sol_transferred(&full_account_list, meta, index, &trade)?;
+            if let Some(followed) = FollowedTrade::new(&trade, transferred_lamports, self.config.copy_amount_source) {
+                trades.push((trade, followed));
+            }
         }
+        Ok((source_signature, trades))
+    }
+
+    fn copy_followed_trade(&self, trade: &ObservedTrade, followed: &FollowedTrade, source_signature: &Signature) -> Result<()> {
+        info!("👥 Followed trader {} {:?} {} raw tokens of {} for {:.4} SOL (SOL limit {:.4})",
+              trade.user, trade.event, trade.token_amount, trade.mint, followed.amount_sol,
+              trade.sol_limit_lamports as f64 / LAMPORTS_PER_SOL as f64);
 
+        let Some(engine) = &self.copy_engine else {
+            return Ok(());
+        };
+        let copy_amount = engine.lock().should_copy_trade(
+            &trade.user,
+            source_signature,
+            &followed.token,
+            &followed.action,
+            followed.amount_sol,
+        )?;
+        match copy_amount {
+            Some(copy_amount_sol) => info!("👥 Copy trade: {:?} {} for {:.4} SOL, mirroring {}",
+                                           followed.action, followed.token, copy_amount_sol, source_signature),
+            None => debug!("👥 Not copying {} from {}: duplicate, cooling down or below the copy criteria",
+                           source_signature, trade.user),
+        }
         Ok(())
     }
 
-    fn extract_account_keys(
+    async fn handle_create_instruction(
         &self,
         instruction: &Instruction,
         full_account_list: &[Vec<u8>],
-    ) -> Result<(Pubkey, Pubkey, Pubkey, Pubkey)> {
-        let known_programs = get_known_program_pubkeys();
-        let mut unknown_accounts = Vec::new();
-        let mut creator_key = Pubkey::default();
-        let mut global_key = Pubkey::default();
-        let mut event_authority_key = Pubkey::default();
-
-        // Process accounts
-        for (i, account_bytes) in full_account_list.iter().enumerate() {
-            let account_pk = Pubkey::try_from(account_bytes.as_slice())
-                .map_err(|e| SniperError::Transaction(format!("Invalid account key: {}", e)))?;
-
-            if i == 0 {
-                creator_key = account_pk;
-            }
-
-            if account_pk == Pubkey::from_str(KNOWN_GLOBAL)? {
-                global_key = account_pk;
-            } else if account_pk == Pubkey::from_str(KNOWN_EVENT_AUTH)? {
-                event_authority_key = account_pk;
-            } else if !known_programs.contains(&account_pk) {
-                unknown_accounts.push(account_pk);
-            }
+        meta: &Meta,
+        structure: CreateStructure,
+        create_slot: u64,
+        received_at: Instant,
+    ) -> Result<()> {
+        // Each create instruction keeps its accounts in its own order
+        let matched = discriminators().matched_create(&instruction.data).unwrap_or_default();
+        let Some(layout) = discriminators().create_layout(matched) else {
+            warn!("Skipping create with discriminator {}: its account layout is unknown", hex::encode(matched));
+            return Ok(());
+        };
+        if instruction.accounts.len() <= layout.user {
+            debug!("Skipping create instruction with only {} accounts", instruction.accounts.len());
+            return Ok(());
         }
 
-        // Find mint key (ends with "pump")
-        let mint_key = unknown_accounts
-            .iter()
-            .find(|pk| pk.to_string().ends_with("pump"))
-            .copied()
-            .unwrap_or_else(|| {
-                // Fallback: use first instruction account
-                if !instruction.accounts.is_empty() {
-                    Pubkey::try_from(full_account_list[instruction.accounts[0] as usize].as_slice())
-                        .unwrap_or_default()
-                } else {
-                    Pubkey::default()
-                }
-            });
+        // The slot the create landed in, for landing the buy right behind it
+        let seen_slot = self.same_block_snipe.as_ref().map(|_| create_slot);
+        let replayed = self.latest_stream_slot()
+            .is_some_and(|slot| slot.saturating_sub(create_slot) > REPLAYED_CREATE_SLOT_LAG);
 
-        // Find bonding curve and associated bonding curve keys
-        let remaining_accounts: Vec<_> = unknown_accounts
-            .into_iter()
-            .filter(|pk| *pk != mint_key && *pk != creator_key)
-            .collect();
+        // Resolve the new token's accounts through the create's own account indices
+        let accounts = resolve_instruction_accounts(&instruction.accounts, full_account_list)?;
+        let create = parse_create_accounts(layout, &accounts).ok_or_else(|| {
+            SniperError::Transaction(format!(
+                "Create instruction has only {} accounts or no token program where its layout puts one",
+                accounts.len()
+            ))
+        })?;
+        let PumpFunAccounts {
+            mint: mint_key,
+            bonding_curve: bonding_curve_key,
+            associated_bonding_curve: associated_bonding_curve_key,
+            creator_vault: creator_vault_key,
+        } = create.accounts;
 
-        let bonding_curve_key = if remaining_accounts.len() >= 2 {
-            remaining_accounts[0]
-        } else if instruction.accounts.len() > 2 {
-            Pubkey::try_from(full_account_list[instruction.accounts[2] as usize].as_slice())?
-        } else {
-            return Err(SniperError::Transaction("Could not find bonding curve key".to_string()));
+        // Calculate initial SOL deposit
+        let initial_sol_lamports = initial_sol_deposit(full_account_list, meta, &create)?;
+
+        if initial_sol_lamports == 0 {
+            return Ok(());
+        }
+
+        let dev_buy_sol = initial_sol_lamports as f64 / LAMPORTS_PER_SOL as f64;
+        info!("🆕 Create detected - Mint: {} | Dev buy: {:.4} SOL", mint_key, dev_buy_sol);
+        info!(
+            "🧬 {} create transaction: {} signers, {} writable accounts, {} buys",
+            mint_key, structure.signers, structure.writable_accounts, structure.buys
+        );
+        if let Some(window) = self.buyer_window.as_ref().filter(|_| seen_slot.is_none()) {
+            window.track(mint_key, received_at);
+        }
+
+        if self.pnl.lock().is_halted() {
+            info!("⏭️ Skipping {}: session halted after hitting its loss limit", mint_key);
+            return Ok(());
+        }
+        if self.dead_man_switch.as_ref().is_some_and(|switch| switch.lock().is_tripped()) {
+            info!("⏭️ Skipping {}: the dead man's switch is tripped", mint_key);
+            return Ok(());
+        }
+
+        // Calculate market cap; SOL is all the market cap band needs unless a bound is in USD
+        let sol_price_usd = self.price_cache.get();
+        let (min_market_cap, max_market_cap) = (self.config.min_market_cap(), self.config.max_market_cap());
+        let needs_sol_price = min_market_cap.needs_sol_price() || max_market_cap.is_some_and(|max| max.needs_sol_price());
+        if sol_price_usd <= 0.0 && needs_sol_price {
+            warn!("SOL price not available for the USD market cap band, skipping transaction");
+            return Ok(());
+        }
+
+        let sol_deposited_in_sol = initial_sol_lamports as f64 / LAMPORTS_PER_SOL as f64;
+        let k = INITIAL_VIRTUAL_SOL * INITIAL_VIRTUAL_TOKENS;
+        let virtual_sol_after = INITIAL_VIRTUAL_SOL + sol_deposited_in_sol;
+        let virtual_tokens_after = k / virtual_sol_after;
+        let current_price_in_sol = virtual_sol_after / virtual_tokens_after;
+        let market_cap_sol = current_price_in_sol * TOTAL_SUPPLY as f64;
+        let market_cap_usd = market_cap_sol * sol_price_usd.max(0.0);
+        info!("📊 {} market cap: {}", mint_key, format_market_cap(market_cap_sol, sol_price_usd));
+
+        let _mint_guard = match self.mint_locks.try_lock(&mint_key) {
+            Some(guard) => guard,
+            None => {
+                debug!("Mint {} is already being processed, skipping", mint_key);
+                return Ok(());
+            }
         };
 
-        let associated_bonding_curve_key = if remaining_accounts.len() >= 2 {
-            remaining_accounts[1]
-        } else if instruction.accounts.len() > 3 {
-            Pubkey::try_from(full_account_list[instruction.accounts[3] as usize].as_slice())?
+        if matched == discriminators().create {
+            debug!("{} created with discriminator {}", mint_key, hex::encode(matched));
         } else {
-            return Err(SniperError::Transaction("Could not find associated bonding curve key".to_string()));
+            info!("🔑 {} created with alternate discriminator {}", mint_key, hex::encode(matched));
+        }
+
+        let (name, symbol, uri) = instruction.data
+            .get(CREATE_DISCRIMINATOR.len()..)
+            .and_then(parse_create_args)
+            .unwrap_or_else(|| {
+                debug!("Could not parse create args for {}, scoring it without name or symbol", mint_key);
+                Default::default()
+            });
+        // A same-block snipe has no time for a metadata round trip
+        let socials = match seen_slot {
+            Some(_) => None,
+            None => self.fetch_social_links(&mint_key, &uri).await,
+        };
+        let candidate = BuyCandidate {
+            metadata: TokenMetadata {
+                mint: mint_key,
+                name,
+                symbol,
+                description: String::new(),
+                image_uri: uri,
+                creator: create.creator,
+                creation_time: received_at,
+                initial_supply: TOTAL_SUPPLY,
+                decimals: TOKEN_DECIMALS,
+                authorities: self.fetch_mint_authorities(&mint_key),
+                socials,
+            },
+            associated_bonding_curve: associated_bonding_curve_key,
+            dev_buy_sol,
+            market_cap_sol,
+            market_cap_usd,
+            sol_price_usd,
+            create_structure: structure,
         };
+        if let GateDecision::Deny(reason) = evaluate_gates(&self.buy_gates, &candidate).await.with_mint(&mint_key)? {
+            info!("⏭️ Skipping {}: {}", mint_key, reason);
+            return Ok(());
+        }
 
-        // Find creator vault key
-        let creator_vault_key = if full_account_list.len() > 7 {
-            Pubkey::try_from(full_account_list[7].as_slice())?
-        } else {
-            return Err(SniperError::Transaction("Could not find creator vault key".to_string()));
+        // Optionally sit out the first moments of the launch and buy only if it
+        // still qualifies; same-block snipes can't wait
+        let (mut market_cap_sol, mut initial_sol_lamports) = (market_cap_sol, initial_sol_lamports);
+        if let Some(delay) = self.config.entry_delay.filter(|_| seen_slot.is_none()) {
+            info!("⏳ Delaying entry into {} by {:?} - Market Cap: {} | Curve: {:.4} SOL",
+                  mint_key, delay, format_market_cap(market_cap_sol, sol_price_usd), dev_buy_sol);
+            let samples = self.sample_bonding_curve(&bonding_curve_key, delay).await;
+            let rug = RugMonitorConfig {
+                drop_percentage: self.config.rug_drop_percentage,
+                window: self.config.rug_window,
+                reaction: self.config.rug_reaction,
+            };
+            match entry_still_qualifies(&mint_key, &samples, sol_price_usd, min_market_cap, max_market_cap,
+                                        self.config.min_curve_sol, rug) {
+                Ok((cap_sol, curve)) => {
+                    info!("✅ {} still qualifies after entry delay - Market Cap: {} | Curve: {:.4} SOL",
+                          mint_key, format_market_cap(cap_sol, sol_price_usd), curve.real_sol);
+                    market_cap_sol = cap_sol;
+                    initial_sol_lamports = (curve.real_sol * LAMPORTS_PER_SOL as f64) as u64;
+                }
+                Err(reason) => {
+                    info!("⏭️ Skipping {} after entry delay: {}", mint_key, reason);
+                    return Ok(());
+                }
+            }
+        }
+
+        if !self.keeps_reserve(&mint_key) {
+            return Ok(());
+        }
+
+        if !self.reserve_creator_slot(&create.creator, &mint_key) {
+            return Ok(());
+        }
+        let Some(buy_slot) = self.claim_buy_slot(&mint_key) else {
+            self.release_creator_slot(&create.creator);
+            return Ok(());
+        };
+
+        let buy_permit = self.buy_semaphore
+            .acquire()
+            .await
+            .map_err(|e| SniperError::Transaction(format!("Buy semaphore closed: {}", e)));
+        let bought = match buy_permit {
+            Ok(_) if !self.early_enough(&mint_key, seen_slot) => Ok(()),
+            Ok(_buy_permit) => {
+                info!("🎯 TARGET ACQUIRED - Market Cap: {} | Mint: {}",
+                      format_market_cap(market_cap_sol, sol_price_usd), mint_key);
+                self.notifications.send(TradeEvent::TargetAcquired {
+                    mint: mint_key,
+                    market_cap_usd: market_cap_sol * sol_price_usd.max(0.0),
+                });
+                info!("🚀 Attempting buy transaction...");
+
+                self.execute_buy_transaction(&create, initial_sol_lamports, received_at, seen_slot, replayed)
+                    .await
+                    .with_mint(&mint_key)
+            }
+            Err(e) => Err(e),
         };
+        // A landed buy now counts as an open position instead
+        self.release_creator_slot(&create.creator);
+        self.release_buy_slot(buy_slot);
+        bought?;
+
+        // Keep scoring the token for as long as it is held
+        if let Some(scam_guard) = &self.scam_guard {
+            if self.position_manager.lock().get_position(&mint_key).is_some() {
+                scam_guard.lock().await.track(candidate.metadata, Instant::now());
+            }
+        }
 
-        Ok((mint_key, bonding_curve_key, associated_bonding_curve_key, creator_vault_key))
+        Ok(())
     }
 
-    fn calculate_initial_sol_deposit(
+    async fn handle_migration_instruction(
         &self,
         instruction: &Instruction,
         full_account_list: &[Vec<u8>],
         meta: &Meta,
-        bonding_curve_key: &Pubkey,
-    ) -> Result<u64> {
-        let mut initial_sol_lamports = 0u64;
-        let creator_key = Pubkey::try_from(full_account_list[0].as_slice())?;
-
-        for inner_instruction in &meta.inner_instructions {
-            for inst in &inner_instruction.instructions {
-                let prog_key = Pubkey::try_from(full_account_list[inst.program_id_index as usize].as_slice())?;
-                
-                if prog_key == solana_sdk::system_program::ID {
-                    if inst.data.len() >= 8 {
-                        let instruction_type = u32::from_le_bytes([
-                            inst.data[0], inst.data[1], inst.data[2], inst.data[3]
-                        ]);
-                        
-                        if instruction_type == system_instruction::SystemInstruction::Transfer as u32 {
-                            let source_key = Pubkey::try_from(full_account_list[inst.accounts[0] as usize].as_slice())?;
-                            let destination_key = Pubkey::try_from(full_account_list[inst.accounts[1] as usize].as_slice())?;
-                            let lamports = u64::from_le_bytes([
-                                inst.data[4], inst.data[5], inst.data[6], inst.data[7],
-                                inst.data[8], inst.data[9], inst.data[10], inst.data[11],
-                            ]);
-
-                            if destination_key == *bonding_curve_key && source_key == creator_key {
-                                if lamports > initial_sol_lamports {
-                                    initial_sol_lamports = lamports;
-                                }
-                            }
-                        }
-                    }
-                }
+    ) -> Result<()> {
+        let accounts = resolve_instruction_accounts(&instruction.accounts, full_account_list)?;
+
+        let migration = match parse_migration_instruction(&instruction.data, &accounts) {
+            Some(migration) => migration,
+            None => return Ok(()),
+        };
+
+        // SOL drained from the bonding curve is the liquidity moved into the pool
+        let liquidity_migrated = full_account_list
+            .iter()
+            .position(|key_bytes| key_bytes.as_slice() == migration.bonding_curve.as_ref())
+            .and_then(|index| Some((*meta.pre_balances.get(index)?, *meta.post_balances.get(index)?)))
+            .map(|(pre, post)| pre.saturating_sub(post) as f64 / LAMPORTS_PER_SOL as f64)
+            .unwrap_or(0.0);
+
+        let migration_event = self.season2.lock().process_migration_instruction(&migration, liquidity_migrated);
+        self.apply_migration_policy(&migration_event.token_mint).await;
+        Ok(())
+    }
+
+    /// Reads the bonding curve every `ENTRY_DELAY_SAMPLE_INTERVAL` until `delay`
+    /// has passed, returning the samples oldest first. Failed reads are skipped.
+    async fn sample_bonding_curve(&self, bonding_curve_key: &Pubkey, delay: Duration) -> Vec<(Instant, BondingCurveState)> {
+        let deadline = Instant::now() + delay;
+        let mut samples = Vec::new();
+        loop {
+            match self.fetch_bonding_curve_state(bonding_curve_key) {
+                Ok(curve) => samples.push((Instant::now(), curve)),
+                Err(e) => debug!("Entry delay sample failed: {}", e),
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return samples;
+            }
+            tokio::time::sleep((deadline - now).min(ENTRY_DELAY_SAMPLE_INTERVAL)).await;
+        }
+    }
+
+    /// Counts a buy into `creator`'s launch against `MAX_POSITIONS_PER_CREATOR`,
+    /// together with positions already held and buys still in flight for the
+    /// same creator. Returns `false`, and logs the skip, once the limit is hit.
+    fn reserve_creator_slot(&self, creator: &Pubkey, mint: &Pubkey) -> bool {
+        let Some(limit) = self.config.max_positions_per_creator else {
+            return true;
+        };
+
+        let mut in_flight = self.creator_buys_in_flight.lock();
+        let held = self.position_manager.lock().positions_by_creator(creator);
+        let pending = in_flight.get(creator).copied().unwrap_or(0);
+        if held + pending >= limit {
+            info!("⏭️ Skipping {}: creator {} already has {} position(s) and {} buy(s) in flight (limit {})",
+                  mint, creator, held, pending, limit);
+            return false;
+        }
+        *in_flight.entry(*creator).or_insert(0) += 1;
+        true
+    }
+
+    fn release_creator_slot(&self, creator: &Pubkey) {
+        if self.config.max_positions_per_creator.is_none() {
+            return;
+        }
+        let mut in_flight = self.creator_buys_in_flight.lock();
+        if let Some(pending) = in_flight.get_mut(creator) {
+            *pending -= 1;
+            if *pending == 0 {
+                in_flight.remove(creator);
             }
         }
+    }
+
+    /// Enforces `global_buy_cooldown` across all mints. Returns `None`, and
+    /// logs the skip, while the cooldown runs; otherwise holds it off other
+    /// buys until the claim is released.
+    fn claim_buy_slot(&self, mint: &Pubkey) -> Option<BuySlot> {
+        let now = Instant::now();
+        let Some(cooldown) = self.config.global_buy_cooldown else {
+            return Some(BuySlot { claimed_at: now, previous: None });
+        };
+
+        let mut last_buy_at = self.last_buy_at.lock();
+        if let Some(remaining) = cooldown_remaining(*last_buy_at, cooldown, now) {
+            info!("⏭️ Skipping {}: global buy cooldown, {:?} remaining", mint, remaining);
+            return None;
+        }
+        let previous = last_buy_at.replace(now);
+        Some(BuySlot { claimed_at: now, previous })
+    }
+
+    /// Restarts the global buy cooldown from now, once a buy has been submitted.
+    fn start_buy_cooldown(&self) {
+        if self.config.global_buy_cooldown.is_some() {
+            *self.last_buy_at.lock() = Some(Instant::now());
+        }
+    }
 
-        Ok(initial_sol_lamports)
+    /// Gives back a claim whose buy was never submitted, so a buy that was
+    /// skipped or failed before sending doesn't hold off the next one.
+    fn release_buy_slot(&self, slot: BuySlot) {
+        if self.config.global_buy_cooldown.is_none() {
+            return;
+        }
+        let mut last_buy_at = self.last_buy_at.lock();
+        if *last_buy_at == Some(slot.claimed_at) {
+            *last_buy_at = slot.previous;
+        }
     }
 
     async fn execute_buy_transaction(
         &self,
-        mint_key: &Pubkey,
-        bonding_curve_key: &Pubkey,
-        associated_bonding_curve_key: &Pubkey,
-        creator_vault_key: &Pubkey,
+        create: &CreateAccounts,
         initial_sol_lamports: u64,
+        received_at: Instant,
+        seen_slot: Option<u64>,
+        replayed: bool,
     ) -> Result<()> {
-        // Get buyer's ATA
-        let buyer_ata = get_associated_token_address(&self.buyer_keypair.pubkey(), mint_key);
+        let PumpFunAccounts {
+            mint: ref mint_key,
+            bonding_curve: ref bonding_curve_key,
+            associated_bonding_curve: ref associated_bonding_curve_key,
+            creator_vault: ref creator_vault_key,
+        } = create.accounts;
 
-        // Get recent blockhash
-        let recent_blockhash = self.rpc_client
-            .get_latest_blockhash()
-            .map_err(|e| SniperError::SolanaClient(format!("Failed to get recent blockhash: {}", e)))?;
+        // Get recent blockhash; dry runs never reach the network, so any hash will do,
+        // and same-block snipes take the block tracker's cached one
+        let blockhash_started = Instant::now();
+        let (mut recent_blockhash, mut last_valid_block_height) = if self.config.dry_run {
+            (Hash::default(), 0)
+        } else if let Some(snipe) = self.same_block_snipe.as_ref().filter(|_| seen_slot.is_some()) {
+            snipe.recent_blockhash().await?
+        } else {
+            self.latest_blockhash_with_height()?
+        };
+        let blockhash_fetch = blockhash_started.elapsed();
+        let build_started = Instant::now();
 
         // Calculate buy parameters
         let sol_deposited_in_sol = initial_sol_lamports as f64 / LAMPORTS_PER_SOL as f64;
         let k = INITIAL_VIRTUAL_SOL * INITIAL_VIRTUAL_TOKENS;
         let current_virtual_sol = INITIAL_VIRTUAL_SOL + sol_deposited_in_sol;
         let current_virtual_tokens = k / current_virtual_sol;
-        let virtual_sol_after_buy = current_virtual_sol + self.config.buy_amount_sol;
-        let virtual_tokens_after_buy = k / virtual_sol_after_buy;
-        let tokens_to_buy = current_virtual_tokens - virtual_tokens_after_buy;
-        let token_amount_to_buy = (tokens_to_buy * 1_000_000.0) as u64;
-        let max_sol_cost_lamports = (self.config.buy_amount_sol * LAMPORTS_PER_SOL as f64 * 1.20) as u64;
-
-        // Build buy instruction data
-        let mut buy_instruction_data = PUMPFUN_BUY_DISCRIMINATOR.to_vec();
-        buy_instruction_data.extend_from_slice(&token_amount_to_buy.to_le_bytes());
-        buy_instruction_data.extend_from_slice(&max_sol_cost_lamports.to_le_bytes());
-
-        // Create transaction
-        let mut instructions = vec![
-            compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(400_000),
-            compute_budget::ComputeBudgetInstruction::set_compute_unit_price(500_000),
-        ];
+        // Exact tokens out; the slippage buffer widens the SOL cap only
+        let (token_amount_to_buy, mut max_sol_cost_lamports) = size_buy(
+            current_virtual_sol,
+            current_virtual_tokens,
+            self.config.buy_amount_sol,
+            self.config.buy_slippage_percentage,
+        );
+        let tokens_to_buy = token_amount_to_buy as f64 / 1_000_000.0;
 
-        // Add ATA creation instruction
-        instructions.push(spl_associated_token_account::instruction::create_associated_token_account(
-            &self.buyer_keypair.pubkey(),
-            &self.buyer_keypair.pubkey(),
-            mint_key,
-            &spl_token::id(),
-        ));
+        // Re-read the curve right before signing when the create was replayed,
+        // since its token may have graduated since, or when
+        // MAX_SEND_SLIPPAGE_PERCENTAGE asks for buys landing since detection to
+        // be priced in. Otherwise the read only slows a fresh create's buy down;
+        // a same-block snipe never reads, its create is from this slot.
+        let detected_reserves = (current_virtual_sol, current_virtual_tokens);
+        if seen_slot.is_none() && (replayed || self.config.max_send_slippage_percentage.is_some()) {
+            let repriced = self.reprice_at_send(
+                mint_key,
+                bonding_curve_key,
+                token_amount_to_buy,
+                detected_reserves,
+                max_sol_cost_lamports,
+            );
+            match repriced {
+                Some(max_sol_cost) => max_sol_cost_lamports = max_sol_cost,
+                None => return Ok(()),
+            }
+        }
 
-        // Add PumpFun buy instruction
-        let pump_fun_pk = Pubkey::from_str(PUMP_FUN_PROGRAM_ID)?;
-        let global_key = Pubkey::from_str(KNOWN_GLOBAL)?;
-        let event_authority_key = Pubkey::from_str(KNOWN_EVENT_AUTH)?;
-        let fee_recipient_pk = Pubkey::from_str(FEE_RECIPIENT)?;
-
-        instructions.push(Instruction {
-            program_id: pump_fun_pk,
-            accounts: vec![
-                AccountMeta::new_readonly(global_key, false),
-                AccountMeta::new(fee_recipient_pk, false),
-                AccountMeta::new(*mint_key, false),
-                AccountMeta::new(*bonding_curve_key, false),
-                AccountMeta::new(*associated_bonding_curve_key, false),
-                AccountMeta::new(buyer_ata, false),
-                AccountMeta::new(self.buyer_keypair.pubkey(), true),
-                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
-                AccountMeta::new_readonly(spl_token::id(), false),
-                AccountMeta::new(*creator_vault_key, false),
-                AccountMeta::new_readonly(event_authority_key, false),
-                AccountMeta::new_readonly(pump_fun_pk, false),
-            ],
-            data: buy_instruction_data,
-        });
+        // Buy under the token program the mint was created with
+        let token_program = create.token_program;
+        let mut params = BuyParams {
+            buyer: self.signer.pubkey(),
+            accounts: PumpFunAccounts {
+                mint: *mint_key,
+                bonding_curve: *bonding_curve_key,
+                associated_bonding_curve: *associated_bonding_curve_key,
+                creator_vault: *creator_vault_key,
+            },
+            fee_recipient: self.fee_recipients.resolve(),
+            token_program,
+            token_amount: token_amount_to_buy,
+            max_sol_cost_lamports,
+        };
 
-        let transaction = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&self.buyer_keypair.pubkey()),
-            &[&self.buyer_keypair],
+        let estimate = self.priority_fee_estimator.as_ref().and_then(|estimator| estimator.latest());
+        let mut priority_fee = self.priority_fees.start_at(estimate);
+        let Some(mut compute_unit_price) = self.gas_capped_price(mint_key, priority_fee.price_micro_lamports()) else {
+            return Ok(());
+        };
+        let mut create_token_account = true;
+        let mut transaction = self.sign_transaction(
+            &buy_instructions(&params, create_token_account, compute_unit_price)?,
             recent_blockhash,
-        );
+        ).await?;
+        let build_and_sign = build_started.elapsed();
 
-        // Send transaction
-        let signature = self.rpc_client
-            .send_and_confirm_transaction(&transaction)
-            .map_err(|e| SniperError::SolanaClient(format!("Failed to send buy transaction: {}", e)))?;
+        if self.config.dry_run {
+            info!("🧪 Dry run - built buy for {} ({} tokens, max {} lamports), not sending",
+                  mint_key, token_amount_to_buy, max_sol_cost_lamports);
+            let simulation = if self.config.dry_run_simulate {
+                self.simulate_dry_run_buy(mint_key, &transaction)
+            } else {
+                None
+            };
+            self.dry_run_buys.lock().push(DryRunBuy {
+                mint: *mint_key,
+                token_amount: token_amount_to_buy,
+                max_sol_cost_lamports,
+                transaction,
+                simulation,
+            });
+            return Ok(());
+        }
 
-        info!("✅ Buy Transaction sent! Signature: {}", signature);
-        info!("🔍 View on Solscan: https://solscan.io/tx/{}", signature);
+        // Held only once the buy meets the confirmation policy; until then it is pending
+        let position = Position {
+            mint: *mint_key,
+            bonding_curve: *bonding_curve_key,
+            associated_bonding_curve: *associated_bonding_curve_key,
+            creator_vault: *creator_vault_key,
+            creator: create.creator,
+            token_program,
+            token_amount: token_amount_to_buy,
+            entry_price_sol: self.config.buy_amount_sol / tokens_to_buy,
+            entry_sol: self.config.buy_amount_sol,
+            entry_fees_sol: 0.0,
+            opened_at: Instant::now(),
+            exiting: false,
+            average_down_buys: 0,
+        };
+
+        // Send transaction, then wait for confirmation separately so both stages are timed.
+        // If the token account turns out to exist already, e.g. because another of our
+        // attempts created it first, resend the buy without the create. A buy that
+        // is not confirmed in time is rebroadcast as is while its blockhash is valid,
+        // and only re-signed on a fresh blockhash at an escalated price once that
+        // has expired without it landing, so two attempts can never both land.
+        let mut ata_retries_left = self.config.ata_race_retries;
+        let mut in_flight: Option<(Signature, FeeBreakdown, Duration)> = None;
+        let mut unresolved = false;
+        // Every attempt that went out paid its fees, whichever one lands
+        let mut entry_fees_sol = 0.0;
+        let (signature, fees, to_submit, confirmation) = loop {
+            let priority_lamports = priority_fee_lamports(COMPUTE_UNIT_LIMIT, compute_unit_price);
+            let watched = self.watch_status(&transaction);
+            let sent = match in_flight {
+                // Still waiting on an attempt already sent and booked
+                Some((signature, fees, to_submit)) => {
+                    let confirmation = self.wait_for_confirmation("Buy", &signature, &self.buy_confirmation).await;
+                    Ok((signature, fees, to_submit, confirmation))
+                }
+                None => match self.submit_buy(&transaction, seen_slot, priority_lamports).await {
+                    Ok((signature, fees)) => {
+                        let to_submit = received_at.elapsed();
+                        self.start_buy_cooldown();
+                        entry_fees_sol += fees.total();
+                        info!("✅ Buy Transaction sent! Signature: {}", signature);
+                        info!("🔍 View on Solscan: https://solscan.io/tx/{}", signature);
+
+                        self.position_manager.lock().add_pending(
+                            Position { entry_fees_sol, ..position.clone() },
+                            signature,
+                            Instant::now(),
+                            last_valid_block_height,
+                        );
+                        let confirmation = self.wait_for_confirmation("Buy", &signature, &self.buy_confirmation).await;
+                        Ok((signature, fees, to_submit, confirmation))
+                    }
+                    Err(e) => Err(e),
+                },
+            };
+            self.unwatch_status(watched);
+
+            let collided = match &sent {
+                Err(e) | Ok((_, _, _, Err(e))) => e.is_account_already_exists(),
+                Ok(_) => false,
+            };
+            if collided && ata_retries_left > 0 {
+                ata_retries_left -= 1;
+                in_flight = None;
+                warn!("🔁 Token account for {} already exists, resending the buy without creating it", mint_key);
+                create_token_account = false;
+                transaction = self.sign_transaction(
+                    &buy_instructions(&params, create_token_account, compute_unit_price)?,
+                    recent_blockhash,
+                ).await?;
+                continue;
+            }
+
+            if let Ok((signature, fees, to_submit, Err(e))) = &sent {
+                if e.is_not_landed() {
+                    match self.stuck_buy(signature, last_valid_block_height) {
+                        StuckBuy::InFlight => {
+                            warn!("📡 Buy {} for {} is not confirmed yet but can still land, rebroadcasting it",
+                                  signature, mint_key);
+                            self.rebroadcast_buy(&transaction);
+                            in_flight = Some((*signature, *fees, *to_submit));
+                            continue;
+                        }
+                        // The next wait confirms it or reports how it failed
+                        StuckBuy::LandedLate | StuckBuy::Failed => {
+                            info!("🕰️ Buy {} for {} landed after the wait, waiting for it to confirm", signature, mint_key);
+                            in_flight = Some((*signature, *fees, *to_submit));
+                            continue;
+                        }
+                        StuckBuy::Dropped => {
+                            // The resend is priced like the first send, against the curve as it is now
+                            let resend = priority_fee.escalate()
+                                .and_then(|price| self.gas_capped_price(mint_key, price))
+                                .and_then(|price| {
+                                    let max_sol_cost = self.reprice_at_send(
+                                        mint_key,
+                                        bonding_curve_key,
+                                        token_amount_to_buy,
+                                        detected_reserves,
+                                        params.max_sol_cost_lamports,
+                                    )?;
+                                    Some((price, max_sol_cost))
+                                });
+                            if let Some((price, max_sol_cost)) = resend {
+                                warn!("🔁 Buy for {} expired without landing, resending at {} µlamports/CU (retry {}/{})",
+                                      mint_key, price, priority_fee.retries(), self.priority_fees.max_retries);
+                                params.max_sol_cost_lamports = max_sol_cost;
+                                compute_unit_price = price;
+                                (recent_blockhash, last_valid_block_height) = self.latest_blockhash_with_height()?;
+                                transaction = self.sign_transaction(
+                                    &buy_instructions(&params, create_token_account, price)?,
+                                    recent_blockhash,
+                                ).await?;
+                                in_flight = None;
+                                continue;
+                            }
+                            info!("🧹 Buy {} for {} expired without landing, cleaning up", signature, mint_key);
+                        }
+                        // Left pending for the stale-pending sweep to resolve once it can be read
+                        StuckBuy::Unknown => {
+                            warn!("❓ Could not tell whether buy {} for {} can still land, leaving it pending", signature, mint_key);
+                            unresolved = true;
+                        }
+                    }
+                }
+            }
+            match sent {
+                Ok(sent) => break sent,
+                Err(e) => {
+                    self.position_manager.lock().drop_pending(mint_key);
+                    return Err(e);
+                }
+            }
+        };
+
+        let timings = BuyTimings {
+            blockhash_fetch,
+            build_and_sign,
+            to_submit,
+            to_confirm: confirmation.is_ok().then(|| received_at.elapsed()),
+        };
+        self.latency_tracker.record(&timings);
+        info!("⏱️ Buy {} latency - blockhash: {:?} | build+sign: {:?} | create→submit: {:?} | create→confirm: {:?}",
+              signature, timings.blockhash_fetch, timings.build_and_sign, timings.to_submit, timings.to_confirm);
+
+        match confirmation {
+            Ok(landed_slot) => {
+                if let Some(snipe) = &self.same_block_snipe {
+                    snipe.record_landing(&signature, landed_slot);
+                }
+            }
+            Err(e) if unresolved => return Err(e).with_signature(&signature),
+            Err(e) => {
+                if let Some(snipe) = &self.same_block_snipe {
+                    snipe.forget(&signature);
+                }
+                if self.position_manager.lock().drop_pending(mint_key).is_some() {
+                    warn!("🗑️ Dropped pending buy {} for {}: {}", signature, mint_key, e);
+                }
+                return Err(e).with_signature(&signature);
+            }
+        }
+
+        info!("⛽ Buy {} landed at {} µlamports/CU ({:.6} SOL priority fee) after {} resend(s)",
+              signature, compute_unit_price, fees.priority_sol, priority_fee.retries());
+        self.pnl.lock().record_buy_compute_unit_price(compute_unit_price, priority_fee.is_escalated());
+
+        self.position_manager.lock().confirm_pending(mint_key);
+        let settlement = self.settle_bought_tokens(&signature, mint_key, token_amount_to_buy).await;
+        if settlement.as_ref().is_some_and(|settlement| settlement.token_amount == 0) {
+            return Err(SniperError::Transaction(format!("Buy of {} confirmed without receiving any tokens", mint_key)))
+                .with_signature(&signature);
+        }
+        let (token_amount_to_buy, sol_spent) = match settlement {
+            Some(settlement) => (settlement.token_amount, settlement.sol_spent),
+            None => (token_amount_to_buy, self.config.buy_amount_sol),
+        };
+        self.notifications.send(TradeEvent::BuyConfirmed {
+            mint: *mint_key,
+            signature,
+            sol_spent,
+        });
+        self.journal_trade(JournalEntry {
+            timestamp: chrono::Utc::now(),
+            mint: mint_key.to_string(),
+            side: TradeSide::Buy,
+            sol_amount: sol_spent,
+            token_amount: token_amount_to_buy,
+            price_sol: journal_price_sol(sol_spent, token_amount_to_buy),
+            fees_sol: fees.base_sol + fees.priority_sol,
+            tips_sol: fees.tip_sol,
+            signature: signature.to_string(),
+            realized_pnl_sol: None,
+        });
 
         Ok(())
     }
+
+    /// Re-reads `bonding_curve` right before a buy of `token_amount`, sized at
+    /// the `detected` reserves, is signed. Returns the `max_sol_cost` to sign
+    /// with, `current` while the curve can't be read yet, or `None` to skip
+    /// the buy once the token has graduated or its price has moved too far.
+    fn reprice_at_send(
+        &self,
+        mint: &Pubkey,
+        bonding_curve: &Pubkey,
+        token_amount: u64,
+        detected: (f64, f64),
+        current: u64,
+    ) -> Option<u64> {
+        let live_curve = self.fetch_bonding_curve_state(bonding_curve);
+        if has_graduated(mint, &live_curve) {
+            return None;
+        }
+        let Ok(curve) = &live_curve else {
+            return Some(current);
+        };
+        let max_move_pct = self.config.max_send_slippage_percentage.unwrap_or(self.config.buy_slippage_percentage);
+        match reprice_buy(token_amount, detected, (curve.virtual_sol, curve.virtual_tokens),
+                          self.config.buy_slippage_percentage, max_move_pct) {
+            Ok(max_sol_cost) => Some(max_sol_cost),
+            Err(moved_pct) => {
+                info!("⏭️ Skipping {}: price moved {:.1}% since detection, over the {:.1}% allowed at send",
+                      mint, moved_pct, max_move_pct);
+                None
+            }
+        }
+    }
+
+    /// With `verify_token_balance` on, reads what a confirmed buy actually
+    /// moved from its transaction's balances and books the tokens received and
+    /// SOL spent on its position in place of the estimates. Tokens the account
+    /// held before the buy are not counted, and a buy that received none has
+    /// its empty position closed. Returns the settlement, or `None` to keep
+    /// the estimates.
+    async fn settle_bought_tokens(&self, signature: &Signature, mint: &Pubkey, estimated: u64) -> Option<BuySettlement> {
+        if !self.config.verify_token_balance {
+            return None;
+        }
+        let Some(settlement) = self.read_buy_settlement(signature, mint).await else {
+            warn!("Could not read what buy {} received, holding the estimated {} tokens of {}", signature, estimated, mint);
+            return None;
+        };
+        let received = settlement.token_amount;
+        if received == 0 {
+            warn!("❌ Buy {} of {} confirmed without receiving any tokens; closing its empty position", signature, mint);
+            self.position_manager.lock().close_position(mint);
+            return Some(settlement);
+        }
+        if received != estimated {
+            let difference = (received as f64 - estimated as f64) / estimated.max(1) as f64 * 100.0;
+            info!("🧾 {} buy received {} tokens, {:+.2}% against the estimated {}", mint, received, difference, estimated);
+        }
+        self.position_manager.lock().settle_token_amount(mint, received, settlement.sol_spent);
+        Some(settlement)
+    }
+
+    /// Waits for a sent buy or sell to meet `policy`; one still short of it
+    /// after the pending timeout counts as not landed. With streamed statuses
+    /// on, the stream says when it lands and RPC is asked only then or as a
+    /// fallback; otherwise RPC is polled throughout.
+    async fn wait_for_confirmation(&self, kind: &str, signature: &Signature, policy: &BuyConfirmationPolicy) -> Result<u64> {
+        let deadline = Instant::now() + policy.pending_timeout;
+        // Counted from the send, so streaming waits a full fallback interval first
+        let mut last_poll = Instant::now();
+        loop {
+            let streamed = self.stream_statuses.as_ref().and_then(|statuses| statuses.status(signature));
+            if let Some(outcome) = streamed.and_then(|status| streamed_status_outcome(status, policy)) {
+                return outcome;
+            }
+            if should_poll_status(self.stream_statuses.is_some(), streamed, last_poll.elapsed()) {
+                last_poll = Instant::now();
+                match self.rpc_client.get_signature_statuses(&[*signature]) {
+                    Ok(response) => {
+                        let status = response.value.into_iter().next().flatten();
+                        if let Some(outcome) = status_outcome(kind, status.as_ref(), policy) {
+                            return outcome;
+                        }
+                    }
+                    Err(e) => debug!("Could not fetch status of {} {}: {}", kind.to_lowercase(), signature, e),
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(not_landed(kind, policy.pending_timeout));
+            }
+            match (&self.stream_statuses, streamed) {
+                (Some(statuses), None) => {
+                    statuses.wait_for(signature, BUY_STATUS_POLL_INTERVAL).await;
+                }
+                _ => tokio::time::sleep(BUY_STATUS_POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Starts watching the stream for `transaction`'s status, when streamed
+    /// statuses are on. Returns the signature to hand to [`Self::unwatch_status`].
+    fn watch_status(&self, transaction: &Transaction) -> Option<Signature> {
+        let statuses = self.stream_statuses.as_ref()?;
+        let signature = *transaction.signatures.first()?;
+        statuses.watch(signature);
+        Some(signature)
+    }
+
+    fn unwatch_status(&self, watched: Option<Signature>) {
+        if let (Some(statuses), Some(signature)) = (&self.stream_statuses, watched) {
+            statuses.unwatch(&signature);
+        }
+    }
+
+    /// Sends a sell and waits for it to land, booking its fees once it is
+    /// out. With streamed statuses on, the stream reports the landing;
+    /// otherwise the RPC sends and confirms it in one call, and the fees are
+    /// booked if it landed, failed or not, see [`landed_on_chain`].
+    async fn send_and_confirm_sell(
+        &self,
+        transaction: &Transaction,
+        kind: &str,
+        priority_lamports: u64,
+    ) -> Result<(Signature, FeeBreakdown)> {
+        let send_failed = |e| SniperError::SolanaClient(format!("Failed to send {} transaction: {}", kind, e));
+        let client = self.sell_client();
+        if self.stream_statuses.is_none() {
+            let sent = client.send_and_confirm_transaction(transaction);
+            let fees = landed_on_chain(&sent).then(|| self.record_fees(transaction, priority_lamports, 0));
+            return sent.map(|signature| (signature, fees.unwrap_or_default())).map_err(send_failed);
+        }
+        let policy = BuyConfirmationPolicy {
+            commitment: client.commitment(),
+            min_confirmations: 0,
+            pending_timeout: self.buy_confirmation.pending_timeout,
+        };
+        let watched = self.watch_status(transaction);
+        let confirmed = match client.send_transaction(transaction) {
+            Ok(signature) => {
+                let fees = self.record_fees(transaction, priority_lamports, 0);
+                self.wait_for_confirmation("Sell", &signature, &policy).await.map(|_| (signature, fees))
+            }
+            Err(e) => Err(send_failed(e)),
+        };
+        self.unwatch_status(watched);
+        confirmed
+    }
+
+    /// Builds a transaction paid for by the buyer, with a single compute budget,
+    /// and has the signer sign it.
+    async fn sign_transaction(&self, instructions: &[Instruction], recent_blockhash: Hash) -> Result<Transaction> {
+        let instructions = finalize_instructions(instructions.to_vec());
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&self.signer.pubkey()));
+        transaction.message.recent_blockhash = recent_blockhash;
+        self.signer.sign(&mut transaction).await?;
+        Ok(transaction)
+    }
+
+    /// Reads the social links from `mint`'s metadata at `uri`, when they are
+    /// checked. `None` means they are not checked or the fetch failed or timed out.
+    async fn fetch_social_links(&self, mint: &Pubkey, uri: &str) -> Option<SocialLinks> {
+        let fetcher = self.social_links.as_ref()?;
+        if uri.is_empty() {
+            return None;
+        }
+        match fetcher.fetch(uri).await {
+            Ok(socials) => Some(socials),
+            Err(e) => {
+                debug!("Could not read the social links of {} from {}: {}", mint, uri, e);
+                None
+            }
+        }
+    }
+
+    /// Reads who can still mint or freeze `mint`, when `CHECK_MINT_AUTHORITIES`
+    /// is on. `None` means the mint account could not be read. This is a
+    /// blocking read in the create's path, which is why the check is off by
+    /// default: PumpFun's creates renounce both authorities anyway.
+    fn fetch_mint_authorities(&self, mint: &Pubkey) -> Option<MintAuthorities> {
+        if !self.config.check_mint_authorities {
+            return None;
+        }
+        // The create was seen at processed, so read the mint at processed too
+        let account = match self.read_rpc(|client| {
+            client.get_account_with_commitment(mint, CommitmentConfig::processed())
+        }) {
+            Ok(response) => response.value,
+            Err(e) => {
+                debug!("Could not fetch mint {} ({}), authorities unknown", mint, e);
+                return None;
+            }
+        }?;
+        match MintAuthorities::from_mint_data(&account.data) {
+            Ok(authorities) => {
+                if !authorities.is_renounced() {
+                    info!("🔑 {} has live authorities - mint: {:?} | freeze: {:?}",
+                          mint, authorities.mint_authority, authorities.freeze_authority);
+                }
+                Some(authorities)
+            }
+            Err(e) => {
+                warn!("Failed to parse mint {}: {}", mint, e);
+                None
+            }
+        }
+    }
+
+    /// Sends a signed buy, first as a same-block Jito snipe when that mode is on.
+    /// A missed slot or a failed snipe falls back to a regular send, except for
+    /// a bundle rejected under `JITO_STRICT`, which aborts the buy. With
+    /// `FANOUT_SEND`, a regular send goes to every send endpoint at once.
+    async fn submit_buy(
+        &self,
+        transaction: &Transaction,
+        seen_slot: Option<u64>,
+        priority_lamports: u64,
+    ) -> Result<(Signature, FeeBreakdown)> {
+        if let (Some(snipe), Some(seen_slot)) = (&self.same_block_snipe, seen_slot) {
+            // The tip rides in its own transaction behind the buy, signed over
+            // the buy's blockhash so the bundle lands whole or not at all
+            let tip = match snipe.bundle_tip(self.config.buy_amount_sol) {
+                Some(tip_lamports) => {
+                    let tip_transfer = snipe.tip_transfer(&self.signer.pubkey(), tip_lamports);
+                    let mut tip_transaction = Transaction::new_with_payer(&[tip_transfer], Some(&self.signer.pubkey()));
+                    tip_transaction.message.recent_blockhash = transaction.message.recent_blockhash;
+                    self.signer.sign(&mut tip_transaction).await?;
+                    Some((tip_transaction, tip_lamports))
+                }
+                None => None,
+            };
+            let bundled_tip = tip.as_ref().map(|(tip_transaction, tip_lamports)| (tip_transaction, *tip_lamports));
+            match snipe.snipe(transaction, bundled_tip, seen_slot).await {
+                Ok(SnipeOutcome::Submitted { signature, tip_lamports, bundle_status }) => {
+                    if let BundleStatus::FellBack { reason } = &bundle_status {
+                        warn!("Jito rejected the buy bundle, sent over regular RPC instead: {}", reason);
+                    }
+                    // An accepted bundle also pays the tip transaction's own signature
+                    let tip_lamports = if tip_lamports > 0 { tip_lamports + LAMPORTS_PER_SIGNATURE } else { 0 };
+                    let fees = self.record_fees(transaction, priority_lamports, tip_lamports);
+                    return Ok((signature, fees));
+                }
+                Ok(SnipeOutcome::MissedBlock { seen_slot, current_slot }) => {
+                    warn!("⌛ Missed same-block snipe (create in slot {}, now {}), sending regular buy",
+                          seen_slot, current_slot);
+                }
+                Err(e) if e.is_bundle_rejected() => {
+                    warn!("🛑 Jito rejected the buy bundle, aborting (JITO_STRICT): {}", e);
+                    return Err(e);
+                }
+                Err(e) => warn!("Same-block snipe failed, sending regular buy: {}", e),
+            }
+        }
+
+        let signature = if self.fanout_senders.is_empty() {
+            self.send_rpc_client
+                .send_transaction(transaction)
+                .map_err(|e| classify_client_error("Failed to send buy transaction", &e))?
+        } else {
+            fanout_send(&self.fanout_senders, transaction).map_err(|errors| {
+                for (endpoint, e) in &errors[1..] {
+                    debug!("Buy send to {} failed: {}", endpoint, e);
+                }
+                let (endpoint, e) = &errors[0];
+                classify_client_error(&format!("Failed to send buy transaction to any endpoint (first: {})", endpoint), e)
+            })?
+        };
+        Ok((signature, self.record_fees(transaction, priority_lamports, 0)))
+    }
+
+    /// The compute unit price a buy of `mint` may be signed with under
+    /// `MAX_GAS_PRICE_LAMPORTS`: `price_micro_lamports` itself when its fee
+    /// fits, otherwise the clamped price or `None` to skip, per `GAS_CEILING_ACTION`.
+    fn gas_capped_price(&self, mint: &Pubkey, price_micro_lamports: u64) -> Option<u64> {
+        let max_gas_price = self.config.max_gas_price_lamports;
+        let capped = gas_capped_price(price_micro_lamports, COMPUTE_UNIT_LIMIT, max_gas_price, self.config.gas_ceiling_action);
+        match capped {
+            Some(price) if price < price_micro_lamports => {
+                info!("⛽ Clamped the buy of {} from {} to {} µlamports/CU under the {} lamport gas ceiling",
+                      mint, price_micro_lamports, price, max_gas_price);
+            }
+            Some(_) => {}
+            None => {
+                info!("⛽ Skipping the buy of {}: {} µlamports/CU would pay {} lamports, over the {} lamport gas ceiling",
+                      mint, price_micro_lamports, priority_fee_lamports(COMPUTE_UNIT_LIMIT, price_micro_lamports), max_gas_price);
+            }
+        }
+        capped
+    }
+
+    /// Books the base and priority fee of a sent transaction, plus any tip,
+    /// against session PnL, and returns what this transaction paid. Called once
+    /// the transaction is out, whether or not it goes on to succeed, since one
+    /// that lands and fails pays its fees all the same; a send the RPC rejects
+    /// costs nothing and is not booked.
+    fn record_fees(&self, transaction: &Transaction, priority_lamports: u64, tip_lamports: u64) -> FeeBreakdown {
+        let to_sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL as f64;
+        let fees = FeeBreakdown {
+            base_sol: to_sol(transaction.signatures.len() as u64 * LAMPORTS_PER_SIGNATURE),
+            priority_sol: to_sol(priority_lamports),
+            tip_sol: to_sol(tip_lamports),
+        };
+
+        let mut pnl = self.pnl.lock();
+        let mut events = vec![pnl.record_fee(FeeKind::Base, fees.base_sol)];
+        if priority_lamports > 0 {
+            events.push(pnl.record_fee(FeeKind::Priority, fees.priority_sol));
+        }
+        if tip_lamports > 0 {
+            events.push(pnl.record_fee(FeeKind::Tip, fees.tip_sol));
+        }
+        drop(pnl);
+
+        for event in events.into_iter().flatten() {
+            if let SessionEvent::FeeBudgetExceeded { fees_sol, budget_sol } = event {
+                self.notifications.send(TradeEvent::FeeBudgetExceeded { fees_sol, budget_sol });
+            }
+        }
+        fees
+    }
+
+    /// Appends a completed trade to the trade journal, when one is configured.
+    fn journal_trade(&self, entry: JournalEntry) {
+        if let Some(journal) = &self.trade_journal {
+            if let Err(e) = journal.record(&entry) {
+                warn!("Failed to write {} to trade journal {}: {}", entry.signature, journal.path().display(), e);
+            }
+        }
+    }
+
+    /// Runs an RPC read against the primary endpoint, falling back to the send
+    /// endpoint so exits can still be priced and signed while one is down.
+    fn read_rpc<T, E: std::fmt::Display>(
+        &self,
+        call: impl Fn(&RpcClient) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        with_retries(self.config.rpc_max_retries, || call(&self.rpc_client)).or_else(|e| {
+            if self.config.send_rpc_endpoint == self.config.solana_rpc_endpoint {
+                return Err(e);
+            }
+            debug!("Primary RPC read failed ({}), trying the send endpoint", e);
+            call(&self.send_rpc_client)
+        })
+    }
+
+    /// Simulates a dry-run buy against current chain state and reports the
+    /// outcome. The RPC swaps in a fresh blockhash and skips signature checks,
+    /// so the placeholder blockhash of a dry run is fine.
+    fn simulate_dry_run_buy(&self, mint: &Pubkey, transaction: &Transaction) -> Option<DryRunSimulation> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::processed()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = match self.read_rpc(|client| client.simulate_transaction_with_config(transaction, config.clone())) {
+            Ok(response) => response.value,
+            Err(e) => {
+                warn!("🧪 Could not simulate dry-run buy for {}: {}", mint, e);
+                return None;
+            }
+        };
+        let simulation = DryRunSimulation::new(result.err.as_ref(), result.units_consumed, result.logs);
+
+        match &simulation.error {
+            None => info!("🧪 Simulated buy for {} succeeded using {} compute units",
+                          mint, simulation.units_consumed.unwrap_or_default()),
+            Some(error) => warn!("🧪 Simulated buy for {} failed: {}", mint, error),
+        }
+        for line in &simulation.logs {
+            debug!("  {}", line);
+        }
+        self.notifications.send(TradeEvent::DryRunSimulated {
+            mint: *mint,
+            error: simulation.error.clone(),
+            units_consumed: simulation.units_consumed,
+            logs: simulation.logs.clone(),
+        });
+        Some(simulation)
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash> {
+        self.read_rpc(|client| client.get_latest_blockhash())
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to get recent blockhash: {}", e)))
+    }
+
+    /// The latest blockhash and the last block height a transaction signed
+    /// with it can land at.
+    fn latest_blockhash_with_height(&self) -> Result<(Hash, u64)> {
+        self.read_rpc(|client| client.get_latest_blockhash_with_commitment(client.commitment()))
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to get recent blockhash: {}", e)))
+    }
+
+    /// Classifies a buy that was not confirmed within the pending budget. The
+    /// block height is read before the status, so the buy can't land unseen
+    /// between the two reads.
+    fn stuck_buy(&self, signature: &Signature, last_valid_block_height: u64) -> StuckBuy {
+        let block_height = match self.read_rpc(|client| client.get_block_height()) {
+            Ok(height) => Some(height),
+            Err(e) => {
+                debug!("Could not read the block height: {}", e);
+                None
+            }
+        };
+        let landed = match self.read_rpc(|client| client.get_signature_statuses(&[*signature])) {
+            Ok(response) => Some(response.value.into_iter().next().flatten().map(|status| status.err.is_none())),
+            Err(e) => {
+                debug!("Could not fetch status of buy {}: {}", signature, e);
+                None
+            }
+        };
+        StuckBuy::classify(block_height, last_valid_block_height, landed)
+    }
+
+    /// Sends an unconfirmed buy again, unchanged, so it keeps its chance to
+    /// land. Its signature and fees stay those of the first send, and a
+    /// failed rebroadcast leaves the original in flight all the same.
+    fn rebroadcast_buy(&self, transaction: &Transaction) {
+        let config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..RpcSendTransactionConfig::default()
+        };
+        if let Err(e) = self.send_rpc_client.send_transaction_with_config(transaction, config) {
+            debug!("Rebroadcast of buy {} failed: {}", transaction.signatures[0], e);
+        }
+    }
+
+    fn fetch_bonding_curve_state(&self, bonding_curve_key: &Pubkey) -> Result<BondingCurveState> {
+        let data = self.read_rpc(|client| client.get_account_data(bonding_curve_key))
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch bonding curve {}: {}", bonding_curve_key, e)))?;
+        BondingCurveState::from_account_data(&data)
+    }
+
+    fn fetch_pump_swap_route(&self, pool_key: &Pubkey) -> Result<SellRoute> {
+        let data = self.read_rpc(|client| client.get_account_data(pool_key))
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch PumpSwap pool {}: {}", pool_key, e)))?;
+        let pool = PumpSwapPool::from_account_data(*pool_key, &data)?;
+
+        let reserve = |account: &Pubkey| -> Result<u64> {
+            let balance = self.read_rpc(|client| client.get_token_account_balance(account))
+                .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch pool reserve {}: {}", account, e)))?;
+            balance.amount
+                .parse()
+                .map_err(|_| SniperError::Transaction(format!("Invalid reserve amount for {}", account)))
+        };
+
+        Ok(SellRoute::PumpSwap {
+            base_reserve: reserve(&pool.pool_base_token_account)?,
+            quote_reserve: reserve(&pool.pool_quote_token_account)?,
+            pool,
+        })
+    }
+
+    /// Evaluates every open position against its live price and sells those that
+    /// hit stop loss, take profit or the hold timeout. Positions in tokens that
+    /// have migrated are priced and sold through their PumpSwap pool. Returns
+    /// the positions that dipped far enough to average down into.
+    async fn check_positions(&self) -> Vec<(Position, BondingCurveState)> {
+        let positions = self.position_manager.lock().open_positions();
+        let mut dipped = Vec::new();
+
+        for position in positions {
+            let route = match self.sell_route(&position) {
+                Ok(route) => route,
+                Err(e) => {
+                    warn!("Could not price position {}: {}", position.mint, e);
+                    continue;
+                }
+            };
+
+            if let SellRoute::BondingCurve(curve) = &route {
+                self.curves.lock().track_curve(&position.mint, curve.clone());
+            }
+            let current_price = route.current_price();
+            let current_value_sol = current_price * position.token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+            self.pnl.lock().mark(&position.mint, current_value_sol, position.entry_sol);
+
+            let mut exit_reason = self.position_manager.lock().evaluate(&position, current_price);
+
+            // A collapse in curve reserves pre-empts the regular exit checks
+            if let SellRoute::BondingCurve(curve) = &route {
+                let mut rug_monitor = self.rug_monitor.lock();
+                if rug_monitor.record(&position.mint, curve.real_sol, Instant::now()).is_some()
+                    && rug_monitor.reaction() == RugReaction::AutoExit
+                {
+                    exit_reason = Some(ExitReason::RugPull);
+                }
+            }
+
+            // Signals that only appear after entry, such as holder concentration
+            if exit_reason.is_none() {
+                if let Some(analysis) = self.rescore_held_token(&position).await {
+                    self.notifications.send(TradeEvent::ScamFlagged {
+                        mint: position.mint,
+                        scam_score: analysis.scam_score,
+                        recommendation: analysis.recommendation,
+                    });
+                    exit_reason = Some(ExitReason::ScamScore);
+                }
+            }
+
+            // A halted session sells everything it still holds, if configured to
+            let pnl = self.pnl.lock();
+            if pnl.is_halted() && pnl.close_positions_on_halt() {
+                exit_reason.get_or_insert(ExitReason::SessionHalt);
+            }
+            drop(pnl);
+
+            // Lost monitoring means we can no longer manage risk on anything held
+            if self.dead_man_switch.as_ref().is_some_and(|switch| switch.lock().should_close_positions()) {
+                exit_reason.get_or_insert(ExitReason::DeadManSwitch);
+            }
+
+            if let Some(reason) = exit_reason {
+                if !self.position_manager.lock().mark_exiting(&position.mint) {
+                    continue;
+                }
+                self.sell_position(&position, &route, reason, position.token_amount).await;
+            } else if let SellRoute::BondingCurve(curve) = route {
+                // A dip short of the stop loss may be bought into
+                if self.position_manager.lock().should_average_down(&position, current_price) {
+                    dipped.push((position, curve));
+                }
+            }
+        }
+
+        // Positions sold by the halt go out on the next check
+        let halted = self.pnl.lock().check_limits(self.price_cache.get());
+        if let Some(SessionEvent::SessionHalted { loss_usd, realized_sol, unrealized_sol }) = halted {
+            self.notifications.send(TradeEvent::CircuitBreaker {
+                reason: format!("session loss limit hit (${:.2} down; {:+.4} SOL realized, {:+.4} SOL open)",
+                                loss_usd, realized_sol, unrealized_sol),
+            });
+        }
+        dipped
+    }
+
+    /// Where `position` can be sold right now: its PumpSwap pool once the token
+    /// has migrated, otherwise its bonding curve.
+    fn sell_route(&self, position: &Position) -> Result<SellRoute> {
+        let migrated_pool = self.season2.lock().pump_swap_pool(&position.mint);
+        match migrated_pool {
+            Some(pool_key) => self.fetch_pump_swap_route(&pool_key),
+            None => self.fetch_bonding_curve_state(&position.bonding_curve).map(SellRoute::BondingCurve),
+        }
+    }
+
+    /// Sells `token_amount` of a position already marked exiting through
+    /// `route` and books the result. Selling less than the whole position keeps
+    /// the rest open. A failed sell clears the exiting flag so it is retried.
+    async fn sell_position(&self, position: &Position, route: &SellRoute, reason: ExitReason, token_amount: u64) {
+        let is_partial = token_amount < position.token_amount;
+        let share = token_amount as f64 / position.token_amount as f64;
+        let portion = Position {
+            token_amount,
+            entry_sol: position.entry_sol * share,
+            entry_fees_sol: position.entry_fees_sol * share,
+            ..position.clone()
+        };
+        let current_price = route.current_price();
+        let estimated_value_sol = current_price * token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+
+        info!("🚪 Exiting {} ({}){}", position.mint, reason, if is_partial { " partially" } else { "" });
+        let sell_result = match route {
+            SellRoute::BondingCurve(curve) => self.execute_sell_transaction(&portion, curve, reason).await,
+            SellRoute::PumpSwap { pool, base_reserve, quote_reserve } => {
+                self.execute_pump_swap_sell(&portion, pool, *base_reserve, *quote_reserve, reason).await
+            }
+        }
+        .with_mint(&position.mint);
+        let (signature, fees) = match sell_result {
+            Ok(sold) => sold,
+            Err(e) => {
+                error!("Failed to sell ({}): {}", reason, e);
+                self.position_manager.lock().clear_exiting(&position.mint);
+                return;
+            }
+        };
+
+        let sold_value_sol = match self.read_sell_proceeds(&signature).await {
+            Some(proceeds) => proceeds,
+            None => {
+                warn!("Could not read what sell {} paid, booking the estimated {} SOL", signature, estimated_value_sol);
+                estimated_value_sol
+            }
+        };
+
+        if is_partial {
+            self.position_manager.lock().reduce_position(&position.mint, token_amount);
+        } else {
+            self.position_manager.lock().close_position(&position.mint);
+        }
+        self.pnl.lock().realize(&position.mint, sold_value_sol, portion.entry_sol);
+        // Session PnL books fees on their own; the journal charges them to the trade
+        let net_pnl_sol = sold_value_sol - portion.entry_sol - portion.entry_fees_sol - fees.total();
+        self.journal_trade(JournalEntry {
+            timestamp: chrono::Utc::now(),
+            mint: position.mint.to_string(),
+            side: TradeSide::Sell,
+            sol_amount: sold_value_sol,
+            token_amount,
+            price_sol: journal_price_sol(sold_value_sol, token_amount),
+            fees_sol: fees.base_sol + fees.priority_sol,
+            tips_sol: fees.tip_sol,
+            signature: signature.to_string(),
+            realized_pnl_sol: Some(net_pnl_sol),
+        });
+        self.notifications.send(TradeEvent::Sold {
+            mint: position.mint,
+            signature,
+            reason,
+            pnl_sol: net_pnl_sol,
+        });
+        if is_partial {
+            return;
+        }
+
+        if self.config.close_ata_after_sell {
+            if let Err(e) = self.reclaim_token_account(position).await {
+                warn!("Failed to close token account for {}: {}", position.mint, e);
+            }
+        }
+        self.rug_monitor.lock().forget(&position.mint);
+        if let Some(scam_guard) = &self.scam_guard {
+            scam_guard.lock().await.forget(&position.mint);
+        }
+    }
+
+    /// Checks whether a dipped position in `mint` may be averaged into now,
+    /// given the session and dead man's switch, and marks it exiting so no
+    /// exit or second dip buy races the one about to go out. Returns the
+    /// policy to buy under.
+    fn claim_average_down(&self, mint: &Pubkey) -> Option<AverageDownPolicy> {
+        let policy = self.config.average_down?;
+        if self.pnl.lock().is_halted() {
+            return None;
+        }
+        if self.dead_man_switch.as_ref().is_some_and(|switch| switch.lock().is_tripped()) {
+            return None;
+        }
+        self.position_manager.lock().mark_exiting(mint).then_some(policy)
+    }
+
+    /// Buys `AVERAGE_DOWN_BUY_SOL` more of a dipped position claimed through
+    /// [`Self::claim_average_down`], behind the wallet reserve and the buy
+    /// semaphore like any buy. The SOL and tokens the buy actually moved are
+    /// blended into its entry, and the exiting flag is cleared whether or not
+    /// it lands.
+    async fn average_down(&self, position: Position, curve: BondingCurveState, policy: AverageDownPolicy) {
+        let mint = position.mint;
+        if !self.can_afford(&mint, policy.buy_amount_sol) {
+            self.position_manager.lock().clear_exiting(&mint);
+            return;
+        }
+        let Ok(_buy_permit) = self.buy_semaphore.acquire().await else {
+            self.position_manager.lock().clear_exiting(&mint);
+            return;
+        };
+        if self.config.dry_run {
+            info!("🧪 Dry run - {} dipped below its entry, not averaging down with {} SOL", mint, policy.buy_amount_sol);
+            self.position_manager.lock().clear_exiting(&mint);
+            return;
+        }
+        info!("📉 {} dipped below its entry, averaging down with {} SOL (buy {}/{})",
+              mint, policy.buy_amount_sol, position.average_down_buys + 1, policy.max_buys);
+
+        let bought = self.execute_average_down_buy(&position, &curve, policy.buy_amount_sol).await.with_mint(&mint);
+        let (signature, fees, estimated_tokens) = match bought {
+            Ok(bought) => bought,
+            Err(e) => {
+                error!("Failed to average down: {}", e);
+                self.position_manager.lock().clear_exiting(&mint);
+                return;
+            }
+        };
+        let (token_amount, sol_spent) = match self.read_buy_settlement(&signature, &mint).await {
+            Some(settlement) => (settlement.token_amount, settlement.sol_spent),
+            None => {
+                warn!("Could not read what averaging-down buy {} moved, booking the estimated {} tokens for {} SOL",
+                      signature, estimated_tokens, policy.buy_amount_sol);
+                (estimated_tokens, policy.buy_amount_sol)
+            }
+        };
+
+        let Some(averaged) = self.position_manager.lock().average_down(&mint, token_amount, sol_spent, fees.total()) else {
+            return;
+        };
+        let current_value_sol = curve.get_current_price() * averaged.token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+        self.pnl.lock().mark(&mint, current_value_sol, averaged.entry_sol);
+        info!("📉 Averaged down {}: {:.6} SOL in for {} tokens, entry now {:.10} SOL per token",
+              mint, averaged.entry_sol, averaged.token_amount, averaged.entry_price_sol);
+
+        self.notifications.send(TradeEvent::BuyConfirmed {
+            mint,
+            signature,
+            sol_spent,
+        });
+        self.journal_trade(JournalEntry {
+            timestamp: chrono::Utc::now(),
+            mint: mint.to_string(),
+            side: TradeSide::Buy,
+            sol_amount: sol_spent,
+            token_amount,
+            price_sol: journal_price_sol(sol_spent, token_amount),
+            fees_sol: fees.base_sol + fees.priority_sol,
+            tips_sol: fees.tip_sol,
+            signature: signature.to_string(),
+            realized_pnl_sol: None,
+        });
+    }
+
+    /// Whether a buy of `mint` leaves `RESERVE_SOL` in the wallet. The balance
+    /// gate already checked it when it runs; without it, left out of
+    /// `BUY_GATE_ORDER` or not built for a dry run, the balance is read here.
+    fn keeps_reserve(&self, mint: &Pubkey) -> bool {
+        let balance_gated = self.buy_gates.iter().any(|gate| gate.kind() == BuyGateKind::Balance);
+        self.config.reserve_sol <= 0.0 || balance_gated || self.can_afford(mint, self.config.buy_amount_sol)
+    }
+
+    /// Whether the wallet can pay for a buy of `buy_amount_sol` without dipping
+    /// into `RESERVE_SOL`. A balance that cannot be read refuses the buy.
+    fn can_afford(&self, mint: &Pubkey, buy_amount_sol: f64) -> bool {
+        let balance_lamports = match self.rpc_client.get_balance(&self.signer.pubkey()) {
+            Ok(balance) => balance,
+            Err(e) => {
+                warn!("⏭️ Skipping the buy of {}: could not read the wallet balance: {}", mint, e);
+                return false;
+            }
+        };
+        let required_sol = required_buy_sol(&self.config, buy_amount_sol);
+        match balance_decision(balance_lamports as f64 / LAMPORTS_PER_SOL as f64, required_sol, self.config.reserve_sol) {
+            GateDecision::Allow => true,
+            GateDecision::Deny(reason) => {
+                info!("⏭️ Skipping the buy of {}: {}", mint, reason);
+                false
+            }
+        }
+    }
+
+    /// Reads what a confirmed buy of `mint` moved, from the transaction's own
+    /// balances: the tokens the wallet received and the SOL it paid for them,
+    /// net of the transaction fee. `None` if the transaction can't be read.
+    async fn read_buy_settlement(&self, signature: &Signature, mint: &Pubkey) -> Option<BuySettlement> {
+        let meta = self.read_transaction_meta(signature, "Buy").await?;
+        buy_settlement(&meta, &self.signer.pubkey(), mint)
+    }
+
+    /// Reads the SOL a confirmed sell paid the wallet, before the transaction
+    /// fee. `None` if the transaction can't be read.
+    async fn read_sell_proceeds(&self, signature: &Signature) -> Option<f64> {
+        let meta = self.read_transaction_meta(signature, "Sell").await?;
+        sell_proceeds(&meta)
+    }
+
+    /// Fetches a confirmed transaction's meta, retrying while the RPC catches up.
+    async fn read_transaction_meta(&self, signature: &Signature, what: &str) -> Option<UiTransactionStatusMeta> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        for attempt in 1..=TOKEN_BALANCE_READ_ATTEMPTS {
+            match self.rpc_client.get_transaction_with_config(signature, config) {
+                Ok(transaction) => return transaction.transaction.meta,
+                Err(e) => debug!("{} transaction read {}/{} failed: {}", what, attempt, TOKEN_BALANCE_READ_ATTEMPTS, e),
+            }
+            if attempt < TOKEN_BALANCE_READ_ATTEMPTS {
+                tokio::time::sleep(TOKEN_BALANCE_RETRY_DELAY).await;
+            }
+        }
+        None
+    }
+
+    /// Buys `buy_amount_sol` more of a held token on its bonding curve and
+    /// waits for the buy to land. Returns the raw token amount bought.
+    async fn execute_average_down_buy(
+        &self,
+        position: &Position,
+        curve: &BondingCurveState,
+        buy_amount_sol: f64,
+    ) -> Result<(Signature, FeeBreakdown, u64)> {
+        let (token_amount, max_sol_cost_lamports) =
+            size_buy(curve.virtual_sol, curve.virtual_tokens, buy_amount_sol, self.config.buy_slippage_percentage);
+        let params = BuyParams {
+            buyer: self.signer.pubkey(),
+            accounts: PumpFunAccounts {
+                mint: position.mint,
+                bonding_curve: position.bonding_curve,
+                associated_bonding_curve: position.associated_bonding_curve,
+                creator_vault: position.creator_vault,
+            },
+            fee_recipient: self.fee_recipients.resolve(),
+            token_program: position.token_program,
+            token_amount,
+            max_sol_cost_lamports,
+        };
+        let compute_unit_price = self
+            .gas_capped_price(&position.mint, self.priority_fees.floor_micro_lamports)
+            .ok_or_else(|| SniperError::Transaction("Averaging-down buy is over the gas ceiling".to_string()))?;
+
+        // The token account already holds the position, so it is not created
+        let transaction = self.sign_transaction(
+            &buy_instructions(&params, false, compute_unit_price)?,
+            self.latest_blockhash()?,
+        ).await?;
+        let priority_lamports = priority_fee_lamports(COMPUTE_UNIT_LIMIT, compute_unit_price);
+        let watched = self.watch_status(&transaction);
+        let sent = match self.submit_buy(&transaction, None, priority_lamports).await {
+            Ok((signature, fees)) => self
+                .wait_for_confirmation("Buy", &signature, &self.buy_confirmation)
+                .await
+                .map(|_| (signature, fees, token_amount))
+                .with_signature(&signature),
+            Err(e) => Err(e),
+        };
+        self.unwatch_status(watched);
+        sent
+    }
+
+    /// Applies `ON_MIGRATION` to a held position whose token just migrated.
+    async fn apply_migration_policy(&self, mint: &Pubkey) {
+        let Some(position) = self.position_manager.lock().get_position(mint).cloned() else {
+            return;
+        };
+        let policy = self.config.on_migration;
+        let Some(token_amount) = policy.tokens_to_sell(position.token_amount) else {
+            info!("🚀 {} migrated; holding the position ({})", mint, policy);
+            return;
+        };
+        if !self.position_manager.lock().mark_exiting(mint) {
+            return;
+        }
+
+        let route = match self.sell_route(&position) {
+            Ok(route) => route,
+            Err(e) => {
+                warn!("Could not price {} for its migration sell: {}", mint, e);
+                self.position_manager.lock().clear_exiting(mint);
+                return;
+            }
+        };
+        let venue = match route {
+            SellRoute::BondingCurve(_) => "PumpFun",
+            SellRoute::PumpSwap { .. } => "PumpSwap",
+        };
+        info!("🚀 {} migrated; applying {} through {}", mint, policy, venue);
+        self.sell_position(&position, &route, ExitReason::Migration, token_amount).await;
+    }
+
+    /// Tops up the buyer wallet from the funding wallet once its balance falls
+    /// under `TOP_UP_THRESHOLD_SOL`, within the per-window cap. Every top-up is
+    /// logged and announced; dry runs only log what they would send.
+    async fn check_funding(&self) {
+        let Some(funding) = &self.funding else {
+            return;
+        };
+        let buyer = self.signer.pubkey();
+        let balance = match self.async_rpc_client.get_balance(&buyer).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                debug!("Could not read the buyer balance for a top-up check: {}", e);
+                return;
+            }
+        };
+        let Some(lamports) = funding.lock().top_up_amount(balance, Instant::now()) else {
+            return;
+        };
+        let (amount_sol, balance_sol) = (lamports as f64 / LAMPORTS_PER_SOL as f64, balance as f64 / LAMPORTS_PER_SOL as f64);
+
+        if self.config.dry_run {
+            info!("🧪 Dry run - would top up {} with {:.4} SOL (balance {:.4} SOL)", buyer, amount_sol, balance_sol);
+            return;
+        }
+        let recent_blockhash = match self.async_rpc_client.get_latest_blockhash().await {
+            Ok(hash) => hash,
+            Err(e) => {
+                warn!("Could not fetch a blockhash for the buyer top-up: {}", e);
+                return;
+            }
+        };
+        // Charged up front: a top-up whose send errors out may still land
+        funding.lock().record(lamports, Instant::now());
+        let transaction = funding.lock().top_up_transaction(&buyer, lamports, recent_blockhash);
+        match self.async_send_rpc_client.send_and_confirm_transaction(&transaction).await {
+            Ok(signature) => {
+                info!("⛽ Topped up {} with {:.4} SOL from {} (balance was {:.4} SOL): {}",
+                      buyer, amount_sol, funding.lock().pubkey(), balance_sol, signature);
+                self.notifications.send(TradeEvent::WalletToppedUp { signature, amount_sol, balance_sol });
+            }
+            Err(e) => error!("Failed to top up {} with {:.4} SOL: {}", buyer, amount_sol, e),
+        }
+    }
+
+    /// Trips the dead man's switch once Geyser has been silent for too long,
+    /// and while it is tripped with `close_all`, sells what is held through
+    /// whichever RPC endpoint still answers.
+    async fn check_connectivity(&self) {
+        let Some(switch) = &self.dead_man_switch else {
+            return;
+        };
+
+        let action = switch.lock().check(Instant::now());
+        if let Some(action) = action {
+            self.notifications.send(TradeEvent::CircuitBreaker {
+                reason: format!("dead man's switch, no Geyser message (action: {:?})", action),
+            });
+        }
+        // Latched: every check retries whatever is still held
+        if !switch.lock().should_close_positions() {
+            return;
+        }
+
+        let send_up = self.async_send_rpc_client.get_slot().await.is_ok();
+        let read_up = self.config.send_rpc_endpoint != self.config.solana_rpc_endpoint
+            && self.async_rpc_client.get_slot().await.is_ok();
+        self.send_endpoint_down.store(!send_up && read_up, Ordering::Relaxed);
+        if !send_up && !read_up {
+            error!("☠️ Dead man's switch tripped but no RPC endpoint answers; positions will be sold once one does");
+            return;
+        }
+        if !send_up {
+            warn!("☠️ Send RPC endpoint is down, selling through the read endpoint");
+        }
+        self.check_positions().await;
+        let still_open = self.position_manager.lock().open_positions().len();
+        if still_open > 0 {
+            error!("☠️ Dead man's switch is closing positions; {} still open and will be retried", still_open);
+        }
+    }
+
+    /// Re-arms a tripped dead man's switch, e.g. once the operator has checked
+    /// on the stream. Buys resume and held positions are managed as before.
+    pub fn reset_dead_man_switch(&self) {
+        if let Some(switch) = &self.dead_man_switch {
+            switch.lock().reset(Instant::now());
+        }
+        self.send_endpoint_down.store(false, Ordering::Relaxed);
+    }
+
+    /// The endpoint sells go out through: the send endpoint, unless the
+    /// connectivity check found it down with the read endpoint still up.
+    fn sell_client(&self) -> &RpcClient {
+        if self.send_endpoint_down.load(Ordering::Relaxed) {
+            &self.rpc_client
+        } else {
+            &self.send_rpc_client
+        }
+    }
+
+    /// Re-runs scam detection on a held token once its recheck is due. Returns
+    /// the analysis when the token now scores at or above the exit level.
+    async fn rescore_held_token(&self, position: &Position) -> Option<ScamAnalysis> {
+        let scam_guard = self.scam_guard.as_ref()?;
+
+        let now = Instant::now();
+        if !scam_guard.lock().await.is_due(&position.mint, now) {
+            return None;
+        }
+
+        let trading_data = fetch_trading_data(&self.rpc_client, &position.mint, &position.bonding_curve);
+        scam_guard.lock().await.recheck(&trading_data, now).await
+    }
+
+    async fn execute_sell_transaction(
+        &self,
+        position: &Position,
+        curve: &BondingCurveState,
+        reason: ExitReason,
+    ) -> Result<(Signature, FeeBreakdown)> {
+        let recent_blockhash = self.latest_blockhash()?;
+
+        // Expected SOL out, reduced by the configured slippage tolerance
+        let tokens_to_sell = position.token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+        let (expected_sol, _) = curve.calculate_sell_output(tokens_to_sell);
+        let min_sol_output = expected_sol * (1.0 - self.config.sell_slippage_percentage / 100.0);
+        let min_sol_output_lamports = (min_sol_output.max(0.0) * LAMPORTS_PER_SOL as f64) as u64;
+
+        let instructions = vec![
+            compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+            compute_budget::ComputeBudgetInstruction::set_compute_unit_price(COMPUTE_UNIT_PRICE_MICRO_LAMPORTS),
+            build_sell_instruction(&SellParams {
+                seller: self.signer.pubkey(),
+                accounts: PumpFunAccounts {
+                    mint: position.mint,
+                    bonding_curve: position.bonding_curve,
+                    associated_bonding_curve: position.associated_bonding_curve,
+                    creator_vault: position.creator_vault,
+                },
+                fee_recipient: self.fee_recipients.resolve(),
+                token_program: position.token_program,
+                token_amount: position.token_amount,
+                min_sol_output_lamports,
+            }),
+        ];
+
+        let transaction = self.sign_transaction(&instructions, recent_blockhash).await?;
+
+        let (signature, fees) = self.send_and_confirm_sell(&transaction, "sell", PRIORITY_FEE_LAMPORTS).await?;
+
+        info!("✅ Sell Transaction sent ({})! Signature: {}", reason, signature);
+        info!("🔍 View on Solscan: https://solscan.io/tx/{}", signature);
+
+        Ok((signature, fees))
+    }
+
+    /// Picks up the positions saved before a restart, kept only where the
+    /// wallet still holds the tokens, so their exits are managed as before.
+    fn restore_positions(&self) -> Result<()> {
+        let stored = match self.position_manager.lock().store() {
+            Some(store) => store.load()?,
+            None => return Ok(()),
+        };
+        if stored.is_empty() {
+            return Ok(());
+        }
+
+        let stored_count = stored.len();
+        let restored = reconcile(stored, |position| self.held_token_amount(position));
+        info!("♻️ Restored {} saved position(s), {} sold out while the bot was down",
+              restored.len(), stored_count - restored.len());
+        self.position_manager.lock().restore(restored);
+        Ok(())
+    }
+
+    /// Raw tokens of `position`'s mint in the wallet's token account, 0 if the
+    /// account is gone, or `None` if the balance could not be read.
+    fn held_token_amount(&self, position: &Position) -> Option<u64> {
+        let token_account =
+            get_associated_token_address_with_program_id(&self.signer.pubkey(), &position.mint, &position.token_program);
+        let exists = self.read_rpc(|client| client.get_account_with_commitment(&token_account, CommitmentConfig::confirmed()));
+        let balance = match exists {
+            Ok(response) if response.value.is_none() => return Some(0),
+            Ok(_) => self.read_rpc(|client| client.get_token_account_balance(&token_account)),
+            Err(e) => Err(e),
+        };
+        match balance.map(|balance| balance.amount.parse()) {
+            Ok(Ok(amount)) => Some(amount),
+            Ok(Err(_)) => None,
+            Err(e) => {
+                warn!("Could not read the {} balance, keeping the saved position: {}", position.mint, e);
+                None
+            }
+        }
+    }
+
+    /// Closes the position's token account once it is empty, returning its rent
+    /// to the wallet. An account with any tokens left in it is never closed.
+    async fn reclaim_token_account(&self, position: &Position) -> Result<()> {
+        let owner = self.signer.pubkey();
+        let token_account =
+            get_associated_token_address_with_program_id(&owner, &position.mint, &position.token_program);
+
+        // Read at confirmed so the sell that just confirmed is reflected
+        let balance = self
+            .read_rpc(|client| client.get_token_account_balance_with_commitment(&token_account, CommitmentConfig::confirmed()))
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch token account {}: {}", token_account, e)))?
+            .value;
+        if balance.amount != "0" {
+            info!("Keeping token account {}: {} tokens left", token_account, balance.ui_amount_string);
+            return Ok(());
+        }
+        let rent_lamports = self
+            .read_rpc(|client| client.get_balance_with_commitment(&token_account, CommitmentConfig::confirmed()))
+            .map_err(|e| SniperError::SolanaClient(format!("Failed to fetch token account {}: {}", token_account, e)))?
+            .value;
+
+        // CloseAccount is encoded the same under both token programs, so the
+        // SPL Token instruction is pointed at whichever program owns the account
+        let mut close_instruction = spl_token::instruction::close_account(
+            &spl_token::id(),
+            &token_account,
+            &owner,
+            &owner,
+            &[],
+        )
+        .map_err(|e| SniperError::Transaction(format!("Failed to build token account close: {}", e)))?;
+        close_instruction.program_id = position.token_program;
+        let transaction = self.sign_transaction(&[close_instruction], self.latest_blockhash()?).await?;
+
+        let sent = self.send_rpc_client.send_and_confirm_transaction(&transaction);
+        if landed_on_chain(&sent) {
+            self.record_fees(&transaction, 0, 0);
+        }
+        let signature = sent.map_err(|e| SniperError::SolanaClient(format!("Failed to close token account: {}", e)))?;
+
+        let rent_sol = rent_lamports as f64 / LAMPORTS_PER_SOL as f64;
+        self.pnl.lock().record_rent_reclaimed(rent_sol);
+        info!("♻️ Closed token account {} and reclaimed {:.6} SOL rent: {}", token_account, rent_sol, signature);
+        Ok(())
+    }
+
+    /// Sells a position in a migrated token through its PumpSwap pool, receiving
+    /// WSOL into a temporary account that is closed to unwrap the proceeds.
+    async fn execute_pump_swap_sell(
+        &self,
+        position: &Position,
+        pool: &PumpSwapPool,
+        base_reserve: u64,
+        quote_reserve: u64,
+        reason: ExitReason,
+    ) -> Result<(Signature, FeeBreakdown)> {
+        let owner = self.signer.pubkey();
+
+        let recent_blockhash = self.latest_blockhash()?;
+
+        let expected_lamports = quote_sell_output(position.token_amount, base_reserve, quote_reserve);
+        let min_quote_amount_out =
+            (expected_lamports as f64 * (1.0 - self.config.sell_slippage_percentage / 100.0)).max(0.0) as u64;
+
+        let swap = PumpSwapSwap::sell(*pool, owner, position.token_amount, position.token_program)?
+            .with_min_quote_amount_out(min_quote_amount_out);
+        let wsol_account = swap.user_quote_token_account();
+
+        let instructions = vec![
+            compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+            compute_budget::ComputeBudgetInstruction::set_compute_unit_price(COMPUTE_UNIT_PRICE_MICRO_LAMPORTS),
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &owner,
+                &owner,
+                &pool.quote_mint,
+                &spl_token::id(),
+            ),
+            swap.instruction()?,
+            spl_token::instruction::close_account(&spl_token::id(), &wsol_account, &owner, &owner, &[])
+                .map_err(|e| SniperError::Transaction(format!("Failed to build WSOL close: {}", e)))?,
+        ];
+
+        let transaction = self.sign_transaction(&instructions, recent_blockhash).await?;
+
+        let (signature, fees) = self.send_and_confirm_sell(&transaction, "PumpSwap sell", PRIORITY_FEE_LAMPORTS).await?;
+
+        info!("✅ PumpSwap Sell Transaction sent ({})! Signature: {}", reason, signature);
+        info!("🔍 View on Solscan: https://solscan.io/tx/{}", signature);
+
+        Ok((signature, fees))
+    }
+}
+
+/// Builds the Geyser subscription: PumpFun transactions plus slot updates.
+/// Migrations get their own filter on the migration authority so they arrive
+/// even when PumpFun traffic is heavy. With a `status_wallet`, the statuses of
+/// that wallet's transactions, failed ones included, are streamed too.
+fn subscription_request(
+    stream_events: &[PumpFunEvent],
+    filter_mode: GeyserFilterMode,
+    copy_traders: &[Pubkey],
+    status_wallet: Option<&Pubkey>,
+) -> SubscribeRequest {
+    let mut transaction_filters: HashMap<String, SubscribeRequestFilterTransactions> =
+        [(PUMP_FUN_FILTER.to_string(), filter_mode.transaction_filter(PUMP_FUN_PROGRAM_ID))].into();
+    if stream_events.contains(&PumpFunEvent::Migrate) {
+        transaction_filters.insert(
+            MIGRATIONS_FILTER.to_string(),
+            filter_mode.transaction_filter(PUMP_FUN_MIGRATION_AUTHORITY),
+        );
+    }
+    // One filter per trader: `account_required` would otherwise demand every
+    // followed wallet in the same transaction
+    for trader in copy_traders {
+        transaction_filters.insert(
+            format!("{}{}", COPY_TRADER_FILTER_PREFIX, trader),
+            filter_mode.transaction_filter(&trader.to_string()),
+        );
+    }
+
+    SubscribeRequest {
+        transactions: transaction_filters,
+        transactions_status: status_wallet
+            .map(|wallet| {
+                let filter = SubscribeRequestFilterTransactions {
+                    failed: true,
+                    ..filter_mode.transaction_filter(&wallet.to_string())
+                };
+                (OWN_STATUS_FILTER.to_string(), filter)
+            })
+            .into_iter()
+            .collect(),
+        // Slot updates keep the block tracker current without polling RPC
+        slots: [("slots".to_string(), SubscribeRequestFilterSlots::default())].into(),
+        commitment: CommitmentLevel::Processed as i32,
+    }
+}
+
+/// Handlers a streamed transaction goes to, decided by the names of the
+/// filters it matched.
+#[derive(Debug, Default, PartialEq)]
+struct UpdateRoutes {
+    sniper: bool,
+    copy_traders: Vec<Pubkey>,
+}
+
+/// Routes an update by the filter names the response carries. Updates without
+/// any names, from providers that do not echo them, go to the sniper alone.
+fn route_update(filters: &[String]) -> UpdateRoutes {
+    if filters.is_empty() {
+        return UpdateRoutes { sniper: true, copy_traders: Vec::new() };
+    }
+    let mut routes = UpdateRoutes::default();
+    for filter in filters {
+        if filter == PUMP_FUN_FILTER || filter == MIGRATIONS_FILTER {
+            routes.sniper = true;
+        } else if let Some(trader) = filter.strip_prefix(COPY_TRADER_FILTER_PREFIX).and_then(|key| key.parse().ok()) {
+            routes.copy_traders.push(trader);
+        }
+    }
+    routes
+}
+
+/// All keys an instruction index can refer to. v0 messages index the static
+/// keys first, then the lookup-table writable addresses, then the readonly
+/// ones; legacy messages only have the static keys.
+fn combined_account_keys(message: &Message, meta: &Meta) -> Vec<Vec<u8>> {
+    let mut keys = Vec::with_capacity(
+        message.account_keys.len() + meta.loaded_writable_addresses.len() + meta.loaded_readonly_addresses.len(),
+    );
+    keys.extend_from_slice(&message.account_keys);
+    keys.extend_from_slice(&meta.loaded_writable_addresses);
+    keys.extend_from_slice(&meta.loaded_readonly_addresses);
+    keys
+}
+
+/// Counts the signers, writable accounts and PumpFun buys of a transaction,
+/// top-level and inner, from its message header and instructions alone.
+fn create_structure(message: &Message, meta: &Meta, pump_fun_program_index: usize) -> CreateStructure {
+    let (signers, readonly_signed, readonly_unsigned) = message.header.as_ref().map_or((0, 0, 0), |header| {
+        (
+            header.num_required_signatures as usize,
+            header.num_readonly_signed_accounts as usize,
+            header.num_readonly_unsigned_accounts as usize,
+        )
+    });
+    let writable_signed = signers.saturating_sub(readonly_signed);
+    let writable_unsigned = message.account_keys.len().saturating_sub(signers).saturating_sub(readonly_unsigned);
+
+    let inner = meta.inner_instructions.iter().flat_map(|inner| &inner.instructions);
+    let buys = message.instructions.iter()
+        .chain(inner)
+        .filter(|instruction| {
+            instruction.program_id_index as usize == pump_fun_program_index
+                && PumpFunEvent::from_instruction_data(&instruction.data) == Some(PumpFunEvent::Buy)
+        })
+        .count();
+
+    CreateStructure {
+        signers,
+        writable_accounts: writable_signed + writable_unsigned + meta.loaded_writable_addresses.len(),
+        buys,
+    }
+}
+
+fn account_key(full_account_list: &[Vec<u8>], index: usize) -> Result<Pubkey> {
+    let key_bytes = full_account_list.get(index).ok_or_else(|| {
+        SniperError::Transaction(format!(
+            "Account index {} out of range for {} keys", index, full_account_list.len()
+        ))
+    })?;
+    Pubkey::try_from(key_bytes.as_slice())
+        .map_err(|e| SniperError::Transaction(format!("Invalid account key: {}", e)))
+}
+
+/// Rejects a transaction whose key list is larger than any real transaction's,
+/// before anything indexes into it.
+fn check_account_count(full_account_list: &[Vec<u8>], max_accounts: usize) -> Result<()> {
+    if full_account_list.len() > max_accounts {
+        return Err(SniperError::Transaction(format!(
+            "Transaction has {} account keys, more than the {} allowed", full_account_list.len(), max_accounts
+        )));
+    }
+    Ok(())
+}
+
+/// Maps an instruction's account indices to keys in instruction order.
+fn resolve_instruction_accounts(indices: &[u8], full_account_list: &[Vec<u8>]) -> Result<Vec<Pubkey>> {
+    indices.iter().map(|&index| account_key(full_account_list, index as usize)).collect()
+}
+
+/// The largest system transfer from the creator into the bonding curve among
+/// the transaction's inner instructions, i.e. the dev buy.
+fn initial_sol_deposit(full_account_list: &[Vec<u8>], meta: &Meta, create: &CreateAccounts) -> Result<u64> {
+    let mut initial_sol_lamports = 0u64;
+
+    for inner_instruction in &meta.inner_instructions {
+        for inst in &inner_instruction.instructions {
+            if account_key(full_account_list, inst.program_id_index as usize)? != solana_sdk::system_program::ID {
+                continue;
+            }
+            if inst.data.len() < 12 || inst.accounts.len() < 2 {
+                continue;
+            }
+
+            let instruction_type = u32::from_le_bytes(inst.data[0..4].try_into().unwrap());
+            if instruction_type != system_instruction::SystemInstruction::Transfer as u32 {
+                continue;
+            }
+
+            let [source_index, destination_index, ..] = inst.accounts[..] else {
+                continue;
+            };
+            let source_key = account_key(full_account_list, source_index as usize)?;
+            let destination_key = account_key(full_account_list, destination_index as usize)?;
+            let lamports = u64::from_le_bytes(inst.data[4..12].try_into().unwrap());
+
+            if destination_key == create.accounts.bonding_curve && source_key == create.creator {
+                initial_sol_lamports = initial_sol_lamports.max(lamports);
+            }
+        }
+    }
+
+    Ok(initial_sol_lamports)
+}
+
+/// Lamports the trader of `trade` paid into its bonding curve through the
+/// system transfers under the outer instruction at `instruction_index`: the
+/// SOL a buy really spent, before fees. 0 when there are none, as for a sell.
+fn trade_sol_transferred(
+    full_account_list: &[Vec<u8>],
+    meta: &Meta,
+    instruction_index: usize,
+    trade: &ObservedTrade,
+) -> Result<u64> {
+    let mut transferred_lamports = 0u64;
+
+    for inner_instruction in meta.inner_instructions.iter().filter(|inner| inner.index as usize == instruction_index) {
+        for inst in &inner_instruction.instructions {
+            if account_key(full_account_list, inst.program_id_index as usize)? != solana_sdk::system_program::ID {
+                continue;
+            }
+            if inst.data.len() < 12 || u32::from_le_bytes(inst.data[0..4].try_into().unwrap())
+                != system_instruction::SystemInstruction::Transfer as u32
+            {
+                continue;
+            }
+            let [source_index, destination_index, ..] = inst.accounts[..] else {
+                continue;
+            };
+            if account_key(full_account_list, source_index as usize)? == trade.user
+                && account_key(full_account_list, destination_index as usize)? == trade.bonding_curve
+            {
+                transferred_lamports += u64::from_le_bytes(inst.data[4..12].try_into().unwrap());
+            }
+        }
+    }
+
+    Ok(transferred_lamports)
+}
+
+/// The engine that sizes followed traders' trades. The traders in
+/// `COPY_TRADERS` were picked by hand, so they are followed without the
+/// success-rate and reputation bars that gate discovered traders. Copies are
+/// `COPY_TRADING_PERCENTAGE` of what the trader spent, capped at our own buy size.
+fn copy_engine(config: &Config) -> CopyTradingEngine {
+    let mut engine = CopyTradingEngine::new(CopyTradeConfig {
+        min_success_rate: 0.0,
+        min_reputation_score: 0.0,
+        max_traders_to_follow: config.copy_traders.len() + config.auto_follow.as_ref().map_or(0, |policy| policy.max_traders),
+        copy_percentage: config.copy_trading_percentage / 100.0,
+        max_copy_amount_sol: config.buy_amount_sol,
+        ..CopyTradeConfig::default()
+    });
+    for trader in &config.copy_traders {
+        let profile = TraderProfile {
+            wallet_address: *trader,
+            success_rate: 0.0,
+            total_trades: 0,
+            profitable_trades: 0,
+            average_profit: 0.0,
+            last_activity: Instant::now(),
+            reputation_score: 0.0,
+        };
+        if let Err(e) = engine.add_trader(*trader, profile) {
+            warn!("Could not follow trader {}: {}", trader, e);
+        }
+    }
+    engine
+}
+
+/// Logs a failed transaction, leading with the mint when the error carries one
+/// so failures can be grepped per token.
+fn log_processing_error(error: &SniperError) {
+    match error.mint() {
+        Some(mint) => error!("Error processing transaction for mint {}: {}", mint, error),
+        None => error!("Error processing transaction: {}", error),
+    }
+}
+
+/// Wraps `task` to be driven on the blocking pool with `spawn_blocking`. The
+/// bot's RPC clients block the thread they are called from, so tasks making
+/// those calls run there instead of on a runtime worker; their awaits still
+/// go through the runtime.
+fn on_blocking_thread<T>(task: impl std::future::Future<Output = T>) -> impl FnOnce() -> T {
+    move || tokio::runtime::Handle::current().block_on(task)
+}
+
+/// Logs how a transaction's processing task ended, if it failed or panicked.
+fn log_processed(joined: std::result::Result<Result<()>, JoinError>) {
+    match joined {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log_processing_error(&e),
+        Err(e) => error!("Transaction processing task failed: {}", e),
+    }
+}
+
+/// Settles a sent buy or sell from its latest RPC status: the slot it landed
+/// in once it meets `policy`, a hard error if it landed and failed, or `None`
+/// to keep waiting.
+fn status_outcome(kind: &str, status: Option<&TransactionStatus>, policy: &BuyConfirmationPolicy) -> Option<Result<u64>> {
+    let status = status?;
+    if let Some(err) = &status.err {
+        return Some(Err(classify_client_error(&format!("{} transaction not confirmed", kind), err)));
+    }
+    policy.is_satisfied(status).then_some(Ok(status.slot))
+}
+
+/// Settles a transaction from its streamed status alone. The stream runs at
+/// processed, so a landing only settles a policy that asks for no more; a
+/// failure is left to RPC, which can tell why it failed.
+fn streamed_status_outcome(streamed: StreamedStatus, policy: &BuyConfirmationPolicy) -> Option<Result<u64>> {
+    if streamed.failed {
+        return None;
+    }
+    let status = TransactionStatus {
+        slot: streamed.slot,
+        confirmations: Some(0),
+        status: Ok(()),
+        err: None,
+        confirmation_status: Some(TransactionConfirmationStatus::Processed),
+    };
+    policy.is_satisfied(&status).then_some(Ok(streamed.slot))
+}
+
+/// Whether to ask RPC for a sent transaction's status now. Without streamed
+/// statuses RPC is the only source. With them, RPC is asked once the stream has
+/// reported the transaction, for the commitment or failure it cannot give, and
+/// otherwise only as a fallback in case the stream missed it.
+fn should_poll_status(streaming: bool, streamed: Option<StreamedStatus>, since_last_poll: Duration) -> bool {
+    !streaming || streamed.is_some() || since_last_poll >= STREAM_STATUS_FALLBACK_POLL_INTERVAL
+}
+
+/// Reads a `transactions_status` update into the signature and outcome it
+/// reports, or `None` if the signature is malformed.
+fn streamed_status(update: &TransactionStatusUpdate) -> Option<(Signature, StreamedStatus)> {
+    let signature = Signature::try_from(update.signature.as_slice()).ok()?;
+    Some((signature, StreamedStatus { slot: update.slot, failed: update.err.is_some() }))
+}
+
+/// A buy or sell that never met the policy within `timeout`; a buy may be resent.
+fn not_landed(kind: &str, timeout: Duration) -> SniperError {
+    SniperError::NotLanded(format!("{} transaction not confirmed within {:?}", kind, timeout))
+}
+
+/// Decides whether a token still qualifies once its entry delay is over, from
+/// the curve samples taken during the delay. Returns the latest market cap in
+/// SOL and curve, or why the buy is off.
+fn entry_still_qualifies(
+    mint: &Pubkey,
+    samples: &[(Instant, BondingCurveState)],
+    sol_price_usd: f64,
+    min_market_cap: MarketCapLimit,
+    max_market_cap: Option<MarketCapLimit>,
+    min_curve_sol: f64,
+    rug: RugMonitorConfig,
+) -> std::result::Result<(f64, BondingCurveState), String> {
+    let Some((_, latest)) = samples.last() else {
+        return Err("bonding curve could not be read during the delay".to_string());
+    };
+
+    let mut rug_monitor = RugMonitor::new(rug);
+    for (sampled_at, curve) in samples {
+        if let Some(alert) = rug_monitor.record(mint, curve.real_sol, *sampled_at) {
+            return Err(format!("SOL reserves fell {:.1}% during the delay", alert.drop_percentage));
+        }
+    }
+    if latest.complete {
+        return Err("bonding curve completed during the delay".to_string());
+    }
+    if latest.real_sol < min_curve_sol {
+        return Err(format!("curve SOL {:.4} fell below minimum {:.4}", latest.real_sol, min_curve_sol));
+    }
+
+    let market_cap_sol = latest.get_current_price() * TOTAL_SUPPLY as f64;
+    check_market_cap_band(market_cap_sol, sol_price_usd, min_market_cap, max_market_cap)?;
+    Ok((market_cap_sol, latest.clone()))
+}
+
+/// Whether a transaction sent and confirmed in one RPC call made it on
+/// chain, and so paid its fees: it confirmed, or landed and failed. Any other
+/// error means it was rejected or never confirmed.
+fn landed_on_chain(sent: &ClientResult<Signature>) -> bool {
+    match sent {
+        Ok(_) => true,
+        Err(e) => matches!(e.kind(), ClientErrorKind::TransactionError(_)),
+    }
+}
+
+/// A buy's hold on the global buy cooldown, from `claim_buy_slot`.
+struct BuySlot {
+    claimed_at: Instant,
+    /// The last buy before this claim, restored if this one is never sent
+    previous: Option<Instant>,
+}
+
+/// Time left before another buy is allowed, or `None` if the cooldown since
+/// `last_buy_at` has passed.
+fn cooldown_remaining(last_buy_at: Option<Instant>, cooldown: Duration, now: Instant) -> Option<Duration> {
+    let elapsed = now.saturating_duration_since(last_buy_at?);
+    (elapsed < cooldown).then(|| cooldown - elapsed)
+}
+
+/// Runs an RPC read, retrying it up to `max_retries` more times on failure.
+/// Each attempt is already bounded by the client's timeout, so the total wait
+/// stays bounded too.
+fn with_retries<T, E: std::fmt::Display>(
+    max_retries: u32,
+    mut call: impl FnMut() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    let mut attempt = 0;
+    loop {
+        match call() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < max_retries => {
+                attempt += 1;
+                debug!("RPC call failed ({}), retry {}/{}", e, attempt, max_retries);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Runs a startup step until it succeeds. Failures that may clear up on their
+/// own are retried up to `max_retries` times, waiting `backoff` and doubling
+/// it after each, up to [`STARTUP_BACKOFF_MAX`]; fatal ones return at once.
+async fn retry_on_startup<T, F, Fut>(what: &str, max_retries: u32, backoff: Duration, mut step: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match step().await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_recoverable() && attempt < max_retries => {
+                let delay = backoff.saturating_mul(2u32.saturating_pow(attempt.min(31))).min(STARTUP_BACKOFF_MAX);
+                attempt += 1;
+                warn!("⏳ {} unavailable ({}), retry {}/{} in {:?}", what, e, attempt, max_retries, delay);
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                error!("❌ {} failed after {} attempt(s): {}", what, attempt + 1, e);
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// What a confirmed buy moved for the wallet that paid for it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BuySettlement {
+    /// Raw `mint` tokens the wallet's accounts gained
+    token_amount: u64,
+    /// SOL that left the wallet beyond the transaction fee and the rent of
+    /// any token account the buy opened, which comes back when it is closed
+    sol_spent: f64,
+}
+
+/// Reads a buy's settlement from its transaction meta. `wallet` paid the fee,
+/// so its SOL balance is the first one. `None` if the token balances are
+/// missing or the wallet's holding shrank.
+fn buy_settlement(meta: &UiTransactionStatusMeta, wallet: &Pubkey, mint: &Pubkey) -> Option<BuySettlement> {
+    let (wallet, mint) = (wallet.to_string(), mint.to_string());
+    let held = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| -> Option<u64> {
+        let OptionSerializer::Some(balances) = balances else {
+            return None;
+        };
+        balances
+            .iter()
+            .filter(|balance| balance.mint == mint && matches!(&balance.owner, OptionSerializer::Some(owner) if *owner == wallet))
+            .map(|balance| balance.ui_token_amount.amount.parse::<u64>().ok())
+            .sum()
+    };
+    let token_amount = held(&meta.post_token_balances)?.checked_sub(held(&meta.pre_token_balances)?)?;
+    // Accounts of the wallet's that held no lamports before were opened by the buy
+    let OptionSerializer::Some(post_token_balances) = &meta.post_token_balances else {
+        return None;
+    };
+    let rent_lamports: u64 = post_token_balances
+        .iter()
+        .filter(|balance| balance.mint == mint && matches!(&balance.owner, OptionSerializer::Some(owner) if *owner == wallet))
+        .map(|balance| balance.account_index as usize)
+        .filter(|index| meta.pre_balances.get(*index) == Some(&0))
+        .filter_map(|index| meta.post_balances.get(index))
+        .sum();
+    let lamports_spent = meta.pre_balances.first()?
+        .saturating_sub(*meta.post_balances.first()?)
+        .saturating_sub(meta.fee)
+        .saturating_sub(rent_lamports);
+    Some(BuySettlement { token_amount, sol_spent: lamports_spent as f64 / LAMPORTS_PER_SOL as f64 })
+}
+
+/// Reads what a sell paid the wallet from its transaction meta: the rise in the
+/// fee payer's SOL balance with the transaction fee added back, so the fee is
+/// booked once, as a fee. `None` if the balances are missing.
+fn sell_proceeds(meta: &UiTransactionStatusMeta) -> Option<f64> {
+    let lamports_received = (meta.post_balances.first()? + meta.fee).saturating_sub(*meta.pre_balances.first()?);
+    Some(lamports_received as f64 / LAMPORTS_PER_SOL as f64)
+}
+
+/// SOL per whole token for a journal row moving `token_amount` base units for
+/// `sol_amount`. A trade that settled with no tokens is booked at 0 rather
+/// than `inf` or `NaN`.
+fn journal_price_sol(sol_amount: f64, token_amount: u64) -> f64 {
+    if token_amount == 0 {
+        return 0.0;
+    }
+    sol_amount / (token_amount as f64 / 10f64.powi(TOKEN_DECIMALS as i32))
+}
+
+/// Whether a token's bonding curve is already complete, so a PumpFun buy would
+/// fail. A curve the RPC cannot return yet is brand new and assumed live.
+fn has_graduated(mint: &Pubkey, curve: &Result<BondingCurveState>) -> bool {
+    match curve {
+        Ok(curve) if curve.complete => {
+            info!("⏭️ Skipping {}: bonding curve already complete, token has graduated", mint);
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            debug!("Could not read bonding curve for {} ({}), assuming it is live", mint, e);
+            false
+        }
+    }
+}
+
+/// Builds a buy's instructions: compute budget, the buyer's token account
+/// create and the PumpFun buy, which pays the bonding curve in native SOL. The
+/// create is idempotent, and can be left out entirely when a resend finds the
+/// account already exists.
+pub fn buy_instructions(
+    params: &BuyParams,
+    create_token_account: bool,
+    compute_unit_price_micro_lamports: u64,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = vec![
+        compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+        compute_budget::ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price_micro_lamports),
+    ];
+
+    if create_token_account {
+        instructions.push(spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &params.buyer,
+            &params.buyer,
+            &params.accounts.mint,
+            &params.token_program,
+        ));
+    }
+    instructions.push(build_buy_instruction(params));
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geyser;
+    use crate::mock_geyser::CreateFixture;
+    use crate::mock_signer::MockSigner;
+    use crate::pumpfun::CREATE_LAYOUT;
+    use serde_json::json;
+    use solana_client::client_error::ClientError;
+    use solana_client::mock_sender::Mocks;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::instruction::InstructionError;
+    use solana_sdk::signature::Keypair;
+    use spl_associated_token_account::get_associated_token_address;
+
+    // Held by tests while they set the environment and read a config from it
+    static ENV_LOCK: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+    /// A live bot whose RPC calls are answered by the mock sender at `url`,
+    /// with `mocks` overriding the first answer to each request, and which
+    /// checks on a buy once it has waited `pending_timeout` for it.
+    fn mock_rpc_bot(url: &str, mocks: Mocks, pending_timeout: Duration) -> SniperBot {
+        let config = {
+            let _env = ENV_LOCK.lock();
+            std::env::set_var("BUYER_PRIVATE_KEY_PATH", Keypair::new().to_base58_string());
+            std::env::set_var("GRPC_ENDPOINT", "http://127.0.0.1:1");
+            std::env::set_var("GRPC_AUTH_TOKEN", "test");
+            std::env::set_var("SOLANA_RPC_ENDPOINT", "http://127.0.0.1:1");
+            std::env::set_var("DRY_RUN", "false");
+            std::env::set_var("WARMUP", "false");
+            // Poll statuses so the mocks answer them
+            std::env::set_var("STREAM_TRANSACTION_STATUSES", "false");
+            let config = Config::from_env().unwrap();
+            std::env::remove_var("DRY_RUN");
+            std::env::remove_var("STREAM_TRANSACTION_STATUSES");
+            config
+        };
+        let mut bot = SniperBot::with_signer(config, Arc::new(MockSigner::new())).unwrap();
+        bot.rpc_client = RpcClient::new_mock_with_mocks(url.to_string(), mocks);
+        bot.send_rpc_client = RpcClient::new_mock(url.to_string());
+        bot.buy_confirmation.pending_timeout = pending_timeout;
+        bot
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reserve_is_kept_without_the_balance_gate() {
+        let mut mocks = Mocks::new();
+        mocks.insert(RpcRequest::GetBalance, json!({"context": {"slot": 1}, "value": LAMPORTS_PER_SOL / 2}));
+        let mut bot = mock_rpc_bot("succeeds", mocks, Duration::ZERO);
+        bot.buy_gates.retain(|gate| gate.kind() != BuyGateKind::Balance);
+        let mint = Pubkey::new_unique();
+
+        // Half a SOL covers the buy, but not with half a SOL held back
+        assert!(bot.keeps_reserve(&mint));
+        bot.config.reserve_sol = 0.5;
+        assert!(!bot.keeps_reserve(&mint));
+    }
+
+    fn fixture_create(fixture: &CreateFixture) -> CreateAccounts {
+        CreateAccounts {
+            creator: fixture.creator,
+            accounts: PumpFunAccounts {
+                mint: fixture.mint,
+                bonding_curve: fixture.bonding_curve,
+                associated_bonding_curve: fixture.associated_bonding_curve,
+                creator_vault: fixture.creator_vault,
+            },
+            token_program: spl_token::id(),
+        }
+    }
+
+    #[test]
+    fn test_create_accounts_resolve_through_lookup_tables() {
+        let fixture = CreateFixture::new();
+        let dev_buy_lamports = 2 * LAMPORTS_PER_SOL;
+
+        for response in [fixture.response(dev_buy_lamports), fixture.v0_response(dev_buy_lamports)] {
+            let tx = response.transaction.unwrap().transaction.unwrap();
+            let (message, meta) = (tx.message.unwrap(), tx.meta.unwrap());
+            let full_account_list = combined_account_keys(&message, &meta);
+
+            let accounts = resolve_instruction_accounts(&message.instructions[0].accounts, &full_account_list).unwrap();
+            let create = parse_create_accounts(CREATE_LAYOUT, &accounts).unwrap();
+            assert_eq!(create.creator, fixture.creator);
+            assert_eq!(create.accounts.mint, fixture.mint);
+            assert_eq!(create.accounts.bonding_curve, fixture.bonding_curve);
+            assert_eq!(create.accounts.associated_bonding_curve, fixture.associated_bonding_curve);
+            assert_eq!(create.accounts.creator_vault, fixture.creator_vault);
+            assert_eq!(initial_sol_deposit(&full_account_list, &meta, &create).unwrap(), dev_buy_lamports);
+        }
+
+        // Without the lookup-table addresses the indices run past the static keys
+        let tx = fixture.v0_response(dev_buy_lamports).transaction.unwrap().transaction.unwrap();
+        let message = tx.message.unwrap();
+        assert!(resolve_instruction_accounts(&message.instructions[0].accounts, &message.account_keys).is_err());
+    }
+
+    #[test]
+    fn test_create_structure_counts() {
+        let fixture = CreateFixture::new();
+        let tx = fixture.v0_response(LAMPORTS_PER_SOL).transaction.unwrap().transaction.unwrap();
+        let (mut message, mut meta) = (tx.message.unwrap(), tx.meta.unwrap());
+        let pump_fun_index = message.instructions[0].program_id_index as usize;
+        message.header = Some(MessageHeader {
+            num_required_signatures: 2,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 3,
+        });
+
+        let structure = create_structure(&message, &meta, pump_fun_index);
+        let static_writable = message.account_keys.len() - 3;
+        assert_eq!(
+            structure,
+            CreateStructure {
+                signers: 2,
+                writable_accounts: static_writable + meta.loaded_writable_addresses.len(),
+                buys: 0,
+            }
+        );
+
+        // Bundled buys count whether made directly or through another program
+        let buy = geyser::Instruction {
+            program_id_index: pump_fun_index as u32,
+            data: PUMPFUN_BUY_DISCRIMINATOR.to_vec(),
+            ..Default::default()
+        };
+        message.instructions.push(buy.clone());
+        meta.inner_instructions[0].instructions.push(buy.clone());
+        message.instructions.push(geyser::Instruction { program_id_index: 0, ..buy });
+        assert_eq!(create_structure(&message, &meta, pump_fun_index).buys, 2);
+    }
+
+    #[test]
+    fn test_malformed_account_lists_error_cleanly() {
+        let keys: Vec<Vec<u8>> = (0..8).map(|_| Pubkey::new_unique().to_bytes().to_vec()).collect();
+
+        // Indices past the end of a truncated list
+        let error = resolve_instruction_accounts(&[0, 1, 7], &keys[..5]).unwrap_err();
+        assert!(matches!(error, SniperError::Transaction(ref message) if message.contains("index 7 out of range for 5 keys")));
+
+        // A key that is not 32 bytes
+        let mut malformed = keys.clone();
+        malformed[3].truncate(31);
+        let error = resolve_instruction_accounts(&[3], &malformed).unwrap_err();
+        assert!(matches!(error, SniperError::Transaction(ref message) if message.contains("Invalid account key")));
+
+        // Too few accounts for a create is not a create at all
+        let accounts = resolve_instruction_accounts(&[0, 1, 2, 3, 4, 5, 6], &keys).unwrap();
+        assert!(parse_create_accounts(CREATE_LAYOUT, &accounts).is_none());
+        assert!(resolve_instruction_accounts(&[], &[]).unwrap().is_empty());
+
+        // Oversized key lists are rejected before anything indexes into them
+        assert!(check_account_count(&keys, 8).is_ok());
+        let error = check_account_count(&keys, 7).unwrap_err();
+        assert!(matches!(error, SniperError::Transaction(ref message) if message.contains("8 account keys")));
+    }
+
+    #[test]
+    fn test_buy_instruction_uses_resolved_fee_recipient() {
+        let override_recipient = Pubkey::new_unique();
+        let resolver = FeeRecipientResolver::new(Some(override_recipient)).unwrap();
+        let accounts = PumpFunAccounts {
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+        };
+
+        let instruction = build_buy_instruction(&BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts,
+            fee_recipient: resolver.resolve(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        });
+
+        assert_eq!(instruction.accounts[1].pubkey, override_recipient);
+        assert!(instruction.accounts[1].is_writable);
+    }
+
+    #[test]
+    fn test_rpc_retry_budget() {
+        let mut calls = 0;
+        let result: std::result::Result<u32, &str> = with_retries(2, || {
+            calls += 1;
+            if calls < 3 { Err("timeout") } else { Ok(calls) }
+        });
+        assert_eq!(result, Ok(3));
+
+        // The budget is retries on top of the first attempt
+        let mut calls = 0;
+        let result: std::result::Result<(), &str> = with_retries(1, || {
+            calls += 1;
+            Err("timeout")
+        });
+        assert_eq!(result, Err("timeout"));
+        assert_eq!(calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_startup_retries_until_healthy() {
+        let backoff = Duration::from_millis(1);
+
+        // A briefly unavailable endpoint is waited out
+        let mut attempts = 0;
+        let result = retry_on_startup("RPC", 3, backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 { Err(SniperError::SolanaClient("connection refused".to_string())) } else { Ok(attempt) }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+
+        // Retries run out
+        let mut attempts = 0;
+        let result: Result<()> = retry_on_startup("RPC", 2, backoff, || {
+            attempts += 1;
+            async { Err(SniperError::SolanaClient("connection refused".to_string())) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+
+        // A fatal error is not retried
+        let mut attempts = 0;
+        let result: Result<()> = retry_on_startup("RPC", 5, backoff, || {
+            attempts += 1;
+            async { Err(classify_rpc_health_error("Read", &"401 Unauthorized")) }
+        })
+        .await;
+        assert!(matches!(result, Err(SniperError::Config(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    /// A bot whose buy transaction reads back with the wallet holding
+    /// `pre_tokens` then `post_tokens` of `mint`, having spent
+    /// `lamports_spent` plus a 10_000 lamport fee, and holding an open
+    /// position in `mint` booked at the estimated `token_amount` and
+    /// `BUY_AMOUNT_SOL`.
+    fn settling_bot(pre_tokens: u64, post_tokens: u64, lamports_spent: u64, token_amount: u64) -> (SniperBot, Pubkey) {
+        let mut bot = mock_rpc_bot("succeeds", Mocks::new(), Duration::ZERO);
+        bot.config.verify_token_balance = true;
+        let (wallet, mint) = (bot.signer.pubkey(), Pubkey::new_unique());
+        let balance = |amount: u64| json!({
+            "accountIndex": 1,
+            "mint": mint.to_string(),
+            "uiTokenAmount": { "uiAmount": null, "decimals": 6, "amount": amount.to_string(), "uiAmountString": "" },
+            "owner": wallet.to_string(),
+        });
+        let mut mocks = Mocks::new();
+        mocks.insert(RpcRequest::GetTransaction, json!({
+            "slot": 1,
+            "transaction": {
+                "transaction": ["", "base64"],
+                "meta": {
+                    "status": { "Ok": null },
+                    "fee": 10_000,
+                    "preBalances": [2_000_000_000u64, TOKEN_ACCOUNT_RENT_LAMPORTS],
+                    "postBalances": [2_000_000_000u64 - 10_000 - lamports_spent, TOKEN_ACCOUNT_RENT_LAMPORTS],
+                    "preTokenBalances": [balance(pre_tokens)],
+                    "postTokenBalances": [balance(post_tokens)],
+                },
+            },
+            "blockTime": null,
+        }));
+        bot.rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let entry_sol = bot.config.buy_amount_sol;
+        bot.position_manager.lock().open_position(Position {
+            mint,
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount,
+            entry_price_sol: entry_sol / (token_amount as f64 / 1e6),
+            entry_sol,
+            entry_fees_sol: 0.0,
+            opened_at: Instant::now(),
+            exiting: false,
+            average_down_buys: 0,
+        });
+        (bot, mint)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bought_tokens_settle_from_the_buy_transaction() {
+        // The account already held 20 tokens; the buy brought it to 1000,
+        // short of the 1000 estimated
+        let (bot, mint) = settling_bot(20_000_000, 1_000_000_000, 10_000_000, 1_000_000_000);
+        let settlement = bot.settle_bought_tokens(&Signature::new_unique(), &mint, 1_000_000_000).await;
+        assert_eq!(settlement, Some(BuySettlement { token_amount: 980_000_000, sol_spent: 0.01 }));
+
+        let positions = bot.position_manager.lock();
+        let settled = positions.get_position(&mint).unwrap();
+        assert_eq!((settled.token_amount, settled.entry_sol), (980_000_000, 0.01));
+        assert!((settled.entry_price_sol - 0.01 / 980.0).abs() < 1e-15);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_settled_entry_is_the_sol_actually_spent() {
+        // Slippage made the buy cost 0.012 SOL rather than BUY_AMOUNT_SOL
+        let (bot, mint) = settling_bot(0, 1_000_000_000, 12_000_000, 1_000_000_000);
+        assert_ne!(bot.config.buy_amount_sol, 0.012);
+        let settlement = bot.settle_bought_tokens(&Signature::new_unique(), &mint, 1_000_000_000).await;
+        assert_eq!(settlement, Some(BuySettlement { token_amount: 1_000_000_000, sol_spent: 0.012 }));
+
+        // Later sells realize against what the buy cost, not the estimate
+        let positions = bot.position_manager.lock();
+        let settled = positions.get_position(&mint).unwrap();
+        assert_eq!(settled.entry_sol, 0.012);
+        assert!((settled.entry_price_sol - 0.012 / 1000.0).abs() < 1e-15);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sell_realizes_what_it_was_paid() {
+        let mut mocks = Mocks::new();
+        // The sell paid 0.012 SOL, less its 10_000 lamport fee
+        mocks.insert(RpcRequest::GetTransaction, json!({
+            "slot": 1,
+            "transaction": {
+                "transaction": ["", "base64"],
+                "meta": {
+                    "status": { "Ok": null },
+                    "fee": 10_000,
+                    "preBalances": [1_000_000_000u64],
+                    "postBalances": [1_011_990_000u64],
+                    "preTokenBalances": [],
+                    "postTokenBalances": [],
+                },
+            },
+            "blockTime": null,
+        }));
+        let mut bot = mock_rpc_bot("succeeds", mocks, Duration::ZERO);
+        bot.config.close_ata_after_sell = false;
+        let journal_path = std::env::temp_dir().join(format!("trade_journal_{}.csv", Pubkey::new_unique()));
+        bot.trade_journal = Some(TradeJournal::new(&journal_path));
+        let mint = Pubkey::new_unique();
+        let position = Position {
+            mint,
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000_000_000,
+            entry_price_sol: 0.000_01,
+            entry_sol: 0.01,
+            entry_fees_sol: 0.0002,
+            opened_at: Instant::now(),
+            exiting: true,
+            average_down_buys: 0,
+        };
+        bot.position_manager.lock().open_position(position.clone());
+
+        // The curve's spot price values the 1000 tokens far below what the sell got
+        let route = SellRoute::BondingCurve(BondingCurveState::from_initial_deposit(50.0));
+        assert!(route.current_price() * 1000.0 < 0.001);
+        bot.sell_position(&position, &route, ExitReason::TakeProfit, position.token_amount).await;
+
+        assert!(bot.position_manager.lock().get_position(&mint).is_none());
+        assert!((bot.pnl.lock().realized() - 0.002).abs() < 1e-12);
+
+        // The journal charges the buy's and the sell's fees to the trade
+        let entries = TradeJournal::read_entries(&journal_path).unwrap();
+        let sell = &entries[0];
+        assert!(sell.fees_sol > 0.0);
+        let net_pnl_sol = 0.002 - 0.0002 - sell.fees_sol - sell.tips_sol;
+        assert!((sell.realized_pnl_sol.unwrap() - net_pnl_sol).abs() < 1e-12);
+        std::fs::remove_file(&journal_path).ok();
+    }
+
+    #[test]
+    fn test_portfolio_is_marked_from_positions() {
+        let bot = mock_rpc_bot("succeeds", Mocks::new(), Duration::ZERO);
+        bot.price_cache.set(100.0);
+        let position = |mint: Pubkey, bonding_curve: Pubkey| Position {
+            mint,
+            bonding_curve,
+            associated_bonding_curve: Pubkey::new_unique(),
+            creator_vault: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000_000_000,
+            entry_price_sol: 0.000_01,
+            entry_sol: 0.01,
+            entry_fees_sol: 0.0,
+            opened_at: Instant::now(),
+            exiting: false,
+            average_down_buys: 0,
+        };
+        let (on_curve, migrated) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let migrated_curve = Pubkey::new_unique();
+        bot.position_manager.lock().open_position(position(on_curve, Pubkey::new_unique()));
+        bot.position_manager.lock().open_position(position(migrated, migrated_curve));
+
+        let curve = BondingCurveState::from_initial_deposit(0.0);
+        let curve_price = curve.get_current_price();
+        bot.curves.lock().track_curve(&on_curve, curve.clone());
+        // A stale curve read from before the migration must not be used
+        bot.curves.lock().track_curve(&migrated, curve);
+        {
+            let mut season2 = bot.season2.lock();
+            season2.process_migration_instruction(&crate::migration_detector::MigrationInstruction {
+                migration_type: crate::migration_detector::MigrationType::PumpSwap,
+                mint: migrated,
+                bonding_curve: migrated_curve,
+                pool: Pubkey::new_unique(),
+            }, 0.0);
+            season2.apply_pool_updates(&[crate::pump_swap::PoolUpdate::Priced {
+                mint: migrated,
+                price_sol: 0.001,
+                liquidity_sol: 85.0,
+            }]);
+        }
+
+        // 1000 tokens of each, at $100 per SOL
+        let expected = (curve_price * 1000.0 + 0.001 * 1000.0) * 100.0;
+        assert!((bot.portfolio_value_usd() - expected).abs() < 1e-9);
+        assert!(bot.render_metrics().contains("sniper_open_positions 2\n"));
+
+        bot.position_manager.lock().close_position(&migrated);
+        assert!((bot.portfolio_value_usd() - curve_price * 1000.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_buy_settles_from_its_transaction() {
+        let (wallet, curve, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let balance = |index: u8, owner: &Pubkey, amount: u64| serde_json::json!({
+            "accountIndex": index,
+            "mint": mint.to_string(),
+            "uiTokenAmount": { "uiAmount": null, "decimals": 6, "amount": amount.to_string(), "uiAmountString": "" },
+            "owner": owner.to_string(),
+        });
+        // Tops up a holding of 1 token with 50 more, off the curve, for 0.1 SOL
+        let mut meta = serde_json::json!({
+            "status": { "Ok": null },
+            "fee": 10_000,
+            "preBalances": [2_000_000_000u64, TOKEN_ACCOUNT_RENT_LAMPORTS, 0],
+            "postBalances": [1_899_990_000u64, TOKEN_ACCOUNT_RENT_LAMPORTS, 0],
+            "preTokenBalances": [balance(1, &wallet, 1_000_000), balance(2, &curve, 900_000_000)],
+            "postTokenBalances": [balance(1, &wallet, 51_000_000), balance(2, &curve, 850_000_000)],
+        });
+        let settle = |meta: &serde_json::Value| {
+            buy_settlement(&serde_json::from_value(meta.clone()).unwrap(), &wallet, &mint)
+        };
+        assert_eq!(settle(&meta), Some(BuySettlement { token_amount: 50_000_000, sol_spent: 0.1 }));
+
+        // A first buy that opens the token account: its rent is not spent
+        let first_buy = serde_json::json!({
+            "status": { "Ok": null },
+            "fee": 10_000,
+            "preBalances": [2_000_000_000u64, 0, 0],
+            "postBalances": [1_899_990_000u64 - TOKEN_ACCOUNT_RENT_LAMPORTS, TOKEN_ACCOUNT_RENT_LAMPORTS, 0],
+            "preTokenBalances": [balance(2, &curve, 900_000_000)],
+            "postTokenBalances": [balance(1, &wallet, 50_000_000), balance(2, &curve, 850_000_000)],
+        });
+        assert_eq!(settle(&first_buy), Some(BuySettlement { token_amount: 50_000_000, sol_spent: 0.1 }));
+
+        // Balances the node didn't record can't be settled from
+        meta.as_object_mut().unwrap().remove("preTokenBalances");
+        assert_eq!(settle(&meta), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_buy_settling_with_no_tokens_closes_its_position() {
+        // Confirmed, paid for, and the wallet's holding never moved
+        let (bot, mint) = settling_bot(0, 0, 10_000_000, 1_000_000_000);
+        let settlement = bot.settle_bought_tokens(&Signature::new_unique(), &mint, 1_000_000_000).await;
+        assert_eq!(settlement.map(|settlement| settlement.token_amount), Some(0));
+        assert!(bot.position_manager.lock().get_position(&mint).is_none());
+    }
+
+    #[test]
+    fn test_journal_price_of_an_empty_settlement_is_zero() {
+        assert!((journal_price_sol(0.1, 50_000_000) - 0.002).abs() < 1e-12);
+        assert_eq!(journal_price_sol(0.1, 0), 0.0);
+        assert_eq!(journal_price_sol(0.0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_entry_delay_rechecks_the_launch() {
+        let mint = Pubkey::new_unique();
+        let start = Instant::now();
+        let samples = |deposits: &[f64]| -> Vec<(Instant, BondingCurveState)> {
+            deposits
+                .iter()
+                .enumerate()
+                .map(|(i, sol)| (start + Duration::from_millis(500 * i as u64), BondingCurveState::from_initial_deposit(*sol)))
+                .collect()
+        };
+        let recheck = |samples: &[(Instant, BondingCurveState)], max_usd: Option<f64>| {
+            entry_still_qualifies(&mint, samples, 150.0, MarketCapLimit::Usd(4_000.0), max_usd.map(MarketCapLimit::Usd), 0.0,
+                                  RugMonitorConfig::default())
+        };
+
+        // Steady buying keeps the token in band; the latest curve is what gets bought into
+        let (market_cap_sol, curve) = recheck(&samples(&[2.0, 3.0, 4.0]), None).unwrap();
+        assert!((market_cap_sol - 34.0 / INITIAL_VIRTUAL_TOKENS * TOTAL_SUPPLY as f64).abs() < 1e-6);
+        assert_eq!(curve.real_sol, 4.0);
+
+        assert!(recheck(&samples(&[2.0, 3.0, 4.0]), Some(4_500.0)).unwrap_err().contains("above maximum"));
+        assert!(recheck(&samples(&[10.0, 4.0]), None).unwrap_err().contains("SOL reserves fell"));
+        assert!(recheck(&[], None).is_err());
+
+        let mut completed = samples(&[2.0, 80.0]);
+        completed[1].1.complete = true;
+        assert!(recheck(&completed, None).unwrap_err().contains("completed"));
+
+        // A curve drained below the liquidity minimum during the delay is skipped
+        let with_min = |min_curve_sol: f64| {
+            entry_still_qualifies(&mint, &samples(&[2.0, 3.0, 4.0]), 150.0, MarketCapLimit::Usd(4_000.0), None,
+                                  min_curve_sol, RugMonitorConfig::default())
+        };
+        assert!(with_min(4.0).is_ok());
+        assert!(with_min(4.001).unwrap_err().contains("curve SOL"));
+
+        // A SOL band is checked without any SOL price; a USD band cannot be
+        let without_price = |min: MarketCapLimit| {
+            entry_still_qualifies(&mint, &samples(&[2.0, 3.0, 4.0]), 0.0, min, None, 0.0, RugMonitorConfig::default())
+        };
+        assert!((without_price(MarketCapLimit::Sol(20.0)).unwrap().0 - market_cap_sol).abs() < 1e-9);
+        assert!(without_price(MarketCapLimit::Sol(40.0)).unwrap_err().contains("below minimum 40.00 SOL"));
+        assert!(without_price(MarketCapLimit::Usd(4_000.0)).unwrap_err().contains("no SOL price"));
+    }
+
+    #[test]
+    fn test_global_buy_cooldown() {
+        let start = Instant::now();
+        let cooldown = Duration::from_secs(10);
+
+        assert_eq!(cooldown_remaining(None, cooldown, start), None);
+        assert_eq!(cooldown_remaining(Some(start), cooldown, start + Duration::from_secs(4)), Some(Duration::from_secs(6)));
+        assert_eq!(cooldown_remaining(Some(start), cooldown, start + cooldown), None);
+    }
+
+    #[tokio::test]
+    async fn test_buy_cooldown_starts_only_on_a_submitted_buy() {
+        let mut bot = mock_rpc_bot("succeeds", Mocks::new(), Duration::ZERO);
+        bot.config.global_buy_cooldown = Some(Duration::from_secs(60));
+        let mint = Pubkey::new_unique();
+
+        // A claim holds off other buys while its own is under way
+        let skipped = bot.claim_buy_slot(&mint).unwrap();
+        assert!(bot.claim_buy_slot(&mint).is_none());
+
+        // Released without sending, the next buy may go straight ahead
+        bot.release_buy_slot(skipped);
+        let sent = bot.claim_buy_slot(&mint).unwrap();
+        bot.start_buy_cooldown();
+        bot.release_buy_slot(sent);
+        assert!(bot.claim_buy_slot(&mint).is_none());
+    }
+
+    #[test]
+    fn test_graduated_curve_skips_buy() {
+        let mint = Pubkey::new_unique();
+        let mut curve = BondingCurveState::from_initial_deposit(1.0);
+        assert!(!has_graduated(&mint, &Ok(curve.clone())));
+
+        curve.complete = true;
+        assert!(has_graduated(&mint, &Ok(curve)));
+
+        let unreadable = Err(SniperError::SolanaClient("AccountNotFound".to_string()));
+        assert!(!has_graduated(&mint, &unreadable));
+    }
+
+    #[test]
+    fn test_buy_resend_after_token_account_race() {
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: PumpFunAccounts {
+                mint: Pubkey::new_unique(),
+                bonding_curve: Pubkey::new_unique(),
+                associated_bonding_curve: Pubkey::new_unique(),
+                creator_vault: Pubkey::new_unique(),
+            },
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        let creates_account = |instructions: &[Instruction]| {
+            instructions.iter().any(|instruction| instruction.program_id == spl_associated_token_account::id())
+        };
+
+        // The first attempt creates the token account idempotently
+        let first = buy_instructions(&params, true, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS).unwrap();
+        let create = first.iter().find(|instruction| instruction.program_id == spl_associated_token_account::id()).unwrap();
+        assert_eq!(create.data, [1]);
+        assert_eq!(first.last().unwrap().program_id, program_keys().pump_fun);
+
+        // Another attempt won the race: the send fails with the account already in use
+        let error = classify_client_error(
+            "Failed to send buy transaction",
+            &"Program log: Create Account: account Address { address: 9xQe, base: None } already in use",
+        );
+        assert!(error.is_account_already_exists());
+
+        // The resend drops only the create
+        let resend = buy_instructions(&params, false, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS).unwrap();
+        assert!(!creates_account(&resend));
+        assert_eq!(resend.len(), first.len() - 1);
+        assert_eq!(resend.last(), first.last());
+    }
+
+    #[test]
+    fn test_unconfirmed_buy_is_resent_at_escalated_price() {
+        // Polling gave up without meeting the policy: the buy never landed and may be resent
+        let policy = BuyConfirmationPolicy {
+            commitment: CommitmentConfig::confirmed(),
+            min_confirmations: 0,
+            pending_timeout: Duration::from_secs(60),
+        };
+        assert!(status_outcome("Buy", None, &policy).is_none());
+        assert!(not_landed("Buy", policy.pending_timeout).is_not_landed());
+
+        // A buy that landed and failed is a hard error
+        let failed = TransactionStatus {
+            slot: 1,
+            confirmations: Some(1),
+            status: Err(TransactionError::InsufficientFundsForFee),
+            err: Some(TransactionError::InsufficientFundsForFee),
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        };
+        assert!(!status_outcome("Buy", Some(&failed), &policy).unwrap().unwrap_err().is_not_landed());
+        let landed = TransactionStatus { status: Ok(()), err: None, ..failed };
+        assert!(status_outcome("Buy", Some(&landed), &policy).unwrap().is_ok());
+
+        let mut attempt = PriorityFeeSchedule {
+            floor_micro_lamports: COMPUTE_UNIT_PRICE_MICRO_LAMPORTS,
+            step_micro_lamports: 500_000,
+            ceiling_micro_lamports: 1_000_000,
+            max_retries: 1,
+        }.start();
+        let price = attempt.escalate().unwrap();
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: PumpFunAccounts {
+                mint: Pubkey::new_unique(),
+                bonding_curve: Pubkey::new_unique(),
+                associated_bonding_curve: Pubkey::new_unique(),
+                creator_vault: Pubkey::new_unique(),
+            },
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        let resend = buy_instructions(&params, true, price).unwrap();
+        assert_eq!(resend[1], compute_budget::ComputeBudgetInstruction::set_compute_unit_price(1_000_000));
+        assert_eq!(priority_fee_lamports(COMPUTE_UNIT_LIMIT, price), 400_000);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_stuck_buy_that_landed_late_is_kept() {
+        // The wait finds no status and gives up; the check after it finds the buy landed
+        let mut mocks = Mocks::new();
+        mocks.insert(RpcRequest::GetSignatureStatuses, json!({"context": {"slot": 1}, "value": [null]}));
+        let bot = mock_rpc_bot("succeeds", mocks, Duration::ZERO);
+        let fixture = CreateFixture::new();
+
+        bot.execute_buy_transaction(&fixture_create(&fixture), LAMPORTS_PER_SOL, Instant::now(), None, false)
+            .await
+            .unwrap();
+
+        let positions = bot.position_manager.lock();
+        assert_eq!(positions.pending_buys(), 0);
+        assert!(positions.get_position(&fixture.mint).is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fanned_out_buy_is_booked_once() {
+        let mut mocks = Mocks::new();
+        mocks.insert(RpcRequest::GetSignatureStatuses, json!({"context": {"slot": 1}, "value": [null]}));
+        let mut bot = mock_rpc_bot("succeeds", mocks, Duration::ZERO);
+        bot.fanout_senders = (0..3)
+            .map(|_| Arc::new(RpcClient::new_mock("succeeds".to_string())) as Arc<dyn TransactionSender>)
+            .collect();
+        let fixture = CreateFixture::new();
+
+        bot.execute_buy_transaction(&fixture_create(&fixture), LAMPORTS_PER_SOL, Instant::now(), None, false)
+            .await
+            .unwrap();
+
+        // Three endpoints accepted the same signed buy; it is one position and one set of fees
+        let positions = bot.position_manager.lock();
+        assert_eq!(positions.pending_buys(), 0);
+        assert_eq!(positions.open_positions().len(), 1);
+        assert!(positions.get_position(&fixture.mint).is_some());
+        let fees = bot.pnl.lock().fee_breakdown();
+        assert_eq!(fees.base_sol, LAMPORTS_PER_SIGNATURE as f64 / LAMPORTS_PER_SOL as f64);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rejected_buy_books_no_fees() {
+        let mut bot = mock_rpc_bot("succeeds", Mocks::new(), Duration::ZERO);
+        bot.send_rpc_client = RpcClient::new_mock("fails".to_string());
+        let fixture = CreateFixture::new();
+
+        bot.execute_buy_transaction(&fixture_create(&fixture), LAMPORTS_PER_SOL, Instant::now(), None, false)
+            .await
+            .unwrap_err();
+
+        assert_eq!(bot.pnl.lock().fee_breakdown(), FeeBreakdown::default());
+        assert!(bot.position_manager.lock().get_position(&fixture.mint).is_none());
+    }
+
+    #[test]
+    fn test_landed_on_chain() {
+        let failed_on_chain = ClientError::from(TransactionError::InsufficientFundsForFee);
+        let rejected = ClientError::from(std::io::Error::other("connection refused"));
+
+        assert!(landed_on_chain(&Ok(Signature::default())));
+        assert!(landed_on_chain(&Err(failed_on_chain)));
+        assert!(!landed_on_chain(&Err(rejected)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_stuck_buy_dropped_once_its_blockhash_expires() {
+        // No status ever, and the chain is past the blockhash's last valid height
+        let mut mocks = Mocks::new();
+        mocks.insert(
+            RpcRequest::GetLatestBlockhash,
+            json!({"context": {"slot": 1}, "value": {"blockhash": Hash::new_unique().to_string(), "lastValidBlockHeight": 100}}),
+        );
+        mocks.insert(RpcRequest::GetBlockHeight, json!(5_000));
+        let bot = mock_rpc_bot("sig_not_found", mocks, Duration::ZERO);
+        let fixture = CreateFixture::new();
+
+        let error = bot.execute_buy_transaction(&fixture_create(&fixture), LAMPORTS_PER_SOL, Instant::now(), None, false)
+            .await
+            .unwrap_err();
+
+        assert!(error.is_not_landed());
+        let positions = bot.position_manager.lock();
+        assert_eq!(positions.pending_buys(), 0);
+        assert!(positions.get_position(&fixture.mint).is_none());
+    }
+
+    #[test]
+    fn test_subscription_request_filter_modes() {
+        let events = [PumpFunEvent::Create, PumpFunEvent::Migrate];
+
+        let request = subscription_request(&events, GeyserFilterMode::Include, &[], None);
+        let pump_fun = &request.transactions["pump_fun_subscription"];
+        assert_eq!(pump_fun.account_include, [PUMP_FUN_PROGRAM_ID]);
+        assert!(pump_fun.account_required.is_empty());
+        assert_eq!(request.transactions["pump_fun_migrations"].account_include, [PUMP_FUN_MIGRATION_AUTHORITY]);
+        assert!(request.transactions_status.is_empty());
+
+        let wallet = Pubkey::new_unique();
+        let request = subscription_request(&events, GeyserFilterMode::Required, &[], Some(&wallet));
+        for filter in request.transactions.values().chain(request.transactions_status.values()) {
+            assert!(filter.account_include.is_empty());
+            assert_eq!(filter.account_required.len(), 1);
+            assert!(!filter.vote);
+        }
+        assert!(request.transactions.values().all(|filter| !filter.failed));
+        // The wallet's own statuses include failures, so a failed buy is seen too
+        let own = &request.transactions_status[OWN_STATUS_FILTER];
+        assert_eq!(own.account_required, [wallet.to_string()]);
+        assert!(own.failed);
+        assert_eq!(request.transactions["pump_fun_subscription"].account_required, [PUMP_FUN_PROGRAM_ID]);
+        assert_eq!(request.transactions["pump_fun_migrations"].account_required, [PUMP_FUN_MIGRATION_AUTHORITY]);
+
+        // No migration filter when migrations are not streamed
+        let request = subscription_request(&[PumpFunEvent::Create], GeyserFilterMode::Required, &[], None);
+        assert_eq!(request.transactions.len(), 1);
+        assert_eq!(request.slots.len(), 1);
+    }
+
+    #[test]
+    fn test_streamed_statuses_settle_without_polling() {
+        let signature = Signature::new_unique();
+        let update = TransactionStatusUpdate {
+            signature: signature.as_ref().to_vec(),
+            slot: 9,
+            ..Default::default()
+        };
+        let landed = StreamedStatus { slot: 9, failed: false };
+        assert_eq!(streamed_status(&update), Some((signature, landed)));
+        let failed_update = TransactionStatusUpdate { err: Some(Default::default()), ..update.clone() };
+        let failed = StreamedStatus { slot: 9, failed: true };
+        assert_eq!(streamed_status(&failed_update), Some((signature, failed)));
+        assert_eq!(streamed_status(&TransactionStatusUpdate { signature: vec![1, 2, 3], ..update }), None);
+
+        // A processed landing settles a processed policy on its own, but a
+        // confirmed policy and any failure still need RPC
+        let mut policy = BuyConfirmationPolicy {
+            commitment: CommitmentConfig::processed(),
+            min_confirmations: 0,
+            pending_timeout: Duration::from_secs(60),
+        };
+        assert!(streamed_status_outcome(landed, &policy).unwrap().is_ok());
+        assert!(streamed_status_outcome(failed, &policy).is_none());
+        policy.commitment = CommitmentConfig::confirmed();
+        assert!(streamed_status_outcome(landed, &policy).is_none());
+
+        // RPC is asked every time without the stream, and with it only once the
+        // stream has seen the transaction or the fallback interval has passed
+        let recently = Duration::from_millis(100);
+        assert!(should_poll_status(false, None, recently));
+        assert!(!should_poll_status(true, None, recently));
+        assert!(should_poll_status(true, Some(landed), recently));
+        assert!(should_poll_status(true, None, STREAM_STATUS_FALLBACK_POLL_INTERVAL));
+    }
+
+    #[test]
+    fn test_combined_sniper_and_copy_trade_subscription() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let request = subscription_request(&[PumpFunEvent::Create], GeyserFilterMode::Required, &[alice, bob], None);
+
+        assert_eq!(request.transactions.len(), 3);
+        assert_eq!(request.transactions[PUMP_FUN_FILTER].account_required, [PUMP_FUN_PROGRAM_ID]);
+        let alice_filter = format!("{}{}", COPY_TRADER_FILTER_PREFIX, alice);
+        assert_eq!(request.transactions[&alice_filter].account_required, [alice.to_string()]);
+        assert_eq!(request.transactions[&format!("copy_trader_{}", bob)].account_required, [bob.to_string()]);
+
+        // A trader's PumpFun buy matches both filters and goes to both handlers
+        let routes = route_update(&[PUMP_FUN_FILTER.to_string(), alice_filter.clone()]);
+        assert_eq!(routes, UpdateRoutes { sniper: true, copy_traders: vec![alice] });
+        assert_eq!(route_update(&[alice_filter]), UpdateRoutes { sniper: false, copy_traders: vec![alice] });
+        assert_eq!(route_update(&[MIGRATIONS_FILTER.to_string()]), UpdateRoutes { sniper: true, copy_traders: vec![] });
+        assert_eq!(route_update(&[]), UpdateRoutes { sniper: true, copy_traders: vec![] });
+        assert_eq!(route_update(&["copy_trader_nonsense".to_string()]), UpdateRoutes::default());
+    }
+
+    #[test]
+    fn test_dry_run_simulation_reports_program_error() {
+        let error = TransactionError::InstructionError(3, InstructionError::Custom(6001));
+        let logs = vec![
+            "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P invoke [1]".to_string(),
+            "Program log: Error: slippage: Too much SOL required to buy the given amount of tokens.".to_string(),
+        ];
+        let failed = DryRunSimulation::new(Some(&error), Some(41_200), Some(logs.clone()));
+        assert!(!failed.succeeded());
+        assert_eq!(failed.error.as_deref(), Some("Error processing Instruction 3: custom program error: 0x1771"));
+        assert_eq!(failed.units_consumed, Some(41_200));
+        assert_eq!(failed.logs, logs);
+
+        let landed = DryRunSimulation::new(None, Some(65_000), None);
+        assert!(landed.succeeded());
+        assert!(landed.logs.is_empty());
+    }
+
+    #[test]
+    fn test_buy_pays_native_sol_without_wsol() {
+        let params = BuyParams {
+            buyer: Pubkey::new_unique(),
+            accounts: PumpFunAccounts {
+                mint: Pubkey::new_unique(),
+                bonding_curve: Pubkey::new_unique(),
+                associated_bonding_curve: Pubkey::new_unique(),
+                creator_vault: Pubkey::new_unique(),
+            },
+            fee_recipient: Pubkey::new_unique(),
+            token_program: spl_token::id(),
+            token_amount: 1_000,
+            max_sol_cost_lamports: 2_000,
+        };
+        let instructions = buy_instructions(&params, true, COMPUTE_UNIT_PRICE_MICRO_LAMPORTS).unwrap();
+
+        // Nothing is wrapped or closed around the buy, and no WSOL account is touched
+        let native_mint = spl_token::native_mint::id();
+        assert!(instructions.iter().all(|instruction| {
+            instruction.program_id != solana_sdk::system_program::ID
+                && instruction.program_id != spl_token::id()
+                && instruction.accounts.iter().all(|account| account.pubkey != native_mint)
+        }));
+        assert!(!instructions.iter().flat_map(|instruction| &instruction.accounts).any(|account| {
+            account.pubkey == get_associated_token_address(&params.buyer, &native_mint)
+        }));
+
+        // The buy itself debits the buyer's lamports, signed and writable, capped at the max cost
+        let buy = instructions.last().unwrap();
+        assert_eq!(buy.program_id, program_keys().pump_fun);
+        let buyer = buy.accounts.iter().find(|account| account.pubkey == params.buyer).unwrap();
+        assert!(buyer.is_signer && buyer.is_writable);
+        assert_eq!(buy.data[16..24], 2_000u64.to_le_bytes());
+    }
+
+    #[cfg(feature = "mock-geyser")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_against_mock_geyser_builds_dry_run_buy() {
+        let fixture = CreateFixture::new();
+        let endpoint = crate::mock_geyser::serve(vec![fixture.response(10 * LAMPORTS_PER_SOL)]).await;
+
+        let env = ENV_LOCK.lock();
+        std::env::set_var("BUYER_PRIVATE_KEY_PATH", Keypair::new().to_base58_string());
+        std::env::set_var("GRPC_ENDPOINT", &endpoint);
+        std::env::set_var("GRPC_AUTH_TOKEN", "test");
+        // Nothing listens here; the bot must not need RPC in dry-run mode
+        std::env::set_var("SOLANA_RPC_ENDPOINT", "http://127.0.0.1:1");
+        std::env::set_var("MARKET_CAP_THRESHOLD_USD", "1000.0");
+        std::env::set_var("DRY_RUN", "true");
+        std::env::set_var("WARMUP", "false");
+
+        let config = Config::from_env().unwrap();
+        drop(env);
+
+        let signer = Arc::new(MockSigner::new());
+        let bot = Arc::new(SniperBot::with_signer(config, signer.clone()).unwrap());
+        bot.price_cache.set(150.0);
+
+        // The mock ends its stream after the scripted create, which ends `run`
+        tokio::time::timeout(Duration::from_secs(30), Arc::clone(&bot).run()).await.unwrap().unwrap();
+
+        let buys = bot.dry_run_buys();
+        assert_eq!(buys.len(), 1);
+        assert_eq!(buys[0].mint, fixture.mint);
+        assert!(buys[0].token_amount > 0);
+        assert!(buys[0].transaction.message.account_keys.contains(&fixture.bonding_curve));
+        assert_eq!(buys[0].transaction.message.account_keys[0], signer.pubkey());
+        assert!(buys[0].transaction.verify().is_ok());
+        assert!(buys[0].simulation.is_none());
+        assert_eq!(signer.requests(), 1);
+        assert!(bot.position_manager.lock().open_positions().is_empty());
+    }
+
+    #[cfg(feature = "mock-geyser")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_creates_make_progress() {
+        let (first, second) = (CreateFixture::new(), CreateFixture::new());
+        let endpoint = crate::mock_geyser::serve(vec![
+            first.response(10 * LAMPORTS_PER_SOL),
+            second.response(10 * LAMPORTS_PER_SOL),
+        ])
+        .await;
+
+        let env = ENV_LOCK.lock();
+        std::env::set_var("BUYER_PRIVATE_KEY_PATH", Keypair::new().to_base58_string());
+        std::env::set_var("GRPC_ENDPOINT", &endpoint);
+        std::env::set_var("GRPC_AUTH_TOKEN", "test");
+        std::env::set_var("SOLANA_RPC_ENDPOINT", "http://127.0.0.1:1");
+        std::env::set_var("MARKET_CAP_THRESHOLD_USD", "1000.0");
+        std::env::set_var("DRY_RUN", "true");
+        std::env::set_var("WARMUP", "false");
+        let config = Config::from_env().unwrap();
+        drop(env);
+
+        // Each sign blocks its thread until the other create is being signed
+        // too, so the two only get through if they are processed side by side
+        let signer = Arc::new(MockSigner::rendezvous(2));
+        let bot = Arc::new(SniperBot::with_signer(config, signer.clone()).unwrap());
+        bot.price_cache.set(150.0);
+
+        tokio::time::timeout(Duration::from_secs(30), Arc::clone(&bot).run()).await.unwrap().unwrap();
+
+        let mut bought: Vec<Pubkey> = bot.dry_run_buys().iter().map(|buy| buy.mint).collect();
+        bought.sort();
+        let mut expected = vec![first.mint, second.mint];
+        expected.sort();
+        assert_eq!(bought, expected);
+        assert_eq!(signer.requests(), 2);
+    }
 }
@@ -0,0 +1,209 @@
+use crate::error::{Result, SniperError};
+use crate::price_cache::PriceHttpConfig;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Free site builders and link shorteners that cost nothing to throw away
+/// after a rug, so a launch "website" on one of them says little.
+pub const THROWAWAY_DOMAINS: &[&str] = &[
+    "000webhostapp.com",
+    "wixsite.com",
+    "weebly.com",
+    "carrd.co",
+    "netlify.app",
+    "vercel.app",
+    "blogspot.com",
+    "bit.ly",
+    "tinyurl.com",
+];
+
+const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// A social link a token's off-chain metadata can carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocialLinkKind {
+    Twitter,
+    Telegram,
+    Website,
+}
+
+impl FromStr for SocialLinkKind {
+    type Err = SniperError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "twitter" | "x" => Ok(SocialLinkKind::Twitter),
+            "telegram" => Ok(SocialLinkKind::Telegram),
+            "website" => Ok(SocialLinkKind::Website),
+            other => Err(SniperError::Config(format!(
+                "Unknown social link '{}', expected twitter, telegram or website", other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SocialLinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocialLinkKind::Twitter => write!(f, "twitter"),
+            SocialLinkKind::Telegram => write!(f, "telegram"),
+            SocialLinkKind::Website => write!(f, "website"),
+        }
+    }
+}
+
+/// Parses a comma-separated link list such as `twitter,telegram`.
+pub fn parse_social_link_kinds(text: &str) -> Result<Vec<SocialLinkKind>> {
+    let mut kinds = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let kind = entry.parse()?;
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    Ok(kinds)
+}
+
+/// The social links in a token's off-chain metadata JSON. PumpFun writes them
+/// at the top level; some launchpads nest them under `extensions`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SocialLinks {
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub website: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MetadataJson {
+    twitter: Option<String>,
+    telegram: Option<String>,
+    website: Option<String>,
+    extensions: Option<MetadataExtensions>,
+}
+
+#[derive(Default, Deserialize)]
+struct MetadataExtensions {
+    twitter: Option<String>,
+    telegram: Option<String>,
+    website: Option<String>,
+}
+
+impl SocialLinks {
+    /// Reads the links out of a metadata JSON document. Blank links count as absent.
+    pub fn from_metadata_json(json: &[u8]) -> Result<Self> {
+        let metadata: MetadataJson = serde_json::from_slice(json)?;
+        let extensions = metadata.extensions.unwrap_or_default();
+        let link = |top: Option<String>, nested: Option<String>| {
+            top.into_iter()
+                .chain(nested)
+                .map(|link| link.trim().to_string())
+                .find(|link| !link.is_empty())
+        };
+        Ok(Self {
+            twitter: link(metadata.twitter, extensions.twitter),
+            telegram: link(metadata.telegram, extensions.telegram),
+            website: link(metadata.website, extensions.website),
+        })
+    }
+
+    pub fn get(&self, kind: SocialLinkKind) -> Option<&str> {
+        match kind {
+            SocialLinkKind::Twitter => self.twitter.as_deref(),
+            SocialLinkKind::Telegram => self.telegram.as_deref(),
+            SocialLinkKind::Website => self.website.as_deref(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.twitter.is_none() && self.telegram.is_none() && self.website.is_none()
+    }
+
+    /// Whether any of `kinds` is present.
+    pub fn has_any(&self, kinds: &[SocialLinkKind]) -> bool {
+        kinds.iter().any(|kind| self.get(*kind).is_some())
+    }
+
+    /// The links hosted on one of [`THROWAWAY_DOMAINS`].
+    pub fn throwaway_links(&self) -> Vec<&str> {
+        [&self.twitter, &self.telegram, &self.website]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|link| is_throwaway(link))
+            .collect()
+    }
+}
+
+fn link_host(link: &str) -> Option<String> {
+    let rest = link.split_once("://").map_or(link, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+fn is_throwaway(link: &str) -> bool {
+    link_host(link).is_some_and(|host| {
+        THROWAWAY_DOMAINS
+            .iter()
+            .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+    })
+}
+
+/// Fetches token metadata JSON to read its social links, giving up after
+/// `timeout` so a slow metadata host cannot hold up a snipe for long.
+/// Requests go out through the same proxy and user agent as price requests.
+pub struct SocialLinkFetcher {
+    client: reqwest::Client,
+}
+
+impl SocialLinkFetcher {
+    pub fn new(http: &PriceHttpConfig, timeout: Duration) -> Result<Self> {
+        let client = http.client_builder()?
+            .timeout(timeout)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Could not build the metadata HTTP client: {}", e))?;
+        Ok(Self { client })
+    }
+
+    /// The social links in the metadata at `uri`; `ipfs://` URIs are read
+    /// through a public gateway.
+    pub async fn fetch(&self, uri: &str) -> Result<SocialLinks> {
+        let url = match uri.strip_prefix("ipfs://") {
+            Some(cid) => format!("{}{}", IPFS_GATEWAY, cid),
+            None => uri.to_string(),
+        };
+        let body = self.client.get(&url).send().await?.error_for_status()?.bytes().await?;
+        SocialLinks::from_metadata_json(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_are_read_from_metadata() {
+        let linked = SocialLinks::from_metadata_json(br#"{
+            "name": "Cat", "symbol": "CAT",
+            "twitter": "https://x.com/cat", "telegram": " ", "website": "https://cat.carrd.co",
+            "extensions": { "telegram": "https://t.me/cat" }
+        }"#).unwrap();
+        assert_eq!(linked.twitter.as_deref(), Some("https://x.com/cat"));
+        assert_eq!(linked.telegram.as_deref(), Some("https://t.me/cat"));
+        assert!(linked.has_any(&[SocialLinkKind::Telegram]));
+        assert_eq!(linked.throwaway_links(), ["https://cat.carrd.co"]);
+
+        let linkless = SocialLinks::from_metadata_json(br#"{"name": "Cat", "symbol": "CAT", "twitter": ""}"#).unwrap();
+        assert!(linkless.is_empty());
+        assert!(!linkless.has_any(&[SocialLinkKind::Twitter, SocialLinkKind::Telegram, SocialLinkKind::Website]));
+        assert!(SocialLinks::from_metadata_json(b"not json").is_err());
+
+        assert_eq!(
+            parse_social_link_kinds("X, telegram,twitter").unwrap(),
+            [SocialLinkKind::Twitter, SocialLinkKind::Telegram]
+        );
+        assert!(parse_social_link_kinds("discord").is_err());
+    }
+}
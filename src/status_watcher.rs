@@ -0,0 +1,126 @@
+use parking_lot::Mutex;
+use solana_sdk::signature::Signature;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// What a Geyser `transactions_status` update reported for a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamedStatus {
+    pub slot: u64,
+    pub failed: bool,
+}
+
+/// Matches the `transactions_status` updates on the Geyser stream against the
+/// signatures of the bot's own submitted transactions, so a landed buy or sell
+/// is known as soon as the stream sees it instead of on the next RPC poll.
+/// Statuses for signatures nobody is watching are dropped.
+pub struct StatusWatcher {
+    statuses: Mutex<HashMap<Signature, Option<StreamedStatus>>>,
+    notify: Notify,
+}
+
+impl StatusWatcher {
+    pub fn new() -> Self {
+        Self {
+            statuses: Mutex::new(HashMap::new()),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Starts watching `signature`. Call this before sending, so a status that
+    /// arrives right after the send is not missed.
+    pub fn watch(&self, signature: Signature) {
+        self.statuses.lock().entry(signature).or_insert(None);
+    }
+
+    /// Stops watching `signature`, returning its status if one arrived.
+    pub fn unwatch(&self, signature: &Signature) -> Option<StreamedStatus> {
+        self.statuses.lock().remove(signature).flatten()
+    }
+
+    /// Records a status from the stream. Returns whether `signature` was
+    /// watched; anything else is ignored.
+    pub fn record(&self, signature: &Signature, status: StreamedStatus) -> bool {
+        let watched = match self.statuses.lock().get_mut(signature) {
+            Some(entry) => {
+                *entry = Some(status);
+                true
+            }
+            None => false,
+        };
+        if watched {
+            self.notify.notify_waiters();
+        }
+        watched
+    }
+
+    pub fn status(&self, signature: &Signature) -> Option<StreamedStatus> {
+        self.statuses.lock().get(signature).copied().flatten()
+    }
+
+    pub fn watched(&self) -> usize {
+        self.statuses.lock().len()
+    }
+
+    /// Waits up to `timeout` for the stream to report `signature`, returning
+    /// its status if it has arrived by then.
+    pub async fn wait_for(&self, signature: &Signature, timeout: Duration) -> Option<StreamedStatus> {
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        // Registered before checking, so a status recorded in between still wakes us
+        notified.as_mut().enable();
+        if let Some(status) = self.status(signature) {
+            return Some(status);
+        }
+        let _ = tokio::time::timeout(timeout, notified).await;
+        self.status(signature)
+    }
+}
+
+impl Default for StatusWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_only_watched_signatures_are_recorded() {
+        let watcher = StatusWatcher::new();
+        let (ours, theirs) = (Signature::new_unique(), Signature::new_unique());
+        let landed = StreamedStatus { slot: 42, failed: false };
+
+        watcher.watch(ours);
+        assert_eq!(watcher.status(&ours), None);
+        assert!(watcher.record(&ours, landed));
+        assert!(!watcher.record(&theirs, landed));
+        assert_eq!(watcher.status(&ours), Some(landed));
+        assert_eq!(watcher.status(&theirs), None);
+
+        assert_eq!(watcher.unwatch(&ours), Some(landed));
+        assert_eq!(watcher.watched(), 0);
+        assert!(!watcher.record(&ours, landed));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_wakes_on_a_streamed_status() {
+        let watcher = Arc::new(StatusWatcher::new());
+        let signature = Signature::new_unique();
+        watcher.watch(signature);
+
+        assert_eq!(watcher.wait_for(&signature, Duration::from_millis(10)).await, None);
+
+        let failed = StreamedStatus { slot: 7, failed: true };
+        let recorder = Arc::clone(&watcher);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            recorder.record(&signature, failed);
+        });
+        assert_eq!(watcher.wait_for(&signature, Duration::from_secs(5)).await, Some(failed));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// One row of the trade journal. Mints and signatures are kept as base58
+/// strings so the file opens cleanly in a spreadsheet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub mint: String,
+    pub side: TradeSide,
+    /// SOL spent on a buy, or received from a sell.
+    pub sol_amount: f64,
+    /// Raw token amount, in the token's smallest unit.
+    pub token_amount: u64,
+    /// SOL per whole token.
+    pub price_sol: f64,
+    /// Base and priority fees of the trade's transaction.
+    pub fees_sol: f64,
+    pub tips_sol: f64,
+    pub signature: String,
+    /// Set on sells only, net of the fees and tips paid on both the buys and
+    /// the sell of the tokens sold.
+    pub realized_pnl_sol: Option<f64>,
+}
+
+/// Appends a CSV row per completed trade. The header is written when the file
+/// is first created, and every row is flushed before `record` returns so a
+/// crash loses nothing already recorded.
+pub struct TradeJournal {
+    path: PathBuf,
+}
+
+impl TradeJournal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, entry: &JournalEntry) -> Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let is_new = file.metadata()?.len() == 0;
+
+        let mut writer = csv::WriterBuilder::new().has_headers(is_new).from_writer(file);
+        writer.serialize(entry).map_err(std::io::Error::from)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads every row back, oldest first.
+    pub fn read_entries(path: &Path) -> Result<Vec<JournalEntry>> {
+        let mut reader = csv::Reader::from_path(path).map_err(std::io::Error::from)?;
+        let entries = reader
+            .deserialize()
+            .collect::<std::result::Result<Vec<JournalEntry>, _>>()
+            .map_err(std::io::Error::from)?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::Signature;
+
+    #[test]
+    fn test_journal_round_trip() {
+        let path = std::env::temp_dir().join(format!("trade_journal_{}.csv", Pubkey::new_unique()));
+        let journal = TradeJournal::new(&path);
+        let mint = Pubkey::new_unique().to_string();
+
+        let buy = JournalEntry {
+            timestamp: Utc::now(),
+            mint: mint.clone(),
+            side: TradeSide::Buy,
+            sol_amount: 0.5,
+            token_amount: 17_000_000_000_000,
+            price_sol: 0.5 / 17_000_000.0,
+            fees_sol: 0.000205,
+            tips_sol: 0.001,
+            signature: Signature::new_unique().to_string(),
+            realized_pnl_sol: None,
+        };
+        let sell = JournalEntry {
+            side: TradeSide::Sell,
+            sol_amount: 0.8,
+            price_sol: 0.8 / 17_000_000.0,
+            tips_sol: 0.0,
+            signature: Signature::new_unique().to_string(),
+            realized_pnl_sol: Some(0.3),
+            ..buy.clone()
+        };
+        journal.record(&buy).unwrap();
+        journal.record(&sell).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().filter(|line| line.starts_with("timestamp,")).count(), 1);
+
+        let entries = TradeJournal::read_entries(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(entries, [buy, sell]);
+    }
+}